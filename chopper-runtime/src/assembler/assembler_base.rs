@@ -2,45 +2,385 @@ use nom::types::CompleteStr;
 use nom::*;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+use crate::base::constants::NAMED_SLOT_BASE;
 use crate::base::*;
+use crate::fusion::FusedExpr;
 use crate::instruction::CRTOpCode;
 
+// axis attributes are either static or read from an i32 register at run time
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AxisArg {
+    Static(i8),
+    Register(u8),
+}
+
+impl AxisArg {
+    // encode as [kind, value], kind 0 is reserved for absent axes
+    pub fn to_bytes(&self) -> [u8; 2] {
+        match self {
+            AxisArg::Static(axis) => [1u8, *axis as u8],
+            AxisArg::Register(index) => [2u8, *index],
+        }
+    }
+}
+
 // enum type can accept struct-like value.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Token {
     BytecodeOpCode {
         code: CRTOpCode,
     },
+    // tensor slot, such as %0
     Variable {
-        symbol: u8,
+        symbol: usize,
+    },
+    // named value such as %hidden1, resolved into a slot by a SymbolTable before encoding
+    NamedVariable {
+        name: String,
+    },
+    // i32 scalar register, such as $0
+    Register {
+        index: u8,
+    },
+    // ^name, defines a jump target as an opcode and refers to it as an operand
+    Label {
+        name: String,
+    },
+    // operands of multi-value instructions, such as results of return and outputs of split
+    Variables {
+        symbols: Vec<Token>,
     },
-    // TODO maybe support Variable { symbol: String },
     I32Literal {
         value: i32,
     },
     F32Literal {
         value: f32,
     },
+    // scalar of any element type, in little endian of element_type.byte_width() bytes
+    ScalarLiteral {
+        element_type: ElementType,
+        bytes: Vec<u8>,
+    },
+    // dense literal, raw_data holds the scalars in little endian of element_type
     Tensor {
-        raw_data: Vec<f32>,
+        element_type: ElementType,
+        raw_data: Vec<u8>,
         shape: Vec<usize>,
     },
+    // tensor filled with a scalar, value holds the scalar in little endian of element_type
     UninitTensor {
-        data_generator: f32,
+        element_type: ElementType,
+        value: Vec<u8>,
         shape: Vec<usize>,
     },
-    // distribution => u8 : 0 -> uniform; 1 -> normal
-    // for uniform, arg1 => min, arg2 => max
-    // for normal, arg1 => mean, arg2 => std
-    // TODO use above setting, currently only support -1.0 -> 1.0 uniform and 0.0 - 1.0 normal
+    // kind => u8 : 0 -> arange of [start, stop, step]; 1 -> linspace of [start, stop, num]
+    RangeTensor {
+        kind: u8,
+        params: Vec<f64>,
+        element_type: ElementType,
+    },
+    // identity matrix of shape [rows, cols]
+    EyeTensor {
+        element_type: ElementType,
+        shape: Vec<usize>,
+    },
+    // distribution => u8 : 0 -> uniform; 1 -> normal; 2 -> bernoulli; 3 -> truncated normal
+    // params hold two values as base::random::sample reads them, unused ones are 0
     UninitRNGTensor {
         distribution: u8,
+        params: Vec<f32>,
+        seed: u64,
+        element_type: ElementType,
         shape: Vec<usize>,
     },
     DType {
         element_type: ElementType,
     },
+    // attributes of reduction ops, reduce over all axes if axis is None
+    ReduceAttr {
+        axis: Option<AxisArg>,
+        keepdims: bool,
+    },
+    // list of dims for shape ops, such as target shape, permutation, or slice bounds
+    Dims {
+        dims: Vec<usize>,
+    },
+    Axis {
+        axis: AxisArg,
+    },
+    // immediate of load, big endian as the VM decodes it
+    U16Literal {
+        value: u16,
+    },
+    // type of function arguments and results, None dims accept any size
+    TensorType {
+        element_type: ElementType,
+        shape: Vec<Option<usize>>,
+    },
+    // steps of fused elementwise ops
+    FusedExpr {
+        expr: FusedExpr,
+    },
+}
+
+impl Token {
+    // slot id of a variable operand
+    pub fn to_slot(&self) -> usize {
+        match self {
+            Token::Variable { symbol } => *symbol,
+            Token::NamedVariable { name } => panic!("%{} is not resolved into a slot", name),
+            _ => panic!("only variables have slots"),
+        }
+    }
+
+    // slot ids of a single or multi-value operand
+    pub fn to_slots(&self) -> Vec<usize> {
+        match self {
+            Token::Variables { symbols } => symbols.iter().map(|x| x.to_slot()).collect(),
+            _ => vec![self.to_slot()],
+        }
+    }
+
+    // replaces named values with their slots in symbols
+    pub fn resolve_names(&self, symbols: &mut SymbolTable) -> Token {
+        match self {
+            Token::NamedVariable { name } => Token::Variable {
+                symbol: symbols.slot_of(name),
+            },
+            Token::Variables { symbols: operands } => Token::Variables {
+                symbols: operands
+                    .iter()
+                    .map(|operand| operand.resolve_names(symbols))
+                    .collect(),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    // whether a tensor of element_type and shape conforms to this tensor type
+    pub fn accepts(&self, element_type: ElementType, shape: &[usize]) -> bool {
+        match self {
+            Token::TensorType {
+                element_type: expected_type,
+                shape: expected_shape,
+            } => {
+                *expected_type == element_type
+                    && expected_shape.len() == shape.len()
+                    && expected_shape
+                        .iter()
+                        .zip(shape)
+                        .all(|(expected, dim)| expected.map_or(true, |x| x == *dim))
+            }
+            _ => panic!("only tensor types accept tensors"),
+        }
+    }
+
+    // serialise an operand into bytes, in the order the VM decodes them
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut results = vec![];
+        match self {
+            Token::Variable { symbol } => {
+                results.append(&mut encode_slot(*symbol));
+            }
+            Token::Register { index } => {
+                results.push(*index);
+            }
+            Token::Variables { symbols } => {
+                // encode as [count, symbols..]
+                results.push(symbols.len() as u8);
+                for symbol in symbols {
+                    results.append(&mut symbol.to_bytes());
+                }
+            }
+            Token::I32Literal { value } => {
+                // convert i32 into 4 of bytes in little endian order
+                // push it into cmd_buffer
+                let values = value.to_le_bytes();
+                for _value in values {
+                    results.push(_value);
+                }
+            }
+            Token::F32Literal { value } => {
+                let values = value.to_le_bytes();
+                for _value in values {
+                    results.push(_value);
+                }
+            }
+            Token::UninitTensor {
+                element_type,
+                value,
+                shape,
+            } => {
+                // push element type and value
+                results.push(element_type.code());
+                results.extend_from_slice(value);
+                // push shape
+                let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
+                let shape_len = shape_bytes.len() as u16;
+                let shape_len_bytes = shape_len.to_le_bytes();
+                for _shape_len in shape_len_bytes {
+                    results.push(_shape_len);
+                }
+                for _shape in shape_bytes {
+                    results.push(_shape)
+                }
+            }
+            Token::RangeTensor {
+                kind,
+                params,
+                element_type,
+            } => {
+                results.push(*kind);
+                results.push(element_type.code());
+                for param in params {
+                    results.extend_from_slice(&param.to_le_bytes());
+                }
+            }
+            Token::EyeTensor {
+                element_type,
+                shape,
+            } => {
+                results.push(element_type.code());
+                let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
+                results.extend_from_slice(&(shape_bytes.len() as u16).to_le_bytes());
+                results.extend_from_slice(&shape_bytes);
+            }
+            Token::UninitRNGTensor {
+                distribution,
+                params,
+                seed,
+                element_type,
+                shape,
+            } => {
+                // push distribution, element type, params and seed
+                results.push(*distribution);
+                results.push(element_type.code());
+                for param in params {
+                    results.extend_from_slice(&param.to_le_bytes());
+                }
+                results.extend_from_slice(&seed.to_le_bytes());
+                // push shape
+                let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
+                let shape_len = shape_bytes.len() as u16;
+                let shape_len_bytes = shape_len.to_le_bytes();
+                for _shape_len in shape_len_bytes {
+                    results.push(_shape_len);
+                }
+                for _shape in shape_bytes {
+                    results.push(_shape)
+                }
+            }
+            Token::Tensor {
+                element_type,
+                raw_data,
+                shape,
+            } => {
+                results.push(element_type.code());
+                let data_bytes = raw_data;
+                let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
+                let data_len = data_bytes.len() as u16;
+                let data_len_bytes = data_len.to_le_bytes();
+                let shape_len = shape_bytes.len() as u16;
+                let shape_len_bytes = shape_len.to_le_bytes();
+                // println!("{:?}", data_len);
+                // println!("{:?}", shape_bytes);
+                // println!("{:?}", shape_len);
+                // assert_eq!(0, 1);
+                // println!("{:?}", data_len_bytes.len());
+                // assert_eq!(0, 1);
+                for _data_len in data_len_bytes {
+                    results.push(_data_len);
+                }
+                for _data in data_bytes {
+                    results.push(*_data)
+                }
+                for _shape_len in shape_len_bytes {
+                    results.push(_shape_len);
+                }
+                for _shape in shape_bytes {
+                    results.push(_shape)
+                }
+            }
+            Token::ReduceAttr { axis, keepdims } => {
+                // encode as [axis-kind, axis, keepdims]
+                match axis {
+                    Some(axis) => results.extend_from_slice(&axis.to_bytes()),
+                    None => results.extend_from_slice(&[0u8, 0u8]),
+                }
+                results.push(*keepdims as u8);
+            }
+            Token::Dims { dims } => {
+                // encoded same as shapes
+                let dims_bytes: Vec<u8> = bincode::serialize(&dims).unwrap();
+                let dims_len = dims_bytes.len() as u16;
+                for _dims_len in dims_len.to_le_bytes() {
+                    results.push(_dims_len);
+                }
+                for _dims in dims_bytes {
+                    results.push(_dims)
+                }
+            }
+            Token::Axis { axis } => {
+                results.extend_from_slice(&axis.to_bytes());
+            }
+            Token::U16Literal { value } => {
+                results.extend_from_slice(&value.to_be_bytes());
+            }
+            Token::ScalarLiteral { bytes, .. } => {
+                results.extend_from_slice(bytes);
+            }
+            Token::FusedExpr { expr } => {
+                // encoded as [len u16, bincode of the expression], same as shapes
+                let expr_bytes: Vec<u8> = bincode::serialize(expr).unwrap();
+                results.extend_from_slice(&(expr_bytes.len() as u16).to_le_bytes());
+                results.extend_from_slice(&expr_bytes);
+            }
+            Token::NamedVariable { name } => {
+                panic!("%{} must be resolved into a slot before encoding", name);
+            }
+            _ => {
+                panic!("register or literal/operand only");
+            }
+        }
+        results
+    }
+}
+
+// slots are encoded as LEB128, ids below 128 take a single byte as before
+fn encode_slot(slot: usize) -> Vec<u8> {
+    let mut results = vec![];
+    let mut rest = slot;
+    loop {
+        let byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+        if rest == 0 {
+            results.push(byte);
+            return results;
+        }
+        results.push(byte | 0x80);
+    }
+}
+
+// gives named values their slots, numbered from NAMED_SLOT_BASE in the order of first use, so
+// they never collide with numbered values. Kept across programs pushed into the same vm.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    slots: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            slots: HashMap::new(),
+        }
+    }
+
+    pub fn slot_of(&mut self, name: &str) -> usize {
+        let next_slot = NAMED_SLOT_BASE + self.slots.len();
+        *self.slots.entry(name.to_string()).or_insert(next_slot)
+    }
 }
 
 // The abstract struct for asm inst.
@@ -50,6 +390,9 @@ pub struct AsmInstruction {
     pub(crate) operand1: Option<Token>,
     pub(crate) operand2: Option<Token>,
     pub(crate) operand3: Option<Token>,
+    pub(crate) operand4: Option<Token>,
+    // element type written after the colon, such as `: f32`, checked by the verifier
+    pub(crate) annotation: Option<ElementType>,
 }
 
 // impl a function that can throw the asminstruction into a Vec<u8> format
@@ -67,303 +410,297 @@ impl AsmInstruction {
             Token::BytecodeOpCode { code } => {
                 results.push(*code as u8);
             }
+            // labels only mark positions
+            Token::Label { .. } => return results,
             _ => {
                 panic!("Unsuported opcode found");
             }
         }
 
-        // match operand1
-        match &self.operand1 {
-            Some(t) => match t {
-                Token::Variable { symbol } => {
-                    results.push(*symbol);
-                }
-                Token::I32Literal { value } => {
-                    // convert i32 into 4 of bytes in little endian order
-                    // push it into cmd_buffer
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::F32Literal { value } => {
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::UninitTensor {
-                    data_generator,
-                    shape,
-                } => {
-                    // push data_generator
-                    let values = data_generator.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::UninitRNGTensor {
-                    distribution,
-                    shape,
-                } => {
-                    // push distribution
-                    results.push(*distribution);
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::Tensor { raw_data, shape } => {
-                    let data_bytes: Vec<u8> = bincode::serialize(&raw_data).unwrap();
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let data_len = data_bytes.len() as u16;
-                    let data_len_bytes = data_len.to_le_bytes();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    // println!("{:?}", data_len);
-                    // println!("{:?}", shape_bytes);
-                    // println!("{:?}", shape_len);
-                    // assert_eq!(0, 1);
-                    // println!("{:?}", data_len_bytes.len());
-                    // assert_eq!(0, 1);
-                    for _data_len in data_len_bytes {
-                        results.push(_data_len);
-                    }
-                    for _data in data_bytes {
-                        results.push(_data)
-                    }
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                _ => {
-                    panic!("register or literal/operand only");
-                }
-            },
-            None => {} // do nothing if this operand is empty
+        // match operands by order
+        for operand in [
+            &self.operand1,
+            &self.operand2,
+            &self.operand3,
+            &self.operand4,
+        ] {
+            match operand {
+                Some(t) => results.append(&mut t.to_bytes()),
+                None => {} // do nothing if this operand is empty
+            }
         }
+        return results;
+    }
 
-        // match operand2
-        // TODO handle too long digits of i32
-        match &self.operand2 {
-            Some(t) => match t {
-                Token::Variable { symbol } => {
-                    results.push(*symbol);
-                }
-                Token::I32Literal { value } => {
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::F32Literal { value } => {
-                    // println!("{:?}", value);
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::UninitTensor {
-                    data_generator,
-                    shape,
-                } => {
-                    // push data_generator
-                    let values = data_generator.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::UninitRNGTensor {
-                    distribution,
-                    shape,
-                } => {
-                    // push distribution
-                    results.push(*distribution);
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::Tensor { raw_data, shape } => {
-                    let data_bytes: Vec<u8> = bincode::serialize(&raw_data).unwrap();
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let data_len = data_bytes.len() as u16;
-                    let data_len_bytes = data_len.to_le_bytes();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    // println!("{:?}", data_len);
-                    println!("encoding data len {:?}", data_len_bytes);
-                    println!("encoding data bytes {:?}", data_bytes);
-                    println!("encoding shape len {:?}", shape_len_bytes);
-                    println!("encoding shape bytes {:?}", shape_bytes);
-                    // println!("{:?}", shape_len);
-                    // assert_eq!(0, 1);
-                    // println!("{:?}", data_len_bytes.len());
-                    // assert_eq!(0, 1);
-                    for _data_len in data_len_bytes {
-                        results.push(_data_len);
-                    }
-                    for _data in data_bytes {
-                        results.push(_data)
-                    }
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                _ => {
-                    panic!("register or literal/operand only");
-                }
-            },
-            None => {} // do nothing if this operand is empty
+    pub fn resolve_names(&self, symbols: &mut SymbolTable) -> AsmInstruction {
+        let mut resolve = |operand: &Option<Token>| {
+            operand
+                .as_ref()
+                .map(|operand| operand.resolve_names(symbols))
+        };
+        AsmInstruction {
+            opcode: self.opcode.clone(),
+            operand1: resolve(&self.operand1),
+            operand2: resolve(&self.operand2),
+            operand3: resolve(&self.operand3),
+            operand4: resolve(&self.operand4),
+            annotation: self.annotation,
         }
+    }
 
-        // match operand3
-        match &self.operand3 {
-            Some(t) => match t {
-                Token::Variable { symbol } => {
-                    results.push(*symbol);
-                }
-                Token::I32Literal { value } => {
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::F32Literal { value } => {
-                    let values = value.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                }
-                Token::UninitTensor {
-                    data_generator,
-                    shape,
-                } => {
-                    // push data_generator
-                    let values = data_generator.to_le_bytes();
-                    for _value in values {
-                        results.push(_value);
-                    }
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::UninitRNGTensor {
-                    distribution,
-                    shape,
-                } => {
-                    // push distribution
-                    results.push(*distribution);
-                    // push shape
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                Token::Tensor { raw_data, shape } => {
-                    let data_bytes: Vec<u8> = bincode::serialize(&raw_data).unwrap();
-                    let shape_bytes: Vec<u8> = bincode::serialize(&shape).unwrap();
-                    let data_len = data_bytes.len() as u16;
-                    let data_len_bytes = data_len.to_le_bytes();
-                    let shape_len = shape_bytes.len() as u16;
-                    let shape_len_bytes = shape_len.to_le_bytes();
-                    // println!("{:?}", data_len);
-                    // println!("{:?}", shape_bytes);
-                    // println!("{:?}", shape_len);
-                    // assert_eq!(0, 1);
-                    // println!("{:?}", data_len_bytes.len());
-                    // assert_eq!(0, 1);
-                    for _data_len in data_len_bytes {
-                        results.push(_data_len);
-                    }
-                    for _data in data_bytes {
-                        results.push(_data)
-                    }
-                    for _shape_len in shape_len_bytes {
-                        results.push(_shape_len);
-                    }
-                    for _shape in shape_bytes {
-                        results.push(_shape)
-                    }
-                }
-                _ => {
-                    panic!("register or literal/operand only");
-                }
-            },
-            None => {} // do nothing if this operand is empty
+    // replaces label operands with the i32 offsets given by offset_of
+    fn resolve_labels<F: Fn(&str) -> i32>(&self, offset_of: F) -> AsmInstruction {
+        let resolve = |operand: &Option<Token>| match operand {
+            Some(Token::Label { name }) => Some(Token::I32Literal {
+                value: offset_of(name),
+            }),
+            _ => operand.clone(),
+        };
+        AsmInstruction {
+            opcode: self.opcode.clone(),
+            operand1: resolve(&self.operand1),
+            operand2: resolve(&self.operand2),
+            operand3: resolve(&self.operand3),
+            operand4: resolve(&self.operand4),
+            annotation: self.annotation,
         }
-        return results;
+    }
+}
+
+// serialises a sequence of instructions, label references are resolved into byte offsets
+// relative to the end of the referencing instruction, so the bytes stay valid wherever they are
+// pushed into the command buffer. Labels are checked when programs are assembled from text or
+// disassembled from modules, so the panics below are not reachable from either
+pub fn assemble(instructions: &[AsmInstruction]) -> Vec<u8> {
+    let mut labels = HashMap::new();
+    let mut ends = vec![];
+    let mut offset = 0usize;
+    for inst in instructions {
+        if let Token::Label { name } = &inst.opcode {
+            if labels.insert(name.clone(), offset).is_some() {
+                panic!("label ^{} is defined more than once", name);
+            }
+        }
+        // offsets are fixed-width, so placeholders give the final length
+        offset += inst.resolve_labels(|_| 0).to_bytes().len();
+        ends.push(offset);
+    }
+
+    let mut results = vec![];
+    for (inst, end) in instructions.iter().zip(ends) {
+        let resolved = inst.resolve_labels(|name| match labels.get(name) {
+            Some(target) => *target as i32 - end as i32,
+            None => panic!("label ^{} is not defined", name),
+        });
+        results.append(&mut resolved.to_bytes());
+    }
+    results
+}
+
+// func @name(%0: tensor<...>, ...) -> (tensor<...>, ...) { instructions }
+// arguments are bound to their slots by position on calls, results are the operands of return
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    pub(crate) name: String,
+    // pairs of the argument operand and its tensor type
+    pub(crate) arguments: Vec<(Token, Token)>,
+    pub(crate) results: Vec<Token>,
+    pub(crate) instructions: Vec<AsmInstruction>,
+}
+
+impl Function {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        assemble(&self.instructions)
+    }
+
+    pub fn resolve_names(&self, symbols: &mut SymbolTable) -> Function {
+        Function {
+            name: self.name.clone(),
+            arguments: self
+                .arguments
+                .iter()
+                .map(|(operand, tensor_type)| (operand.resolve_names(symbols), tensor_type.clone()))
+                .collect(),
+            results: self.results.clone(),
+            instructions: self
+                .instructions
+                .iter()
+                .map(|inst| inst.resolve_names(symbols))
+                .collect(),
+        }
+    }
+
+    pub fn argument_slots(&self) -> Vec<usize> {
+        self.arguments
+            .iter()
+            .map(|(operand, _)| operand.to_slot())
+            .collect()
+    }
+
+    // operands of the last return instruction in the body
+    pub fn result_slots(&self) -> Vec<usize> {
+        self.instructions
+            .iter()
+            .rev()
+            .find(|inst| {
+                inst.opcode
+                    == Token::BytecodeOpCode {
+                        code: CRTOpCode::RETV,
+                    }
+            })
+            .and_then(|inst| inst.operand1.as_ref())
+            .map_or(vec![], |operands| operands.to_slots())
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Program {
+    // top-level instructions, run in order
     pub(crate) instructions: Vec<AsmInstruction>,
+    // function definitions, called by name
+    pub(crate) functions: Vec<Function>,
 }
 
 // TODO move prase_program to submod, defines the Trait interface in mod.rs and pub it to the
 // outside
 impl Program {
+    // bytes of top-level instructions, function bodies are emitted on calls
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut program = vec![];
-        for inst in &self.instructions {
-            program.append(&mut inst.to_bytes());
+        assemble(&self.resolve_names(&mut SymbolTable::new()).instructions)
+    }
+
+    pub fn resolve_names(&self, symbols: &mut SymbolTable) -> Program {
+        Program {
+            instructions: self
+                .instructions
+                .iter()
+                .map(|inst| inst.resolve_names(symbols))
+                .collect(),
+            functions: self
+                .functions
+                .iter()
+                .map(|function| function.resolve_names(symbols))
+                .collect(),
         }
-        return program;
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|function| function.name == name)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn jump_to(name: &str) -> AsmInstruction {
+        AsmInstruction {
+            opcode: Token::BytecodeOpCode {
+                code: CRTOpCode::JMP,
+            },
+            operand1: Some(Token::Label {
+                name: name.to_string(),
+            }),
+            operand2: None,
+            operand3: None,
+            operand4: None,
+            annotation: None,
+        }
+    }
+
+    fn label(name: &str) -> AsmInstruction {
+        AsmInstruction {
+            opcode: Token::Label {
+                name: name.to_string(),
+            },
+            operand1: None,
+            operand2: None,
+            operand3: None,
+            operand4: None,
+            annotation: None,
+        }
+    }
+
+    #[test]
+    fn test_assemble_resolves_labels() {
+        let halt = AsmInstruction {
+            opcode: Token::BytecodeOpCode {
+                code: CRTOpCode::HALT,
+            },
+            operand1: None,
+            operand2: None,
+            operand3: None,
+            operand4: None,
+            annotation: None,
+        };
+        // jmp ^end; ^head: halt; jmp ^head; ^end:
+        let bytes = assemble(&[
+            jump_to("end"),
+            label("head"),
+            halt,
+            jump_to("head"),
+            label("end"),
+        ]);
+        let mut expected = vec![82];
+        expected.extend_from_slice(&6i32.to_le_bytes());
+        expected.push(0);
+        expected.push(82);
+        expected.extend_from_slice(&(-6i32).to_le_bytes());
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assemble_undefined_label() {
+        assemble(&[jump_to("nowhere")]);
+    }
+
+    #[test]
+    fn test_resolve_named_values() {
+        let named = |name: &str| Token::NamedVariable {
+            name: name.to_string(),
+        };
+        // %w_q = crt.add.f32! %hidden1, %300
+        let add = AsmInstruction {
+            opcode: Token::BytecodeOpCode {
+                code: CRTOpCode::ADDF32,
+            },
+            operand1: Some(named("w_q")),
+            operand2: Some(named("hidden1")),
+            operand3: Some(Token::Variable { symbol: 300 }),
+            operand4: None,
+            annotation: None,
+        };
+        let mut symbols = SymbolTable::new();
+        assert_eq!(symbols.slot_of("hidden1"), NAMED_SLOT_BASE);
+        let resolved = add.resolve_names(&mut symbols);
+        assert_eq!(resolved.operand1.unwrap().to_slot(), NAMED_SLOT_BASE + 1);
+        assert_eq!(resolved.operand2.unwrap().to_slot(), NAMED_SLOT_BASE);
+
+        // slots take as many bytes as they need, names are numbered in the order of first use
+        let bytes = add.resolve_names(&mut SymbolTable::new()).to_bytes();
+        assert_eq!(
+            bytes,
+            vec![
+                CRTOpCode::ADDF32 as u8,
+                0x80,
+                0x80,
+                0x04,
+                0x81,
+                0x80,
+                0x04,
+                0xac,
+                0x02
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_encode_unresolved_name() {
+        Token::NamedVariable {
+            name: "x".to_string(),
+        }
+        .to_bytes();
+    }
 }
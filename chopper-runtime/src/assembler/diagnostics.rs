@@ -0,0 +1,282 @@
+// external crates
+use nom::types::CompleteStr;
+
+use std::collections::HashSet;
+
+use super::assembler_base::{Program, Token};
+use super::parse_instruction::parse_instruction;
+use crate::base::errors::AssemblerError;
+use crate::base::ElementType;
+use crate::instruction::CRTOpCode;
+
+const EXPECTED_ITEM: &str = "an instruction or function definition";
+const EXPECTED_OPCODE: &str = "a known opcode such as `crt.add.f32`";
+const EXPECTED_TYPE: &str = "an element type, one of i32, f32, bool, f16, bf16, i8, u8, f64, i64";
+const EXPECTED_NEW_LABEL: &str = "a label that is not defined before in its program or function";
+const EXPECTED_DEFINED_LABEL: &str = "a label that is defined in its program or function";
+const EXPECTED_LOOP: &str = "a trip count of at least 1 and a label, such as `loop 10, ^head`";
+const EXPECTED_SIGNATURE: &str =
+    "a function signature such as `func @name(%0: tensor<2xf32>) -> (tensor<2xf32>) {`";
+
+// error of the item starting from offset, which is where the program parser stopped
+pub fn diagnose(source: &str, offset: usize) -> AssemblerError {
+    let start = skip_spaces(source, offset);
+    if !source[start..].starts_with("func") {
+        return diagnose_line(source, start);
+    }
+
+    // report the first line of the body that is not an instruction, or else the signature
+    let mut line_start = next_line(source, start);
+    while line_start < source.len() {
+        let line = line_at(source, line_start);
+        let trimmed = line.trim();
+        if trimmed == "}" {
+            break;
+        }
+        if !trimmed.is_empty() && !is_instruction(trimmed) {
+            return diagnose_line(source, skip_spaces(source, line_start));
+        }
+        line_start = next_line(source, line_start);
+    }
+    error_at(source, start, EXPECTED_SIGNATURE)
+}
+
+// names the part of the instruction at start that is wrong, the mnemonic, a type or the operands
+fn diagnose_line(source: &str, start: usize) -> AssemblerError {
+    let line = line_at(source, start);
+    let mnemonic = match (line.find('='), line.find('!')) {
+        (Some(assign), Some(bang)) if assign < bang => {
+            let text = &line[assign + 1..bang];
+            let column = assign + 1 + (text.len() - text.trim_start().len());
+            Some((text.trim(), column))
+        }
+        _ => None,
+    };
+    if let Some((text, column)) = mnemonic {
+        if CRTOpCode::from(CompleteStr(text)) == CRTOpCode::ILLEGAL {
+            return error_at(source, start + column, EXPECTED_OPCODE);
+        }
+    }
+
+    // element types follow colons, such as `: f32`, tensor types of signatures are skipped
+    for (colon, _) in line.match_indices(':') {
+        let column = skip_spaces(line, colon + 1);
+        let word: String = line[column..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric())
+            .collect();
+        if !word.is_empty() && word != "tensor" && word.parse::<ElementType>().is_err() {
+            return error_at(source, start + column, EXPECTED_TYPE);
+        }
+    }
+
+    if line.starts_with("loop ") {
+        return error_at(source, skip_spaces(source, start + 4), EXPECTED_LOOP);
+    }
+
+    match mnemonic {
+        Some((text, _)) => {
+            let column = skip_spaces(line, line.find('!').unwrap() + 1);
+            error_at(source, start + column, &format!("operands of `{}`", text))
+        }
+        None => error_at(source, start, EXPECTED_ITEM),
+    }
+}
+
+// labels are resolved in the top-level instructions and in each function body separately, a label
+// defined twice or never defined in its scope is reported where it appears
+pub fn check_labels(source: &str, program: &Program) -> Result<(), AssemblerError> {
+    let mut scopes = vec![&program.instructions];
+    scopes.extend(
+        program
+            .functions
+            .iter()
+            .map(|function| &function.instructions),
+    );
+    for (scope, instructions) in scopes.into_iter().enumerate() {
+        let mut defined = HashSet::new();
+        for inst in instructions {
+            if let Token::Label { name } = &inst.opcode {
+                if !defined.insert(name) {
+                    let offset = find_label(source, scope, name, true, 1);
+                    return Err(error_at(source, offset, EXPECTED_NEW_LABEL));
+                }
+            }
+        }
+        for inst in instructions {
+            let operands = [
+                &inst.operand1,
+                &inst.operand2,
+                &inst.operand3,
+                &inst.operand4,
+            ];
+            for operand in operands.iter() {
+                if let Some(Token::Label { name }) = operand {
+                    if !defined.contains(name) {
+                        let offset = find_label(source, scope, name, false, 0);
+                        return Err(error_at(source, offset, EXPECTED_DEFINED_LABEL));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// offset of the nth definition or reference of ^name in scope, 0 for the top level and i for the
+// ith function, function bodies end at a line of `}` as in diagnose
+fn find_label(source: &str, scope: usize, name: &str, definition: bool, nth: usize) -> usize {
+    let mut current = 0;
+    let mut functions = 0;
+    let mut count = 0;
+    let mut line_start = 0;
+    while line_start < source.len() {
+        let line = line_at(source, line_start);
+        let trimmed = line.trim();
+        if trimmed.starts_with("func") {
+            functions += 1;
+            current = functions;
+        }
+        if current == scope {
+            for (caret, _) in line.match_indices('^') {
+                let rest = &line[caret + 1..];
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let is_definition = rest[end..].trim_start().starts_with(':');
+                if &rest[..end] == name && is_definition == definition {
+                    if count == nth {
+                        return line_start + caret;
+                    }
+                    count += 1;
+                }
+            }
+        }
+        if current != 0 && trimmed == "}" {
+            current = 0;
+        }
+        line_start = next_line(source, line_start);
+    }
+    0
+}
+
+fn is_instruction(line: &str) -> bool {
+    match parse_instruction(CompleteStr(&format!("{}\n", line))) {
+        Ok((rest, _)) => rest.trim().is_empty(),
+        Err(_) => false,
+    }
+}
+
+fn error_at(source: &str, offset: usize, expected: &str) -> AssemblerError {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+    AssemblerError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        snippet: line_at(source, offset).trim_end().to_string(),
+        expected: expected.to_string(),
+    }
+}
+
+// rest of the line from offset, without the line break
+fn line_at(source: &str, offset: usize) -> &str {
+    let rest = &source[offset..];
+    &rest[..rest.find('\n').unwrap_or(rest.len())]
+}
+
+fn next_line(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |x| offset + x + 1)
+}
+
+fn skip_spaces(source: &str, offset: usize) -> usize {
+    let rest = &source[offset..];
+    offset + rest.len() - rest.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::parse_bytecode;
+
+    #[test]
+    fn test_diagnose_unknown_opcode() {
+        let source = "%0 = crt.add.f32! %1, %2 : f32\n  %3 = crt.addd.f32! %0, %0 : f32\n";
+        let error = diagnose(source, 31);
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 8);
+        assert_eq!(error.snippet, "crt.addd.f32! %0, %0 : f32");
+        assert_eq!(error.expected, EXPECTED_OPCODE);
+    }
+
+    #[test]
+    fn test_diagnose_unknown_type_and_operands() {
+        let error = diagnose("%0 = crt.exp.f32! %1 : f31\n", 0);
+        assert_eq!((error.line, error.column), (1, 24));
+        assert_eq!(error.snippet, "f31");
+        assert_eq!(error.expected, EXPECTED_TYPE);
+
+        let error = diagnose("%0 = crt.exp.f32! %1 %2 : f32\n", 0);
+        assert_eq!((error.line, error.column), (1, 19));
+        assert_eq!(error.expected, "operands of `crt.exp.f32`");
+
+        let error = diagnose("\n\n", 0);
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.snippet, "");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "line 3, column 1: unexpected end of input, expected {}",
+                EXPECTED_ITEM
+            )
+        );
+    }
+
+    #[test]
+    fn test_diagnose_loop() {
+        let error = diagnose("^head:\nloop 0, ^head\n", 7);
+        assert_eq!((error.line, error.column), (2, 6));
+        assert_eq!(error.snippet, "0, ^head");
+        assert_eq!(error.expected, EXPECTED_LOOP);
+    }
+
+    #[test]
+    fn test_diagnose_function_body() {
+        let source = "func @f(%0: tensor<2xf32>) -> (tensor<2xf32>) {
+    %1 = crt.exp.f32! %0 : f32
+    %2 = crt.exp.f32! %1 : i33
+    return %2
+}
+";
+        let error = diagnose(source, 0);
+        assert_eq!((error.line, error.column), (3, 28));
+        assert_eq!(error.expected, EXPECTED_TYPE);
+
+        let error = diagnose("func @f(%0: tensor<2xf32>) {\n}\n", 0);
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.expected, EXPECTED_SIGNATURE);
+    }
+
+    #[test]
+    fn test_check_labels() {
+        let source = "^head:\njmp ^head\n  ^head:\n";
+        let program = parse_bytecode(CompleteStr(source)).unwrap().1;
+        let error = check_labels(source, &program).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(error.snippet, "^head:");
+        assert_eq!(error.expected, EXPECTED_NEW_LABEL);
+
+        // labels of other functions are not visible
+        let source = "^end:
+func @f(%0: tensor<2xf32>) -> (tensor<2xf32>) {
+    jmp ^end
+    return %0
+}
+";
+        let program = parse_bytecode(CompleteStr(source)).unwrap().1;
+        let error = check_labels(source, &program).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 9));
+        assert_eq!(error.expected, EXPECTED_DEFINED_LABEL);
+    }
+}
@@ -0,0 +1,565 @@
+use std::collections::BTreeSet;
+use std::convert::TryInto;
+
+use super::assembler_base::*;
+use super::printer::canonical_annotation;
+use crate::base::errors::RuntimeStatusError;
+use crate::base::ElementType;
+use crate::fusion::FusedExpr;
+use crate::instruction::CRTOpCode;
+
+fn instruction(opcode: Token, operands: Vec<Token>) -> AsmInstruction {
+    let mut operands = operands.into_iter();
+    AsmInstruction {
+        opcode: opcode,
+        operand1: operands.next(),
+        operand2: operands.next(),
+        operand3: operands.next(),
+        operand4: operands.next(),
+        annotation: None,
+    }
+}
+
+fn malformed(message: String) -> RuntimeStatusError {
+    RuntimeStatusError::INVALID_MODULE(message)
+}
+
+fn label_name(position: usize) -> String {
+    format!("L{}", position)
+}
+
+// reads operands in the layouts that the vm decodes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    // byte positions of jump targets
+    targets: BTreeSet<usize>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], RuntimeStatusError> {
+        if self.position + len > self.bytes.len() {
+            return Err(malformed(format!(
+                "bytecode ends at byte {}, expects {} more bytes",
+                self.bytes.len(),
+                self.position + len - self.bytes.len()
+            )));
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, RuntimeStatusError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, RuntimeStatusError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, RuntimeStatusError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, RuntimeStatusError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, RuntimeStatusError> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn element_type(&mut self) -> Result<ElementType, RuntimeStatusError> {
+        match self.u8()? {
+            code @ 0..=8 => Ok(ElementType::from(code)),
+            code => Err(malformed(format!("unknown element type code {}", code))),
+        }
+    }
+
+    // slots are LEB128
+    fn slot(&mut self) -> Result<Token, RuntimeStatusError> {
+        let mut slot = 0usize;
+        let mut shift = 0;
+        loop {
+            if shift >= usize::BITS {
+                return Err(malformed(format!(
+                    "slot at byte {} is too long",
+                    self.position
+                )));
+            }
+            let byte = self.u8()?;
+            slot |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(Token::Variable { symbol: slot });
+            }
+            shift += 7;
+        }
+    }
+
+    fn slots(&mut self) -> Result<Token, RuntimeStatusError> {
+        let count = self.u8()?;
+        Ok(Token::Variables {
+            symbols: (0..count).map(|_| self.slot()).collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn register(&mut self) -> Result<Token, RuntimeStatusError> {
+        Ok(Token::Register { index: self.u8()? })
+    }
+
+    // offsets are relative to the end of the instruction, which is where they end
+    fn label(&mut self) -> Result<Token, RuntimeStatusError> {
+        let offset = self.i32()? as i64;
+        let target = self.position as i64 + offset;
+        if target < 0 || target > self.bytes.len() as i64 {
+            return Err(malformed(format!(
+                "jump target {} is out of the bytecode",
+                target
+            )));
+        }
+        self.targets.insert(target as usize);
+        Ok(Token::Label {
+            name: label_name(target as usize),
+        })
+    }
+
+    // shapes are [len u16, bincode of Vec<usize>]
+    fn shape(&mut self) -> Result<Vec<usize>, RuntimeStatusError> {
+        let len = self.u16()? as usize;
+        bincode::deserialize(self.take(len)?)
+            .map_err(|_| malformed(format!("shape at byte {} is not decodable", self.position)))
+    }
+
+    fn dims(&mut self) -> Result<Token, RuntimeStatusError> {
+        Ok(Token::Dims {
+            dims: self.shape()?,
+        })
+    }
+
+    // fused expressions are [len u16, bincode of FusedExpr]
+    fn fused_expr(&mut self) -> Result<Token, RuntimeStatusError> {
+        let len = self.u16()? as usize;
+        let expr: FusedExpr = bincode::deserialize(self.take(len)?).map_err(|_| {
+            malformed(format!(
+                "fused expression at byte {} is not decodable",
+                self.position
+            ))
+        })?;
+        Ok(Token::FusedExpr { expr: expr })
+    }
+
+    fn axis(&mut self) -> Result<Option<AxisArg>, RuntimeStatusError> {
+        let kind = self.u8()?;
+        let value = self.u8()?;
+        match kind {
+            0 => Ok(None),
+            1 => Ok(Some(AxisArg::Static(value as i8))),
+            2 => Ok(Some(AxisArg::Register(value))),
+            _ => Err(malformed(format!("unknown axis kind {}", kind))),
+        }
+    }
+
+    fn instruction(&mut self) -> Result<AsmInstruction, RuntimeStatusError> {
+        let position = self.position;
+        let code = CRTOpCode::from(self.u8()?);
+        let operands = match code {
+            CRTOpCode::HALT => vec![],
+            CRTOpCode::RETV => vec![self.slots()?],
+            CRTOpCode::JMP => vec![self.label()?],
+            CRTOpCode::JMPIF => vec![self.slot()?, self.label()?],
+            CRTOpCode::JMPIFR => vec![self.register()?, self.label()?],
+            CRTOpCode::LOOP => vec![Token::I32Literal { value: self.i32()? }, self.label()?],
+            CRTOpCode::LOAD => vec![
+                self.register()?,
+                Token::U16Literal {
+                    value: u16::from_be_bytes(self.take(2)?.try_into().unwrap()),
+                },
+            ],
+            CRTOpCode::ADDR
+            | CRTOpCode::SUBR
+            | CRTOpCode::MULR
+            | CRTOpCode::CMPEQR
+            | CRTOpCode::CMPNER
+            | CRTOpCode::CMPLTR
+            | CRTOpCode::CMPLER
+            | CRTOpCode::CMPGTR
+            | CRTOpCode::CMPGER => vec![self.register()?, self.register()?, self.register()?],
+            CRTOpCode::MOVRT => vec![self.slot()?, self.register()?],
+            CRTOpCode::MOVTR => vec![self.register()?, self.slot()?],
+            CRTOpCode::CONSTI32 | CRTOpCode::CONSTREF => {
+                vec![self.slot()?, Token::I32Literal { value: self.i32()? }]
+            }
+            CRTOpCode::CONSTF32 => vec![self.slot()?, Token::F32Literal { value: self.f32()? }],
+            CRTOpCode::CONSTF16
+            | CRTOpCode::CONSTBF16
+            | CRTOpCode::CONSTI8
+            | CRTOpCode::CONSTU8
+            | CRTOpCode::CONSTF64
+            | CRTOpCode::CONSTI64
+            | CRTOpCode::CONSTBOOL => {
+                let out = self.slot()?;
+                let element_type = code.to_const_element_type().unwrap();
+                let bytes = self.take(element_type.byte_width())?.to_vec();
+                vec![
+                    out,
+                    Token::ScalarLiteral {
+                        element_type: element_type,
+                        bytes: bytes,
+                    },
+                ]
+            }
+            CRTOpCode::CONSTTENSOR => {
+                let out = self.slot()?;
+                let element_type = self.element_type()?;
+                let len = self.u16()? as usize;
+                let raw_data = self.take(len)?.to_vec();
+                let shape = self.shape()?;
+                vec![
+                    out,
+                    Token::Tensor {
+                        element_type: element_type,
+                        raw_data: raw_data,
+                        shape: shape,
+                    },
+                ]
+            }
+            CRTOpCode::SVALUETENSOR => {
+                let out = self.slot()?;
+                let element_type = self.element_type()?;
+                let value = self.take(element_type.byte_width())?.to_vec();
+                let shape = self.shape()?;
+                vec![
+                    out,
+                    Token::UninitTensor {
+                        element_type: element_type,
+                        value: value,
+                        shape: shape,
+                    },
+                ]
+            }
+            CRTOpCode::RANGETENSOR => {
+                let out = self.slot()?;
+                let kind = self.u8()?;
+                let element_type = self.element_type()?;
+                let params = (0..3).map(|_| self.f64()).collect::<Result<_, _>>()?;
+                vec![
+                    out,
+                    Token::RangeTensor {
+                        kind: kind,
+                        params: params,
+                        element_type: element_type,
+                    },
+                ]
+            }
+            CRTOpCode::EYETENSOR => {
+                let out = self.slot()?;
+                let element_type = self.element_type()?;
+                let shape = self.shape()?;
+                vec![
+                    out,
+                    Token::EyeTensor {
+                        element_type: element_type,
+                        shape: shape,
+                    },
+                ]
+            }
+            CRTOpCode::RNGTENSOR => {
+                let out = self.slot()?;
+                let distribution = self.u8()?;
+                let element_type = self.element_type()?;
+                let params = (0..2).map(|_| self.f32()).collect::<Result<_, _>>()?;
+                let seed = u64::from_le_bytes(self.take(8)?.try_into().unwrap());
+                let shape = self.shape()?;
+                vec![
+                    out,
+                    Token::UninitRNGTensor {
+                        distribution: distribution,
+                        params: params,
+                        seed: seed,
+                        element_type: element_type,
+                        shape: shape,
+                    },
+                ]
+            }
+            CRTOpCode::REDUCESUMF32
+            | CRTOpCode::REDUCEMEANF32
+            | CRTOpCode::REDUCEMAXF32
+            | CRTOpCode::REDUCEMINF32
+            | CRTOpCode::ARGMAXF32
+            | CRTOpCode::ARGMINF32 => vec![
+                self.slot()?,
+                self.slot()?,
+                Token::ReduceAttr {
+                    axis: self.axis()?,
+                    keepdims: self.u8()? != 0,
+                },
+            ],
+            CRTOpCode::SELECT | CRTOpCode::FMAF32 => {
+                vec![self.slot()?, self.slot()?, self.slot()?, self.slot()?]
+            }
+            CRTOpCode::MATMULTF32 | CRTOpCode::CONV2DF32 => {
+                vec![self.slot()?, self.slot()?, self.slot()?, self.dims()?]
+            }
+            CRTOpCode::MAXPOOL2DF32
+            | CRTOpCode::AVGPOOL2DF32
+            | CRTOpCode::RESHAPE
+            | CRTOpCode::TRANSPOSE
+            | CRTOpCode::SQUEEZE
+            | CRTOpCode::UNSQUEEZE => vec![self.slot()?, self.slot()?, self.dims()?],
+            CRTOpCode::SLICE => vec![self.slot()?, self.slot()?, self.dims()?, self.dims()?],
+            CRTOpCode::SPLIT => vec![
+                self.slots()?,
+                self.slot()?,
+                Token::Axis {
+                    axis: self.axis()?.unwrap_or(AxisArg::Static(0)),
+                },
+            ],
+            CRTOpCode::CONCAT => vec![
+                self.slot()?,
+                self.slot()?,
+                self.slot()?,
+                Token::Axis {
+                    axis: self.axis()?.unwrap_or(AxisArg::Static(0)),
+                },
+            ],
+            CRTOpCode::ADDF32
+            | CRTOpCode::ADDI32
+            | CRTOpCode::SUBF32
+            | CRTOpCode::SUBI32
+            | CRTOpCode::MULF32
+            | CRTOpCode::MULI32
+            | CRTOpCode::DIVF32
+            | CRTOpCode::FLOORDIVI32
+            | CRTOpCode::ANDI32
+            | CRTOpCode::ORI32
+            | CRTOpCode::XORI32
+            | CRTOpCode::SHLI32
+            | CRTOpCode::SHRI32
+            | CRTOpCode::CMPEQF32
+            | CRTOpCode::CMPNEF32
+            | CRTOpCode::CMPLTF32
+            | CRTOpCode::CMPLEF32
+            | CRTOpCode::CMPGTF32
+            | CRTOpCode::CMPGEF32
+            | CRTOpCode::CMPEQI32
+            | CRTOpCode::CMPNEI32
+            | CRTOpCode::CMPLTI32
+            | CRTOpCode::CMPLEI32
+            | CRTOpCode::CMPGTI32
+            | CRTOpCode::CMPGEI32
+            | CRTOpCode::POWF32
+            | CRTOpCode::ATAN2F32
+            | CRTOpCode::MAXF32
+            | CRTOpCode::MINF32
+            | CRTOpCode::REMF32
+            | CRTOpCode::MAXI32
+            | CRTOpCode::MINI32
+            | CRTOpCode::REMI32
+            | CRTOpCode::MATMULF32 => vec![self.slot()?, self.slot()?, self.slot()?],
+            CRTOpCode::EXPF32
+            | CRTOpCode::ABSF32
+            | CRTOpCode::NEGF32
+            | CRTOpCode::CEILF32
+            | CRTOpCode::FLOORF32
+            | CRTOpCode::ATANF32
+            | CRTOpCode::COSF32
+            | CRTOpCode::SINF32
+            | CRTOpCode::EXP2F32
+            | CRTOpCode::EXPM1F32
+            | CRTOpCode::LOGF32
+            | CRTOpCode::LOG10F32
+            | CRTOpCode::LOG1PF32
+            | CRTOpCode::LOG2F32
+            | CRTOpCode::RSQRTF32
+            | CRTOpCode::SQRTF32
+            | CRTOpCode::TANHF32
+            | CRTOpCode::NOTI32
+            | CRTOpCode::CASTF32
+            | CRTOpCode::CASTI32
+            | CRTOpCode::BITCASTF32
+            | CRTOpCode::BITCASTI32
+            | CRTOpCode::CASTF16
+            | CRTOpCode::CASTBF16
+            | CRTOpCode::CASTI8
+            | CRTOpCode::CASTU8
+            | CRTOpCode::CASTF64
+            | CRTOpCode::CASTI64
+            | CRTOpCode::CASTBOOL
+            | CRTOpCode::ZEROSLIKE
+            | CRTOpCode::ONESLIKE => vec![self.slot()?, self.slot()?],
+            CRTOpCode::FUSEDF32 => vec![self.slot()?, self.slots()?, self.fused_expr()?],
+            CRTOpCode::ILLEGAL => {
+                return Err(malformed(format!("illegal opcode at byte {}", position)))
+            }
+        };
+        // annotations are not encoded, the canonical ones are given back
+        Ok(AsmInstruction {
+            annotation: canonical_annotation(code, &operands),
+            ..instruction(Token::BytecodeOpCode { code: code }, operands)
+        })
+    }
+}
+
+// decodes the bytes of top-level instructions, such as the command buffer of a vm, back into a
+// program. Jump targets become labels named after their byte positions, and named values are
+// numbered after their slots, since neither name is kept in the bytes. Malformed bytes are an
+// INVALID_MODULE error
+pub fn disassemble(bytes: &[u8]) -> Result<Program, RuntimeStatusError> {
+    let mut reader = Reader {
+        bytes: bytes,
+        position: 0,
+        targets: BTreeSet::new(),
+    };
+    let mut decoded = vec![];
+    while reader.position < bytes.len() {
+        decoded.push((reader.position, reader.instruction()?));
+    }
+
+    let mut instructions = vec![];
+    let mut labelled = BTreeSet::new();
+    let mut define_label = |position: usize, instructions: &mut Vec<AsmInstruction>| {
+        if reader.targets.contains(&position) {
+            labelled.insert(position);
+            instructions.push(instruction(
+                Token::Label {
+                    name: label_name(position),
+                },
+                vec![],
+            ));
+        }
+    };
+    for (position, inst) in decoded {
+        define_label(position, &mut instructions);
+        instructions.push(inst);
+    }
+    define_label(bytes.len(), &mut instructions);
+    if let Some(target) = reader.targets.difference(&labelled).next() {
+        return Err(malformed(format!(
+            "jump target {} is inside an instruction",
+            target
+        )));
+    }
+
+    Ok(Program {
+        instructions: instructions,
+        functions: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn round_trip(source: &str) -> String {
+        let bytes = assemble(source).unwrap().to_bytes();
+        let text = disassemble(&bytes).unwrap().to_string();
+        assert_eq!(assemble(&text).unwrap().to_bytes(), bytes);
+        text
+    }
+
+    #[test]
+    fn test_disassemble_canonical_text() {
+        let text = round_trip(
+            "%0 = crt.literal.const.f32! 13.0 : f32
+             %1 = crt.literal.const.i32! -2 : i32
+             %2 = crt.add.f32! %0, %0 : f32
+             %3 = crt.exp.f32! %2 : f32
+             return %3\n",
+        );
+        assert_eq!(
+            text,
+            "%0 = crt.literal.const.f32! 13.0 : f32
+%1 = crt.literal.const.i32! -2 : i32
+%2 = crt.add.f32! %0, %0 : f32
+%3 = crt.exp.f32! %2 : f32
+return %3
+"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_labels() {
+        // ^head is at byte 0 and ^end after all, both are renamed after their positions
+        let text = round_trip(
+            "^head:
+             load $0, 3
+             jmp_if $0, ^end
+             %0 = crt.literal.const.i64! -5 : i64
+             loop 2, ^head
+             jmp ^end
+             ^end:\n",
+        );
+        assert_eq!(
+            text,
+            "^L0:
+load $0, 3
+jmp_if $0, ^L34
+%0 = crt.literal.const.i64! -5 : i64
+loop 2, ^L0
+jmp ^L34
+^L34:
+"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_round_trip() {
+        round_trip(
+            "%0 = crt.literal.const.tensor! dense<[1.5 2 3 4 5 6], shape=[2 3]> : f32
+             %1 = crt.literal.const.tensor! dense<[true false], shape=[2]> : bool
+             %2 = crt.helper.svalue.tensor! ones<[2 3]> : bf16
+             %3 = crt.helper.range.tensor! linspace<0., 1., 5> : f64
+             %4 = crt.helper.range.tensor! arange<0, 6, 2> : i32
+             %5 = crt.helper.eye.tensor! eye<2, 3> : f32
+             %6 = crt.helper.rng.tensor! uniform<[2 3], -1., 1., 5> : f32
+             %7 = crt.helper.rng.tensor! bernoulli<[2 3], 0.3, 42> : bool
+             %8 = crt.helper.rng.tensor! truncated_normal<[2 3], 0., 0.02, 42> : f32
+             %9 = crt.matmul.f32! %0, %5, transpose_b = true : f32
+             %10 = crt.reduce.sum.f32! %9, axis = -1, keepdims = true : f32
+             %11 = crt.argmax.f32! %9 : f32
+             %12 = crt.tensor.transpose! %0 : f32
+             %13 = crt.tensor.reshape! %0, [$1, 3] : f32
+             %14 = crt.tensor.slice! %0, [0, 1], [2, 3] : f32
+             %15, %16 = crt.tensor.split! %0, axis = 1 : f32
+             %17 = crt.tensor.concat! %15, %16, axis = $2 : f32
+             %18 = crt.conv2d.f32! %0, %5, stride = [2, 1], groups = 2 : f32
+             %19 = crt.avgpool2d.f32! %0, kernel = [2, 2] : f32
+             %20 = crt.select! %1, %0, %0 : f32
+             %21 = crt.cmp.lt.i32! %4, %4 : bool
+             %22 = crt.cast.f16! %0 : f32
+             %23 = crt.zeros_like! %22 : f16
+             %hidden = crt.literal.const.u8! 7 : u8
+             $1 = crt.reg.add! $0, $0
+             $2 = crt.reg.from_tensor! %hidden
+             %24 = crt.reg.to_tensor! $2 : i32
+             %25 = crt.fused.f32! (%0, %9), expr = [crt.add.f32(0, 1), crt.exp.f32(2)] : f32
+             return %23, %24, %25\n",
+        );
+    }
+
+    #[test]
+    fn test_disassemble_malformed() {
+        let bytes = assemble("%0 = crt.literal.const.f32! 13.0 : f32\njmp ^L0\n^L0:\n")
+            .unwrap()
+            .to_bytes();
+        // cut in the middle of the literal
+        assert!(matches!(
+            disassemble(&bytes[..3]),
+            Err(RuntimeStatusError::INVALID_MODULE(_))
+        ));
+        // jump into the literal
+        let mut bytes = bytes.clone();
+        let end = bytes.len();
+        bytes[end - 4..].copy_from_slice(&(-7i32).to_le_bytes());
+        assert!(matches!(
+            disassemble(&bytes),
+            Err(RuntimeStatusError::INVALID_MODULE(_))
+        ));
+        assert!(matches!(
+            disassemble(&[255]),
+            Err(RuntimeStatusError::INVALID_MODULE(_))
+        ));
+    }
+}
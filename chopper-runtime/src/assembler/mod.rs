@@ -4,6 +4,10 @@ use nom::*;
 
 // submods
 pub mod assembler_base;
+pub mod diagnostics;
+pub mod disassembler;
+pub mod module;
+pub mod parse_function;
 pub mod parse_helper;
 pub mod parse_instruction;
 pub mod parse_literal;
@@ -11,8 +15,13 @@ pub mod parse_module;
 pub mod parse_opcode;
 pub mod parse_operand;
 pub mod parse_type;
+pub mod passes;
+pub mod printer;
+pub mod verifier;
 
+use crate::base::errors::AssemblerError;
 use assembler_base::*;
+use diagnostics::{check_labels, diagnose};
 use parse_module::*;
 
 named!(pub parse_bytecode<CompleteStr, Program>,
@@ -23,7 +32,43 @@ named!(pub parse_bytecode<CompleteStr, Program>,
     )
 );
 
+// assembles the whole source into a program, or locates the first item that fails to parse or
+// refers to labels wrongly
+pub fn assemble(source: &str) -> Result<Program, AssemblerError> {
+    match parse_bytecode(CompleteStr(source)) {
+        Ok((rest, program)) if rest.trim().is_empty() => {
+            check_labels(source, &program)?;
+            Ok(program)
+        }
+        Ok((rest, _)) => Err(diagnose(source, source.len() - rest.len())),
+        Err(_) => Err(diagnose(source, 0)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble("%0 = crt.literal.const.i32! 13 : i32\n\n");
+        assert_eq!(program.unwrap().instructions.len(), 1);
+
+        // the program parser stops at the typo, instead of dropping the rest
+        let error = assemble(
+            "%0 = crt.literal.const.i32! 13 : i32\n%1 = crt.literal.const.i32! 13 : i23\n",
+        )
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (2, 34));
+        assert_eq!(error.snippet, "i23");
+
+        let error = assemble("%1 = crt.foo! %0 : f32\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+        assert_eq!(error.snippet, "crt.foo! %0 : f32");
+
+        // labels are resolved before the program is turned into bytes
+        let error = assemble("load $0, 3\njmp_if $0, ^end\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 12));
+        assert_eq!(error.snippet, "^end");
+    }
 }
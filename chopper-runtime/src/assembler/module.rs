@@ -0,0 +1,444 @@
+// .crtb module files, programs in bytecodes that load without the text assembler
+//
+// integers are little endian
+// module     ::= magic version code functions constants checksum
+// magic      ::= "CRTB"
+// version    ::= u16, MODULE_VERSION
+// code       ::= u32 length, bytecodes of top-level instructions
+// functions  ::= u32 count, (name arguments results code)*
+// name       ::= u16 length, utf-8
+// arguments  ::= u16 count, (u64 slot, tensor-type)*
+// results    ::= u16 count, tensor-type*
+// constants  ::= u32 count, (element-type u8, u16 rank, u64 dims, u32 length, raw bytes)*
+// checksum   ::= u32, crc32 of all bytes before it
+// tensor-type ::= element-type u8, u16 rank, u64 dims, where u64::MAX marks dims of any size
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use super::assembler_base::*;
+use super::disassembler::disassemble;
+use crate::base::constants::{CONST_SECTION_THRESHOLD, MODULE_MAGIC, MODULE_VERSION};
+use crate::base::errors::RuntimeStatusError;
+use crate::base::ElementType;
+use crate::instruction::CRTOpCode;
+use crate::tensors::ActTensorTypes;
+
+// crc32 of the ieee polynomial, as zip and png use
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (!(crc & 1)).wrapping_add(1));
+        }
+    }
+    !crc
+}
+
+fn write_tensor_type(bytes: &mut Vec<u8>, tensor_type: &Token) {
+    match tensor_type {
+        Token::TensorType {
+            element_type,
+            shape,
+        } => {
+            bytes.push(element_type.code());
+            bytes.extend_from_slice(&(shape.len() as u16).to_le_bytes());
+            for dim in shape {
+                let dim = dim.map_or(u64::MAX, |dim| dim as u64);
+                bytes.extend_from_slice(&dim.to_le_bytes());
+            }
+        }
+        _ => panic!("{:?} is not a tensor type", tensor_type),
+    }
+}
+
+// moves large dense literals into constants, and refers to them by index instead
+fn extract_constants(
+    instructions: &[AsmInstruction],
+    constants: &mut Vec<Token>,
+) -> Vec<AsmInstruction> {
+    instructions
+        .iter()
+        .map(|inst| match (&inst.opcode, &inst.operand2) {
+            (
+                Token::BytecodeOpCode {
+                    code: CRTOpCode::CONSTTENSOR,
+                },
+                Some(Token::Tensor { raw_data, .. }),
+            ) if raw_data.len() > CONST_SECTION_THRESHOLD => {
+                constants.push(inst.operand2.clone().unwrap());
+                AsmInstruction {
+                    opcode: Token::BytecodeOpCode {
+                        code: CRTOpCode::CONSTREF,
+                    },
+                    operand1: inst.operand1.clone(),
+                    operand2: Some(Token::I32Literal {
+                        value: constants.len() as i32 - 1,
+                    }),
+                    operand3: None,
+                    operand4: None,
+                    annotation: None,
+                }
+            }
+            _ => inst.clone(),
+        })
+        .collect()
+}
+
+// offsets constant references by base, the index of the first constant of the module in a vm
+fn rebase_constants(instructions: &[AsmInstruction], base: usize) -> Vec<AsmInstruction> {
+    instructions
+        .iter()
+        .map(|inst| match (&inst.opcode, &inst.operand2) {
+            (
+                Token::BytecodeOpCode {
+                    code: CRTOpCode::CONSTREF,
+                },
+                Some(Token::I32Literal { value }),
+            ) => AsmInstruction {
+                operand2: Some(Token::I32Literal {
+                    value: value + base as i32,
+                }),
+                ..inst.clone()
+            },
+            _ => inst.clone(),
+        })
+        .collect()
+}
+
+impl Program {
+    // serialises the program into a module file, named values are resolved into slots first
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let program = self.resolve_names(&mut SymbolTable::new());
+        let mut constants = vec![];
+        let mut bytes = MODULE_MAGIC.to_vec();
+        bytes.extend_from_slice(&MODULE_VERSION.to_le_bytes());
+
+        let code = assemble(&extract_constants(&program.instructions, &mut constants));
+        bytes.extend_from_slice(&(code.len() as u32).to_le_bytes());
+        bytes.extend(code);
+
+        bytes.extend_from_slice(&(program.functions.len() as u32).to_le_bytes());
+        for function in &program.functions {
+            bytes.extend_from_slice(&(function.name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(function.name.as_bytes());
+            bytes.extend_from_slice(&(function.arguments.len() as u16).to_le_bytes());
+            for (operand, tensor_type) in &function.arguments {
+                bytes.extend_from_slice(&(operand.to_slot() as u64).to_le_bytes());
+                write_tensor_type(&mut bytes, tensor_type);
+            }
+            bytes.extend_from_slice(&(function.results.len() as u16).to_le_bytes());
+            for tensor_type in &function.results {
+                write_tensor_type(&mut bytes, tensor_type);
+            }
+            let code = assemble(&extract_constants(&function.instructions, &mut constants));
+            bytes.extend_from_slice(&(code.len() as u32).to_le_bytes());
+            bytes.extend(code);
+        }
+
+        bytes.extend_from_slice(&(constants.len() as u32).to_le_bytes());
+        for constant in &constants {
+            if let Token::Tensor {
+                element_type,
+                raw_data,
+                shape,
+            } = constant
+            {
+                bytes.push(element_type.code());
+                bytes.extend_from_slice(&(shape.len() as u16).to_le_bytes());
+                for dim in shape {
+                    bytes.extend_from_slice(&(*dim as u64).to_le_bytes());
+                }
+                bytes.extend_from_slice(&(raw_data.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(raw_data);
+            }
+        }
+
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        writer.write_all(&bytes)
+    }
+}
+
+// reads the sections of a module file, running out of bytes is an error rather than a panic
+struct ModuleReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ModuleReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], RuntimeStatusError> {
+        if self.position + len > self.bytes.len() {
+            return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                "module ends at byte {}, expects {} more bytes",
+                self.bytes.len(),
+                self.position + len - self.bytes.len()
+            )));
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, RuntimeStatusError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, RuntimeStatusError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, RuntimeStatusError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn element_type(&mut self) -> Result<ElementType, RuntimeStatusError> {
+        let code = self.take(1)?[0];
+        match code {
+            0..=8 => Ok(ElementType::from(code)),
+            _ => Err(RuntimeStatusError::INVALID_MODULE(format!(
+                "unknown element type code {}",
+                code
+            ))),
+        }
+    }
+
+    fn code(&mut self) -> Result<Vec<AsmInstruction>, RuntimeStatusError> {
+        let len = self.u32()? as usize;
+        Ok(disassemble(self.take(len)?)?.instructions)
+    }
+
+    fn tensor_type(&mut self) -> Result<Token, RuntimeStatusError> {
+        let element_type = self.element_type()?;
+        let rank = self.u16()?;
+        let mut shape = vec![];
+        for _ in 0..rank {
+            shape.push(match self.u64()? {
+                u64::MAX => None,
+                dim => Some(dim as usize),
+            });
+        }
+        Ok(Token::TensorType {
+            element_type: element_type,
+            shape: shape,
+        })
+    }
+
+    fn function(&mut self) -> Result<Function, RuntimeStatusError> {
+        let len = self.u16()? as usize;
+        let name = String::from_utf8(self.take(len)?.to_vec()).map_err(|_| {
+            RuntimeStatusError::INVALID_MODULE(String::from("function name is not utf-8"))
+        })?;
+        let mut arguments = vec![];
+        for _ in 0..self.u16()? {
+            let operand = Token::Variable {
+                symbol: self.u64()? as usize,
+            };
+            arguments.push((operand, self.tensor_type()?));
+        }
+        let mut results = vec![];
+        for _ in 0..self.u16()? {
+            results.push(self.tensor_type()?);
+        }
+        Ok(Function {
+            name: name,
+            arguments: arguments,
+            results: results,
+            instructions: self.code()?,
+        })
+    }
+
+    fn constant(&mut self) -> Result<ActTensorTypes, RuntimeStatusError> {
+        let element_type = self.element_type()?;
+        let rank = self.u16()?;
+        let mut shape = vec![];
+        for _ in 0..rank {
+            shape.push(self.u64()? as usize);
+        }
+        let len = self.u32()? as usize;
+        let raw_data = self.take(len)?;
+        if len != shape.iter().product::<usize>() * element_type.byte_width() {
+            return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                "constant of {} bytes does not fill shape {:?} of {}",
+                len, shape, element_type
+            )));
+        }
+        Ok(ActTensorTypes::from_le_bytes(raw_data, element_type, shape))
+    }
+}
+
+// a program loaded from a module file, with the constants it refers to
+#[derive(Debug)]
+pub struct Module {
+    pub(crate) program: Program,
+    pub(crate) constants: Vec<ActTensorTypes>,
+}
+
+impl Module {
+    pub fn load_from<R: Read>(reader: &mut R) -> Result<Module, RuntimeStatusError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| RuntimeStatusError::INVALID_MODULE(err.to_string()))?;
+        if !bytes.starts_with(&MODULE_MAGIC) {
+            return Err(RuntimeStatusError::INVALID_MODULE(String::from(
+                "not a crtb module",
+            )));
+        }
+        // versions are checked before checksums, whose layout may change between versions
+        let mut reader = ModuleReader {
+            bytes: &bytes,
+            position: MODULE_MAGIC.len(),
+        };
+        let version = reader.u16()?;
+        if version != MODULE_VERSION {
+            return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                "module version {} is not supported, expects {}",
+                version, MODULE_VERSION
+            )));
+        }
+        if bytes.len() < reader.position + 4 {
+            return Err(RuntimeStatusError::INVALID_MODULE(String::from(
+                "module is truncated",
+            )));
+        }
+        let (content, checksum) = bytes.split_at(bytes.len() - 4);
+        if crc32(content) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+            return Err(RuntimeStatusError::INVALID_MODULE(String::from(
+                "checksum mismatch, module is corrupted",
+            )));
+        }
+        reader.bytes = content;
+
+        let instructions = reader.code()?;
+        let mut functions = vec![];
+        for _ in 0..reader.u32()? {
+            functions.push(reader.function()?);
+        }
+        let mut constants = vec![];
+        for _ in 0..reader.u32()? {
+            constants.push(reader.constant()?);
+        }
+        if reader.position != content.len() {
+            return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                "{} bytes left after the constant section",
+                content.len() - reader.position
+            )));
+        }
+        Ok(Module {
+            program: Program {
+                instructions: instructions,
+                functions: functions,
+            },
+            constants: constants,
+        })
+    }
+
+    // the program whose constant references count from base, where the constants of the module
+    // start in a vm
+    pub(crate) fn program_at(&self, base: usize) -> Program {
+        Program {
+            instructions: rebase_constants(&self.program.instructions, base),
+            functions: self
+                .program
+                .functions
+                .iter()
+                .map(|function| Function {
+                    instructions: rebase_constants(&function.instructions, base),
+                    ..function.clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble as assemble_source;
+
+    const SOURCE: &str = "func @scale(%0: tensor<?x80xf32>) -> (tensor<?x80xf32>) {
+    %1 = crt.helper.svalue.tensor! ones<[80]>: f32
+    %2 = crt.mul.f32! %0, %1 : f32
+    return %2
+}
+%0 = crt.literal.const.i32! 13 : i32
+%1 = crt.exp.f32! %0 : f32
+";
+
+    fn large_literal(slot: usize) -> String {
+        let scalars: Vec<String> = (0..80).map(|x| x.to_string()).collect();
+        format!(
+            "%{} = crt.literal.const.tensor! dense<[{}], shape=[80]> : f32\n",
+            slot,
+            scalars.join(" ")
+        )
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_module_round_trip() {
+        let source = format!("{}{}", SOURCE, large_literal(7));
+        let program = assemble_source(&source).unwrap();
+        let mut bytes = vec![];
+        program.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes[..6], [b'C', b'R', b'T', b'B', 1, 0]);
+
+        let module = Module::load_from(&mut &bytes[..]).unwrap();
+        assert_eq!(module.constants.len(), 1);
+        assert_eq!(module.constants[0].shape(), vec![80]);
+        // the large literal refers to the constant section instead
+        let inst = &module.program.instructions[2];
+        assert_eq!(
+            inst.opcode,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::CONSTREF
+            }
+        );
+        assert_eq!(inst.operand2, Some(Token::I32Literal { value: 0 }));
+        assert_eq!(
+            assemble(&module.program.instructions[..2]),
+            assemble(&program.instructions[..2])
+        );
+        assert_eq!(module.program.functions, program.functions);
+
+        // constant references count from where the module constants start in the vm
+        let rebased = module.program_at(3);
+        assert_eq!(
+            rebased.instructions[2].operand2,
+            Some(Token::I32Literal { value: 3 })
+        );
+    }
+
+    #[test]
+    fn test_module_rejects_corrupted_files() {
+        let program = assemble_source(SOURCE).unwrap();
+        let mut bytes = vec![];
+        program.write_to(&mut bytes).unwrap();
+
+        let mut corrupted = bytes.clone();
+        corrupted[10] ^= 1;
+        match Module::load_from(&mut &corrupted[..]) {
+            Err(RuntimeStatusError::INVALID_MODULE(msg)) => {
+                assert_eq!(msg.contains("checksum"), true)
+            }
+            _ => panic!("expects a checksum mismatch"),
+        }
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        match Module::load_from(&mut &newer[..]) {
+            Err(RuntimeStatusError::INVALID_MODULE(msg)) => {
+                assert_eq!(msg.contains("version 2"), true)
+            }
+            _ => panic!("expects an unsupported version"),
+        }
+
+        assert_eq!(Module::load_from(&mut &bytes[1..]).is_err(), true);
+        assert_eq!(Module::load_from(&mut &bytes[..8]).is_err(), true);
+    }
+}
@@ -0,0 +1,110 @@
+// external crates
+use nom::types::CompleteStr;
+use nom::*;
+
+use super::assembler_base::*;
+use super::parse_instruction::*;
+use super::parse_operand::*;
+use super::parse_type::*;
+
+// function-argument ::= operand : tensor-type
+named!(parse_function_argument<CompleteStr, (Token, Token)>,
+    do_parse!(
+        operand: parse_operand >>
+        tag!(":") >>
+        tensor_type: parse_tensor_type >>
+        ( (operand, tensor_type) )
+    )
+);
+
+// function-results ::= ( tensor-type (, tensor-type)* ) | tensor-type
+named!(parse_function_results<CompleteStr, Vec<Token>>,
+    alt!(
+        delimited!(
+            ws!(tag!("(")),
+            separated_list!(ws!(tag!(",")), parse_tensor_type),
+            ws!(tag!(")"))
+        )
+        | parse_tensor_type => { |result| vec![result] }
+    )
+);
+
+// function ::= func @name ( function-argument (, function-argument)* ) -> function-results {
+//   instruction*
+// }
+named!(pub parse_function<CompleteStr, Function>,
+    do_parse!(
+        multispace0 >>
+        tag!("func") >>
+        space1 >>
+        tag!("@") >>
+        name: take_while1!(|c: char| c.is_alphanumeric() || c == '_') >>
+        ws!(tag!("(")) >>
+        arguments: separated_list!(ws!(tag!(",")), parse_function_argument) >>
+        ws!(tag!(")")) >>
+        ws!(tag!("->")) >>
+        results: parse_function_results >>
+        ws!(tag!("{")) >>
+        instructions: many0!(preceded!(multispace0, parse_instruction)) >>
+        ws!(tag!("}")) >>
+        (
+            Function {
+                name: name.to_string(),
+                arguments: arguments,
+                results: results,
+                instructions: instructions,
+            }
+        )
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::*;
+
+    #[test]
+    fn test_parse_function() {
+        let result = parse_function(CompleteStr(
+            "func @forward(%0: tensor<2x3xf32>, %1: tensor<?x3xf32>) -> (tensor<2x3xf32>) {
+    %2 = crt.add.f32! %0, %1 : f32
+    return %2
+}
+",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let (rest, function) = result.unwrap();
+        assert_eq!(rest, CompleteStr(""));
+        assert_eq!(function.name, "forward");
+        assert_eq!(function.argument_slots(), vec![0, 1]);
+        assert_eq!(function.result_slots(), vec![2]);
+        assert_eq!(
+            function.arguments[1].1,
+            Token::TensorType {
+                element_type: ElementType::F32,
+                shape: vec![None, Some(3)],
+            }
+        );
+        assert_eq!(function.results.len(), 1);
+        assert_eq!(function.instructions.len(), 2);
+        assert_eq!(function.to_bytes(), vec![8, 2, 0, 1, 17, 1, 2]);
+    }
+
+    #[test]
+    fn test_parse_function_single_result_without_parens() {
+        let result = parse_function(CompleteStr(
+            "func @exp(%0: tensor<f32>) -> tensor<f32> {\n%1 = crt.exp.f32! %0 : f32\nreturn %1\n}\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let function = result.unwrap().1;
+        assert_eq!(function.name, "exp");
+        assert_eq!(function.results.len(), 1);
+        assert_eq!(function.result_slots(), vec![1]);
+    }
+
+    #[test]
+    fn test_parse_function_missing_signature() {
+        let result = parse_function(CompleteStr("func @forward {\nreturn %0\n}\n"));
+        assert_eq!(result.is_ok(), false);
+    }
+}
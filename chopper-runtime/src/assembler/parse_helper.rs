@@ -3,66 +3,256 @@ use nom::types::CompleteStr;
 use nom::*;
 
 use super::assembler_base::*;
-use super::parse_literal::parse_integer_list;
+use super::parse_literal::{
+    encode_scalar, is_scalar_char, parse_integer_list, parse_usize_literal,
+};
 use super::parse_type::*;
+use crate::base::ElementType;
 
-// "crt.helper.zeros<[2 3]>"
-// TODO accept other types
+// float-param ::= , float
+named!(parse_float_param<CompleteStr, f32>,
+    do_parse!(
+        _s: space0 >>
+        tag!(",") >>
+        _s2: space0 >>
+        value: float >>
+        ( value )
+    )
+);
+
+// integer-param ::= , integer
+named!(parse_integer_param<CompleteStr, u64>,
+    do_parse!(
+        _s: space0 >>
+        tag!(",") >>
+        _s2: space0 >>
+        value: map_res!(digit, |d: CompleteStr| d.parse::<u64>()) >>
+        ( value )
+    )
+);
+
+// helper-type ::= : element-type, generated values are converted into the element type
+named!(parse_helper_type<CompleteStr, ElementType>,
+    do_parse!(
+        _s: space0 >>
+        tag!(":") >>
+        element_type: parse_element_type >>
+        ( element_type )
+    )
+);
+
+fn svalue_tensor(
+    text: &str,
+    element_type: ElementType,
+    shape: Vec<usize>,
+) -> Result<Token, String> {
+    Ok(Token::UninitTensor {
+        element_type: element_type,
+        value: encode_scalar(text, element_type)?,
+        shape: shape,
+    })
+}
+
+// "crt.helper.svalue.tensor! zeros<[2 3]>: f32"
 named!(pub parse_helper_zeros<CompleteStr, Token>,
     do_parse!(
         _sp: space0 >>
         tag!("zeros<") >>
         _shape: parse_integer_list >>
         tag!(">") >>
-        type_tag: parse_f32_type >>
-        (
-            Token::UninitTensor { data_generator: 0f32, shape: _shape }
-        )
+        element_type: parse_helper_type >>
+        token: expr_res!(svalue_tensor("0", element_type, _shape)) >>
+        ( token )
     )
 );
 
-// "crt.helper.ones<[2 3]>"
+// "crt.helper.svalue.tensor! ones<[2 3]>: f32"
 named!(pub parse_helper_ones<CompleteStr, Token>,
     do_parse!(
         _sp: space0 >>
         tag!("ones<") >>
         _shape: parse_integer_list >>
         tag!(">") >>
-        type_tag: parse_f32_type >>
+        element_type: parse_helper_type >>
+        token: expr_res!(svalue_tensor("1", element_type, _shape)) >>
+        ( token )
+    )
+);
+
+// "crt.helper.svalue.tensor! full<[2 3], 7>: i32", the value must be a literal of the type
+named!(pub parse_helper_full<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("full<") >>
+        _shape: parse_integer_list >>
+        _s: space0 >>
+        tag!(",") >>
+        _s2: space0 >>
+        value: take_while1!(is_scalar_char) >>
+        _s3: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
+        token: expr_res!(svalue_tensor(&value, element_type, _shape)) >>
+        ( token )
+    )
+);
+
+fn range_tensor(kind: u8, params: Vec<f64>, element_type: ElementType) -> Result<Token, String> {
+    match kind {
+        0 if params[2] == 0f64 => Err(String::from("arange step must not be 0")),
+        1 if params[2] < 1f64 => Err(String::from("linspace needs at least one value")),
+        _ => Ok(Token::RangeTensor {
+            kind: kind,
+            params: params,
+            element_type: element_type,
+        }),
+    }
+}
+
+// "crt.helper.range.tensor! arange<0, 10, 2>: i64", values in [start, stop), step defaults to 1
+named!(pub parse_helper_arange<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("arange<") >>
+        _s: space0 >>
+        start: float >>
+        stop: parse_float_param >>
+        step: opt!(parse_float_param) >>
+        _s2: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
+        token: expr_res!(range_tensor(
+            0,
+            vec![start as f64, stop as f64, step.unwrap_or(1f32) as f64],
+            element_type
+        )) >>
+        ( token )
+    )
+);
+
+// "crt.helper.range.tensor! linspace<0., 1., 5>: f32", num values evenly spaced in [start, stop]
+named!(pub parse_helper_linspace<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("linspace<") >>
+        _s: space0 >>
+        start: float >>
+        stop: parse_float_param >>
+        num: parse_integer_param >>
+        _s2: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
+        token: expr_res!(range_tensor(1, vec![start as f64, stop as f64, num as f64], element_type)) >>
+        ( token )
+    )
+);
+
+// "crt.helper.eye.tensor! eye<3>: f32" or "eye<2, 3>" for rectangular identities
+named!(pub parse_helper_eye<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("eye<") >>
+        rows: parse_usize_literal >>
+        cols: opt!(parse_integer_param) >>
+        _s: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
         (
-            Token::UninitTensor { data_generator: 1f32, shape: _shape }
+            Token::EyeTensor {
+                element_type: element_type,
+                shape: vec![rows, cols.map_or(rows, |x| x as usize)],
+            }
         )
     )
 );
 
-// "crt.helper.uniform<[2 3]>"
+fn rng_tensor(
+    distribution: u8,
+    params: Vec<f32>,
+    seed: u64,
+    element_type: ElementType,
+    shape: Vec<usize>,
+) -> Token {
+    Token::UninitRNGTensor {
+        distribution: distribution,
+        params: params,
+        seed: seed,
+        element_type: element_type,
+        shape: shape,
+    }
+}
+
+// "crt.helper.rng.tensor! uniform<[2 3], -1., 1., 42>: f32", min, max and seed default to -1, 1
+// and 0
 named!(pub parse_helper_uniform<CompleteStr, Token>,
     do_parse!(
         _sp: space0 >>
         tag!("uniform<") >>
         _shape: parse_integer_list >>
+        args: opt!(tuple!(parse_float_param, parse_float_param, parse_integer_param)) >>
+        _s: space0 >>
         tag!(">") >>
-        type_tag: parse_f32_type >>
+        element_type: parse_helper_type >>
         (
-            Token::UninitRNGTensor { distribution: 0 as u8, shape: _shape }
+            match args {
+                Some((min, max, seed)) => rng_tensor(0, vec![min, max], seed, element_type, _shape),
+                None => rng_tensor(0, vec![-1f32, 1f32], 0, element_type, _shape),
+            }
         )
     )
 );
 
-// "crt.helper.normal<[2 3]>"
+// "crt.helper.rng.tensor! normal<[2 3], 0., 1., 42>: f32", mean, std and seed default to 0, 1
+// and 0
 named!(pub parse_helper_normal<CompleteStr, Token>,
     do_parse!(
         _sp: space0 >>
         tag!("normal<") >>
         _shape: parse_integer_list >>
+        args: opt!(tuple!(parse_float_param, parse_float_param, parse_integer_param)) >>
+        _s: space0 >>
         tag!(">") >>
-        type_tag: parse_f32_type >>
+        element_type: parse_helper_type >>
         (
-            Token::UninitRNGTensor { distribution: 1 as u8, shape: _shape }
+            match args {
+                Some((mean, std, seed)) => rng_tensor(1, vec![mean, std], seed, element_type, _shape),
+                None => rng_tensor(1, vec![0f32, 1f32], 0, element_type, _shape),
+            }
         )
     )
 );
 
+// "crt.helper.rng.tensor! bernoulli<[2 3], 0.3, 42>: bool"
+named!(pub parse_helper_bernoulli<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("bernoulli<") >>
+        _shape: parse_integer_list >>
+        p: parse_float_param >>
+        seed: parse_integer_param >>
+        _s: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
+        ( rng_tensor(2, vec![p, 0f32], seed, element_type, _shape) )
+    )
+);
+
+// "crt.helper.rng.tensor! truncated_normal<[2 3], 0., 0.02, 42>: f32", truncated at two std
+named!(pub parse_helper_truncated_normal<CompleteStr, Token>,
+    do_parse!(
+        _sp: space0 >>
+        tag!("truncated_normal<") >>
+        _shape: parse_integer_list >>
+        mean: parse_float_param >>
+        std: parse_float_param >>
+        seed: parse_integer_param >>
+        _s: space0 >>
+        tag!(">") >>
+        element_type: parse_helper_type >>
+        ( rng_tensor(3, vec![mean, std], seed, element_type, _shape) )
+    )
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,7 +266,8 @@ mod tests {
         assert_eq!(
             _bytes_result,
             Token::UninitTensor {
-                data_generator: 0f32,
+                element_type: ElementType::F32,
+                value: vec![0, 0, 0, 0],
                 shape: vec![2, 3, 1]
             }
         );
@@ -91,10 +282,102 @@ mod tests {
         assert_eq!(
             _bytes_result,
             Token::UninitTensor {
-                data_generator: 1f32,
+                element_type: ElementType::F32,
+                value: 1f32.to_le_bytes().to_vec(),
                 shape: vec![2, 3, 1]
             }
         );
+
+        let result = parse_helper_ones(CompleteStr("ones<[2]> : i64"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::UninitTensor {
+                element_type: ElementType::I64,
+                value: 1i64.to_le_bytes().to_vec(),
+                shape: vec![2]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_helper_full() {
+        let result = parse_helper_full(CompleteStr("full<[2 2], -3>: i8"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::UninitTensor {
+                element_type: ElementType::I8,
+                value: vec![0xfd],
+                shape: vec![2, 2]
+            }
+        );
+        let result = parse_helper_full(CompleteStr("full<[3], true> : bool"));
+        assert_eq!(result.unwrap().1.to_bytes(), {
+            let mut bytes = vec![2, 1, 16, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+            bytes.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+            bytes
+        });
+        // the value must be a literal of the element type
+        assert_eq!(
+            parse_helper_full(CompleteStr("full<[3], 0.5> : i32")).is_ok(),
+            false
+        );
+    }
+
+    #[test]
+    fn test_parse_helper_arange_linspace_eye() {
+        let result = parse_helper_arange(CompleteStr("arange<0, 10, 2>: i64"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::RangeTensor {
+                kind: 0,
+                params: vec![0., 10., 2.],
+                element_type: ElementType::I64
+            }
+        );
+        let result = parse_helper_arange(CompleteStr("arange<-1.5, 1.5> : f32"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::RangeTensor {
+                kind: 0,
+                params: vec![-1.5, 1.5, 1.],
+                element_type: ElementType::F32
+            }
+        );
+        assert_eq!(
+            parse_helper_arange(CompleteStr("arange<0, 10, 0>: i32")).is_ok(),
+            false
+        );
+
+        let result = parse_helper_linspace(CompleteStr("linspace<0., 1., 5>: f64"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::RangeTensor {
+                kind: 1,
+                params: vec![0., 1., 5.],
+                element_type: ElementType::F64
+            }
+        );
+        assert_eq!(
+            parse_helper_linspace(CompleteStr("linspace<0., 1., 0>: f64")).is_ok(),
+            false
+        );
+
+        let result = parse_helper_eye(CompleteStr("eye<3>: f32"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::EyeTensor {
+                element_type: ElementType::F32,
+                shape: vec![3, 3]
+            }
+        );
+        let result = parse_helper_eye(CompleteStr("eye<2, 4> : bf16"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::EyeTensor {
+                element_type: ElementType::BF16,
+                shape: vec![2, 4]
+            }
+        );
     }
 
     #[test]
@@ -107,9 +390,24 @@ mod tests {
             _bytes_result,
             Token::UninitRNGTensor {
                 distribution: 0 as u8,
+                params: vec![-1., 1.],
+                seed: 0,
+                element_type: ElementType::F32,
                 shape: vec![2, 3, 1]
             }
         );
+
+        let result = parse_helper_uniform(CompleteStr("uniform<[4], -0.5, 2., 42> : f64"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::UninitRNGTensor {
+                distribution: 0 as u8,
+                params: vec![-0.5, 2.],
+                seed: 42,
+                element_type: ElementType::F64,
+                shape: vec![4]
+            }
+        );
     }
 
     #[test]
@@ -122,8 +420,45 @@ mod tests {
             _bytes_result,
             Token::UninitRNGTensor {
                 distribution: 1 as u8,
+                params: vec![0., 1.],
+                seed: 0,
+                element_type: ElementType::F32,
                 shape: vec![2, 3, 1]
             }
         );
     }
+
+    #[test]
+    fn test_parse_helper_bernoulli_and_truncated_normal() {
+        let result = parse_helper_bernoulli(CompleteStr("bernoulli<[2 2], 0.3, 7>: bool"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::UninitRNGTensor {
+                distribution: 2 as u8,
+                params: vec![0.3, 0.],
+                seed: 7,
+                element_type: ElementType::BOOL,
+                shape: vec![2, 2]
+            }
+        );
+        // bernoulli needs its probability and seed
+        assert_eq!(
+            parse_helper_bernoulli(CompleteStr("bernoulli<[2 2]>: bool")).is_ok(),
+            false
+        );
+
+        let result = parse_helper_truncated_normal(CompleteStr(
+            "truncated_normal<[3], 0., 0.02, 1234>: f32",
+        ));
+        assert_eq!(
+            result.unwrap().1,
+            Token::UninitRNGTensor {
+                distribution: 3 as u8,
+                params: vec![0., 0.02],
+                seed: 1234,
+                element_type: ElementType::F32,
+                shape: vec![3]
+            }
+        );
+    }
 }
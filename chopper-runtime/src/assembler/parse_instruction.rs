@@ -3,6 +3,8 @@ use nom::types::CompleteStr;
 use nom::*;
 
 // mods from local crate
+use crate::base::ElementType;
+use crate::fusion::*;
 use crate::instruction::CRTOpCode;
 
 // use assembler_base::*;
@@ -16,7 +18,28 @@ use crate::assembler::parse_type::*;
 named!(pub parse_instruction<CompleteStr, AsmInstruction>,
     do_parse!(
         _inst: alt!(
-            parse_halt | parse_return | parse_binary_assignment | parse_unary_assignment
+            parse_halt
+            | parse_return
+            | parse_label_definition
+            | parse_jump_if
+            | parse_jump
+            | parse_loop
+            | parse_load
+            | parse_register_assignment
+            | parse_register_to_tensor
+            | parse_split_assignment
+            | parse_shape_assignment
+            | parse_slice_assignment
+            | parse_concat_assignment
+            | parse_conv2d_assignment
+            | parse_pool2d_assignment
+            | parse_matmul_assignment
+            | parse_fused_assignment
+            | parse_ternary_assignment
+            | parse_binary_assignment
+            | parse_reduce_assignment
+            | parse_scalar_const_assignment
+            | parse_unary_assignment
         ) >> (
             _inst
         )
@@ -37,59 +60,205 @@ named!(parse_halt<CompleteStr, AsmInstruction>,
                 operand1: None,
                 operand2: None,
                 operand3: None,
+                operand4: None,
+                annotation: None,
             }
         )
     )
 );
 
 // return %1
+// return %3, %4
 named!(parse_return<CompleteStr, AsmInstruction>,
     do_parse!(
         _opcode: alt!(
             tag!("return")
         ) >>
         opt!(multispace) >>
-        // TODO runtime function is functional, no side
-        //  effect, must clear all results without returns
-        _operand1: parse_operand >>
+        // runtime function is functional, no side effect, all but the results are cleared
+        _operands: parse_operand_list >>
         // must use multispace, since we have to identify change lines of halt itself.
         opt!(multispace) >>
         (
             AsmInstruction {
                 opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
-                operand1: Some(_operand1),
+                operand1: Some(_operands),
                 operand2: None,
                 operand3: None,
+                operand4: None,
+                annotation: None,
             }
         )
     )
 );
 
-// return %2, %1
-// TODO multi-ret-values support
-// named!(parse_return_pair<CompleteStr, AsmInstruction>,
-//     do_parse!(
-//         _opcode: alt!(
-//             tag!("return")
-//         ) >>
-//         opt!(multispace) >>
-//         // TODO runtime function is functional, no side
-//         //  effect, must clear all results without returns
-//         _operand1: parse_operand >>
-//         tag!(", ") >>
-//         _operand2: parse_operand >>
-//         // must use multispace, since we have to identify change lines of halt itself.
-//         opt!(multispace) >>
-//         (
-//             AsmInstruction {
-//                 opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
-//                 operand1: Some(_operand1),
-//                 operand2: Some(_operand1),
-//                 operand3: None,
-//             }
-//         )
-//     )
-// );
+// label-definition ::= label :
+named!(parse_label_definition<CompleteStr, AsmInstruction>,
+    do_parse!(
+        label: parse_label >>
+        tag!(":") >>
+        opt!(multispace) >>
+        (
+            AsmInstruction {
+                opcode: label,
+                operand1: None,
+                operand2: None,
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// jmp ^head
+named!(parse_jump<CompleteStr, AsmInstruction>,
+    do_parse!(
+        _opcode: tag!("jmp") >>
+        target: parse_label >>
+        opt!(multispace) >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(target),
+                operand2: None,
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// jmp_if %0, ^head
+// jmp_if $0, ^head
+// jumps when the scalar tensor or the i32 register is non-zero
+named!(parse_jump_if<CompleteStr, AsmInstruction>,
+    do_parse!(
+        tag!("jmp_if") >>
+        condition: alt!(
+            parse_operand => { |operand| (CRTOpCode::JMPIF, operand) }
+            | parse_register => { |register| (CRTOpCode::JMPIFR, register) }
+        ) >>
+        tag!(",") >>
+        target: parse_label >>
+        opt!(multispace) >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: condition.0 },
+                operand1: Some(condition.1),
+                operand2: Some(target),
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// loop 10, ^head
+// placed at the end of the loop body, which starts from ^head and runs 10 times, the body runs
+// before the count is checked, so counts below 1 are rejected
+named!(parse_loop<CompleteStr, AsmInstruction>,
+    do_parse!(
+        _opcode: tag!("loop") >>
+        count: map_res!(parse_integer_literal, |count: Token| match count {
+            Token::I32Literal { value } if value >= 1 => Ok(count),
+            _ => Err(()),
+        }) >>
+        ws!(tag!(",")) >>
+        target: parse_label >>
+        opt!(multispace) >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(count),
+                operand2: Some(target),
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// load $0, 1000
+named!(parse_load<CompleteStr, AsmInstruction>,
+    do_parse!(
+        _opcode: tag!("load") >>
+        register: parse_register >>
+        tag!(",") >>
+        _s: space0 >>
+        value: map_res!(digit, |d: CompleteStr| d.parse::<u16>()) >>
+        opt!(multispace) >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(register),
+                operand2: Some(Token::U16Literal { value: value }),
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// register-assignment ::= register = crt.reg.<op>! register, register
+//                       | register = crt.reg.from_tensor! operand
+named!(parse_register_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_register: parse_register >>
+        tag!("=") >>
+        _s: space0 >>
+        opcode: parse_opcode >>
+        operands: alt!(
+            do_parse!(
+                lhs: parse_register >>
+                tag!(",") >>
+                rhs: parse_register >>
+                ( (lhs, Some(rhs)) )
+            )
+            | parse_operand => { |operand| (operand, None) }
+        ) >>
+        // registers are always i32, the type annotation is optional
+        opt!(parse_i32_type) >>
+        (
+            AsmInstruction {
+                opcode: opcode,
+                operand1: Some(out_register),
+                operand2: Some(operands.0),
+                operand3: operands.1,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
+
+// register-to-tensor ::= operand = crt.reg.to_tensor! register
+named!(parse_register_to_tensor<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s: space0 >>
+        _opcode: tag!("crt.reg.to_tensor") >>
+        tag!("!") >>
+        register: parse_register >>
+        // registers are always i32, the type annotation is optional
+        opt!(parse_i32_type) >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(register),
+                operand3: None,
+                operand4: None,
+                annotation: None,
+            }
+        )
+    )
+);
 
 // binary-assignment ::= out-operand opcode lhs-operand rhs-operand
 // lhs-operand ::= operand | numeric-literal
@@ -99,50 +268,570 @@ named!(
     do_parse!(
         _result: parse_operand >>
         tag!("= ") >>
-        _opcode: parse_opcode >>
+        _opcode: parse_generic_opcode >>
         _operand_lhs: parse_operand >>
         tag!(", ") >>
         _operand_rhs: parse_operand >>
         tag!(": ") >>
-        _dtype: parse_type >>
+        dtype: parse_element_type >>
         (
             AsmInstruction {
                 opcode: _opcode,
                 operand1: Some(_result),
                 operand2: Some(_operand_lhs),
                 operand3: Some(_operand_rhs),
+                operand4: None,
+                annotation: Some(dtype),
+
+            }
+        )
+    )
+);
+
+// ternary-assignment ::= out-operand opcode first-operand second-operand third-operand
+// such as select, whose operands are mask, on-true and on-false by order
+named!(
+    parse_ternary_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        _result: parse_operand >>
+        tag!("= ") >>
+        _opcode: parse_generic_opcode >>
+        _operand_first: parse_operand >>
+        tag!(", ") >>
+        _operand_second: parse_operand >>
+        tag!(", ") >>
+        _operand_third: parse_operand >>
+        tag!(": ") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: _opcode,
+                operand1: Some(_result),
+                operand2: Some(_operand_first),
+                operand3: Some(_operand_second),
+                operand4: Some(_operand_third),
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// shape-assignment ::= out-operand = shape-opcode in-operand [, dims-list] : type
+// dims-list is the target shape of reshape, the permutation of transpose, or the axes of
+// squeeze/unsqueeze. transpose reverses all dims and squeeze drops all dims of size 1 if omitted
+named!(
+    parse_shape_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: alt!(
+            tag!("crt.tensor.reshape")
+            | tag!("crt.tensor.transpose")
+            | tag!("crt.tensor.permute")
+            | tag!("crt.tensor.squeeze")
+            | tag!("crt.tensor.unsqueeze")
+        ) >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        _dims: opt!(
+            do_parse!(
+                tag!(",") >>
+                dims: parse_dims_list >>
+                _s2: space0 >>
+                ( dims )
+            )
+        ) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(in_operand),
+                operand3: Some(Token::Dims { dims: _dims.unwrap_or(vec![]) }),
+                operand4: None,
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// slice-assignment ::= out-operand = crt.tensor.slice! in-operand, begins-list, ends-list : type
+named!(
+    parse_slice_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.tensor.slice") >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        tag!(",") >>
+        _begins: parse_dims_list >>
+        _s2: space0 >>
+        tag!(",") >>
+        _ends: parse_dims_list >>
+        _s3: space0 >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(in_operand),
+                operand3: Some(Token::Dims { dims: _begins }),
+                operand4: Some(Token::Dims { dims: _ends }),
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// split-assignment ::= out-operand (, out-operand)+ = crt.tensor.split! in-operand, axis = integer : type
+// splits into equal parts, one for each out-operand
+named!(
+    parse_split_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        first_operand: parse_operand >>
+        tag!(",") >>
+        out_operands: parse_operand_list >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.tensor.split") >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        _axis: parse_axis_attr >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(Token::Variables {
+                    symbols: match out_operands {
+                        Token::Variables { symbols } => [vec![first_operand], symbols].concat(),
+                        _ => unreachable!(),
+                    },
+                }),
+                operand2: Some(in_operand),
+                operand3: Some(Token::Axis { axis: _axis }),
+                operand4: None,
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// concat-assignment ::= out-operand = crt.tensor.concat! lhs-operand, rhs-operand, axis = integer : type
+named!(
+    parse_concat_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.tensor.concat") >>
+        tag!("!") >>
+        _operand_lhs: parse_operand >>
+        tag!(",") >>
+        _operand_rhs: parse_operand >>
+        _axis: parse_axis_attr >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(_operand_lhs),
+                operand3: Some(_operand_rhs),
+                operand4: Some(Token::Axis { axis: _axis }),
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// conv2d-assignment ::= out-operand = crt.conv2d.f32! in-operand, weight-operand
+//     [, stride = pair] [, padding = pair] [, dilation = pair] [, groups = integer] : type
+// omitted attributes default to stride 1, padding 0, dilation 1 and a single group
+named!(
+    parse_conv2d_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.conv2d.f32") >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        tag!(",") >>
+        weight_operand: parse_operand >>
+        _stride: opt!(apply!(parse_pair_attr, "stride")) >>
+        _padding: opt!(apply!(parse_pair_attr, "padding")) >>
+        _dilation: opt!(apply!(parse_pair_attr, "dilation")) >>
+        _groups: opt!(parse_groups_attr) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(in_operand),
+                operand3: Some(weight_operand),
+                operand4: Some(Token::Dims {
+                    dims: [
+                        _stride.unwrap_or(vec![1, 1]),
+                        _padding.unwrap_or(vec![0, 0]),
+                        _dilation.unwrap_or(vec![1, 1]),
+                        vec![_groups.unwrap_or(1)],
+                    ]
+                    .concat(),
+                }),
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// pool2d-assignment ::= out-operand = pool-opcode in-operand, kernel = pair
+//     [, stride = pair] [, padding = pair] : type
+// stride defaults to the kernel size and padding defaults to 0
+named!(
+    parse_pool2d_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: alt!(
+            tag!("crt.maxpool2d.f32")
+            | tag!("crt.avgpool2d.f32")
+        ) >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        _kernel: apply!(parse_pair_attr, "kernel") >>
+        _stride: opt!(apply!(parse_pair_attr, "stride")) >>
+        _padding: opt!(apply!(parse_pair_attr, "padding")) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(in_operand),
+                operand3: Some(Token::Dims {
+                    dims: [
+                        _kernel.clone(),
+                        _stride.unwrap_or(_kernel),
+                        _padding.unwrap_or(vec![0, 0]),
+                    ]
+                    .concat(),
+                }),
+                operand4: None,
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// matmul-assignment ::= out-operand = crt.matmul.f32! lhs-operand, rhs-operand
+//     [, transpose_a = bool] [, transpose_b = bool] : type
+// encoded as plain matmul if no transpose flag is given
+named!(
+    parse_matmul_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.matmul.f32") >>
+        tag!("!") >>
+        _operand_lhs: parse_operand >>
+        tag!(",") >>
+        _operand_rhs: parse_operand >>
+        _transpose_a: opt!(apply!(parse_flag_attr, "transpose_a")) >>
+        _transpose_b: opt!(apply!(parse_flag_attr, "transpose_b")) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            match (_transpose_a, _transpose_b) {
+                (None, None) => AsmInstruction {
+                    opcode: Token::BytecodeOpCode { code: CRTOpCode::MATMULF32 },
+                    operand1: Some(out_operand),
+                    operand2: Some(_operand_lhs),
+                    operand3: Some(_operand_rhs),
+                    operand4: None,
+                    annotation: Some(dtype),
+                },
+                (transpose_a, transpose_b) => AsmInstruction {
+                    opcode: Token::BytecodeOpCode { code: CRTOpCode::MATMULTF32 },
+                    operand1: Some(out_operand),
+                    operand2: Some(_operand_lhs),
+                    operand3: Some(_operand_rhs),
+                    operand4: Some(Token::Dims {
+                        dims: vec![
+                            transpose_a.unwrap_or(false) as usize,
+                            transpose_b.unwrap_or(false) as usize,
+                        ],
+                    }),
+                    annotation: Some(dtype),
+                },
+            }
+        )
+    )
+);
+
+// fused-assignment ::= out-operand = crt.fused.f32! ( operand (, operand)* ), expr = [ step (, step)* ] : type
+// step ::= opcode ( integer (, integer)* ), integers index the operands first, then earlier steps
+named!(
+    parse_fused_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: tag!("crt.fused.f32") >>
+        tag!("!") >>
+        _s2: space0 >>
+        tag!("(") >>
+        _operands: separated_nonempty_list!(tag!(","), parse_operand) >>
+        tag!(")") >>
+        _expr: map_res!(parse_fused_expr, |expr: FusedExpr| expr.check(_operands.len()).map(|_| expr)) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(Token::Variables { symbols: _operands }),
+                operand3: Some(Token::FusedExpr { expr: _expr }),
+                operand4: None,
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+fn to_fused_opcode(mnemonic: CompleteStr) -> Result<CRTOpCode, String> {
+    match CRTOpCode::from(CompleteStr(mnemonic.trim())) {
+        code if is_fusible(code) => Ok(code),
+        _ => Err(format!("{} is not fusible", mnemonic)),
+    }
+}
+
+named!(
+    parse_fused_step<CompleteStr, FusedStep>,
+    ws!(
+        do_parse!(
+            _opcode: map_res!(take_until_and_consume1!("("), to_fused_opcode) >>
+            _operands: separated_nonempty_list!(tag!(","), parse_usize_literal) >>
+            tag!(")") >>
+            ( FusedStep { opcode: _opcode, operands: _operands } )
+        )
+    )
+);
+
+named!(
+    parse_fused_expr<CompleteStr, FusedExpr>,
+    ws!(
+        do_parse!(
+            tag!(",") >>
+            tag!("expr") >>
+            tag!("=") >>
+            tag!("[") >>
+            _steps: separated_nonempty_list!(tag!(","), parse_fused_step) >>
+            tag!("]") >>
+            ( FusedExpr { steps: _steps } )
+        )
+    )
+);
+
+// flag-attr ::= , name = true | false
+fn parse_flag_attr<'a>(input: CompleteStr<'a>, name: &str) -> IResult<CompleteStr<'a>, bool> {
+    ws!(
+        input,
+        do_parse!(
+            tag!(",") >>
+            tag!(name) >>
+            tag!("=") >>
+            _flag: alt!(tag!("true") | tag!("false")) >>
+            ( _flag == CompleteStr("true") )
+        )
+    )
+}
+
+// pair-attr ::= , name = [integer, integer], such as the height and width of stride
+fn parse_pair_attr<'a>(input: CompleteStr<'a>, name: &str) -> IResult<CompleteStr<'a>, Vec<usize>> {
+    ws!(
+        input,
+        do_parse!(
+            tag!(",") >>
+            tag!(name) >>
+            tag!("=") >>
+            tag!("[") >>
+            _first: parse_usize_literal >>
+            tag!(",") >>
+            _second: parse_usize_literal >>
+            tag!("]") >>
+            ( vec![_first, _second] )
+        )
+    )
+}
+
+named!(
+    parse_groups_attr<CompleteStr, usize>,
+    ws!(
+        do_parse!(
+            tag!(",") >>
+            tag!("groups") >>
+            tag!("=") >>
+            _groups: parse_usize_literal >>
+            ( _groups )
+        )
+    )
+);
+
+// reduce-assignment ::= out-operand = reduce-opcode in-operand [, axis = integer] [, keepdims = bool] : type
+// omits axis to reduce over all axes
+named!(
+    parse_reduce_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        _opcode: alt!(
+            tag!("crt.reduce.sum.f32")
+            | tag!("crt.reduce.mean.f32")
+            | tag!("crt.reduce.max.f32")
+            | tag!("crt.reduce.min.f32")
+            | tag!("crt.argmax.f32")
+            | tag!("crt.argmin.f32")
+        ) >>
+        tag!("!") >>
+        in_operand: parse_operand >>
+        _axis: opt!(parse_axis_attr) >>
+        _keepdims: opt!(parse_reduce_keepdims) >>
+        tag!(":") >>
+        dtype: parse_element_type >>
+        (
+            AsmInstruction {
+                opcode: Token::BytecodeOpCode { code: CRTOpCode::from(_opcode) },
+                operand1: Some(out_operand),
+                operand2: Some(in_operand),
+                operand3: Some(Token::ReduceAttr { axis: _axis, keepdims: _keepdims.unwrap_or(false) }),
+                operand4: None,
+                annotation: Some(dtype),
+            }
+        )
+    )
+);
+
+// axis-attr ::= , axis = (integer | register)
+named!(
+    parse_axis_attr<CompleteStr, AxisArg>,
+    ws!(
+        do_parse!(
+            tag!(",") >>
+            tag!("axis") >>
+            tag!("=") >>
+            _axis: alt!(
+                parse_register_index => { |index| AxisArg::Register(index) }
+                | do_parse!(
+                    _sign: opt!(tag!("-")) >>
+                    _axis: digit >>
+                    (
+                        match _sign {
+                            Some(_) => AxisArg::Static(-_axis.parse::<i8>().unwrap()),
+                            None => AxisArg::Static(_axis.parse::<i8>().unwrap()),
+                        }
+                    )
+                )
+            ) >>
+            ( _axis )
+        )
+    )
+);
 
-            }
+named!(
+    parse_reduce_keepdims<CompleteStr, bool>,
+    ws!(
+        do_parse!(
+            tag!(",") >>
+            tag!("keepdims") >>
+            tag!("=") >>
+            _keepdims: alt!(tag!("true") | tag!("false")) >>
+            ( _keepdims == CompleteStr("true") )
         )
     )
 );
 
 // unary-assignment ::= out-operand = opcode in-operand
-// in-operand ::= operand | numeric-literal
+// in-operand ::= operand : type | numeric-literal | tensor-literal | helper
+// tensor literals and helpers carry their own element types
 named!(
     parse_unary_assignment<CompleteStr, AsmInstruction>,
     do_parse!(
         out_operand: parse_operand >>
         tag!("=") >>
         _s1: space0 >>
-        opcode: parse_opcode >>
+        opcode: parse_generic_opcode >>
         in_operand: alt!(
-            parse_operand_with_type
-            | parse_float_literal_with_type
-            | parse_integer_literal_with_type
-            | parse_tensor_literal
-            | parse_helper_zeros
-            | parse_helper_ones
-            | parse_helper_uniform
-            | parse_helper_normal
+            parse_annotated_operand => { |(operand, dtype)| (operand, Some(dtype)) }
+            | parse_float_literal_with_type => { |literal| (literal, Some(ElementType::F32)) }
+            | parse_integer_literal_with_type => { |literal| (literal, Some(ElementType::I32)) }
+            | alt!(
+                parse_tensor_literal
+                | parse_helper_zeros
+                | parse_helper_ones
+                | parse_helper_full
+                | parse_helper_arange
+                | parse_helper_linspace
+                | parse_helper_eye
+                | parse_helper_uniform
+                | parse_helper_normal
+                | parse_helper_bernoulli
+                | parse_helper_truncated_normal
+            ) => { |literal| (literal, None) }
         ) >>
         (
             AsmInstruction {
                 opcode: opcode,
                 operand1: Some(out_operand),
-                operand2: Some(in_operand),
+                operand2: Some(in_operand.0),
                 operand3: None,
+                operand4: None,
+                annotation: in_operand.1,
+            }
+        )
+    )
+);
 
+// the literal of extended element types must be of the type of the opcode, i32 and f32
+// literals are parsed as unary assignments
+fn check_const_type(opcode: &Token, literal: &Token) -> Result<(), String> {
+    match (opcode, literal) {
+        (Token::BytecodeOpCode { code }, Token::ScalarLiteral { element_type, .. })
+            if code.to_const_element_type() == Some(*element_type) =>
+        {
+            Ok(())
+        }
+        _ => Err(format!("{:?} does not take literal {:?}", opcode, literal)),
+    }
+}
+
+// scalar-const-assignment ::= out-operand = crt.literal.const.type! scalar-literal
+named!(
+    parse_scalar_const_assignment<CompleteStr, AsmInstruction>,
+    do_parse!(
+        out_operand: parse_operand >>
+        tag!("=") >>
+        _s1: space0 >>
+        opcode: parse_opcode >>
+        literal: parse_scalar_literal_with_type >>
+        _checked: expr_res!(check_const_type(&opcode, &literal)) >>
+        (
+            AsmInstruction {
+                opcode: opcode,
+                operand1: Some(out_operand),
+                operand2: Some(literal),
+                operand3: None,
+                operand4: None,
+                annotation: None,
             }
         )
     )
@@ -152,6 +841,125 @@ named!(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_extended_dtype_ops() {
+        let result = parse_instruction(CompleteStr("%0 = crt.literal.const.i64! -5 : i64\n"));
+        let mut expected = vec![103, 0];
+        expected.extend_from_slice(&(-5i64).to_le_bytes());
+        assert_eq!(result.unwrap().1.to_bytes(), expected);
+
+        let result = parse_instruction(CompleteStr("%1 = crt.literal.const.f16! 0.5 : f16\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![98, 1, 0x00, 0x38]);
+
+        // literals must be of the type of the opcode
+        let result = parse_instruction(CompleteStr("%1 = crt.literal.const.f16! 0.5 : f64\n"));
+        assert_eq!(result.is_ok(), false);
+
+        let result = parse_instruction(CompleteStr("%2 = crt.cast.bf16! %1 : bf16\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![106, 2, 1]);
+
+        // i32 and f32 literals keep their tokens
+        let result = parse_instruction(CompleteStr("%0 = crt.literal.const.i32! 13 : i32\n"));
+        assert_eq!(
+            result.unwrap().1.operand2,
+            Some(Token::I32Literal { value: 13 })
+        );
+    }
+
+    #[test]
+    fn test_parse_conv2d_and_pool2d() {
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.conv2d.f32! %0, %1, stride = [2, 1], padding = [1, 1], groups = 2 : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.to_bytes()[..4], [78, 2, 0, 1]);
+        assert_eq!(
+            _inst.operand4,
+            Some(Token::Dims {
+                dims: vec![2, 1, 1, 1, 1, 1, 2]
+            })
+        );
+
+        let result = parse_instruction(CompleteStr("%2 = crt.conv2d.f32! %0, %1 : f32\n"));
+        assert_eq!(
+            result.unwrap().1.operand4,
+            Some(Token::Dims {
+                dims: vec![1, 1, 0, 0, 1, 1, 1]
+            })
+        );
+
+        let result = parse_instruction(CompleteStr(
+            "%1 = crt.maxpool2d.f32! %0, kernel = [3, 3], padding = [1, 1] : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.to_bytes()[..3], [79, 1, 0]);
+        assert_eq!(
+            _inst.operand3,
+            Some(Token::Dims {
+                dims: vec![3, 3, 3, 3, 1, 1]
+            })
+        );
+
+        let result = parse_instruction(CompleteStr(
+            "%1 = crt.avgpool2d.f32! %0, kernel = [2, 2], stride = [1, 1] : f32\n",
+        ));
+        assert_eq!(
+            result.unwrap().1.operand3,
+            Some(Token::Dims {
+                dims: vec![2, 2, 1, 1, 0, 0]
+            })
+        );
+
+        // pooling requires the kernel size
+        let result = parse_instruction(CompleteStr("%1 = crt.maxpool2d.f32! %0 : f32\n"));
+        assert_eq!(result.is_ok(), false);
+    }
+
+    #[test]
+    fn test_parse_fused() {
+        let result = parse_instruction(CompleteStr(
+            "%4 = crt.fused.f32! (%0, %1, %2), expr = [crt.add.f32(0, 1), crt.exp.f32(3), crt.mul.f32(4, 2)] : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.to_bytes()[..6], [117, 4, 3, 0, 1, 2]);
+        assert_eq!(
+            _inst.operand3,
+            Some(Token::FusedExpr {
+                expr: FusedExpr {
+                    steps: vec![
+                        FusedStep {
+                            opcode: CRTOpCode::ADDF32,
+                            operands: vec![0, 1]
+                        },
+                        FusedStep {
+                            opcode: CRTOpCode::EXPF32,
+                            operands: vec![3]
+                        },
+                        FusedStep {
+                            opcode: CRTOpCode::MULF32,
+                            operands: vec![4, 2]
+                        },
+                    ]
+                }
+            })
+        );
+
+        // steps are fusible ops over the operands and earlier steps
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.fused.f32! (%0, %1), expr = [crt.cmp.lt.f32(0, 1)] : f32\n",
+        ));
+        assert_eq!(result.is_ok(), false);
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.fused.f32! (%0), expr = [crt.exp.f32(1)] : f32\n",
+        ));
+        assert_eq!(result.is_ok(), false);
+        let result = parse_instruction(CompleteStr("%2 = crt.fused.f32! %0 : f32\n"));
+        assert_eq!(result.is_ok(), false);
+    }
+
     #[test]
     fn test_parse_halt_from_bytecode() {
         let result = parse_instruction(CompleteStr("halt\n"));
@@ -175,13 +983,131 @@ mod tests {
         assert_eq!(_bytes_result, vec![0])
     }
 
+    #[test]
+    fn test_parse_split_assignment() {
+        let result = parse_instruction(CompleteStr(
+            "%1, %2, %3 = crt.tensor.split! %0, axis = -1 : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(
+            result.unwrap().1.to_bytes(),
+            vec![97, 3, 1, 2, 3, 0, 1, 255]
+        );
+        // needs more than one output
+        let result = parse_instruction(CompleteStr("%1 = crt.tensor.split! %0, axis = 0 : f32\n"));
+        assert_eq!(result.is_ok(), false);
+    }
+
+    #[test]
+    fn test_parse_control_flow() {
+        let result = parse_instruction(CompleteStr("^head:\n"));
+        assert_eq!(
+            result.unwrap().1.opcode,
+            Token::Label {
+                name: "head".to_string()
+            }
+        );
+
+        let result = parse_instruction(CompleteStr("jmp ^head\n"));
+        let _inst = result.unwrap().1;
+        assert_eq!(
+            _inst.opcode,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::JMP
+            }
+        );
+        assert_eq!(
+            _inst.operand1,
+            Some(Token::Label {
+                name: "head".to_string()
+            })
+        );
+
+        let result = parse_instruction(CompleteStr("jmp_if %3, ^head\n"));
+        let _inst = result.unwrap().1;
+        assert_eq!(
+            _inst.opcode,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::JMPIF
+            }
+        );
+        assert_eq!(_inst.operand1, Some(Token::Variable { symbol: 3 }));
+
+        let result = parse_instruction(CompleteStr("jmp_if $1, ^head\n"));
+        let _inst = result.unwrap().1;
+        assert_eq!(
+            _inst.opcode,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::JMPIFR
+            }
+        );
+        assert_eq!(_inst.operand1, Some(Token::Register { index: 1 }));
+
+        let result = parse_instruction(CompleteStr("loop 10, ^head\n"));
+        let _inst = result.unwrap().1;
+        assert_eq!(
+            _inst.opcode,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::LOOP
+            }
+        );
+        assert_eq!(_inst.operand1, Some(Token::I32Literal { value: 10 }));
+        assert_eq!(
+            parse_instruction(CompleteStr("loop 0, ^head\n")).is_ok(),
+            false
+        );
+
+        assert_eq!(parse_instruction(CompleteStr("jmp head\n")).is_ok(), false);
+    }
+
+    #[test]
+    fn test_parse_register_ops() {
+        use crate::base::constants::REGISTER_DIM_FLAG;
+
+        let result = parse_instruction(CompleteStr("load $1, 1000\n"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1.to_bytes(), vec![1, 1, 3, 232]);
+        assert_eq!(
+            parse_instruction(CompleteStr("load $1, 70000\n")).is_ok(),
+            false
+        );
+
+        let result = parse_instruction(CompleteStr("$2 = crt.reg.add! $0, $1\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![86, 2, 0, 1]);
+        let result = parse_instruction(CompleteStr("$2 = crt.reg.cmp.lt! $0, $1 : i32\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![91, 2, 0, 1]);
+
+        let result = parse_instruction(CompleteStr("%3 = crt.reg.to_tensor! $2 : i32\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![95, 3, 2]);
+        let result = parse_instruction(CompleteStr("$4 = crt.reg.from_tensor! %3\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![96, 4, 3]);
+
+        // registers as shape and axis arguments
+        let result = parse_instruction(CompleteStr("%1 = crt.tensor.reshape! %0, [$0, 3] : f32\n"));
+        assert_eq!(
+            result.unwrap().1.operand3,
+            Some(Token::Dims {
+                dims: vec![REGISTER_DIM_FLAG, 3]
+            })
+        );
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.reduce.sum.f32! %1, axis = $3 : f32\n",
+        ));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![44, 2, 1, 2, 3, 0]);
+    }
+
     // tests that covers parse_halt
     #[test]
     fn test_parse_return() {
         let result = parse_instruction(CompleteStr("return %1\n"));
         assert_eq!(result.is_ok(), true);
         let _bytes_result = result.unwrap().1.to_bytes();
-        assert_eq!(_bytes_result, vec![17, 1])
+        assert_eq!(_bytes_result, vec![17, 1, 1]);
+
+        let result = parse_instruction(CompleteStr("return %3, %4\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![17, 2, 3, 4])
     }
 
     // tests that covers parse_binary
@@ -218,6 +1144,131 @@ mod tests {
         assert_eq!(_bytes_result, vec![5, 3, 2, 0])
     }
 
+    #[test]
+    fn test_parse_assignment_bitwise() {
+        let result = parse_instruction(CompleteStr("%3 = crt.xor.i32! %1, %2 : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![37, 3, 1, 2]);
+
+        let result = parse_instruction(CompleteStr("%3 = crt.shr.i32! %1, %2 : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![39, 3, 1, 2]);
+    }
+
+    #[test]
+    fn test_parse_compare_and_select() {
+        let result = parse_instruction(CompleteStr("%2 = crt.cmp.gt.f32! %0, %1 : bool\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![60, 2, 0, 1]);
+
+        let result = parse_instruction(CompleteStr("%3 = crt.select! %2, %0, %1 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![68, 3, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_parse_binary_and_ternary_math() {
+        let result = parse_instruction(CompleteStr("%2 = crt.atan2.f32! %0, %1 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![70, 2, 0, 1]);
+
+        let result = parse_instruction(CompleteStr("%2 = crt.rem.i32! %0, %1 : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![76, 2, 0, 1]);
+
+        let result = parse_instruction(CompleteStr("%4 = crt.fma.f32! %0, %1, %2 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.operand4, Some(Token::Variable { symbol: 2 }));
+        assert_eq!(_inst.to_bytes(), vec![77, 4, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_instruction_cast() {
+        let result = parse_instruction(CompleteStr("%2 = crt.cast.f32! %1 : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![40, 2, 1]);
+
+        let result = parse_instruction(CompleteStr("%2 = crt.bitcast.i32! %1 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![43, 2, 1]);
+    }
+
+    #[test]
+    fn test_parse_reduce_assignment() {
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.reduce.sum.f32! %1, axis = 1, keepdims = true : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![44, 2, 1, 1, 1, 1]);
+
+        let result = parse_instruction(CompleteStr("%2 = crt.argmax.f32! %1, axis = -1 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![48, 2, 1, 1, 255, 0]);
+
+        // full reduction
+        let result = parse_instruction(CompleteStr("%2 = crt.reduce.mean.f32! %1 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![45, 2, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_parse_shape_assignment() {
+        let result =
+            parse_instruction(CompleteStr("%6 = crt.tensor.transpose! %2, [1, 0] : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        // opcode, out, in, u16 len of dims bytes, bincode of Vec<usize>
+        let mut expected = vec![51, 6, 2, 24, 0];
+        expected.extend(bincode::serialize(&vec![1usize, 0]).unwrap());
+        assert_eq!(_bytes_result, expected);
+
+        let result = parse_instruction(CompleteStr("%1 = crt.tensor.reshape! %0, [3 2] : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(
+            result.unwrap().1.operand3,
+            Some(Token::Dims { dims: vec![3, 2] })
+        );
+
+        let result = parse_instruction(CompleteStr("%1 = crt.tensor.squeeze! %0 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result[0], CRTOpCode::SQUEEZE as u8);
+        assert_eq!(
+            _bytes_result[3..],
+            Token::Dims { dims: vec![] }.to_bytes()[..]
+        );
+    }
+
+    #[test]
+    fn test_parse_slice_and_concat() {
+        let result = parse_instruction(CompleteStr(
+            "%1 = crt.tensor.slice! %0, [0, 1], [2, 3] : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.operand3, Some(Token::Dims { dims: vec![0, 1] }));
+        assert_eq!(_inst.operand4, Some(Token::Dims { dims: vec![2, 3] }));
+
+        let result = parse_instruction(CompleteStr(
+            "%2 = crt.tensor.concat! %0, %1, axis = -1 : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![53, 2, 0, 1, 1, 255]);
+    }
+
     #[test]
     fn test_parse_integer_literal() {
         let result = parse_integer_literal(CompleteStr("23"));
@@ -268,9 +1319,9 @@ mod tests {
         assert_eq!(
             _bytes_result,
             vec![
-                12, 0, 32, 0, 6, 0, 0, 0, 0, 0, 0, 0, 205, 204, 140, 63, 205, 204, 12, 64, 51, 51,
-                83, 64, 205, 204, 140, 64, 0, 0, 176, 64, 51, 51, 211, 64, 24, 0, 2, 0, 0, 0, 0, 0,
-                0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0
+                12, 0, 1, 24, 0, 205, 204, 140, 63, 205, 204, 12, 64, 51, 51, 83, 64, 205, 204,
+                140, 64, 0, 0, 176, 64, 51, 51, 211, 64, 24, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0,
+                0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0
             ]
         )
     }
@@ -284,6 +1335,38 @@ mod tests {
         assert_eq!(_bytes_result, vec![13, 0, 7, 9]);
     }
 
+    #[test]
+    fn test_instruction_assignment_matmul_transpose() {
+        let result = parse_instruction(CompleteStr(
+            "%0 = crt.matmul.f32! %7, %9, transpose_b = true : f32\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let _inst = result.unwrap().1;
+        assert_eq!(_inst.to_bytes()[..4], [81, 0, 7, 9]);
+        assert_eq!(_inst.operand4, Some(Token::Dims { dims: vec![0, 1] }));
+
+        let result = parse_instruction(CompleteStr(
+            "%0 = crt.matmul.f32! %7, %9, transpose_a = true, transpose_b = false : f32\n",
+        ));
+        assert_eq!(
+            result.unwrap().1.operand4,
+            Some(Token::Dims { dims: vec![1, 0] })
+        );
+    }
+
+    #[test]
+    fn test_instruction_unary_math() {
+        let result = parse_instruction(CompleteStr("%1 = crt.tanh.f32! %0 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![33, 1, 0]);
+
+        let result = parse_instruction(CompleteStr("%4 = crt.abs.f32! %2 : f32\n"));
+        assert_eq!(result.is_ok(), true);
+        let _bytes_result = result.unwrap().1.to_bytes();
+        assert_eq!(_bytes_result, vec![18, 4, 2]);
+    }
+
     #[test]
     fn test_instruction_tensor_literal_with_zeros_helper() {
         // w. \n
@@ -293,14 +1376,37 @@ mod tests {
         println!("{:?}", result);
         assert_eq!(result.is_ok(), true);
         let _bytes_result = result.unwrap().1.to_bytes();
-        // assert_eq!(
-        //     _bytes_result,
-        //     vec![
-        //         12, 0, 32, 0, 6, 0, 0, 0, 0, 0, 0, 0, 205, 204, 140, 63, 205, 204, 12, 64, 51, 51,
-        //         83, 64, 205, 204, 140, 64, 0, 0, 176, 64, 51, 51, 211, 64, 24, 0, 2, 0, 0, 0, 0, 0,
-        //         0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0
-        //     ]
-        // )
+        assert_eq!(
+            _bytes_result,
+            vec![
+                14, 0, 1, 0, 0, 0, 0, 24, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0,
+                0, 0, 0, 0, 0, 0
+            ]
+        )
+    }
+
+    #[test]
+    fn test_instruction_generator_helpers() {
+        let result = parse_instruction(CompleteStr(
+            "%0 = crt.helper.range.tensor! arange<0, 6, 2> : i32\n",
+        ));
+        let mut expected = vec![112, 0, 0, 0];
+        for param in [0f64, 6., 2.] {
+            expected.extend_from_slice(&param.to_le_bytes());
+        }
+        assert_eq!(result.unwrap().1.to_bytes(), expected);
+
+        let result = parse_instruction(CompleteStr("%1 = crt.helper.eye.tensor! eye<2> : f32\n"));
+        assert_eq!(
+            result.unwrap().1.to_bytes(),
+            vec![
+                113, 1, 1, 24, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0,
+                0, 0
+            ]
+        );
+
+        let result = parse_instruction(CompleteStr("%2 = crt.ones_like! %1 : f32\n"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![115, 2, 1]);
     }
 
     #[test]
@@ -326,19 +1432,18 @@ mod tests {
     fn test_instruction_tensor_literal_with_uniform_helper() {
         // w. \n
         let result = parse_instruction(CompleteStr(
-            "%0 = crt.helper.rng.tensor! uniform<[2 3]>: f32\n",
+            "%0 = crt.helper.rng.tensor! uniform<[2 3], -1., 1., 5>: f32\n",
         ));
         println!("{:?}", result);
         assert_eq!(result.is_ok(), true);
         let _bytes_result = result.unwrap().1.to_bytes();
-        // assert_eq!(
-        //     _bytes_result,
-        //     vec![
-        //         12, 0, 32, 0, 6, 0, 0, 0, 0, 0, 0, 0, 205, 204, 140, 63, 205, 204, 12, 64, 51, 51,
-        //         83, 64, 205, 204, 140, 64, 0, 0, 176, 64, 51, 51, 211, 64, 24, 0, 2, 0, 0, 0, 0, 0,
-        //         0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0
-        //     ]
-        // )
+        assert_eq!(
+            _bytes_result,
+            vec![
+                15, 0, 0, 1, 0, 0, 128, 191, 0, 0, 128, 63, 5, 0, 0, 0, 0, 0, 0, 0, 24, 0, 2, 0, 0,
+                0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0
+            ]
+        )
     }
 
     #[test]
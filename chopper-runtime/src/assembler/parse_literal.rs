@@ -2,8 +2,13 @@
 use nom::types::CompleteStr;
 use nom::*;
 
+use half::{bf16, f16};
+
 use super::assembler_base::*;
+use super::parse_operand::parse_register_index;
 use super::parse_type::*;
+use crate::base::constants::REGISTER_DIM_FLAG;
+use crate::base::ElementType;
 
 // numeric-literal ::= integer-literal | float-literal
 named!(pub parse_numeric_literal<CompleteStr, Token>,
@@ -16,19 +21,42 @@ named!(pub parse_numeric_literal<CompleteStr, Token>,
     )
 );
 
-// TODO with type, need a none-type version
-// "dense<[1.1 2.2 3.3 4.4 5.5 6.6], shape=[2 3]>"
+// tensor-literal ::= dense<[scalar*], shape=[dims]> : element-type, such as
+// "dense<[1.1 2.2 3.3 4.4 5.5 6.6], shape=[2 3]>: f32" or "dense<[0 2 1], shape=[3]> : i32"
 named!(pub parse_tensor_literal<CompleteStr, Token>,
     do_parse!(
         _s: space0 >>
         tag!("dense<") >>
-        datalist: parse_float_list >>
+        datalist: parse_scalar_text_list >>
         tag!(", shape=") >>
         shapelist: parse_integer_list >>
         tag!(">") >>
-        type_tag: parse_f32_type >>
+        _s2: space0 >>
+        tag!(":") >>
+        element_type: parse_element_type >>
+        raw_data: expr_res!(encode_dense(&datalist, element_type, &shapelist)) >>
         (
-            Token::Tensor { raw_data: datalist, shape: shapelist }
+            Token::Tensor { element_type: element_type, raw_data: raw_data, shape: shapelist }
+        )
+    )
+);
+
+pub fn is_scalar_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '.' || c == '-' || c == '+'
+}
+
+// scalars of dense literals are kept as text until the element type is known
+named!(parse_scalar_text_list<CompleteStr, Vec<CompleteStr>>,
+    do_parse!(
+        _s: space0 >>
+        tag!("[") >>
+        data: many1!(
+            preceded!(space0, take_while1!(is_scalar_char))
+        ) >>
+        _s2: space0 >>
+        tag!("]") >>
+        (
+            data
         )
     )
 );
@@ -81,6 +109,35 @@ named!(pub parse_usize_literal<CompleteStr, usize>,
     )
 );
 
+// dim ::= integer | register, registers are flagged with REGISTER_DIM_FLAG
+named!(parse_dim<CompleteStr, usize>,
+    alt!(
+        parse_usize_literal
+        | parse_register_index => { |index| REGISTER_DIM_FLAG | index as usize }
+    )
+);
+
+// dims-list ::= [ (dim [,])* ], separated by commas or spaces, may be empty
+named!(pub parse_dims_list<CompleteStr, Vec<usize>>,
+    do_parse!(
+        _s: space0 >>
+        tag!("[") >>
+        data: many0!(
+            do_parse!(
+                data: parse_dim >>
+                _s: space0 >>
+                opt!(tag!(",")) >>
+                ( data )
+            )
+        ) >>
+        _s: space0 >>
+        tag!("]") >>
+        (
+            data
+        )
+    )
+);
+
 named!(pub parse_integer_literal<CompleteStr, Token>,
     do_parse!(
         _s: space0 >>
@@ -91,10 +148,11 @@ named!(pub parse_integer_literal<CompleteStr, Token>,
     )
 );
 
+// typed integers may be negative, such as `-3 : i32`
 named!(pub parse_integer_literal_with_type<CompleteStr, Token>,
     do_parse!(
         _s: space0 >>
-        data: digit >>
+        data: recognize!(pair!(opt!(tag!("-")), digit)) >>
         type_tag: parse_i32_type >>
         (
             Token::I32Literal { value: data.parse::<i32>().unwrap() }
@@ -123,6 +181,80 @@ named!(pub parse_float_literal_with_type<CompleteStr, Token>,
     )
 );
 
+// encodes the text of a scalar literal in little endian, integers must fit into the element
+// type and bools are written as true, false, 1 or 0
+pub fn encode_scalar(text: &str, element_type: ElementType) -> Result<Vec<u8>, String> {
+    let invalid = || format!("{} is not a literal of {:?}", text, element_type);
+    let float = || text.parse::<f64>().map_err(|_| invalid());
+    let bytes = match element_type {
+        ElementType::F16 => f16::from_f64(float()?).to_bits().to_le_bytes().to_vec(),
+        ElementType::BF16 => bf16::from_f64(float()?).to_bits().to_le_bytes().to_vec(),
+        ElementType::F32 => (float()? as f32).to_le_bytes().to_vec(),
+        ElementType::F64 => float()?.to_le_bytes().to_vec(),
+        ElementType::I8 => text
+            .parse::<i8>()
+            .map_err(|_| invalid())?
+            .to_le_bytes()
+            .to_vec(),
+        ElementType::U8 => text
+            .parse::<u8>()
+            .map_err(|_| invalid())?
+            .to_le_bytes()
+            .to_vec(),
+        ElementType::I32 => text
+            .parse::<i32>()
+            .map_err(|_| invalid())?
+            .to_le_bytes()
+            .to_vec(),
+        ElementType::I64 => text
+            .parse::<i64>()
+            .map_err(|_| invalid())?
+            .to_le_bytes()
+            .to_vec(),
+        ElementType::BOOL => match text {
+            "true" | "1" => vec![1],
+            "false" | "0" => vec![0],
+            _ => return Err(invalid()),
+        },
+    };
+    Ok(bytes)
+}
+
+// encodes the scalars of a dense literal, which must fill the shape
+fn encode_dense(
+    texts: &[CompleteStr],
+    element_type: ElementType,
+    shape: &[usize],
+) -> Result<Vec<u8>, String> {
+    if texts.len() != shape.iter().product::<usize>() {
+        return Err(format!(
+            "{} scalars do not fill shape {:?}",
+            texts.len(),
+            shape
+        ));
+    }
+    let mut bytes = vec![];
+    for text in texts {
+        bytes.extend(encode_scalar(text, element_type)?);
+    }
+    Ok(bytes)
+}
+
+// scalar-literal ::= number : element-type, such as `-3 : i8` or `0.5 : bf16`
+named!(pub parse_scalar_literal_with_type<CompleteStr, Token>,
+    do_parse!(
+        _s: space0 >>
+        text: take_while1!(is_scalar_char) >>
+        _s2: space0 >>
+        tag!(":") >>
+        element_type: parse_element_type >>
+        bytes: expr_res!(encode_scalar(&text, element_type)) >>
+        (
+            Token::ScalarLiteral { element_type: element_type, bytes: bytes }
+        )
+    )
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +272,9 @@ mod tests {
         assert_eq!(result.is_ok(), true);
         let _bytes_result = result.unwrap().1;
         assert_eq!(_bytes_result, Token::I32Literal { value: 23 });
+
+        let result = parse_integer_literal_with_type(CompleteStr(" -23 : i32\n"));
+        assert_eq!(result.unwrap().1, Token::I32Literal { value: -23 });
     }
 
     #[test]
@@ -172,6 +307,25 @@ mod tests {
         assert_eq!(_bytes_result, vec![23, 2]);
     }
 
+    #[test]
+    fn test_parse_dims_list() {
+        let result = parse_dims_list(CompleteStr("[1, 0]"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, vec![1, 0]);
+
+        let result = parse_dims_list(CompleteStr(" [2 3 4]"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, vec![2, 3, 4]);
+
+        let result = parse_dims_list(CompleteStr("[]"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, Vec::<usize>::new());
+
+        let result = parse_dims_list(CompleteStr("[$2, 3]"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, vec![REGISTER_DIM_FLAG | 2, 3]);
+    }
+
     #[test]
     fn test_parse_float_literal() {
         // w.o. \n
@@ -217,6 +371,41 @@ mod tests {
         assert_eq!(_bytes_result, vec![2.3, 2.2]);
     }
 
+    #[test]
+    fn test_parse_scalar_literal_with_type() {
+        let result = parse_scalar_literal_with_type(CompleteStr(" -3 : i8\n"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::ScalarLiteral {
+                element_type: ElementType::I8,
+                bytes: vec![0xfd]
+            }
+        );
+        let result = parse_scalar_literal_with_type(CompleteStr("1.5 : f64"));
+        assert_eq!(result.unwrap().1.to_bytes(), 1.5f64.to_le_bytes().to_vec());
+        let result = parse_scalar_literal_with_type(CompleteStr("-2 : bf16"));
+        assert_eq!(
+            result.unwrap().1.to_bytes(),
+            bf16::from_f32(-2.0).to_bits().to_le_bytes().to_vec()
+        );
+        let result = parse_scalar_literal_with_type(CompleteStr("true : bool"));
+        assert_eq!(result.unwrap().1.to_bytes(), vec![1]);
+
+        // out of range or mistyped literals
+        assert_eq!(
+            parse_scalar_literal_with_type(CompleteStr("300 : u8")).is_ok(),
+            false
+        );
+        assert_eq!(
+            parse_scalar_literal_with_type(CompleteStr("1.5 : i64")).is_ok(),
+            false
+        );
+        assert_eq!(
+            parse_scalar_literal_with_type(CompleteStr("2 : bool")).is_ok(),
+            false
+        );
+    }
+
     #[test]
     fn test_parse_tensor_literal() {
         // w.o. \n
@@ -228,9 +417,43 @@ mod tests {
         assert_eq!(
             _bytes_result,
             Token::Tensor {
-                raw_data: vec![1.1, 2.2, 3.3, 4.4, 5.5, 6.6],
+                element_type: ElementType::F32,
+                raw_data: [1.1f32, 2.2, 3.3, 4.4, 5.5, 6.6]
+                    .iter()
+                    .flat_map(|x| x.to_le_bytes())
+                    .collect(),
                 shape: vec![2, 3]
             }
         );
+
+        // integer and bool literals carry their element type
+        let result = parse_tensor_literal(CompleteStr("dense<[0 -2 1], shape=[3]> : i32\n"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::Tensor {
+                element_type: ElementType::I32,
+                raw_data: vec![0, 0, 0, 0, 254, 255, 255, 255, 1, 0, 0, 0],
+                shape: vec![3]
+            }
+        );
+        let result =
+            parse_tensor_literal(CompleteStr("dense<[true false 1 0], shape=[2 2]> : bool"));
+        assert_eq!(
+            result.unwrap().1.to_bytes(),
+            vec![
+                2, 4, 0, 1, 0, 1, 0, 24, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2, 0,
+                0, 0, 0, 0, 0, 0
+            ]
+        );
+
+        // scalars must fit into the element type and fill the shape
+        assert_eq!(
+            parse_tensor_literal(CompleteStr("dense<[1.5 2], shape=[2]> : i32")).is_ok(),
+            false
+        );
+        assert_eq!(
+            parse_tensor_literal(CompleteStr("dense<[1 2 3], shape=[2]> : i32")).is_ok(),
+            false
+        );
     }
 }
@@ -3,15 +3,42 @@ use nom::types::CompleteStr;
 use nom::*;
 
 use super::assembler_base::*;
+use super::parse_function::*;
 use super::parse_instruction::*;
 
+// top-level items of a program
+enum ProgramItem {
+    Instruction(AsmInstruction),
+    Function(Function),
+}
+
+// program ::= (function | instruction)+
 named!(pub parse_program<CompleteStr, Program>,
     do_parse!(
-        instructions: many1!(
-            parse_instruction
+        // instructions such as halt, return and jumps start with keywords, which do not skip
+        // the line breaks before them
+        items: many1!(
+            preceded!(
+                multispace0,
+                alt!(
+                    parse_function => { |function| ProgramItem::Function(function) }
+                    | parse_instruction => { |inst| ProgramItem::Instruction(inst) }
+                )
+            )
         ) >> (
-            Program {
-                instructions: instructions
+            {
+                let mut instructions = vec![];
+                let mut functions = vec![];
+                for item in items {
+                    match item {
+                        ProgramItem::Instruction(inst) => instructions.push(inst),
+                        ProgramItem::Function(function) => functions.push(function),
+                    }
+                }
+                Program {
+                    instructions: instructions,
+                    functions: functions,
+                }
             }
         )
     )
@@ -46,4 +73,44 @@ mod tests {
         assert_eq!(full_result.is_ok(), true);
         let _bytes_result = full_result.unwrap().1.to_bytes();
     }
+
+    #[test]
+    fn test_parse_program_with_functions() {
+        let result = parse_program(CompleteStr(
+            "%0 = crt.literal.const.i32! 13 : i32
+func @double(%1: tensor<?xf32>) -> (tensor<?xf32>) {
+    %2 = crt.add.f32! %1, %1 : f32
+    return %2
+}
+func @square(%1: tensor<?xf32>) -> (tensor<?xf32>) {
+    %2 = crt.mul.f32! %1, %1 : f32
+    return %2
+}
+",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let program = result.unwrap().1;
+        // function bodies are not part of the top-level bytes
+        assert_eq!(program.to_bytes(), vec![6, 0, 13, 0, 0, 0]);
+        assert_eq!(program.functions.len(), 2);
+        assert_eq!(program.function("double").is_some(), true);
+        assert_eq!(program.function("square").unwrap().result_slots(), vec![2]);
+        assert_eq!(program.function("cube").is_none(), true);
+    }
+
+    #[test]
+    fn test_parse_program_with_labels() {
+        let result = parse_program(CompleteStr(
+            "^head:\n%0 = crt.exp.f32! %0 : f32\nloop 3, ^head\nreturn %0\n",
+        ));
+        assert_eq!(result.is_ok(), true);
+        let (rest, program) = result.unwrap();
+        assert_eq!(rest, CompleteStr(""));
+        assert_eq!(program.instructions.len(), 4);
+        // the loop jumps back over itself (9 bytes) and the exp (3 bytes)
+        let mut expected = vec![16, 0, 0, 85, 3, 0, 0, 0];
+        expected.extend_from_slice(&(-12i32).to_le_bytes());
+        expected.extend_from_slice(&[17, 1, 0]);
+        assert_eq!(program.to_bytes(), expected);
+    }
 }
@@ -8,12 +8,48 @@ use crate::instruction::CRTOpCode;
 // use assembler_base::*;
 use crate::assembler::assembler_base::Token;
 
-// opcode
+fn to_opcode(mnemonic: CompleteStr) -> Result<Token, String> {
+    match CRTOpCode::from(mnemonic) {
+        CRTOpCode::ILLEGAL => Err(format!("unknown opcode {}", mnemonic)),
+        code => Ok(Token::BytecodeOpCode { code: code }),
+    }
+}
+
+// opcode, unknown mnemonics fail to parse rather than assemble into ILLEGAL
 named!(pub parse_opcode<CompleteStr, Token>,
     do_parse!(
         // use ! tag to specify the bytecode opcode for simplicity
-        opcode: take_until_and_consume1!("!")
-        >> (Token::BytecodeOpCode { code: CRTOpCode::from(opcode) })
+        opcode: map_res!(take_until_and_consume1!("!"), to_opcode)
+        >> ( opcode )
+    )
+);
+
+// ops with attributes of their own syntax, generic assignments would take them without the
+// attributes once their own rules fail
+fn has_own_syntax(code: CRTOpCode) -> bool {
+    match code {
+        CRTOpCode::CONV2DF32
+        | CRTOpCode::MAXPOOL2DF32
+        | CRTOpCode::AVGPOOL2DF32
+        | CRTOpCode::FUSEDF32 => true,
+        _ => false,
+    }
+}
+
+fn to_generic_opcode(mnemonic: CompleteStr) -> Result<Token, String> {
+    match to_opcode(mnemonic)? {
+        Token::BytecodeOpCode { code } if has_own_syntax(code) => {
+            Err(format!("{} takes attributes of its own syntax", mnemonic))
+        }
+        opcode => Ok(opcode),
+    }
+}
+
+// opcode of generic unary, binary and ternary assignments
+named!(pub parse_generic_opcode<CompleteStr, Token>,
+    do_parse!(
+        opcode: map_res!(take_until_and_consume1!("!"), to_generic_opcode)
+        >> ( opcode )
     )
 );
 
@@ -35,6 +71,29 @@ mod tests {
         assert_eq!(opcode, CRTOpCode::MATMULF32);
     }
 
+    #[test]
+    fn test_parse_generic_opcode() {
+        let result = parse_generic_opcode(CompleteStr("crt.add.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::ADDF32
+            }
+        );
+        assert_eq!(
+            parse_generic_opcode(CompleteStr("crt.conv2d.f32!")).is_ok(),
+            false
+        );
+        assert_eq!(
+            parse_generic_opcode(CompleteStr("crt.maxpool2d.f32!")).is_ok(),
+            false
+        );
+        assert_eq!(
+            parse_opcode(CompleteStr("crt.maxpool2d.f32!")).is_ok(),
+            true
+        );
+    }
+
     #[test]
     fn test_parse_halt() {
         // test halt
@@ -49,45 +108,16 @@ mod tests {
 
     #[test]
     fn test_load_op() {
+        // unknown mnemonics are rejected
         let result = parse_opcode(CompleteStr("lload!"));
-        assert_eq!(result.is_ok(), true);
-        let (rest, token) = result.unwrap();
-        assert_eq!(
-            token,
-            Token::BytecodeOpCode {
-                code: CRTOpCode::ILLEGAL
-            }
-        );
-        assert_eq!(rest, CompleteStr(""));
-        let result = parse_opcode(CompleteStr("lload!"));
-        assert_eq!(
-            result.unwrap().1,
-            Token::BytecodeOpCode {
-                code: CRTOpCode::ILLEGAL
-            }
-        );
+        assert_eq!(result.is_ok(), false);
         let result = parse_opcode(CompleteStr("l oad!"));
-        assert_eq!(
-            result.unwrap().1,
-            Token::BytecodeOpCode {
-                code: CRTOpCode::ILLEGAL
-            }
-        );
+        assert_eq!(result.is_ok(), false);
         // case sensitive
         let result = parse_opcode(CompleteStr("Load!"));
-        assert_eq!(
-            result.unwrap().1,
-            Token::BytecodeOpCode {
-                code: CRTOpCode::ILLEGAL
-            }
-        );
+        assert_eq!(result.is_ok(), false);
         let result = parse_opcode(CompleteStr("LoAd!"));
-        assert_eq!(
-            result.unwrap().1,
-            Token::BytecodeOpCode {
-                code: CRTOpCode::ILLEGAL
-            }
-        );
+        assert_eq!(result.is_ok(), false);
         // test load
         let result = parse_opcode(CompleteStr("load!"));
         assert_eq!(
@@ -167,6 +197,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_unary_code() {
+        let result = parse_opcode(CompleteStr("crt.exp.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::EXPF32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.tanh.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::TANHF32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.rsqrt.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::RSQRTF32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.log1p.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::LOG1PF32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.floor.f32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::FLOORF32
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_bitwise_code() {
+        let result = parse_opcode(CompleteStr("crt.not.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::NOTI32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.and.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::ANDI32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.or.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::ORI32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.xor.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::XORI32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.shl.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::SHLI32
+            }
+        );
+        let result = parse_opcode(CompleteStr("crt.shr.i32!"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::BytecodeOpCode {
+                code: CRTOpCode::SHRI32
+            }
+        );
+    }
+
     // test parse crt literal const op only
     #[test]
     fn test_literal_const_i32_op() {
@@ -4,19 +4,32 @@ use nom::*;
 
 use super::parse_type::*;
 use crate::assembler::assembler_base::Token;
+use crate::base::constants::NAMED_SLOT_BASE;
+use crate::base::ElementType;
 
+// numbered values take their number as slot, other names are resolved into slots on assembly
+fn to_operand(symbol: CompleteStr) -> Result<Token, String> {
+    if !symbol.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(Token::NamedVariable {
+            name: symbol.to_string(),
+        });
+    }
+    match symbol.parse::<usize>() {
+        Ok(slot) if slot < NAMED_SLOT_BASE => Ok(Token::Variable { symbol: slot }),
+        _ => Err(format!("%{} exceeds the numbered slots", symbol)),
+    }
+}
+
+// operand ::= % (integer | identifier)
 named!(pub parse_operand <CompleteStr, Token>,
     ws!(
         do_parse!(
             tag!("%") >>
-            //lab_symbol: alphanumeric1 >>
-            lab_symbol: digit >>
-            (
-                Token::Variable{
-                  symbol: lab_symbol.parse::<u8>().unwrap(),
-                  // symbol: lab_symbol.parse::<String>().unwrap(),
-                }
-            )
+            operand: map_res!(
+                take_while1!(|c: char| c.is_alphanumeric() || c == '_'),
+                to_operand
+            ) >>
+            ( operand )
         )
     )
 );
@@ -24,16 +37,62 @@ named!(pub parse_operand <CompleteStr, Token>,
 named!(pub parse_operand_with_type <CompleteStr, Token>,
     ws!(
         do_parse!(
-            tag!("%") >>
-            //lab_symbol: alphanumeric1 >>
-            lab_symbol: digit >>
-            // TODO hardcoded to f32, consider how types suits operand
-            type_tag: parse_f32_type >>
+            operand: parse_operand >>
+            tag!(":") >>
+            type_tag: parse_type >>
+            ( operand )
+        )
+    )
+);
+
+// operand : type, keeps the element type of the annotation
+named!(pub parse_annotated_operand <CompleteStr, (Token, ElementType)>,
+    ws!(
+        do_parse!(
+            operand: parse_operand >>
+            tag!(":") >>
+            element_type: parse_element_type >>
+            ( (operand, element_type) )
+        )
+    )
+);
+
+// operand-list ::= operand (, operand)*
+named!(pub parse_operand_list <CompleteStr, Token>,
+    do_parse!(
+        operands: separated_nonempty_list!(tag!(","), parse_operand) >>
+        (
+            Token::Variables { symbols: operands }
+        )
+    )
+);
+
+// register ::= $ integer
+named!(pub parse_register_index <CompleteStr, u8>,
+    ws!(
+        do_parse!(
+            tag!("$") >>
+            index: digit >>
+            ( index.parse::<u8>().unwrap() )
+        )
+    )
+);
+
+named!(pub parse_register <CompleteStr, Token>,
+    do_parse!(
+        index: parse_register_index >>
+        ( Token::Register { index: index } )
+    )
+);
+
+// label ::= ^ identifier
+named!(pub parse_label <CompleteStr, Token>,
+    ws!(
+        do_parse!(
+            tag!("^") >>
+            name: take_while1!(|c: char| c.is_alphanumeric() || c == '_') >>
             (
-                Token::Variable{
-                  symbol: lab_symbol.parse::<u8>().unwrap(),
-                  // symbol: lab_symbol.parse::<String>().unwrap(),
-                }
+                Token::Label { name: name.to_string() }
             )
         )
     )
@@ -48,11 +107,63 @@ mod tests {
         let result = parse_operand(CompleteStr("%0"));
         assert_eq!(result.is_ok(), true);
         let _raw_result = result.unwrap().1;
-        assert_eq!(_raw_result, Token::Variable { symbol: 0 as u8 });
+        assert_eq!(_raw_result, Token::Variable { symbol: 0 });
         let result = parse_operand(CompleteStr("0"));
         assert_eq!(result.is_ok(), false);
-        // TODO add label max id check
-        let result = parse_operand(CompleteStr("%arg1"));
+        let result = parse_operand(CompleteStr("%300"));
+        assert_eq!(result.unwrap().1, Token::Variable { symbol: 300 });
+        let result = parse_operand(CompleteStr("%w_q"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::NamedVariable {
+                name: "w_q".to_string()
+            }
+        );
+        let result = parse_operand(CompleteStr("%99999999"));
         assert_eq!(result.is_ok(), false);
     }
+
+    #[test]
+    fn test_parse_operand_with_type() {
+        let result = parse_operand_with_type(CompleteStr("%3 : f32"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, Token::Variable { symbol: 3 });
+        let result = parse_operand_with_type(CompleteStr("%3 : i32\n"));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.unwrap().1, Token::Variable { symbol: 3 });
+    }
+
+    #[test]
+    fn test_parse_operand_list() {
+        let result = parse_operand_list(CompleteStr("%3, %4\n"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::Variables {
+                symbols: vec![Token::Variable { symbol: 3 }, Token::Variable { symbol: 4 }]
+            }
+        );
+        let result = parse_operand_list(CompleteStr("%3"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::Variables {
+                symbols: vec![Token::Variable { symbol: 3 }]
+            }
+        );
+        assert_eq!(parse_operand_list(CompleteStr("")).is_ok(), false);
+    }
+
+    #[test]
+    fn test_parse_register_and_label() {
+        let result = parse_register(CompleteStr("$7"));
+        assert_eq!(result.unwrap().1, Token::Register { index: 7 });
+        assert_eq!(parse_register(CompleteStr("%7")).is_ok(), false);
+        let result = parse_label(CompleteStr(" ^loop_head\n"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::Label {
+                name: "loop_head".to_string()
+            }
+        );
+        assert_eq!(parse_label(CompleteStr("^")).is_ok(), false);
+    }
 }
@@ -5,13 +5,43 @@ use nom::*;
 use crate::assembler::assembler_base::*;
 use crate::base::*;
 
-named!(pub parse_type<CompleteStr, Token>,
+named!(pub parse_element_type<CompleteStr, ElementType>,
     do_parse!(
         _s: space0 >>
         token: alt!(
-            tag!("i32") | tag!("f32")
+            tag!("i32") | tag!("f32") | tag!("bool") | tag!("f16") | tag!("bf16") | tag!("f64")
+            | tag!("i8") | tag!("u8") | tag!("i64")
         ) >>
-        ( Token::DType { element_type: ElementType::from(token) } )
+        ( ElementType::from(token) )
+    )
+);
+
+named!(pub parse_type<CompleteStr, Token>,
+    do_parse!(
+        element_type: parse_element_type >>
+        ( Token::DType { element_type: element_type } )
+    )
+);
+
+// tensor-type ::= tensor<(dim x)* element-type>, dim ::= integer | ?
+// such as tensor<2x3xf32>, where ? marks a dim of any size
+named!(pub parse_tensor_type<CompleteStr, Token>,
+    do_parse!(
+        _s: space0 >>
+        tag!("tensor<") >>
+        shape: many0!(
+            do_parse!(
+                dim: alt!(
+                    digit => { |d: CompleteStr| Some(d.parse::<usize>().unwrap()) }
+                    | tag!("?") => { |_| None }
+                ) >>
+                tag!("x") >>
+                ( dim )
+            )
+        ) >>
+        element_type: parse_element_type >>
+        tag!(">") >>
+        ( Token::TensorType { element_type: element_type, shape: shape } )
     )
 );
 
@@ -59,5 +89,67 @@ mod tests {
                 element_type: ElementType::F32
             }
         );
+
+        let result = parse_type(CompleteStr(" bool\n"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::DType {
+                element_type: ElementType::BOOL
+            }
+        );
+
+        for (suffix, element_type) in [
+            ("f16", ElementType::F16),
+            ("bf16", ElementType::BF16),
+            ("f64", ElementType::F64),
+            ("i8", ElementType::I8),
+            ("u8", ElementType::U8),
+            ("i64", ElementType::I64),
+        ] {
+            let result = parse_type(CompleteStr(suffix));
+            assert_eq!(result.unwrap().1, Token::DType { element_type });
+        }
+    }
+
+    #[test]
+    fn test_parse_tensor_type() {
+        let result = parse_tensor_type(CompleteStr("tensor<2x3xf32>"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::TensorType {
+                element_type: ElementType::F32,
+                shape: vec![Some(2), Some(3)],
+            }
+        );
+
+        let result = parse_tensor_type(CompleteStr(" tensor<?x4xi32>"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::TensorType {
+                element_type: ElementType::I32,
+                shape: vec![None, Some(4)],
+            }
+        );
+
+        // 0-d tensor
+        let result = parse_tensor_type(CompleteStr("tensor<bool>"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::TensorType {
+                element_type: ElementType::BOOL,
+                shape: vec![],
+            }
+        );
+
+        let result = parse_tensor_type(CompleteStr("tensor<8xbf16>"));
+        assert_eq!(
+            result.unwrap().1,
+            Token::TensorType {
+                element_type: ElementType::BF16,
+                shape: vec![Some(8)],
+            }
+        );
+
+        assert_eq!(parse_tensor_type(CompleteStr("tensor<2x3>")).is_ok(), false);
     }
 }
@@ -0,0 +1,972 @@
+// optimisations over programs whose names are resolved and that pass verification. Each pass
+// rewrites the top-level instructions and the function bodies one by one, every instruction it
+// removes saves a round trip to the executors. Values a program leaves in the vm are observable
+// by later programs, so only those dropped by return are taken as dead, or fused away
+use std::collections::{HashMap, HashSet};
+
+use super::assembler_base::*;
+use super::verifier::*;
+use crate::base::constants::{FOLD_BYTES_LIMIT, FUSION_MAX_INPUTS, REGISTER_DIM_FLAG};
+use crate::base::{normalize_axis, ElementType};
+use crate::fusion::*;
+use crate::host_functor::HostFunctor;
+use crate::instruction::CRTOpCode;
+use crate::tensors::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pass {
+    // evaluates ops over literals on host, and replaces them with the literals of their results
+    ConstantFolding,
+    // removes instructions whose values are never read
+    DeadCodeElimination,
+    // removes instructions recomputing the values of earlier ones, their uses read those instead
+    CommonSubexpressionElimination,
+    // replaces chains of f32 elementwise ops with single fused ops, whose intermediates are
+    // never uploaded nor downloaded
+    ElementwiseFusion,
+}
+
+impl Pass {
+    pub fn run(&self, instructions: &[AsmInstruction]) -> Vec<AsmInstruction> {
+        match self {
+            Pass::ConstantFolding => fold_constants(instructions),
+            Pass::DeadCodeElimination => eliminate_dead_code(instructions),
+            Pass::CommonSubexpressionElimination => eliminate_common_subexpressions(instructions),
+            Pass::ElementwiseFusion => fuse_elementwise(instructions),
+        }
+    }
+}
+
+// runs passes by order, none by default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PassManager {
+    passes: Vec<Pass>,
+}
+
+impl PassManager {
+    pub fn new() -> PassManager {
+        PassManager { passes: vec![] }
+    }
+
+    // folding exposes common subexpressions, and both leave the values they replace for dce.
+    // Fusion runs last, over the chains left
+    pub fn standard() -> PassManager {
+        PassManager {
+            passes: vec![
+                Pass::ConstantFolding,
+                Pass::CommonSubexpressionElimination,
+                Pass::DeadCodeElimination,
+                Pass::ElementwiseFusion,
+            ],
+        }
+    }
+
+    pub fn add_pass(&mut self, pass: Pass) {
+        self.passes.push(pass);
+    }
+
+    pub fn passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    pub fn run(&self, program: Program) -> Program {
+        let mut program = program;
+        for pass in &self.passes {
+            program = Program {
+                instructions: pass.run(&program.instructions),
+                functions: program
+                    .functions
+                    .iter()
+                    .map(|function| Function {
+                        instructions: pass.run(&function.instructions),
+                        ..function.clone()
+                    })
+                    .collect(),
+            };
+        }
+        program
+    }
+}
+
+fn opcode_of(inst: &AsmInstruction) -> Option<CRTOpCode> {
+    match &inst.opcode {
+        Token::BytecodeOpCode { code } => Some(*code),
+        _ => None,
+    }
+}
+
+fn defined(inst: &AsmInstruction) -> Vec<usize> {
+    opcode_of(inst).map_or(vec![], |code| defined_slots(code, inst))
+}
+
+fn used(inst: &AsmInstruction) -> Vec<usize> {
+    opcode_of(inst).map_or(vec![], |code| used_slots(code, inst))
+}
+
+// labels start blocks, jumps and the ends of programs finish them
+fn is_label(inst: &AsmInstruction) -> bool {
+    match inst.opcode {
+        Token::Label { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_branch(inst: &AsmInstruction) -> bool {
+    match opcode_of(inst) {
+        Some(CRTOpCode::JMP)
+        | Some(CRTOpCode::JMPIF)
+        | Some(CRTOpCode::JMPIFR)
+        | Some(CRTOpCode::LOOP)
+        | Some(CRTOpCode::RETV)
+        | Some(CRTOpCode::HALT) => true,
+        _ => false,
+    }
+}
+
+// values of instructions may differ between runs of them if they read registers
+fn reads_registers(inst: &AsmInstruction) -> bool {
+    opcode_of(inst) == Some(CRTOpCode::MOVRT)
+        || [&inst.operand2, &inst.operand3, &inst.operand4]
+            .iter()
+            .any(|operand| match operand {
+                Some(Token::Register { .. }) => true,
+                Some(Token::Dims { dims }) => dims.iter().any(|dim| dim & REGISTER_DIM_FLAG != 0),
+                Some(Token::Axis {
+                    axis: AxisArg::Register(_),
+                })
+                | Some(Token::ReduceAttr {
+                    axis: Some(AxisArg::Register(_)),
+                    ..
+                }) => true,
+                _ => false,
+            })
+}
+
+// values are dropped only if every path ends by return, other values stay in the vm
+fn drops_values(instructions: &[AsmInstruction]) -> bool {
+    instructions.last().and_then(opcode_of) == Some(CRTOpCode::RETV)
+        && instructions
+            .iter()
+            .all(|inst| opcode_of(inst) != Some(CRTOpCode::HALT))
+}
+
+// values of literal instructions, rng tensors are left to sample at run time
+fn literal_value(code: CRTOpCode, inst: &AsmInstruction) -> Option<ActTensorTypes> {
+    match (code, &inst.operand2) {
+        (CRTOpCode::CONSTI32, Some(Token::I32Literal { value })) => {
+            Some(ActTensorTypes::I32Tensor {
+                data: TensorView::new(vec![*value], ElementType::I32, vec![1]),
+            })
+        }
+        (CRTOpCode::CONSTF32, Some(Token::F32Literal { value })) => {
+            Some(ActTensorTypes::F32Tensor {
+                data: TensorView::new(vec![*value], ElementType::F32, vec![1]),
+            })
+        }
+        (
+            _,
+            Some(Token::ScalarLiteral {
+                element_type,
+                bytes,
+            }),
+        ) => Some(ActTensorTypes::from_le_bytes(bytes, *element_type, vec![1])),
+        (
+            CRTOpCode::CONSTTENSOR,
+            Some(Token::Tensor {
+                element_type,
+                raw_data,
+                shape,
+            }),
+        ) => Some(ActTensorTypes::from_le_bytes(
+            raw_data,
+            *element_type,
+            shape.clone(),
+        )),
+        (
+            CRTOpCode::SVALUETENSOR,
+            Some(Token::UninitTensor {
+                element_type,
+                value,
+                shape,
+            }),
+        ) => Some(ActTensorTypes::from_le_bytes(
+            &value.repeat(shape.iter().product()),
+            *element_type,
+            shape.clone(),
+        )),
+        (
+            CRTOpCode::RANGETENSOR,
+            Some(Token::RangeTensor {
+                kind,
+                params,
+                element_type,
+            }),
+        ) => {
+            let values = match kind {
+                0 => arange_values(params[0], params[1], params[2]),
+                1 => linspace_values(params[0], params[1], params[2] as usize),
+                _ => return None,
+            };
+            let shape = vec![values.len()];
+            Some(ActTensorTypes::from_f64_vec(values, *element_type, shape))
+        }
+        (
+            CRTOpCode::EYETENSOR,
+            Some(Token::EyeTensor {
+                element_type,
+                shape,
+            }),
+        ) => Some(ActTensorTypes::from_f64_vec(
+            eye_values(shape[0], shape[1]),
+            *element_type,
+            shape.clone(),
+        )),
+        _ => None,
+    }
+}
+
+// ops named .f32 compute float tensors on host, and ops named .i32 integer ones
+fn computes(code: CRTOpCode, element_type: ElementType) -> bool {
+    match element_type.is_float() {
+        true => code.to_mnemonic().ends_with(".f32"),
+        false => code.to_mnemonic().ends_with(".i32"),
+    }
+}
+
+// evaluates an op over literal operands by the cpu reference of executors. Ops that are not
+// folded, or that would fail at run time, give None and are left to the vm
+fn evaluate(
+    host: &HostFunctor,
+    code: CRTOpCode,
+    inst: &AsmInstruction,
+    operands: &[&ActTensorTypes],
+) -> Option<ActTensorTypes> {
+    let first = operands[0];
+    let element_type = first.element_type();
+    match code {
+        CRTOpCode::ZEROSLIKE | CRTOpCode::ONESLIKE => {
+            let value = match code {
+                CRTOpCode::ZEROSLIKE => 0f64,
+                _ => 1f64,
+            };
+            let shape = first.shape();
+            Some(ActTensorTypes::from_f64_vec(
+                vec![value; shape.iter().product()],
+                element_type,
+                shape,
+            ))
+        }
+        CRTOpCode::BITCASTF32 | CRTOpCode::BITCASTI32 => match element_type {
+            ElementType::F32 | ElementType::I32 => Some(host.unary_compute(code, first)),
+            _ => None,
+        },
+        _ if code.to_cast_element_type().is_some() => Some(host.unary_compute(code, first)),
+        _ if is_unary_elementwise(code) && computes(code, element_type) => {
+            Some(host.unary_compute(code, first))
+        }
+        _ if is_binary_elementwise(code) && computes(code, element_type) => {
+            let rhs = operands[1];
+            if rhs.element_type() != element_type {
+                return None;
+            }
+            broadcast_shape(&first.shape(), &rhs.shape())?;
+            // integer division by zero is left to run time, where kernels give 0
+            let divides = code == CRTOpCode::FLOORDIVI32 || code == CRTOpCode::REMI32;
+            if divides && rhs.to_i64_vec().contains(&0) {
+                return None;
+            }
+            Some(host.binary_compute(code, first, rhs))
+        }
+        _ if code.is_reduction() && element_type == ElementType::F32 => {
+            let (axis, keepdims) = match &inst.operand3 {
+                Some(Token::ReduceAttr { axis, keepdims }) => (*axis, *keepdims),
+                _ => (None, false),
+            };
+            // -1 reduces over all axes, as the vm encodes attributes
+            let axis = match axis {
+                None => -1,
+                Some(AxisArg::Static(axis)) => {
+                    normalize_axis(axis as i32, first.shape().len()).ok()? as i32
+                }
+                Some(AxisArg::Register(_)) => return None,
+            };
+            let attr = ActTensorTypes::I32Tensor {
+                data: TensorView::new(vec![axis, keepdims as i32], ElementType::I32, vec![2]),
+            };
+            Some(host.reduce_compute(code, first, &attr))
+        }
+        CRTOpCode::RESHAPE | CRTOpCode::SQUEEZE | CRTOpCode::UNSQUEEZE => {
+            let dims = static_dims(&inst.operand3)?;
+            let shape = first.shape();
+            let shape = match code {
+                CRTOpCode::RESHAPE => reshape(&shape, dims),
+                CRTOpCode::SQUEEZE => squeeze(&shape, dims),
+                _ => unsqueeze(&shape, dims),
+            };
+            let mut result = first.clone();
+            result.reshape(shape.ok()?);
+            Some(result)
+        }
+        CRTOpCode::TRANSPOSE => match first {
+            ActTensorTypes::F32Tensor { .. } | ActTensorTypes::I32Tensor { .. } => {
+                let perm = static_dims(&inst.operand3)?;
+                transpose(&first.shape(), perm.clone()).ok()?;
+                Some(host.transpose(first, &perm))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// dense literal defining the slot of inst with value
+fn literal_instruction(inst: &AsmInstruction, value: &ActTensorTypes) -> AsmInstruction {
+    AsmInstruction {
+        opcode: Token::BytecodeOpCode {
+            code: CRTOpCode::CONSTTENSOR,
+        },
+        operand1: inst.operand1.clone(),
+        operand2: Some(Token::Tensor {
+            element_type: value.element_type(),
+            raw_data: value.to_le_bytes(),
+            shape: value.shape(),
+        }),
+        operand3: None,
+        operand4: None,
+        annotation: None,
+    }
+}
+
+// values of slots are tracked in program order, and forgotten at labels since other paths may
+// reach them with other values
+fn fold_constants(instructions: &[AsmInstruction]) -> Vec<AsmInstruction> {
+    let host = HostFunctor::new();
+    let mut known: HashMap<usize, ActTensorTypes> = HashMap::new();
+    let mut folded = vec![];
+    for inst in instructions {
+        let code = match opcode_of(inst) {
+            Some(code) => code,
+            None => {
+                known.clear();
+                folded.push(inst.clone());
+                continue;
+            }
+        };
+        let slots = defined_slots(code, inst);
+        let literal = literal_value(code, inst);
+        let value = match (&literal, slots.len()) {
+            (Some(literal), _) => Some(literal.clone()),
+            (None, 1) => {
+                let operands: Option<Vec<&ActTensorTypes>> = used_slots(code, inst)
+                    .iter()
+                    .map(|slot| known.get(slot))
+                    .collect();
+                match operands {
+                    Some(operands) if !operands.is_empty() => {
+                        evaluate(&host, code, inst, &operands)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        for slot in &slots {
+            known.remove(slot);
+        }
+
+        let value = match value {
+            Some(value)
+                if value.shape().iter().product::<usize>() * value.element_type().byte_width()
+                    <= FOLD_BYTES_LIMIT =>
+            {
+                value
+            }
+            _ => {
+                folded.push(inst.clone());
+                continue;
+            }
+        };
+        match literal {
+            Some(_) => folded.push(inst.clone()),
+            None => folded.push(literal_instruction(inst, &value)),
+        }
+        known.insert(slots[0], value);
+    }
+    folded
+}
+
+fn eliminate_dead_code(instructions: &[AsmInstruction]) -> Vec<AsmInstruction> {
+    let straight = !instructions.iter().any(|inst| {
+        is_label(inst) || (is_branch(inst) && opcode_of(inst) != Some(CRTOpCode::RETV))
+    });
+    if !straight {
+        // any value read somewhere may be read after any definition of it
+        if !drops_values(instructions) {
+            return instructions.to_vec();
+        }
+        let mut kept = instructions.to_vec();
+        loop {
+            let read: HashSet<usize> = kept.iter().flat_map(used).collect();
+            let count = kept.len();
+            kept.retain(|inst| {
+                let slots = defined(inst);
+                slots.is_empty() || slots.iter().any(|slot| read.contains(slot))
+            });
+            if kept.len() == count {
+                return kept;
+            }
+        }
+    }
+
+    // backward liveness, values left in the vm at the end are live unless return drops them
+    let mut live: HashSet<usize> = instructions.iter().flat_map(defined).collect();
+    let mut kept = vec![];
+    for inst in instructions.iter().rev() {
+        if opcode_of(inst) == Some(CRTOpCode::RETV) {
+            live = used(inst).into_iter().collect();
+        }
+        let slots = defined(inst);
+        if !slots.is_empty() && slots.iter().all(|slot| !live.contains(slot)) {
+            continue;
+        }
+        for slot in &slots {
+            live.remove(slot);
+        }
+        live.extend(used(inst));
+        kept.push(inst.clone());
+    }
+    kept.reverse();
+    kept
+}
+
+fn rename_operand(operand: &Option<Token>, renames: &HashMap<usize, usize>) -> Option<Token> {
+    let rename = |slot: usize| Token::Variable {
+        symbol: *renames.get(&slot).unwrap_or(&slot),
+    };
+    match operand {
+        Some(Token::Variable { symbol }) => Some(rename(*symbol)),
+        Some(Token::Variables { symbols }) => Some(Token::Variables {
+            symbols: symbols
+                .iter()
+                .map(|symbol| rename(symbol.to_slot()))
+                .collect(),
+        }),
+        _ => operand.clone(),
+    }
+}
+
+// renames the operands an instruction reads
+fn rename_uses(inst: &AsmInstruction, renames: &HashMap<usize, usize>) -> AsmInstruction {
+    let operand1 = match opcode_of(inst) {
+        Some(CRTOpCode::RETV) | Some(CRTOpCode::JMPIF) => rename_operand(&inst.operand1, renames),
+        _ => inst.operand1.clone(),
+    };
+    AsmInstruction {
+        operand1: operand1,
+        operand2: rename_operand(&inst.operand2, renames),
+        operand3: rename_operand(&inst.operand3, renames),
+        operand4: rename_operand(&inst.operand4, renames),
+        ..inst.clone()
+    }
+}
+
+// an instruction is replaced by an earlier identical one of its block, if both define their
+// values once, and the values of the later one are only read after it in the block
+fn eliminate_common_subexpressions(instructions: &[AsmInstruction]) -> Vec<AsmInstruction> {
+    if !drops_values(instructions) {
+        return instructions.to_vec();
+    }
+    let mut definitions: HashMap<usize, usize> = HashMap::new();
+    for slot in instructions.iter().flat_map(defined) {
+        *definitions.entry(slot).or_insert(0) += 1;
+    }
+    let returned: HashSet<usize> = instructions
+        .iter()
+        .filter(|inst| opcode_of(inst) == Some(CRTOpCode::RETV))
+        .flat_map(used)
+        .collect();
+    // block and position of every read of each slot
+    let mut blocks = vec![];
+    let mut block = 0;
+    for inst in instructions {
+        if is_label(inst) {
+            block += 1;
+        }
+        blocks.push(block);
+        if is_branch(inst) {
+            block += 1;
+        }
+    }
+    let mut reads: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, inst) in instructions.iter().enumerate() {
+        for slot in used(inst) {
+            reads.entry(slot).or_default().push(index);
+        }
+    }
+    let replaceable = |index: usize, slot: usize| {
+        definitions[&slot] == 1
+            && !returned.contains(&slot)
+            && reads.get(&slot).map_or(true, |positions| {
+                positions
+                    .iter()
+                    .all(|position| *position > index && blocks[*position] == blocks[index])
+            })
+    };
+
+    let mut available: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut versions: HashMap<usize, usize> = HashMap::new();
+    let mut renames: HashMap<usize, usize> = HashMap::new();
+    let mut kept = vec![];
+    for (index, inst) in instructions.iter().enumerate() {
+        if is_label(inst) {
+            available.clear();
+        }
+        let inst = rename_uses(inst, &renames);
+        let slots = defined(&inst);
+        if !slots.is_empty() && !reads_registers(&inst) {
+            // operands are keyed by their versions, as slots may be redefined in between
+            let operand_versions: Vec<usize> = used(&inst)
+                .iter()
+                .map(|slot| *versions.get(slot).unwrap_or(&0))
+                .collect();
+            let key = format!(
+                "{:?} {} {:?} {:?} {:?} {:?}",
+                inst.opcode,
+                slots.len(),
+                inst.operand2,
+                inst.operand3,
+                inst.operand4,
+                operand_versions
+            );
+            let earlier = available.get(&key).cloned().filter(|earlier| {
+                earlier.iter().all(|slot| definitions[slot] == 1)
+                    && slots.iter().all(|slot| replaceable(index, *slot))
+            });
+            match earlier {
+                Some(earlier) => {
+                    for (slot, earlier_slot) in slots.iter().zip(earlier) {
+                        renames.insert(*slot, earlier_slot);
+                    }
+                    continue;
+                }
+                None => {
+                    available.insert(key, slots.clone());
+                }
+            }
+        }
+        for slot in &slots {
+            *versions.entry(*slot).or_insert(0) += 1;
+        }
+        if is_branch(&inst) {
+            available.clear();
+        }
+        kept.push(inst);
+    }
+    kept
+}
+
+// operand of a step while fusing, either a slot read by the fused op or an earlier step
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FusedOperand {
+    Input(usize),
+    Step(usize),
+}
+
+// fusible instructions of a chain ending at its root, and the steps computing them in order
+#[derive(Debug, Clone, Default)]
+struct FusionGroup {
+    members: Vec<usize>,
+    steps: Vec<(CRTOpCode, Vec<FusedOperand>)>,
+}
+
+impl FusionGroup {
+    fn inputs(&self) -> Vec<usize> {
+        let mut inputs = vec![];
+        for (_, operands) in &self.steps {
+            for operand in operands {
+                if let FusedOperand::Input(slot) = operand {
+                    if !inputs.contains(slot) {
+                        inputs.push(*slot);
+                    }
+                }
+            }
+        }
+        inputs
+    }
+
+    // appends the steps of a chain feeding this one, and gives the step of its root
+    fn merge(&mut self, other: FusionGroup) -> FusedOperand {
+        let offset = self.steps.len();
+        for (code, operands) in other.steps {
+            let operands = operands
+                .iter()
+                .map(|operand| match operand {
+                    FusedOperand::Step(step) => FusedOperand::Step(step + offset),
+                    _ => *operand,
+                })
+                .collect();
+            self.steps.push((code, operands));
+        }
+        self.members.extend(other.members);
+        FusedOperand::Step(self.steps.len() - 1)
+    }
+
+    fn to_instruction(&self, root: &AsmInstruction) -> AsmInstruction {
+        let inputs = self.inputs();
+        let steps = self
+            .steps
+            .iter()
+            .map(|(code, operands)| FusedStep {
+                opcode: *code,
+                operands: operands
+                    .iter()
+                    .map(|operand| match operand {
+                        FusedOperand::Input(slot) => inputs.iter().position(|x| x == slot).unwrap(),
+                        FusedOperand::Step(step) => inputs.len() + step,
+                    })
+                    .collect(),
+            })
+            .collect();
+        AsmInstruction {
+            opcode: Token::BytecodeOpCode {
+                code: CRTOpCode::FUSEDF32,
+            },
+            operand1: root.operand1.clone(),
+            operand2: Some(Token::Variables {
+                symbols: inputs
+                    .iter()
+                    .map(|slot| Token::Variable { symbol: *slot })
+                    .collect(),
+            }),
+            operand3: Some(Token::FusedExpr {
+                expr: FusedExpr { steps: steps },
+            }),
+            operand4: None,
+            annotation: Some(ElementType::F32),
+        }
+    }
+}
+
+// a fusible instruction takes in the chain of an operand if the value is defined once, only read
+// by it in the same block and not returned. The fused op replaces the last instruction of the
+// chain, so chains whose inputs are redefined after being read are left as they are
+fn fuse_elementwise(instructions: &[AsmInstruction]) -> Vec<AsmInstruction> {
+    if !drops_values(instructions) {
+        return instructions.to_vec();
+    }
+    let mut definitions: HashMap<usize, usize> = HashMap::new();
+    for slot in instructions.iter().flat_map(defined) {
+        *definitions.entry(slot).or_insert(0) += 1;
+    }
+    let returned: HashSet<usize> = instructions
+        .iter()
+        .filter(|inst| opcode_of(inst) == Some(CRTOpCode::RETV))
+        .flat_map(used)
+        .collect();
+    let mut reads: HashMap<usize, usize> = HashMap::new();
+    for slot in instructions.iter().flat_map(used) {
+        *reads.entry(slot).or_insert(0) += 1;
+    }
+    let fusible_into = |slot: usize| {
+        definitions.get(&slot) == Some(&1) && reads[&slot] == 1 && !returned.contains(&slot)
+    };
+
+    // groups by the index of their roots, and the roots of open chains by their values
+    let mut groups: HashMap<usize, FusionGroup> = HashMap::new();
+    let mut open: HashMap<usize, usize> = HashMap::new();
+    for (index, inst) in instructions.iter().enumerate() {
+        if is_label(inst) || is_branch(inst) {
+            open.clear();
+            continue;
+        }
+        let code = match opcode_of(inst) {
+            Some(code) if is_fusible(code) => code,
+            _ => continue,
+        };
+        let slots = defined(inst);
+        let operands = used(inst);
+        let mut group = FusionGroup::default();
+        let mut step_operands = vec![];
+        for (position, slot) in operands.iter().enumerate() {
+            let chain = open
+                .get(slot)
+                .filter(|_| fusible_into(*slot))
+                .map(|root| groups[root].clone());
+            let merged = chain.and_then(|chain| {
+                let mut merged = group.clone();
+                let step = merged.merge(chain);
+                // the operands left are counted as inputs
+                let mut inputs = merged.inputs();
+                for other in &operands[position + 1..] {
+                    if !inputs.contains(other) {
+                        inputs.push(*other);
+                    }
+                }
+                match inputs.len() <= FUSION_MAX_INPUTS {
+                    true => Some((merged, step)),
+                    false => None,
+                }
+            });
+            match merged {
+                Some((merged, step)) => {
+                    groups.remove(&open[slot]);
+                    open.remove(slot);
+                    group = merged;
+                    step_operands.push(step);
+                }
+                None => step_operands.push(FusedOperand::Input(*slot)),
+            }
+        }
+        group.steps.push((code, step_operands));
+        group.members.push(index);
+        groups.insert(index, group);
+        if slots.len() == 1 {
+            open.insert(slots[0], index);
+        }
+    }
+
+    let mut fused: HashMap<usize, AsmInstruction> = HashMap::new();
+    let mut removed: HashSet<usize> = HashSet::new();
+    for (root, group) in &groups {
+        if group.members.len() < 2 {
+            continue;
+        }
+        let inputs = group.inputs();
+        let redefined = group
+            .members
+            .iter()
+            .filter(|member| *member != root)
+            .any(|member| {
+                let read: Vec<usize> = used(&instructions[*member])
+                    .into_iter()
+                    .filter(|slot| inputs.contains(slot))
+                    .collect();
+                instructions[member + 1..*root]
+                    .iter()
+                    .flat_map(defined)
+                    .any(|slot| read.contains(&slot))
+            });
+        if redefined {
+            continue;
+        }
+        fused.insert(*root, group.to_instruction(&instructions[*root]));
+        removed.extend(group.members.iter().filter(|member| *member != root));
+    }
+    instructions
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !removed.contains(index))
+        .map(|(index, inst)| fused.get(&index).unwrap_or(inst).clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn run_passes(passes: Vec<Pass>, source: &str) -> Program {
+        let mut manager = PassManager::new();
+        for pass in passes {
+            manager.add_pass(pass);
+        }
+        manager.run(assemble(source).unwrap())
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let program = run_passes(
+            vec![Pass::ConstantFolding, Pass::DeadCodeElimination],
+            "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4.], shape=[2 2]>: f32
+%1 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%2 = crt.add.f32! %0, %1 : f32
+%3 = crt.tensor.transpose! %2, [1, 0] : f32
+%4 = crt.cmp.gt.f32! %3, %2 : bool
+%5 = crt.reduce.sum.f32! %3, axis = 1 : f32
+%6 = crt.literal.const.i32! 7 : i32
+%7 = crt.literal.const.i32! 0 : i32
+%8 = crt.floordiv.i32! %6, %7 : i32
+return %4, %5, %8
+",
+        );
+        let expected = assemble(
+            "%4 = crt.literal.const.tensor! dense<[false true false false], shape=[2 2]>: bool
+%5 = crt.literal.const.tensor! dense<[6. 8.], shape=[2]>: f32
+%6 = crt.literal.const.i32! 7 : i32
+%7 = crt.literal.const.i32! 0 : i32
+%8 = crt.floordiv.i32! %6, %7 : i32
+return %4, %5, %8
+",
+        )
+        .unwrap();
+        // division by zero is left to run time
+        assert_eq!(program, expected);
+    }
+
+    #[test]
+    fn test_constant_folding_stops_at_labels() {
+        let source = "%0 = crt.literal.const.f32! 1.0 : f32
+^head:
+%1 = crt.exp.f32! %0 : f32
+%0 = crt.add.f32! %0, %0 : f32
+loop 2, ^head
+";
+        let program = run_passes(vec![Pass::ConstantFolding], source);
+        assert_eq!(program, assemble(source).unwrap());
+    }
+
+    #[test]
+    fn test_dead_code_elimination() {
+        // values left in the vm are kept, but for those overwritten before being read
+        let program = run_passes(
+            vec![Pass::DeadCodeElimination],
+            "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.exp.f32! %0 : f32
+%1 = crt.abs.f32! %0 : f32
+%2 = crt.add.f32! %0, %1 : f32
+",
+        );
+        let expected = assemble(
+            "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.abs.f32! %0 : f32
+%2 = crt.add.f32! %0, %1 : f32
+",
+        )
+        .unwrap();
+        assert_eq!(program, expected);
+
+        let program = run_passes(
+            vec![Pass::DeadCodeElimination],
+            "func @f(%0: tensor<2x2xf32>) -> (tensor<2x2xf32>) {
+    %1 = crt.exp.f32! %0 : f32
+    %2 = crt.abs.f32! %1 : f32
+    %3 = crt.neg.f32! %0 : f32
+    return %3
+}
+",
+        );
+        let expected = assemble(
+            "func @f(%0: tensor<2x2xf32>) -> (tensor<2x2xf32>) {
+    %3 = crt.neg.f32! %0 : f32
+    return %3
+}
+",
+        )
+        .unwrap();
+        assert_eq!(program, expected);
+    }
+
+    #[test]
+    fn test_common_subexpression_elimination() {
+        let program = run_passes(
+            vec![
+                Pass::CommonSubexpressionElimination,
+                Pass::DeadCodeElimination,
+            ],
+            "func @f(%0: tensor<2x2xf32>, %1: tensor<2x2xf32>) -> (tensor<2x2xf32>) {
+    %2 = crt.add.f32! %0, %1 : f32
+    %3 = crt.add.f32! %0, %1 : f32
+    %4 = crt.exp.f32! %2 : f32
+    %5 = crt.exp.f32! %3 : f32
+    %1 = crt.neg.f32! %1 : f32
+    %6 = crt.add.f32! %0, %1 : f32
+    %7 = crt.mul.f32! %4, %5 : f32
+    %8 = crt.mul.f32! %7, %6 : f32
+    return %8
+}
+",
+        );
+        // %6 reads %1 after it is redefined, so it is kept
+        let expected = assemble(
+            "func @f(%0: tensor<2x2xf32>, %1: tensor<2x2xf32>) -> (tensor<2x2xf32>) {
+    %2 = crt.add.f32! %0, %1 : f32
+    %4 = crt.exp.f32! %2 : f32
+    %1 = crt.neg.f32! %1 : f32
+    %6 = crt.add.f32! %0, %1 : f32
+    %7 = crt.mul.f32! %4, %4 : f32
+    %8 = crt.mul.f32! %7, %6 : f32
+    return %8
+}
+",
+        )
+        .unwrap();
+        assert_eq!(program, expected);
+
+        // values left in the vm are kept
+        let source = "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.exp.f32! %0 : f32
+%2 = crt.exp.f32! %0 : f32
+";
+        let program = run_passes(vec![Pass::CommonSubexpressionElimination], source);
+        assert_eq!(program, assemble(source).unwrap());
+    }
+
+    #[test]
+    fn test_elementwise_fusion() {
+        let program = run_passes(
+            vec![Pass::ElementwiseFusion],
+            "func @f(%0: tensor<2x2xf32>, %1: tensor<2xf32>, %2: tensor<2x2xf32>) -> (tensor<2x2xf32>, tensor<2x2xf32>) {
+    %3 = crt.add.f32! %0, %1 : f32
+    %4 = crt.exp.f32! %3 : f32
+    %5 = crt.mul.f32! %4, %2 : f32
+    %6 = crt.neg.f32! %5 : f32
+    %7 = crt.sub.f32! %6, %6 : f32
+    %8 = crt.tanh.f32! %0 : f32
+    %9 = crt.sqrt.f32! %8 : f32
+    return %7, %8
+}
+",
+        );
+        // %6 is read twice and %8 is returned, so the chains end there
+        let expected = assemble(
+            "func @f(%0: tensor<2x2xf32>, %1: tensor<2xf32>, %2: tensor<2x2xf32>) -> (tensor<2x2xf32>, tensor<2x2xf32>) {
+    %6 = crt.fused.f32! (%0, %1, %2), expr = [crt.add.f32(0, 1), crt.exp.f32(3), crt.mul.f32(4, 2), crt.neg.f32(5)] : f32
+    %7 = crt.sub.f32! %6, %6 : f32
+    %8 = crt.tanh.f32! %0 : f32
+    %9 = crt.sqrt.f32! %8 : f32
+    return %7, %8
+}
+",
+        )
+        .unwrap();
+        assert_eq!(program, expected);
+
+        // the chain of %5 would take a fourth input, and %0 is redefined after exp reads it
+        let program = run_passes(
+            vec![Pass::ElementwiseFusion],
+            "func @f(%0: tensor<2xf32>, %1: tensor<2xf32>, %2: tensor<2xf32>, %3: tensor<2xf32>) -> (tensor<2xf32>) {
+    %4 = crt.add.f32! %0, %1 : f32
+    %5 = crt.mul.f32! %2, %3 : f32
+    %6 = crt.sub.f32! %4, %5 : f32
+    %7 = crt.exp.f32! %0 : f32
+    %0 = crt.abs.f32! %1 : f32
+    %8 = crt.add.f32! %7, %0 : f32
+    %9 = crt.max.f32! %6, %8 : f32
+    return %9
+}
+",
+        );
+        let expected = assemble(
+            "func @f(%0: tensor<2xf32>, %1: tensor<2xf32>, %2: tensor<2xf32>, %3: tensor<2xf32>) -> (tensor<2xf32>) {
+    %5 = crt.mul.f32! %2, %3 : f32
+    %6 = crt.fused.f32! (%0, %1, %5), expr = [crt.add.f32(0, 1), crt.sub.f32(3, 2)] : f32
+    %7 = crt.exp.f32! %0 : f32
+    %0 = crt.abs.f32! %1 : f32
+    %8 = crt.add.f32! %7, %0 : f32
+    %9 = crt.max.f32! %6, %8 : f32
+    return %9
+}
+",
+        )
+        .unwrap();
+        assert_eq!(program, expected);
+
+        // values left in the vm are kept
+        let source = "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.exp.f32! %0 : f32
+%2 = crt.neg.f32! %1 : f32
+";
+        let program = run_passes(vec![Pass::ElementwiseFusion], source);
+        assert_eq!(program, assemble(source).unwrap());
+    }
+}
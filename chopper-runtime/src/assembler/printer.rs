@@ -0,0 +1,458 @@
+// canonical text form of programs, which parse_bytecode reads back into the same bytes
+use std::convert::TryInto;
+use std::fmt;
+
+use half::{bf16, f16};
+
+use super::assembler_base::*;
+use crate::base::constants::{NAMED_SLOT_BASE, REGISTER_DIM_FLAG};
+use crate::base::ElementType;
+use crate::instruction::CRTOpCode;
+
+// floats always carry a fraction, so that they read as floats
+fn float_text<T: fmt::Display>(value: T) -> String {
+    let text = value.to_string();
+    match text.contains(|c: char| c == '.' || !(c.is_ascii_digit() || c == '-')) {
+        true => text,
+        false => format!("{}.0", text),
+    }
+}
+
+// a scalar of element_type from its little endian bytes
+fn scalar_text(bytes: &[u8], element_type: ElementType) -> String {
+    match element_type {
+        ElementType::F16 => float_text(f16::from_bits(u16::from_le_bytes([bytes[0], bytes[1]]))),
+        ElementType::BF16 => float_text(bf16::from_bits(u16::from_le_bytes([bytes[0], bytes[1]]))),
+        ElementType::F32 => float_text(f32::from_le_bytes(bytes.try_into().unwrap())),
+        ElementType::F64 => float_text(f64::from_le_bytes(bytes.try_into().unwrap())),
+        ElementType::I8 => (bytes[0] as i8).to_string(),
+        ElementType::U8 => bytes[0].to_string(),
+        ElementType::I32 => i32::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+        ElementType::I64 => i64::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+        ElementType::BOOL => (bytes[0] != 0).to_string(),
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+// dims of shape ops, dims read from registers are written as the register
+fn dim_text(dim: usize) -> String {
+    match dim & REGISTER_DIM_FLAG {
+        0 => dim.to_string(),
+        _ => format!("${}", dim & !REGISTER_DIM_FLAG),
+    }
+}
+
+fn dims_of(token: &Token) -> &[usize] {
+    match token {
+        Token::Dims { dims } => dims,
+        _ => panic!("{:?} is not a dims list", token),
+    }
+}
+
+// type annotation of an instruction, the result type for casts and comparisons, otherwise the
+// type named by the opcode. Type agnostic ops, such as shape ops, are annotated as f32
+fn annotation_type(code: CRTOpCode) -> ElementType {
+    if code.is_comparison() {
+        return ElementType::BOOL;
+    }
+    match code.to_cast_element_type() {
+        Some(element_type) => element_type,
+        None if code.to_mnemonic().ends_with(".i32") => ElementType::I32,
+        None => ElementType::F32,
+    }
+}
+
+// annotation of the canonical text of an instruction, None if it is written without one, such as
+// register ops, or with a literal that carries its own type
+pub(crate) fn canonical_annotation(code: CRTOpCode, operands: &[Token]) -> Option<ElementType> {
+    match code {
+        CRTOpCode::HALT
+        | CRTOpCode::RETV
+        | CRTOpCode::JMP
+        | CRTOpCode::JMPIF
+        | CRTOpCode::JMPIFR
+        | CRTOpCode::LOOP
+        | CRTOpCode::LOAD
+        | CRTOpCode::ADDR
+        | CRTOpCode::SUBR
+        | CRTOpCode::MULR
+        | CRTOpCode::CMPEQR
+        | CRTOpCode::CMPNER
+        | CRTOpCode::CMPLTR
+        | CRTOpCode::CMPLER
+        | CRTOpCode::CMPGTR
+        | CRTOpCode::CMPGER
+        | CRTOpCode::MOVRT
+        | CRTOpCode::MOVTR => None,
+        _ => match operands.get(1) {
+            Some(Token::I32Literal { .. }) => Some(ElementType::I32),
+            Some(Token::F32Literal { .. }) => Some(ElementType::F32),
+            Some(Token::Variable { .. })
+            | Some(Token::NamedVariable { .. })
+            | Some(Token::Variables { .. }) => Some(annotation_type(code)),
+            _ => None,
+        },
+    }
+}
+
+impl fmt::Display for AxisArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AxisArg::Static(axis) => write!(f, "{}", axis),
+            AxisArg::Register(index) => write!(f, "${}", index),
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::BytecodeOpCode { code } => write!(f, "{}", code.to_mnemonic()),
+            // slots of named values lost their names, they are renamed after the slot
+            Token::Variable { symbol } if *symbol >= NAMED_SLOT_BASE => {
+                write!(f, "%v{}", symbol - NAMED_SLOT_BASE)
+            }
+            Token::Variable { symbol } => write!(f, "%{}", symbol),
+            Token::NamedVariable { name } => write!(f, "%{}", name),
+            Token::Register { index } => write!(f, "${}", index),
+            Token::Label { name } => write!(f, "^{}", name),
+            Token::Variables { symbols } => write!(f, "{}", join(symbols, ", ")),
+            Token::I32Literal { value } => write!(f, "{}", value),
+            Token::F32Literal { value } => write!(f, "{}", float_text(value)),
+            Token::ScalarLiteral {
+                element_type,
+                bytes,
+            } => write!(
+                f,
+                "{} : {}",
+                scalar_text(bytes, *element_type),
+                element_type
+            ),
+            Token::Tensor {
+                element_type,
+                raw_data,
+                shape,
+            } => {
+                let scalars: Vec<String> = raw_data
+                    .chunks(element_type.byte_width())
+                    .map(|bytes| scalar_text(bytes, *element_type))
+                    .collect();
+                write!(
+                    f,
+                    "dense<[{}], shape=[{}]> : {}",
+                    scalars.join(" "),
+                    join(shape, " "),
+                    element_type
+                )
+            }
+            Token::UninitTensor {
+                element_type,
+                value,
+                shape,
+            } => write!(
+                f,
+                "full<[{}], {}> : {}",
+                join(shape, " "),
+                scalar_text(value, *element_type),
+                element_type
+            ),
+            // params were parsed as f32, so they are written as f32
+            Token::RangeTensor {
+                kind,
+                params,
+                element_type,
+            } => match kind {
+                0 => write!(
+                    f,
+                    "arange<{}, {}, {}> : {}",
+                    float_text(params[0] as f32),
+                    float_text(params[1] as f32),
+                    float_text(params[2] as f32),
+                    element_type
+                ),
+                _ => write!(
+                    f,
+                    "linspace<{}, {}, {}> : {}",
+                    float_text(params[0] as f32),
+                    float_text(params[1] as f32),
+                    params[2] as usize,
+                    element_type
+                ),
+            },
+            Token::EyeTensor {
+                element_type,
+                shape,
+            } => write!(f, "eye<{}, {}> : {}", shape[0], shape[1], element_type),
+            Token::UninitRNGTensor {
+                distribution,
+                params,
+                seed,
+                element_type,
+                shape,
+            } => {
+                let shape = join(shape, " ");
+                let first = float_text(params[0]);
+                let second = float_text(params[1]);
+                match distribution {
+                    0 => write!(f, "uniform<[{}], {}, {}, {}>", shape, first, second, seed),
+                    1 => write!(f, "normal<[{}], {}, {}, {}>", shape, first, second, seed),
+                    2 => write!(f, "bernoulli<[{}], {}, {}>", shape, first, seed),
+                    _ => write!(
+                        f,
+                        "truncated_normal<[{}], {}, {}, {}>",
+                        shape, first, second, seed
+                    ),
+                }?;
+                write!(f, " : {}", element_type)
+            }
+            Token::DType { element_type } => write!(f, "{}", element_type),
+            Token::ReduceAttr { axis, keepdims } => match axis {
+                Some(axis) => write!(f, "axis = {}, keepdims = {}", axis, keepdims),
+                None => write!(f, "keepdims = {}", keepdims),
+            },
+            Token::Dims { dims } => {
+                let dims: Vec<String> = dims.iter().map(|dim| dim_text(*dim)).collect();
+                write!(f, "[{}]", dims.join(", "))
+            }
+            Token::Axis { axis } => write!(f, "{}", axis),
+            Token::U16Literal { value } => write!(f, "{}", value),
+            Token::TensorType {
+                element_type,
+                shape,
+            } => {
+                write!(f, "tensor<")?;
+                for dim in shape {
+                    match dim {
+                        Some(dim) => write!(f, "{}x", dim)?,
+                        None => write!(f, "?x")?,
+                    }
+                }
+                write!(f, "{}>", element_type)
+            }
+            Token::FusedExpr { expr } => {
+                let steps: Vec<String> = expr
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        format!(
+                            "{}({})",
+                            step.opcode.to_mnemonic(),
+                            join(&step.operands, ", ")
+                        )
+                    })
+                    .collect();
+                write!(f, "[{}]", steps.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for AsmInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let code = match &self.opcode {
+            Token::BytecodeOpCode { code } => *code,
+            Token::Label { name } => return write!(f, "^{}:", name),
+            opcode => panic!("{:?} is not an opcode", opcode),
+        };
+        let operands: Vec<&Token> = [
+            &self.operand1,
+            &self.operand2,
+            &self.operand3,
+            &self.operand4,
+        ]
+        .iter()
+        .filter_map(|operand| operand.as_ref())
+        .collect();
+        let mnemonic = code.to_mnemonic();
+        let dtype = self.annotation.unwrap_or(annotation_type(code));
+        match code {
+            CRTOpCode::HALT => write!(f, "{}", mnemonic),
+            CRTOpCode::RETV | CRTOpCode::JMP => write!(f, "{} {}", mnemonic, operands[0]),
+            CRTOpCode::JMPIF | CRTOpCode::JMPIFR | CRTOpCode::LOOP | CRTOpCode::LOAD => {
+                write!(f, "{} {}, {}", mnemonic, operands[0], operands[1])
+            }
+            CRTOpCode::ADDR
+            | CRTOpCode::SUBR
+            | CRTOpCode::MULR
+            | CRTOpCode::CMPEQR
+            | CRTOpCode::CMPNER
+            | CRTOpCode::CMPLTR
+            | CRTOpCode::CMPLER
+            | CRTOpCode::CMPGTR
+            | CRTOpCode::CMPGER => write!(
+                f,
+                "{} = {}! {}, {}",
+                operands[0], mnemonic, operands[1], operands[2]
+            ),
+            CRTOpCode::MOVRT | CRTOpCode::MOVTR => {
+                write!(f, "{} = {}! {}", operands[0], mnemonic, operands[1])
+            }
+            CRTOpCode::SPLIT => write!(
+                f,
+                "{} = {}! {}, axis = {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], dtype
+            ),
+            CRTOpCode::CONCAT => write!(
+                f,
+                "{} = {}! {}, {}, axis = {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], operands[3], dtype
+            ),
+            CRTOpCode::SLICE => write!(
+                f,
+                "{} = {}! {}, {}, {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], operands[3], dtype
+            ),
+            CRTOpCode::RESHAPE
+            | CRTOpCode::TRANSPOSE
+            | CRTOpCode::SQUEEZE
+            | CRTOpCode::UNSQUEEZE => match dims_of(operands[2]).is_empty() {
+                true => write!(
+                    f,
+                    "{} = {}! {} : {}",
+                    operands[0], mnemonic, operands[1], dtype
+                ),
+                false => write!(
+                    f,
+                    "{} = {}! {}, {} : {}",
+                    operands[0], mnemonic, operands[1], operands[2], dtype
+                ),
+            },
+            CRTOpCode::CONV2DF32 => {
+                let attr = dims_of(operands[3]);
+                write!(
+                    f,
+                    "{} = {}! {}, {}, stride = [{}, {}], padding = [{}, {}], dilation = [{}, {}], groups = {} : {}",
+                    operands[0],
+                    mnemonic,
+                    operands[1],
+                    operands[2],
+                    attr[0],
+                    attr[1],
+                    attr[2],
+                    attr[3],
+                    attr[4],
+                    attr[5],
+                    attr[6],
+                    dtype
+                )
+            }
+            CRTOpCode::MAXPOOL2DF32 | CRTOpCode::AVGPOOL2DF32 => {
+                let attr = dims_of(operands[2]);
+                write!(
+                    f,
+                    "{} = {}! {}, kernel = [{}, {}], stride = [{}, {}], padding = [{}, {}] : {}",
+                    operands[0],
+                    mnemonic,
+                    operands[1],
+                    attr[0],
+                    attr[1],
+                    attr[2],
+                    attr[3],
+                    attr[4],
+                    attr[5],
+                    dtype
+                )
+            }
+            CRTOpCode::FUSEDF32 => write!(
+                f,
+                "{} = {}! ({}), expr = {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], dtype
+            ),
+            CRTOpCode::MATMULTF32 => {
+                let flags = dims_of(operands[3]);
+                write!(
+                    f,
+                    "{} = {}! {}, {}, transpose_a = {}, transpose_b = {} : {}",
+                    operands[0],
+                    mnemonic,
+                    operands[1],
+                    operands[2],
+                    flags[0] != 0,
+                    flags[1] != 0,
+                    dtype
+                )
+            }
+            _ if code.is_reduction() => write!(
+                f,
+                "{} = {}! {}, {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], dtype
+            ),
+            _ if operands.len() == 4 => write!(
+                f,
+                "{} = {}! {}, {}, {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], operands[3], dtype
+            ),
+            _ if operands.len() == 3 => write!(
+                f,
+                "{} = {}! {}, {} : {}",
+                operands[0], mnemonic, operands[1], operands[2], dtype
+            ),
+            // unary ops and literals, literals other than i32 and f32 carry their own types
+            _ => match operands[1] {
+                Token::I32Literal { .. } => write!(
+                    f,
+                    "{} = {}! {} : {}",
+                    operands[0],
+                    mnemonic,
+                    operands[1],
+                    ElementType::I32
+                ),
+                Token::F32Literal { .. } => write!(
+                    f,
+                    "{} = {}! {} : {}",
+                    operands[0],
+                    mnemonic,
+                    operands[1],
+                    ElementType::F32
+                ),
+                Token::Variable { .. } | Token::NamedVariable { .. } => write!(
+                    f,
+                    "{} = {}! {} : {}",
+                    operands[0], mnemonic, operands[1], dtype
+                ),
+                _ => write!(f, "{} = {}! {}", operands[0], mnemonic, operands[1]),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|(operand, tensor_type)| format!("{}: {}", operand, tensor_type))
+            .collect();
+        writeln!(
+            f,
+            "func @{}({}) -> ({}) {{",
+            self.name,
+            arguments.join(", "),
+            join(&self.results, ", ")
+        )?;
+        for inst in &self.instructions {
+            writeln!(f, "    {}", inst)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+// functions come before top-level instructions, one item per line
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for function in &self.functions {
+            writeln!(f, "{}", function)?;
+        }
+        for inst in &self.instructions {
+            writeln!(f, "{}", inst)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,809 @@
+// static checks of programs before they are pushed into the vm. Element types and shapes of values
+// are inferred in program order from literals, helpers and the rules of each op, then checked
+// against type annotations and the shapes each op accepts. Values whose type or shape is only
+// known at run time, such as tensors reshaped by registers, pass unchecked
+use std::collections::HashMap;
+
+use super::assembler_base::*;
+use crate::base::constants::REGISTER_DIM_FLAG;
+use crate::base::errors::VerifyError;
+use crate::base::{normalize_axis, ElementType, ValueType};
+use crate::instruction::CRTOpCode;
+use crate::tensors::*;
+
+// arguments of functions, dims of any size leave the shape unknown
+fn value_of_tensor_type(tensor_type: &Token) -> ValueType {
+    match tensor_type {
+        Token::TensorType {
+            element_type,
+            shape,
+        } => ValueType {
+            element_type: Some(*element_type),
+            shape: shape.iter().cloned().collect(),
+        },
+        _ => panic!("only tensor types describe values"),
+    }
+}
+
+// values defined so far, the ones defined before the program are looked up in existing
+struct Scope<'a, F: Fn(usize) -> Option<ValueType>> {
+    values: HashMap<usize, ValueType>,
+    existing: &'a F,
+}
+
+impl<'a, F: Fn(usize) -> Option<ValueType>> Scope<'a, F> {
+    fn lookup(&mut self, slot: usize) -> Option<ValueType> {
+        if !self.values.contains_key(&slot) {
+            let value = (self.existing)(slot)?;
+            self.values.insert(slot, value);
+        }
+        self.values.get(&slot).cloned()
+    }
+}
+
+fn variable_text(slot: usize) -> String {
+    Token::Variable { symbol: slot }.to_string()
+}
+
+// ops that convert between element types may be annotated with the type of either side
+fn converts(code: CRTOpCode) -> bool {
+    code.to_cast_element_type().is_some()
+        || code.is_comparison()
+        || code == CRTOpCode::ARGMAXF32
+        || code == CRTOpCode::ARGMINF32
+}
+
+pub(crate) fn is_binary_elementwise(code: CRTOpCode) -> bool {
+    match code {
+        CRTOpCode::ADDF32
+        | CRTOpCode::ADDI32
+        | CRTOpCode::SUBF32
+        | CRTOpCode::SUBI32
+        | CRTOpCode::MULF32
+        | CRTOpCode::MULI32
+        | CRTOpCode::DIVF32
+        | CRTOpCode::FLOORDIVI32
+        | CRTOpCode::ANDI32
+        | CRTOpCode::ORI32
+        | CRTOpCode::XORI32
+        | CRTOpCode::SHLI32
+        | CRTOpCode::SHRI32
+        | CRTOpCode::POWF32
+        | CRTOpCode::ATAN2F32
+        | CRTOpCode::MAXF32
+        | CRTOpCode::MINF32
+        | CRTOpCode::REMF32
+        | CRTOpCode::MAXI32
+        | CRTOpCode::MINI32
+        | CRTOpCode::REMI32 => true,
+        _ => code.is_comparison(),
+    }
+}
+
+pub(crate) fn is_unary_elementwise(code: CRTOpCode) -> bool {
+    match code {
+        CRTOpCode::EXPF32
+        | CRTOpCode::ABSF32
+        | CRTOpCode::NEGF32
+        | CRTOpCode::CEILF32
+        | CRTOpCode::FLOORF32
+        | CRTOpCode::ATANF32
+        | CRTOpCode::COSF32
+        | CRTOpCode::SINF32
+        | CRTOpCode::EXP2F32
+        | CRTOpCode::EXPM1F32
+        | CRTOpCode::LOGF32
+        | CRTOpCode::LOG10F32
+        | CRTOpCode::LOG1PF32
+        | CRTOpCode::LOG2F32
+        | CRTOpCode::RSQRTF32
+        | CRTOpCode::SQRTF32
+        | CRTOpCode::TANHF32
+        | CRTOpCode::NOTI32
+        | CRTOpCode::ZEROSLIKE
+        | CRTOpCode::ONESLIKE => true,
+        _ => false,
+    }
+}
+
+// slots of the values an instruction defines, and of the values it reads by order
+pub(crate) fn defined_slots(code: CRTOpCode, inst: &AsmInstruction) -> Vec<usize> {
+    match (code, &inst.operand1) {
+        (CRTOpCode::RETV, _) | (CRTOpCode::JMPIF, _) => vec![],
+        (_, Some(operand @ Token::Variable { .. }))
+        | (_, Some(operand @ Token::Variables { .. })) => operand.to_slots(),
+        _ => vec![],
+    }
+}
+
+pub(crate) fn used_slots(code: CRTOpCode, inst: &AsmInstruction) -> Vec<usize> {
+    let operands = match code {
+        CRTOpCode::RETV | CRTOpCode::JMPIF => [&inst.operand1, &inst.operand2, &inst.operand3],
+        _ => [&inst.operand2, &inst.operand3, &inst.operand4],
+    };
+    operands
+        .iter()
+        .filter_map(|operand| match operand {
+            Some(operand @ Token::Variable { .. }) | Some(operand @ Token::Variables { .. }) => {
+                Some(operand.to_slots())
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+// dims of shape ops, None if any of them is read from a register
+pub(crate) fn static_dims(operand: &Option<Token>) -> Option<Vec<usize>> {
+    match operand {
+        Some(Token::Dims { dims }) if dims.iter().all(|dim| dim & REGISTER_DIM_FLAG == 0) => {
+            Some(dims.clone())
+        }
+        _ => None,
+    }
+}
+
+fn dims_of(operand: &Option<Token>) -> Vec<usize> {
+    match operand {
+        Some(Token::Dims { dims }) => dims.clone(),
+        _ => vec![],
+    }
+}
+
+// axis of split and concat, None if it is read from a register
+fn static_axis(operand: &Option<Token>) -> Option<i32> {
+    match operand {
+        Some(Token::Axis {
+            axis: AxisArg::Static(axis),
+        }) => Some(*axis as i32),
+        _ => None,
+    }
+}
+
+// type of the tensor made by literals and helpers
+fn literal_type(literal: &Option<Token>) -> ValueType {
+    match literal {
+        Some(Token::Tensor {
+            element_type,
+            shape,
+            ..
+        })
+        | Some(Token::UninitTensor {
+            element_type,
+            shape,
+            ..
+        })
+        | Some(Token::EyeTensor {
+            element_type,
+            shape,
+        })
+        | Some(Token::UninitRNGTensor {
+            element_type,
+            shape,
+            ..
+        }) => ValueType::new(*element_type, shape.clone()),
+        Some(Token::RangeTensor {
+            kind,
+            params,
+            element_type,
+        }) => {
+            let len = match *kind {
+                0 if params[2] != 0f64 => {
+                    Some(arange_values(params[0], params[1], params[2]).len())
+                }
+                1 => Some(params[2] as usize),
+                _ => None,
+            };
+            ValueType {
+                element_type: Some(*element_type),
+                shape: len.map(|len| vec![len]),
+            }
+        }
+        _ => ValueType::unknown(),
+    }
+}
+
+fn broadcast(shapes: &[&Vec<usize>]) -> Result<Vec<usize>, String> {
+    let mut result = shapes[0].clone();
+    for shape in &shapes[1..] {
+        result = match broadcast_shape(&result, shape) {
+            Some(result) => result,
+            None => {
+                let texts: Vec<String> =
+                    shapes.iter().map(|shape| format!("{:?}", shape)).collect();
+                return Err(format!("cannot broadcast shapes {}", texts.join(" and ")));
+            }
+        };
+    }
+    Ok(result)
+}
+
+fn matmul(
+    lhs: &[usize],
+    rhs: &[usize],
+    transpose_a: bool,
+    transpose_b: bool,
+) -> Result<Vec<usize>, String> {
+    if lhs.len() < 2 || rhs.len() < 2 {
+        return Err(format!(
+            "matmul takes operands of rank 2 or more, got shapes {:?} and {:?}",
+            lhs, rhs
+        ));
+    }
+    let lhs_k = lhs[lhs.len() - 1 - transpose_a as usize];
+    let rhs_k = rhs[rhs.len() - 2 + transpose_b as usize];
+    if lhs_k != rhs_k {
+        return Err(format!(
+            "inner dims of matmul do not match, {} of {:?} and {} of {:?}",
+            lhs_k, lhs, rhs_k, rhs
+        ));
+    }
+    matmul_shape(lhs, rhs, transpose_a, transpose_b).ok_or(format!(
+        "cannot broadcast batch dims of shapes {:?} and {:?}",
+        lhs, rhs
+    ))
+}
+
+pub(crate) fn reshape(shape: &[usize], dims: Vec<usize>) -> Result<Vec<usize>, String> {
+    match shape.iter().product::<usize>() == dims.iter().product::<usize>() {
+        true => Ok(dims),
+        false => Err(format!("cannot reshape {:?} into {:?}", shape, dims)),
+    }
+}
+
+pub(crate) fn squeeze(shape: &[usize], axes: Vec<usize>) -> Result<Vec<usize>, String> {
+    match axes
+        .iter()
+        .find(|axis| **axis >= shape.len() || shape[**axis] != 1)
+    {
+        Some(axis) => Err(format!("cannot squeeze axis {} of shape {:?}", axis, shape)),
+        None => Ok(squeeze_shape(shape, &axes)),
+    }
+}
+
+pub(crate) fn unsqueeze(shape: &[usize], mut axes: Vec<usize>) -> Result<Vec<usize>, String> {
+    axes.sort();
+    let mut unsqueezed = shape.to_vec();
+    for axis in axes {
+        if axis > unsqueezed.len() {
+            return Err(format!(
+                "cannot unsqueeze axis {} of shape {:?}",
+                axis, shape
+            ));
+        }
+        unsqueezed.insert(axis, 1);
+    }
+    Ok(unsqueezed)
+}
+
+// reverses all dims if perm is empty
+pub(crate) fn transpose(shape: &[usize], perm: Vec<usize>) -> Result<Vec<usize>, String> {
+    let perm: Vec<usize> = match perm.is_empty() {
+        true => (0..shape.len()).rev().collect(),
+        false => perm,
+    };
+    let mut sorted_perm = perm.clone();
+    sorted_perm.sort();
+    match sorted_perm == (0..shape.len()).collect::<Vec<usize>>() {
+        true => Ok(perm.iter().map(|d| shape[*d]).collect()),
+        false => Err(format!(
+            "{:?} is not a permutation of shape {:?}",
+            perm, shape
+        )),
+    }
+}
+
+// ends are clamped to the dims, as slicing does at run time
+fn slice(shape: &[usize], begins: Vec<usize>, ends: Vec<usize>) -> Result<Vec<usize>, String> {
+    if begins.len() != shape.len() || ends.len() != shape.len() {
+        return Err(format!(
+            "slice bounds {:?}, {:?} mismatch rank of shape {:?}",
+            begins, ends, shape
+        ));
+    }
+    Ok((0..shape.len())
+        .map(|d| ends[d].min(shape[d]).saturating_sub(begins[d]))
+        .collect())
+}
+
+fn concat(lhs: &[usize], rhs: &[usize], axis: i32) -> Result<Vec<usize>, String> {
+    let axis = normalize_axis(axis, lhs.len())?;
+    if lhs.len() != rhs.len() || (0..lhs.len()).any(|d| d != axis && lhs[d] != rhs[d]) {
+        return Err(format!(
+            "cannot concat shapes {:?} and {:?} on axis {}",
+            lhs, rhs, axis
+        ));
+    }
+    let mut shape = lhs.to_vec();
+    shape[axis] += rhs[axis];
+    Ok(shape)
+}
+
+fn split(shape: &[usize], axis: i32, count: usize) -> Result<Vec<usize>, String> {
+    let axis = normalize_axis(axis, shape.len())?;
+    if count == 0 || shape[axis] % count != 0 {
+        return Err(format!(
+            "cannot split shape {:?} into {} parts on axis {}",
+            shape, count, axis
+        ));
+    }
+    let mut part = shape.to_vec();
+    part[axis] /= count;
+    Ok(part)
+}
+
+// conditions of jumps and values moved into registers must hold a single value
+fn check_scalar(value: &ValueType) -> Result<(), String> {
+    match &value.shape {
+        Some(shape) if shape.iter().product::<usize>() != 1 => Err(format!(
+            "a scalar tensor is expected, got shape {:?}",
+            shape
+        )),
+        _ => Ok(()),
+    }
+}
+
+// operands of return must conform to the results declared by the function
+fn check_results(function: &Function, operands: &[ValueType]) -> Result<(), String> {
+    if operands.len() != function.results.len() {
+        return Err(format!(
+            "@{} declares {} results, returns {}",
+            function.name,
+            function.results.len(),
+            operands.len()
+        ));
+    }
+    for (position, (operand, tensor_type)) in operands.iter().zip(&function.results).enumerate() {
+        if let ValueType {
+            element_type: Some(element_type),
+            shape: Some(shape),
+        } = operand
+        {
+            if !tensor_type.accepts(*element_type, shape) {
+                return Err(format!(
+                    "result #{} is {} of shape {:?}, declared {}",
+                    position, element_type, shape, tensor_type
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// types of the values an instruction defines, by the order of defined_slots
+fn infer(
+    code: CRTOpCode,
+    inst: &AsmInstruction,
+    inputs: &[ValueType],
+) -> Result<Vec<ValueType>, String> {
+    let unknown = ValueType::unknown();
+    let first = inputs.get(0).unwrap_or(&unknown);
+    let shapes: Option<Vec<&Vec<usize>>> =
+        inputs.iter().map(|input| input.shape.as_ref()).collect();
+    let result = match code {
+        CRTOpCode::CONSTI32 => ValueType::new(ElementType::I32, vec![1]),
+        CRTOpCode::CONSTF32 => ValueType::new(ElementType::F32, vec![1]),
+        CRTOpCode::CONSTF16
+        | CRTOpCode::CONSTBF16
+        | CRTOpCode::CONSTI8
+        | CRTOpCode::CONSTU8
+        | CRTOpCode::CONSTF64
+        | CRTOpCode::CONSTI64
+        | CRTOpCode::CONSTBOOL => ValueType::new(code.to_const_element_type().unwrap(), vec![1]),
+        CRTOpCode::CONSTTENSOR
+        | CRTOpCode::SVALUETENSOR
+        | CRTOpCode::RANGETENSOR
+        | CRTOpCode::EYETENSOR
+        | CRTOpCode::RNGTENSOR => literal_type(&inst.operand2),
+        CRTOpCode::MOVRT => ValueType::new(ElementType::I32, vec![]),
+        CRTOpCode::JMPIF | CRTOpCode::MOVTR => {
+            check_scalar(first)?;
+            return Ok(vec![]);
+        }
+        _ if is_unary_elementwise(code) => first.clone(),
+        _ if code.to_cast_element_type().is_some() => ValueType {
+            element_type: code.to_cast_element_type(),
+            shape: first.shape.clone(),
+        },
+        _ if is_binary_elementwise(code) => {
+            let element_type = match code.is_comparison() {
+                true => Some(ElementType::BOOL),
+                false if inputs[0].element_type == inputs[1].element_type => first.element_type,
+                false => None,
+            };
+            ValueType {
+                element_type: element_type,
+                shape: shapes.map(|shapes| broadcast(&shapes)).transpose()?,
+            }
+        }
+        CRTOpCode::SELECT | CRTOpCode::FMAF32 => {
+            // select takes a bool or i32 mask and values of one dtype, fma takes f32 only
+            let dtypes = (
+                inputs[0].element_type,
+                inputs[1].element_type,
+                inputs[2].element_type,
+            );
+            let supported = match code {
+                CRTOpCode::SELECT => {
+                    let values = match (dtypes.1, dtypes.2) {
+                        (Some(on_true), Some(on_false)) => on_true == on_false,
+                        _ => true,
+                    };
+                    values
+                        && matches!(
+                            dtypes.0,
+                            None | Some(ElementType::BOOL) | Some(ElementType::I32)
+                        )
+                }
+                _ => [dtypes.0, dtypes.1, dtypes.2]
+                    .iter()
+                    .all(|dtype| dtype.map_or(true, |dtype| dtype == ElementType::F32)),
+            };
+            if !supported {
+                let name = |dtype: Option<ElementType>| {
+                    dtype.map_or(String::from("?"), |dtype| dtype.to_string())
+                };
+                return Err(format!(
+                    "{:?} does not take tensors of {}, {} and {}",
+                    code,
+                    name(dtypes.0),
+                    name(dtypes.1),
+                    name(dtypes.2)
+                ));
+            }
+            let element_type = match code {
+                CRTOpCode::SELECT => inputs[1].element_type,
+                _ => first.element_type,
+            };
+            ValueType {
+                element_type: element_type,
+                shape: shapes.map(|shapes| broadcast(&shapes)).transpose()?,
+            }
+        }
+        CRTOpCode::FUSEDF32 => {
+            if let Some(Token::FusedExpr { expr }) = &inst.operand3 {
+                expr.check(inputs.len())?;
+            }
+            let element_type = match inputs
+                .iter()
+                .all(|input| input.element_type == first.element_type)
+            {
+                true => first.element_type,
+                false => None,
+            };
+            ValueType {
+                element_type: element_type,
+                shape: shapes.map(|shapes| broadcast(&shapes)).transpose()?,
+            }
+        }
+        CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => {
+            let flags = match code {
+                CRTOpCode::MATMULTF32 => dims_of(&inst.operand4),
+                _ => vec![0, 0],
+            };
+            first.with_shape(
+                shapes
+                    .map(|shapes| matmul(shapes[0], shapes[1], flags[0] != 0, flags[1] != 0))
+                    .transpose()?,
+            )
+        }
+        _ if code.is_reduction() => {
+            let (axis, keepdims) = match &inst.operand3 {
+                Some(Token::ReduceAttr { axis, keepdims }) => (*axis, *keepdims),
+                _ => (None, false),
+            };
+            let shape = match (&first.shape, axis) {
+                (Some(shape), None) => Some(reduce_shape(shape, None, keepdims)),
+                (Some(shape), Some(AxisArg::Static(axis))) => {
+                    let axis = normalize_axis(axis as i32, shape.len())?;
+                    Some(reduce_shape(shape, Some(axis), keepdims))
+                }
+                _ => None,
+            };
+            match code {
+                CRTOpCode::ARGMAXF32 | CRTOpCode::ARGMINF32 => ValueType {
+                    element_type: Some(ElementType::I32),
+                    shape: shape,
+                },
+                _ => first.with_shape(shape),
+            }
+        }
+        CRTOpCode::RESHAPE | CRTOpCode::SQUEEZE | CRTOpCode::UNSQUEEZE | CRTOpCode::TRANSPOSE => {
+            let shape = match (&first.shape, static_dims(&inst.operand3)) {
+                (Some(shape), Some(dims)) => Some(match code {
+                    CRTOpCode::RESHAPE => reshape(shape, dims)?,
+                    CRTOpCode::SQUEEZE => squeeze(shape, dims)?,
+                    CRTOpCode::UNSQUEEZE => unsqueeze(shape, dims)?,
+                    _ => transpose(shape, dims)?,
+                }),
+                (None, Some(dims)) if code == CRTOpCode::RESHAPE => Some(dims),
+                _ => None,
+            };
+            first.with_shape(shape)
+        }
+        CRTOpCode::SLICE => {
+            let bounds = (
+                &first.shape,
+                static_dims(&inst.operand3),
+                static_dims(&inst.operand4),
+            );
+            let shape = match bounds {
+                (Some(shape), Some(begins), Some(ends)) => Some(slice(shape, begins, ends)?),
+                _ => None,
+            };
+            first.with_shape(shape)
+        }
+        CRTOpCode::CONCAT => {
+            if let (Some(lhs), Some(rhs)) = (inputs[0].element_type, inputs[1].element_type) {
+                if lhs != rhs {
+                    return Err(format!("cannot concat tensors of {} and {}", lhs, rhs));
+                }
+            }
+            let shape = match (shapes, static_axis(&inst.operand4)) {
+                (Some(shapes), Some(axis)) => Some(concat(shapes[0], shapes[1], axis)?),
+                _ => None,
+            };
+            first.with_shape(shape)
+        }
+        CRTOpCode::SPLIT => {
+            let count = defined_slots(code, inst).len();
+            let part = match (&first.shape, static_axis(&inst.operand3)) {
+                (Some(shape), Some(axis)) => Some(split(shape, axis, count)?),
+                _ => None,
+            };
+            return Ok(vec![first.with_shape(part); count]);
+        }
+        CRTOpCode::CONV2DF32 => {
+            let attr = Conv2dAttr::from_vec(&dims_of(&inst.operand4));
+            let shape = match shapes {
+                Some(shapes) => Some(conv2d_shape(shapes[0], shapes[1], &attr).ok_or(format!(
+                    "cannot convolve shape {:?} with weight of shape {:?} by {:?}",
+                    shapes[0], shapes[1], attr
+                ))?),
+                None => None,
+            };
+            first.with_shape(shape)
+        }
+        CRTOpCode::MAXPOOL2DF32 | CRTOpCode::AVGPOOL2DF32 => {
+            let attr = Pool2dAttr::from_vec(&dims_of(&inst.operand3));
+            let shape = match &first.shape {
+                Some(shape) => Some(
+                    pool2d_shape(shape, &attr)
+                        .ok_or(format!("cannot pool shape {:?} by {:?}", shape, attr))?,
+                ),
+                None => None,
+            };
+            first.with_shape(shape)
+        }
+        // constants of modules are only known to the vm
+        _ => ValueType::unknown(),
+    };
+    Ok(vec![result])
+}
+
+// infers the values an instruction defines and checks its annotation
+fn check<F: Fn(usize) -> Option<ValueType>>(
+    scope: &mut Scope<F>,
+    inst: &AsmInstruction,
+    function: Option<&Function>,
+) -> Result<Vec<ValueType>, String> {
+    let code = match &inst.opcode {
+        Token::BytecodeOpCode { code } => *code,
+        _ => return Ok(vec![]),
+    };
+    let mut inputs = vec![];
+    for slot in used_slots(code, inst) {
+        match scope.lookup(slot) {
+            Some(value) => inputs.push(value),
+            None => {
+                return Err(format!(
+                    "{} is used before it is defined",
+                    variable_text(slot)
+                ))
+            }
+        }
+    }
+    if code == CRTOpCode::RETV {
+        return match function {
+            Some(function) => check_results(function, &inputs).map(|_| vec![]),
+            None => Ok(vec![]),
+        };
+    }
+
+    let results = infer(code, inst, &inputs)?;
+    let result_type = results.first().and_then(|result| result.element_type);
+    if let (Some(annotation), Some(result_type)) = (inst.annotation, result_type) {
+        let operand_type = inputs.first().and_then(|input| input.element_type);
+        if annotation != result_type && !(converts(code) && Some(annotation) == operand_type) {
+            return Err(format!(
+                "annotated as {}, but the result is {}",
+                annotation, result_type
+            ));
+        }
+    }
+    Ok(results)
+}
+
+fn verify_instructions<F: Fn(usize) -> Option<ValueType>>(
+    scope: &mut Scope<F>,
+    instructions: &[AsmInstruction],
+    function: Option<&Function>,
+    errors: &mut Vec<VerifyError>,
+) {
+    for (index, inst) in instructions.iter().enumerate() {
+        let slots = match &inst.opcode {
+            Token::BytecodeOpCode { code } => defined_slots(*code, inst),
+            _ => vec![],
+        };
+        // values of failed instructions are taken as unknown, so errors do not cascade
+        let results = match check(scope, inst, function) {
+            Ok(results) => results,
+            Err(message) => {
+                errors.push(VerifyError {
+                    function: function.map(|function| function.name.clone()),
+                    index: index,
+                    instruction: inst.to_string(),
+                    message: message,
+                });
+                vec![]
+            }
+        };
+        for (position, slot) in slots.into_iter().enumerate() {
+            let value = results
+                .get(position)
+                .cloned()
+                .unwrap_or(ValueType::unknown());
+            scope.values.insert(slot, value);
+        }
+    }
+}
+
+// verifies the top-level instructions and function bodies of a program whose names are resolved.
+// existing gives the values defined before the program, such as those of earlier programs in the
+// vm. Function bodies see their arguments besides
+pub fn verify<F: Fn(usize) -> Option<ValueType>>(
+    program: &Program,
+    existing: F,
+) -> Result<(), Vec<VerifyError>> {
+    let mut errors = vec![];
+    for function in &program.functions {
+        let mut scope = Scope {
+            values: HashMap::new(),
+            existing: &existing,
+        };
+        for (operand, tensor_type) in &function.arguments {
+            scope
+                .values
+                .insert(operand.to_slot(), value_of_tensor_type(tensor_type));
+        }
+        verify_instructions(
+            &mut scope,
+            &function.instructions,
+            Some(function),
+            &mut errors,
+        );
+    }
+    let mut scope = Scope {
+        values: HashMap::new(),
+        existing: &existing,
+    };
+    verify_instructions(&mut scope, &program.instructions, None, &mut errors);
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn verify_source(source: &str) -> Result<(), Vec<VerifyError>> {
+        verify(&assemble(source).unwrap(), |_| None)
+    }
+
+    #[test]
+    fn test_verify_inferred_types() {
+        let source = "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4. 5. 6.], shape=[2 3]>: f32
+%1 = crt.helper.svalue.tensor! ones<[3 4]> : f32
+%2 = crt.matmul.f32! %0, %1 : f32
+%3 = crt.reduce.sum.f32! %2, axis = -1 : f32
+%4 = crt.argmax.f32! %2, axis = 1 : i32
+%5 = crt.cmp.gt.f32! %3, %3 : bool
+%6 = crt.tensor.reshape! %2, [4, 2] : f32
+%7 = crt.matmul.f32! %0, %0, transpose_b = true : f32
+%8 = crt.helper.range.tensor! arange<0, 8, 2> : i64
+%9 = crt.tensor.concat! %8, %8, axis = 0 : i64
+%10 = crt.fused.f32! (%0), expr = [crt.exp.f32(0), crt.add.f32(0, 1)] : f32
+";
+        assert_eq!(verify_source(source), Ok(()));
+
+        let errors = verify_source(&format!(
+            "{}%11 = crt.matmul.f32! %0, %6 : f32\n%12 = crt.add.f32! %3, %8 : f32\n",
+            source
+        ))
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].index, 11);
+        assert_eq!(
+            errors[0].to_string(),
+            "instruction 11 `%11 = crt.matmul.f32! %0, %6 : f32`: inner dims of matmul do not match, 3 of [2, 3] and 4 of [4, 2]"
+        );
+        assert_eq!(errors[1].message, "cannot broadcast shapes [2] and [4]");
+    }
+
+    #[test]
+    fn test_verify_annotations() {
+        // literals take the type of the const op
+        let errors = verify_source("%0 = crt.literal.const.i32! 1.3 : f32\n").unwrap_err();
+        assert_eq!(errors[0].message, "annotated as f32, but the result is i32");
+
+        let errors = verify_source(
+            "%0 = crt.literal.const.f32! 1.3 : f32\n%1 = crt.exp.f32! %0 : i32\n%2 = crt.cast.i32! %0 : f32\n",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].index, 1);
+    }
+
+    #[test]
+    fn test_verify_dtypes() {
+        let errors = verify_source(
+            "%0 = crt.helper.svalue.tensor! ones<[2 3]> : f32
+%1 = crt.helper.svalue.tensor! ones<[2 3]> : i32
+%2 = crt.select! %0, %0, %0 : f32
+%3 = crt.select! %1, %0, %1 : f32
+%4 = crt.tensor.concat! %0, %1, axis = 0 : f32
+",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0].message,
+            "SELECT does not take tensors of f32, f32 and f32"
+        );
+        assert_eq!(
+            errors[1].message,
+            "SELECT does not take tensors of i32, f32 and i32"
+        );
+        assert_eq!(errors[2].message, "cannot concat tensors of f32 and i32");
+    }
+
+    #[test]
+    fn test_verify_use_before_define() {
+        let program = assemble("%1 = crt.exp.f32! %0 : f32\n%2 = crt.exp.f32! %1 : f32\n").unwrap();
+        // the failed instruction defines %1 of unknown type, so errors do not cascade
+        let errors = verify(&program, |_| None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "%0 is used before it is defined");
+
+        // values of earlier programs are defined
+        let existing = |slot| match slot {
+            0 => Some(ValueType::new(ElementType::F32, vec![2])),
+            _ => None,
+        };
+        assert_eq!(verify(&program, existing), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_function() {
+        let errors = verify_source(
+            "func @f(%0: tensor<2x3xf32>, %1: tensor<?x3xf32>) -> (tensor<3x2xf32>) {
+    %2 = crt.exp.f32! %0 : f32
+    %3 = crt.add.f32! %2, %1 : f32
+    %4 = crt.tensor.slice! %0, [0, 1], [2, 3] : f32
+    return %2
+}
+",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].function, Some(String::from("f")));
+        assert_eq!(errors[0].index, 3);
+        assert_eq!(
+            errors[0].message,
+            "result #0 is f32 of shape [2, 3], declared tensor<3x2xf32>"
+        );
+    }
+}
@@ -10,3 +10,23 @@ pub const I32STRIDE: buffer::Stride = std::mem::size_of::<i32>() as buffer::Stri
 
 // const attributes for unsigned integer data types
 pub const U8STRIDE: buffer::Stride = std::mem::size_of::<u8>() as buffer::Stride;
+
+// marks an entry of dims lists as the index of an i32 register, whose value is read at run time
+pub const REGISTER_DIM_FLAG: usize = 1 << (usize::BITS - 1);
+
+// slots of named values such as %hidden1 start here, numbered values must stay below it
+pub const NAMED_SLOT_BASE: usize = 1 << 16;
+
+// leading bytes and format version of .crtb module files
+pub const MODULE_MAGIC: [u8; 4] = *b"CRTB";
+pub const MODULE_VERSION: u16 = 1;
+
+// dense literals of more bytes are stored in the constant section of module files
+pub const CONST_SECTION_THRESHOLD: usize = 256;
+
+// results of constant folding of more bytes stay as ops, so that programs do not grow with literals
+pub const FOLD_BYTES_LIMIT: usize = 4096;
+
+// operands of fused ops, each of them and the result is bound to its own descriptor set, and
+// vulkan guarantees 4 bound sets at least
+pub const FUSION_MAX_INPUTS: usize = 3;
@@ -4,6 +4,77 @@ use std::fmt;
 pub enum RuntimeStatusError {
     EXEC_FINISH,
     RT_ERROR,
+    // operands of an instruction have incompatible shapes
+    SHAPE_MISMATCH(String),
+    // operands of an instruction have dtypes that the op does not take
+    DTYPE_MISMATCH(String),
+    // called function is not defined in any loaded program
+    FUNCTION_NOT_FOUND(String),
+    // arguments or results of a call do not conform to the function signature
+    ARGUMENT_MISMATCH(String),
+    // bytecodes fail to assemble
+    SYNTAX_ERROR(AssemblerError),
+    // module files or bytecodes fail to load, such as a wrong checksum, an unsupported version or
+    // malformed instructions
+    INVALID_MODULE(String),
+    // programs fail static verification, such as mismatched shapes or wrong type annotations
+    VERIFY_ERROR(Vec<VerifyError>),
+}
+
+// location of the first item of a source that fails to assemble, line and column count from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblerError {
+    pub line: usize,
+    pub column: usize,
+    // rest of the line from the column
+    pub snippet: String,
+    // what the assembler expects at the column
+    pub expected: String,
+}
+
+impl fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.snippet[..] {
+            "" => write!(
+                f,
+                "line {}, column {}: unexpected end of input, expected {}",
+                self.line, self.column, self.expected
+            ),
+            snippet => write!(
+                f,
+                "line {}, column {}: unexpected `{}`, expected {}",
+                self.line, self.column, snippet, self.expected
+            ),
+        }
+    }
+}
+
+// instruction that fails static verification, index counts from 0 over the top-level
+// instructions, or over the body of function if it is given
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyError {
+    pub function: Option<String>,
+    pub index: usize,
+    // canonical text of the instruction
+    pub instruction: String,
+    pub message: String,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.function {
+            Some(name) => write!(
+                f,
+                "@{}, instruction {} `{}`: {}",
+                name, self.index, self.instruction, self.message
+            ),
+            None => write!(
+                f,
+                "instruction {} `{}`: {}",
+                self.index, self.instruction, self.message
+            ),
+        }
+    }
 }
 
 impl fmt::Display for RuntimeStatusError {
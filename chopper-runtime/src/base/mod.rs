@@ -1,26 +1,166 @@
 pub mod constants;
 pub mod errors;
 pub mod kernel;
+pub mod random;
 
+use half::{bf16, f16};
 use nom::types::CompleteStr;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum ElementType {
     I32,
     F32,
+    // masks of comparison ops, carried by i32 tensors holding 0 or 1
+    BOOL,
+    // storage types of inference models, computed in their 32-bit counterparts
+    F16,
+    BF16,
+    I8,
+    U8,
+    F64,
+    I64,
 }
 
+impl ElementType {
+    // bytes taken by a scalar in literals, bool literals take a byte
+    pub fn byte_width(&self) -> usize {
+        match self {
+            ElementType::BOOL | ElementType::I8 | ElementType::U8 => 1,
+            ElementType::F16 | ElementType::BF16 => 2,
+            ElementType::I32 | ElementType::F32 => 4,
+            ElementType::F64 | ElementType::I64 => 8,
+        }
+    }
+
+    // byte of the element type in bytecodes, such as dense literals
+    pub fn code(&self) -> u8 {
+        match self {
+            ElementType::I32 => 0,
+            ElementType::F32 => 1,
+            ElementType::BOOL => 2,
+            ElementType::F16 => 3,
+            ElementType::BF16 => 4,
+            ElementType::I8 => 5,
+            ElementType::U8 => 6,
+            ElementType::F64 => 7,
+            ElementType::I64 => 8,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        match self {
+            ElementType::F16 | ElementType::BF16 | ElementType::F32 | ElementType::F64 => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<u8> for ElementType {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => ElementType::I32,
+            1 => ElementType::F32,
+            2 => ElementType::BOOL,
+            3 => ElementType::F16,
+            4 => ElementType::BF16,
+            5 => ElementType::I8,
+            6 => ElementType::U8,
+            7 => ElementType::F64,
+            8 => ElementType::I64,
+            _ => panic!("not recognise this element type code"),
+        }
+    }
+}
+
+impl FromStr for ElementType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i32" => Ok(ElementType::I32),
+            "f32" => Ok(ElementType::F32),
+            "bool" => Ok(ElementType::BOOL),
+            "f16" => Ok(ElementType::F16),
+            "bf16" => Ok(ElementType::BF16),
+            "i8" => Ok(ElementType::I8),
+            "u8" => Ok(ElementType::U8),
+            "f64" => Ok(ElementType::F64),
+            "i64" => Ok(ElementType::I64),
+            _ => Err(format!("unknown element type {}", s)),
+        }
+    }
+}
+
+// names of element types in type annotations, the inverse of from_str
+impl fmt::Display for ElementType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ElementType::I32 => "i32",
+            ElementType::F32 => "f32",
+            ElementType::BOOL => "bool",
+            ElementType::F16 => "f16",
+            ElementType::BF16 => "bf16",
+            ElementType::I8 => "i8",
+            ElementType::U8 => "u8",
+            ElementType::F64 => "f64",
+            ElementType::I64 => "i64",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// parsers only convert the names matched by parse_element_type
 impl From<CompleteStr<'_>> for ElementType {
     fn from(s: CompleteStr<'_>) -> Self {
-        match s {
-            CompleteStr("i32") => ElementType::I32,
-            CompleteStr("f32") => ElementType::F32,
-            _ => panic!("not recognise this element type"),
+        s.0.parse().expect("not recognise this element type")
+    }
+}
+
+// element type and shape of a value, None if it is not known before run time
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueType {
+    pub element_type: Option<ElementType>,
+    pub shape: Option<Vec<usize>>,
+}
+
+impl ValueType {
+    pub fn new(element_type: ElementType, shape: Vec<usize>) -> ValueType {
+        ValueType {
+            element_type: Some(element_type),
+            shape: Some(shape),
+        }
+    }
+
+    pub fn unknown() -> ValueType {
+        ValueType {
+            element_type: None,
+            shape: None,
+        }
+    }
+
+    pub fn with_shape(&self, shape: Option<Vec<usize>>) -> ValueType {
+        ValueType {
+            element_type: self.element_type,
+            shape: shape,
         }
     }
 }
 
+// negative axes count from the last dim
+pub(crate) fn normalize_axis(axis: i32, rank: usize) -> Result<usize, String> {
+    let normalized = match axis < 0 {
+        true => axis + rank as i32,
+        false => axis,
+    };
+    match normalized >= 0 && normalized < rank as i32 {
+        true => Ok(normalized as usize),
+        false => Err(format!("axis {} is out of range of rank {}", axis, rank)),
+    }
+}
+
 pub trait SupportedType {
     fn get_type_code(&self) -> ElementType;
 }
@@ -36,3 +176,45 @@ impl SupportedType for f32 {
         return ElementType::F32;
     }
 }
+
+impl SupportedType for f16 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::F16;
+    }
+}
+
+impl SupportedType for bf16 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::BF16;
+    }
+}
+
+impl SupportedType for i8 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::I8;
+    }
+}
+
+impl SupportedType for u8 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::U8;
+    }
+}
+
+impl SupportedType for f64 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::F64;
+    }
+}
+
+impl SupportedType for i64 {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::I64;
+    }
+}
+
+impl SupportedType for bool {
+    fn get_type_code(&self) -> ElementType {
+        return ElementType::BOOL;
+    }
+}
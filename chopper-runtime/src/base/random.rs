@@ -0,0 +1,83 @@
+use std::f64::consts::PI;
+
+// generators of random tensors run on host, so that a seed yields the same tensor whichever
+// executor consumes it
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    // splitmix64
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    pub fn next_uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // standard normal by box-muller, one of the pair is dropped to keep the stream simple
+    pub fn next_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_uniform();
+        let u2 = self.next_uniform();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+// distribution => u8 : 0 -> uniform; 1 -> normal; 2 -> bernoulli; 3 -> truncated normal
+// for uniform, params => [min, max]
+// for normal and truncated normal, params => [mean, std], truncated normal resamples values
+// beyond two std from the mean
+// for bernoulli, params => [p], values are 1 with probability p and 0 otherwise
+pub fn sample(distribution: u8, params: &[f32], seed: u64, len: usize) -> Vec<f64> {
+    let mut rng = SeededRng::new(seed);
+    let arg1 = params[0] as f64;
+    let arg2 = params.get(1).map_or(0f64, |x| *x as f64);
+    (0..len)
+        .map(|_| match distribution {
+            0 => arg1 + (arg2 - arg1) * rng.next_uniform(),
+            1 => arg1 + arg2 * rng.next_normal(),
+            2 => (rng.next_uniform() < arg1) as i32 as f64,
+            3 => loop {
+                let x = rng.next_normal();
+                if x.abs() <= 2.0 {
+                    break arg1 + arg2 * x;
+                }
+            },
+            _ => panic!("unknown rng category????"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_is_deterministic() {
+        assert_eq!(sample(1, &[0., 1.], 7, 16), sample(1, &[0., 1.], 7, 16));
+        assert_ne!(sample(1, &[0., 1.], 7, 16), sample(1, &[0., 1.], 8, 16));
+
+        let values = sample(0, &[-2., 3.], 42, 1000);
+        assert_eq!(values.iter().all(|x| *x >= -2.0 && *x < 3.0), true);
+
+        let values = sample(3, &[1., 0.5], 42, 1000);
+        assert_eq!(values.iter().all(|x| *x >= 0.0 && *x <= 2.0), true);
+        let mean = values.iter().sum::<f64>() / 1000.0;
+        assert_eq!((mean - 1.0).abs() < 0.1, true);
+
+        let values = sample(2, &[0.25], 42, 1000);
+        assert_eq!(values.iter().all(|x| *x == 0.0 || *x == 1.0), true);
+        let ones = values.iter().sum::<f64>();
+        assert_eq!(ones > 150.0 && ones < 350.0, true);
+    }
+}
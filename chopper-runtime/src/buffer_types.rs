@@ -89,7 +89,7 @@ impl<B: hal::Backend, T> UniBuffer<B, T> {
             ptr::copy_nonoverlapping(
                 tensor_view.data.as_ptr() as *const u8,
                 mapping,
-                dsize * F32STRIDE as usize,
+                dsize * std::mem::size_of::<T>(),
             );
             device.unmap_memory(&mut host_buffer.memory);
         }
@@ -113,7 +113,7 @@ impl<B: hal::Backend, T> UniBuffer<B, T> {
             ptr::copy_nonoverlapping(
                 mapping,
                 self.raw_data.as_ptr() as *mut u8,
-                self.data_size * F32STRIDE as usize,
+                self.data_size * std::mem::size_of::<T>(),
             );
             device.unmap_memory(&mut self.host_buffer.as_mut().unwrap().memory);
         }
@@ -208,6 +208,8 @@ impl<B: hal::Backend> NewBufferView<B> {
         let stride = match dtype {
             ElementType::F32 => std::mem::size_of::<f32>() as buffer::Stride,
             ElementType::I32 => std::mem::size_of::<f64>() as buffer::Stride,
+            ElementType::BOOL => std::mem::size_of::<i32>() as buffer::Stride,
+            ElementType::F64 | ElementType::I64 => std::mem::size_of::<f64>() as buffer::Stride,
             _ => std::mem::size_of::<u8>() as buffer::Stride,
         };
         let properties = match buffer_type {
@@ -270,6 +272,8 @@ impl<B: hal::Backend> BufferView<B> {
         let stride = match dtype {
             ElementType::F32 => std::mem::size_of::<f32>() as buffer::Stride,
             ElementType::I32 => std::mem::size_of::<f64>() as buffer::Stride,
+            ElementType::BOOL => std::mem::size_of::<i32>() as buffer::Stride,
+            ElementType::F64 | ElementType::I64 => std::mem::size_of::<f64>() as buffer::Stride,
             _ => std::mem::size_of::<u8>() as buffer::Stride,
         };
         let properties = match buffer_type {
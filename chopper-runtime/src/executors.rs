@@ -17,6 +17,7 @@ use crate::base::*;
 use crate::buffer_types::*;
 use crate::functor::TensorFunctor;
 use crate::functor::*;
+use crate::host_functor::HostFunctor;
 use crate::instance::*;
 use crate::kernel::kernel_registry::KernelRegistry;
 use crate::tensors::*;
@@ -79,16 +80,20 @@ impl ExecutorLike for ActExecutorTypes {
         // debug!("============ on computing unary =============");
         match self {
             #[cfg(feature = "mock")]
-            ActExecutorTypes::MockExecutor(ref mut _executor) => {
-                _executor.mock_unary::<Self::TensorType>(op.into(), in_tensor)
-            }
+            ActExecutorTypes::MockExecutor(ref mut _executor) => match op {
+                CRTOpCode::EXPF32 if !in_tensor.read().unwrap().is_host_only() => {
+                    _executor.mock_unary::<Self::TensorType>(op.into(), in_tensor)
+                }
+                // ops not registered in MockOpCode and broadcasting fallback to host impls
+                _ => HostFunctor::new().unary_compute(op, &*in_tensor.read().unwrap()),
+            },
             #[cfg(feature = "vulkan")]
             ActExecutorTypes::VkGPUExecutor(ref mut _executor) => {
-                panic!("not implemented");
+                Self::vulkan_unary_compute(_executor, op, &in_tensor)
             }
             #[cfg(all(feature = "blas"))]
             ActExecutorTypes::BlasExecutor(ref mut _executor) => {
-                panic!("not implemented");
+                HostFunctor::new().unary_compute(op, &*in_tensor.read().unwrap())
             }
             _ => panic!("not registered backend typeid"),
         }
@@ -103,16 +108,24 @@ impl ExecutorLike for ActExecutorTypes {
         // debug!("============ on computing unary =============");
         match self {
             #[cfg(feature = "mock")]
-            ActExecutorTypes::MockExecutor(ref mut _executor) => {
-                _executor.mock_unary_v2::<Self::TensorType>(op.into(), in_tensor, out_tensor);
-            }
+            ActExecutorTypes::MockExecutor(ref mut _executor) => match op {
+                CRTOpCode::EXPF32 if !in_tensor.read().unwrap().is_host_only() => {
+                    _executor.mock_unary_v2::<Self::TensorType>(op.into(), in_tensor, out_tensor);
+                }
+                _ => {
+                    let outs = HostFunctor::new().unary_compute(op, &*in_tensor.read().unwrap());
+                    *out_tensor.write().unwrap() = outs;
+                }
+            },
             #[cfg(feature = "vulkan")]
             ActExecutorTypes::VkGPUExecutor(ref mut _executor) => {
-                panic!("not implemented");
+                let outs = Self::vulkan_unary_compute(_executor, op, &in_tensor);
+                *out_tensor.write().unwrap() = outs;
             }
             #[cfg(all(feature = "blas"))]
             ActExecutorTypes::BlasExecutor(ref mut _executor) => {
-                panic!("not implemented");
+                let outs = HostFunctor::new().unary_compute(op, &*in_tensor.read().unwrap());
+                *out_tensor.write().unwrap() = outs;
             }
             _ => panic!("not registered backend typeid"),
         }
@@ -127,100 +140,77 @@ impl ExecutorLike for ActExecutorTypes {
         // debug!("============ on computing binary =============");
         match self {
             #[cfg(feature = "mock")]
-            ActExecutorTypes::MockExecutor(ref mut _executor) => {
-                _executor.mock_binary::<Self::TensorType>(op.into(), lhs_tensor, rhs_tensor)
-                // TODO use pattern match on matching tensortypes, rather than call as generic
-                // WIP match lhs_tensor {
-                // WIP     // TODO WIP make this MockTensor
-                // WIP     ActTensorTypes::F32Tensor { data } => {
-                // WIP         let lhs_data = data;
-                // WIP         match rhs_tensor {
-                // WIP             ActTensorTypes::F32Tensor { data } => {
-                // WIP                 let rhs_data = data;
-                // WIP                 return ActTensorTypes::MockTensor {
-                // WIP                     data: _executor.mock_binary::<Self::TensorType>(lhs_data, rhs_data),
-                // WIP                     // WIP data: _executor.binary_compute(op, lhs_data, rhs_data),
-                // WIP                 };
-                // WIP             }
-                // WIP             _ => panic!("lhs and rhs type mismatch"),
-                // WIP         }
-                // WIP     }
-                // WIP     ActTensorTypes::MockTensor => panic!("TODO WIP, CRT use TensorView, make this changable"),
-                // WIP     _ => panic!("dtype not compatible, exp_executor <type: MockTensor> {:#?}", lhs_tensor),
-                // WIP }
-            }
+            ActExecutorTypes::MockExecutor(ref mut _executor) => match op {
+                CRTOpCode::ADDF32
+                | CRTOpCode::SUBF32
+                | CRTOpCode::MULF32
+                | CRTOpCode::DIVF32
+                | CRTOpCode::ADDI32
+                | CRTOpCode::SUBI32
+                | CRTOpCode::MULI32
+                | CRTOpCode::FLOORDIVI32
+                | CRTOpCode::MATMULF32
+                    if !Self::is_broadcasting(op, &lhs_tensor, &rhs_tensor)
+                        && !Self::is_host_only(&lhs_tensor, &rhs_tensor) =>
+                {
+                    _executor.mock_binary::<Self::TensorType>(op.into(), lhs_tensor, rhs_tensor)
+                    // TODO use pattern match on matching tensortypes, rather than call as generic
+                    // WIP match lhs_tensor {
+                    // WIP     // TODO WIP make this MockTensor
+                    // WIP     ActTensorTypes::F32Tensor { data } => {
+                    // WIP         let lhs_data = data;
+                    // WIP         match rhs_tensor {
+                    // WIP             ActTensorTypes::F32Tensor { data } => {
+                    // WIP                 let rhs_data = data;
+                    // WIP                 return ActTensorTypes::MockTensor {
+                    // WIP                     data: _executor.mock_binary::<Self::TensorType>(lhs_data, rhs_data),
+                    // WIP                     // WIP data: _executor.binary_compute(op, lhs_data, rhs_data),
+                    // WIP                 };
+                    // WIP             }
+                    // WIP             _ => panic!("lhs and rhs type mismatch"),
+                    // WIP         }
+                    // WIP     }
+                    // WIP     ActTensorTypes::MockTensor => panic!("TODO WIP, CRT use TensorView, make this changable"),
+                    // WIP     _ => panic!("dtype not compatible, exp_executor <type: MockTensor> {:#?}", lhs_tensor),
+                    // WIP }
+                }
+                // ops not registered in MockOpCode and broadcasting fallback to host impls
+                _ => HostFunctor::new().binary_compute(
+                    op,
+                    &*lhs_tensor.read().unwrap(),
+                    &*rhs_tensor.read().unwrap(),
+                ),
+            },
             #[cfg(feature = "vulkan")]
             ActExecutorTypes::VkGPUExecutor(ref mut _executor) => {
-                match lhs_tensor {
-                    ActTensorTypes::F32Tensor { data } => {
-                        let lhs_data = data;
-                        match rhs_tensor {
-                            ActTensorTypes::F32Tensor { data } => {
-                                let rhs_data = data;
-                                return ActTensorTypes::F32Tensor {
-                                    data: _executor.binary_compute_f32(op, lhs_data, rhs_data),
-                                };
-                            }
-                            _ => panic!("dtype mismatch"),
-                        }
-                    }
-                    ActTensorTypes::I32Tensor { data } => {
-                        let lhs_data = data;
-                        match rhs_tensor {
-                            ActTensorTypes::I32Tensor { data } => {
-                                let rhs_data = data;
-                                return ActTensorTypes::I32Tensor {
-                                    data: _executor.binary_compute_i32(op, lhs_data, rhs_data),
-                                };
-                            }
-                            _ => panic!("dtype mismatch"),
-                        }
-                    }
-                    _ => panic!("dtype-comp not implemented"),
-                };
+                Self::vulkan_binary_compute(_executor, op, &lhs_tensor, &rhs_tensor)
             }
             #[cfg(feature = "blas")]
-            ActExecutorTypes::BlasExecutor(ref mut _executor) => {
-                match *lhs_tensor.read().unwrap() {
-                    ActTensorTypes::F32Tensor { ref data } => {
-                        let lhs_data = data.into();
-                        match *rhs_tensor.read().unwrap() {
-                            ActTensorTypes::F32Tensor { ref data } => {
-                                let rhs_data = data.into();
-                                let out = ActTensorTypes::F32Tensor {
-                                    // TODO tadd to be replace into binary and unary
-                                    // op to be handled
-                                    data: _executor
-                                        .binary_compute_owned(op.into(), lhs_data, rhs_data)
-                                        .into(),
-                                };
-                                // println!("{:#?}", out);
-                                return out;
-                            }
-                            _ => panic!("dtype mismatch"),
-                        }
-                    }
-                    ActTensorTypes::I32Tensor { ref data } => {
-                        let lhs_data = data.into();
-                        match *rhs_tensor.read().unwrap() {
-                            ActTensorTypes::I32Tensor { ref data } => {
-                                let rhs_data = data.into();
-                                let out = ActTensorTypes::I32Tensor {
-                                    // TODO tadd to be replace into binary and unary
-                                    // op to be handled
-                                    data: _executor
-                                        .binary_compute_owned(op.into(), lhs_data, rhs_data)
-                                        .into(),
-                                };
-                                // println!("{:#?}", out);
-                                return out;
-                            }
-                            _ => panic!("dtype mismatch"),
-                        }
-                    }
-                    _ => panic!("dtype-comp not implemented"),
-                };
-            }
+            ActExecutorTypes::BlasExecutor(ref mut _executor) => match op {
+                CRTOpCode::ADDF32
+                | CRTOpCode::SUBF32
+                | CRTOpCode::MULF32
+                | CRTOpCode::DIVF32
+                | CRTOpCode::ADDI32
+                | CRTOpCode::SUBI32
+                | CRTOpCode::MULI32
+                | CRTOpCode::FLOORDIVI32
+                | CRTOpCode::MATMULF32
+                    if !Self::is_broadcasting(op, &lhs_tensor, &rhs_tensor)
+                        && !Self::is_host_only(&lhs_tensor, &rhs_tensor) =>
+                {
+                    Self::blas_binary_compute(_executor, op, &lhs_tensor, &rhs_tensor)
+                }
+                CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => {
+                    Self::blas_batched_matmul(_executor, op, &lhs_tensor, &rhs_tensor)
+                }
+                // ops not registered in BlasOpCode and broadcasting fallback to host impls
+                _ => HostFunctor::new().binary_compute(
+                    op,
+                    &*lhs_tensor.read().unwrap(),
+                    &*rhs_tensor.read().unwrap(),
+                ),
+            },
             _ => panic!("not registered backend typeid"),
         }
     }
@@ -235,53 +225,366 @@ impl ExecutorLike for ActExecutorTypes {
         // debug!("============ on computing binary =============");
         match self {
             #[cfg(feature = "mock")]
-            ActExecutorTypes::MockExecutor(ref mut _executor) => {
-                _executor.mock_binary_v2::<Self::TensorType>(
-                    op.into(),
-                    lhs_tensor,
-                    rhs_tensor,
-                    out_tensor,
-                );
+            ActExecutorTypes::MockExecutor(ref mut _executor) => match op {
+                CRTOpCode::ADDF32
+                | CRTOpCode::SUBF32
+                | CRTOpCode::MULF32
+                | CRTOpCode::DIVF32
+                | CRTOpCode::ADDI32
+                | CRTOpCode::SUBI32
+                | CRTOpCode::MULI32
+                | CRTOpCode::FLOORDIVI32
+                | CRTOpCode::MATMULF32
+                    if !Self::is_broadcasting(op, &lhs_tensor, &rhs_tensor)
+                        && !Self::is_host_only(&lhs_tensor, &rhs_tensor) =>
+                {
+                    _executor.mock_binary_v2::<Self::TensorType>(
+                        op.into(),
+                        lhs_tensor,
+                        rhs_tensor,
+                        out_tensor,
+                    );
+                }
+                _ => {
+                    let outs = HostFunctor::new().binary_compute(
+                        op,
+                        &*lhs_tensor.read().unwrap(),
+                        &*rhs_tensor.read().unwrap(),
+                    );
+                    *out_tensor.write().unwrap() = outs;
+                }
+            },
+            #[cfg(feature = "vulkan")]
+            ActExecutorTypes::VkGPUExecutor(ref mut _executor) => {
+                let outs = Self::vulkan_binary_compute(_executor, op, &lhs_tensor, &rhs_tensor);
+                *out_tensor.write().unwrap() = outs;
             }
             #[cfg(feature = "blas")]
             ActExecutorTypes::BlasExecutor(ref mut _executor) => {
-                match *lhs_tensor.read().unwrap() {
+                let outs = match op {
+                    CRTOpCode::ADDF32
+                    | CRTOpCode::SUBF32
+                    | CRTOpCode::MULF32
+                    | CRTOpCode::DIVF32
+                    | CRTOpCode::ADDI32
+                    | CRTOpCode::SUBI32
+                    | CRTOpCode::MULI32
+                    | CRTOpCode::FLOORDIVI32
+                    | CRTOpCode::MATMULF32
+                        if !Self::is_broadcasting(op, &lhs_tensor, &rhs_tensor)
+                            && !Self::is_host_only(&lhs_tensor, &rhs_tensor) =>
+                    {
+                        Self::blas_binary_compute(_executor, op, &lhs_tensor, &rhs_tensor)
+                    }
+                    CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => {
+                        Self::blas_batched_matmul(_executor, op, &lhs_tensor, &rhs_tensor)
+                    }
+                    _ => HostFunctor::new().binary_compute(
+                        op,
+                        &*lhs_tensor.read().unwrap(),
+                        &*rhs_tensor.read().unwrap(),
+                    ),
+                };
+                *out_tensor.write().unwrap() = outs;
+            }
+            _ => panic!("not registered backend typeid"),
+        }
+    }
+}
+
+impl ActExecutorTypes {
+    // elementwise ops on operands of different shapes and matmul over batch dims, that mock and
+    // blas executors cannot handle
+    #[cfg(any(feature = "mock", feature = "blas"))]
+    fn is_broadcasting(
+        op: CRTOpCode,
+        lhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+        rhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> bool {
+        let lhs_shape = lhs_tensor.read().unwrap().shape();
+        let rhs_shape = rhs_tensor.read().unwrap().shape();
+        match op {
+            CRTOpCode::MATMULF32 => lhs_shape.len() != 2 || rhs_shape.len() != 2,
+            _ => lhs_shape != rhs_shape,
+        }
+    }
+
+    // extended dtypes have no kernels in the mock and blas executors and run on host only, see
+    // ActTensorTypes::is_host_only
+    fn is_host_only(
+        lhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+        rhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> bool {
+        lhs_tensor.read().unwrap().is_host_only() || rhs_tensor.read().unwrap().is_host_only()
+    }
+
+    #[cfg(feature = "vulkan")]
+    fn vulkan_unary_compute(
+        executor: &mut VkGPUExecutor,
+        op: CRTOpCode,
+        in_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> ActTensorTypes {
+        // cast kernels only convert between f32 and i32
+        let into_host_only = match op.to_cast_element_type() {
+            Some(ElementType::F32) | Some(ElementType::I32) | None => false,
+            _ => true,
+        };
+        if in_tensor.read().unwrap().is_host_only() || into_host_only {
+            return HostFunctor::new().unary_compute(op, &*in_tensor.read().unwrap());
+        }
+        if op.to_cast_element_type().is_some() {
+            return executor.cast_compute(op, &*in_tensor.read().unwrap());
+        }
+        match *in_tensor.read().unwrap() {
+            ActTensorTypes::F32Tensor { ref data } => ActTensorTypes::F32Tensor {
+                data: executor.unary_compute_f32(op, data.clone()),
+            },
+            ActTensorTypes::I32Tensor { ref data } => ActTensorTypes::I32Tensor {
+                data: executor.unary_compute_i32(op, data.clone()),
+            },
+            _ => panic!("dtype-comp not implemented"),
+        }
+    }
+
+    #[cfg(feature = "vulkan")]
+    fn vulkan_binary_compute(
+        executor: &mut VkGPUExecutor,
+        op: CRTOpCode,
+        lhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+        rhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> ActTensorTypes {
+        // f64 and i64 arithmetic runs on device when it offers the 64-bit shader features
+        let entry_64 = match (&*lhs_tensor.read().unwrap(), &*rhs_tensor.read().unwrap()) {
+            (ActTensorTypes::F64Tensor { .. }, ActTensorTypes::F64Tensor { .. }) => {
+                executor.binary_entry_64(op, ElementType::F64)
+            }
+            (ActTensorTypes::I64Tensor { .. }, ActTensorTypes::I64Tensor { .. }) => {
+                executor.binary_entry_64(op, ElementType::I64)
+            }
+            _ => None,
+        };
+        if Self::is_host_only(lhs_tensor, rhs_tensor) && entry_64.is_none() {
+            return HostFunctor::new().binary_compute(
+                op,
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+            );
+        }
+        if op.is_reduction() {
+            return executor.reduce_compute(
+                op,
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+            );
+        }
+        if op.is_comparison() {
+            return executor.compare_compute(
+                op,
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+            );
+        }
+        if op == CRTOpCode::SELECT {
+            return executor
+                .select_compute(&*lhs_tensor.read().unwrap(), &*rhs_tensor.read().unwrap());
+        }
+        if op == CRTOpCode::MATMULF32 {
+            return executor.matmul_compute(
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+                false,
+                false,
+            );
+        }
+        if op == CRTOpCode::MATMULTF32 {
+            let (transpose_a, transpose_b) = rhs_tensor.read().unwrap().to_matmul_attr();
+            return match &*lhs_tensor.read().unwrap() {
+                ActTensorTypes::TupleTensor { data } if data.len() == 2 => {
+                    executor.matmul_compute(&data[0], &data[1], transpose_a, transpose_b)
+                }
+                _ => panic!("matmul expects the operands packed as lhs"),
+            };
+        }
+        if op == CRTOpCode::CONV2DF32 {
+            return executor
+                .conv2d_compute(&*lhs_tensor.read().unwrap(), &*rhs_tensor.read().unwrap());
+        }
+        if op == CRTOpCode::FUSEDF32 {
+            return executor
+                .fused_compute(&*lhs_tensor.read().unwrap(), &*rhs_tensor.read().unwrap());
+        }
+        if op == CRTOpCode::MAXPOOL2DF32 || op == CRTOpCode::AVGPOOL2DF32 {
+            return executor.pool2d_compute(
+                op,
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+            );
+        }
+        if op == CRTOpCode::FMAF32 {
+            return executor.ternary_compute_f32(
+                op,
+                &*lhs_tensor.read().unwrap(),
+                &*rhs_tensor.read().unwrap(),
+            );
+        }
+        match (&*lhs_tensor.read().unwrap(), &*rhs_tensor.read().unwrap()) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => ActTensorTypes::F32Tensor {
+                data: executor.binary_compute_f32(op, lhs_data.clone(), rhs_data.clone()),
+            },
+            (
+                ActTensorTypes::I32Tensor { data: lhs_data },
+                ActTensorTypes::I32Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: executor.binary_compute_i32(op, lhs_data.clone(), rhs_data.clone()),
+            },
+            (
+                ActTensorTypes::F64Tensor { data: lhs_data },
+                ActTensorTypes::F64Tensor { data: rhs_data },
+            ) => ActTensorTypes::F64Tensor {
+                data: executor.binary_compute_entry(
+                    op,
+                    lhs_data.clone(),
+                    rhs_data.clone(),
+                    entry_64.unwrap(),
+                ),
+            },
+            (
+                ActTensorTypes::I64Tensor { data: lhs_data },
+                ActTensorTypes::I64Tensor { data: rhs_data },
+            ) => ActTensorTypes::I64Tensor {
+                data: executor.binary_compute_entry(
+                    op,
+                    lhs_data.clone(),
+                    rhs_data.clone(),
+                    entry_64.unwrap(),
+                ),
+            },
+            _ => panic!("dtype mismatch"),
+        }
+    }
+
+    // transposes and broadcasts operands into [batch..., M, K] and [batch..., K, N] on host, then
+    // runs a gemm for each batch
+    #[cfg(feature = "blas")]
+    fn blas_batched_matmul(
+        executor: &mut BlasExecutor,
+        op: CRTOpCode,
+        lhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+        rhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> ActTensorTypes {
+        let (lhs, rhs, transpose_a, transpose_b) = match op {
+            CRTOpCode::MATMULTF32 => {
+                let (transpose_a, transpose_b) = rhs_tensor.read().unwrap().to_matmul_attr();
+                match &*lhs_tensor.read().unwrap() {
+                    ActTensorTypes::TupleTensor { data } if data.len() == 2 => {
+                        (data[0].clone(), data[1].clone(), transpose_a, transpose_b)
+                    }
+                    _ => panic!("matmul expects the operands packed as lhs"),
+                }
+            }
+            _ => (
+                lhs_tensor.read().unwrap().clone(),
+                rhs_tensor.read().unwrap().clone(),
+                false,
+                false,
+            ),
+        };
+        let (lhs, rhs) = match (lhs, rhs) {
+            (ActTensorTypes::F32Tensor { data: lhs }, ActTensorTypes::F32Tensor { data: rhs }) => {
+                (lhs, rhs)
+            }
+            _ => panic!("dtype mismatch"),
+        };
+        let out_shape = matmul_shape(&lhs.shape, &rhs.shape, transpose_a, transpose_b).expect(
+            &format!("cannot matmul shapes {:?} and {:?}", lhs.shape, rhs.shape),
+        );
+        let out_rank = out_shape.len();
+        let (m, n) = (out_shape[out_rank - 2], out_shape[out_rank - 1]);
+        let k = lhs.data.len() / lhs.shape[..lhs.shape.len() - 2].iter().product::<usize>() / m;
+
+        let host = HostFunctor::new();
+        // swap the last two dims of transposed operands
+        let swap_last_two = |rank: usize| -> Vec<usize> {
+            let mut perm: Vec<usize> = (0..rank).collect();
+            perm.swap(rank - 2, rank - 1);
+            perm
+        };
+        let lhs = match transpose_a {
+            true => host.transpose_compute(&lhs, &swap_last_two(lhs.shape.len())),
+            false => lhs,
+        };
+        let rhs = match transpose_b {
+            true => host.transpose_compute(&rhs, &swap_last_two(rhs.shape.len())),
+            false => rhs,
+        };
+        let batch_shape = &out_shape[..out_rank - 2];
+        let lhs = host.broadcast_to_compute(&lhs, &[batch_shape, &[m, k]].concat());
+        let rhs = host.broadcast_to_compute(&rhs, &[batch_shape, &[k, n]].concat());
+
+        let mut out_data = Vec::<f32>::with_capacity(out_shape.iter().product());
+        for batch in 0..batch_shape.iter().product::<usize>() {
+            let lhs_batch = TensorView::<f32>::new(
+                lhs.data[batch * m * k..(batch + 1) * m * k].to_vec(),
+                ElementType::F32,
+                vec![m, k],
+            );
+            let rhs_batch = TensorView::<f32>::new(
+                rhs.data[batch * k * n..(batch + 1) * k * n].to_vec(),
+                ElementType::F32,
+                vec![k, n],
+            );
+            let outs: TensorView<f32> = executor
+                .binary_compute_owned(BlasOpCode::GemmF, (&lhs_batch).into(), (&rhs_batch).into())
+                .into();
+            out_data.extend(outs.data);
+        }
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(out_data, ElementType::F32, out_shape),
+        }
+    }
+
+    #[cfg(feature = "blas")]
+    fn blas_binary_compute(
+        executor: &mut BlasExecutor,
+        op: CRTOpCode,
+        lhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+        rhs_tensor: &Arc<RwLock<ActTensorTypes>>,
+    ) -> ActTensorTypes {
+        match *lhs_tensor.read().unwrap() {
+            ActTensorTypes::F32Tensor { ref data } => {
+                let lhs_data = data.into();
+                match *rhs_tensor.read().unwrap() {
                     ActTensorTypes::F32Tensor { ref data } => {
-                        let lhs_data = data.into();
-                        match *rhs_tensor.read().unwrap() {
-                            ActTensorTypes::F32Tensor { ref data } => {
-                                let rhs_data = data.into();
-                                let mut _out = out_tensor.write().unwrap();
-                                *_out = ActTensorTypes::F32Tensor {
-                                    data: _executor
-                                        .binary_compute_owned(op.into(), lhs_data, rhs_data)
-                                        .into(),
-                                };
-                            }
-                            _ => panic!("dtype mismatch"),
+                        let rhs_data = data.into();
+                        ActTensorTypes::F32Tensor {
+                            // TODO tadd to be replace into binary and unary
+                            // op to be handled
+                            data: executor
+                                .binary_compute_owned(op.into(), lhs_data, rhs_data)
+                                .into(),
                         }
                     }
+                    _ => panic!("dtype mismatch"),
+                }
+            }
+            ActTensorTypes::I32Tensor { ref data } => {
+                let lhs_data = data.into();
+                match *rhs_tensor.read().unwrap() {
                     ActTensorTypes::I32Tensor { ref data } => {
-                        let lhs_data = data.into();
-                        match *rhs_tensor.read().unwrap() {
-                            ActTensorTypes::I32Tensor { ref data } => {
-                                let rhs_data = data.into();
-                                let out = ActTensorTypes::I32Tensor {
-                                    // TODO tadd to be replace into binary and unary
-                                    // op to be handled
-                                    data: _executor
-                                        .binary_compute_owned(op.into(), lhs_data, rhs_data)
-                                        .into(),
-                                };
-                                // println!("{:#?}", out);
-                            }
-                            _ => panic!("dtype mismatch"),
+                        let rhs_data = data.into();
+                        ActTensorTypes::I32Tensor {
+                            data: executor
+                                .binary_compute_owned(op.into(), lhs_data, rhs_data)
+                                .into(),
                         }
                     }
-                    _ => panic!("dtype-comp not implemented"),
-                };
+                    _ => panic!("dtype mismatch"),
+                }
             }
-            _ => panic!("not registered backend typeid"),
+            _ => panic!("dtype-comp not implemented"),
         }
     }
 }
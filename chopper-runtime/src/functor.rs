@@ -47,122 +47,139 @@ impl TensorFunctor {
     pub fn apply<T: SupportedType + std::clone::Clone + std::default::Default>(
         &mut self,
         device_context: &mut VkGPUExecutor,
-        mut lhs_buffer_functor: UniBuffer<concrete_backend::Backend, T>,
-        mut rhs_buffer_functor: UniBuffer<concrete_backend::Backend, T>,
+        lhs_buffer_functor: UniBuffer<concrete_backend::Backend, T>,
+        rhs_buffer_functor: UniBuffer<concrete_backend::Backend, T>,
         opcode: CRTOpCode,
     ) -> UniBuffer<concrete_backend::Backend, T> {
-        let shader = device_context.dispatch_kernel(opcode);
-        let device_instance_ref = &device_context.device_instance;
-        /*
-        let init_literal: Vec<T> = match T {
-            f32 => vec![T.default(); lhs_buffer_functor.data_size],
-            i32 => vec![i32.default(); lhs_buffer_functor.data_size],
-            _ => panic!("invalid type to apply functor"),
-        };
-        */
-
-        /* calculate shape of output
-        For arithmetic op, shape of output should keep same as lhs operand;
-        For matmul, check value of last dimension in lhs equal to first of rhs.
-          And shape of output should calculated from lhs and rhs operand
-                lhs: k x m x n
-                rhs: n x l
-                res: k x m x l
-        */
-        let mut res_shape = Vec::new();
-        let mut res_dsize = 1;
-        let lhs_shape = Cow::from(&lhs_buffer_functor.shape[..]);
-        let rhs_shape = Cow::from(&rhs_buffer_functor.shape[..]);
-
-        let lhs_shape_size = lhs_shape.len();
-
-        let shape_transfer_to_specialization = |id: usize| {
-            let dim_const_struct = pso::SpecializationConstant {
-                id: (id + 1) as u32,
-                range: std::ops::Range {
-                    start: (id * 4 + 4) as u16,
-                    end: (id * 4 + 7) as u16,
-                },
-            };
-            dim_const_struct
-        };
-
-        let mut opcode_constant = self.wrap_kernel_specialise_attr(opcode).0;
-        let mut opcode_data = self.wrap_kernel_specialise_attr(opcode).1;
+        self.apply_n::<T>(
+            device_context,
+            vec![lhs_buffer_functor, rhs_buffer_functor],
+            opcode,
+        )
+    }
 
-        for (id, value) in lhs_shape.iter().enumerate() {
-            // println!("dim id: {}; dim value: {}", id, value);
-            let spec_shape = shape_transfer_to_specialization(id);
-            opcode_constant.push(spec_shape);
+    pub fn apply_unary<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        in_buffer_functor: UniBuffer<concrete_backend::Backend, T>,
+        opcode: CRTOpCode,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        self.apply_n::<T>(device_context, vec![in_buffer_functor], opcode)
+    }
 
-            let value_bytes = (*value as u32).to_le_bytes();
-            for x in value_bytes {
-                opcode_data.push(x);
+    /* calculate shape of output
+    For unary arithmetic op, shape of output should keep same as lhs operand;
+    For binary arithmetic op, shape of output is the numpy-style broadcast of lhs and rhs;
+    For ternary op such as select, shape of output is the shape of the first operand;
+    For matmul, check value of last dimension in lhs equal to the second last of rhs after
+      transposes, and batch dims of lhs and rhs broadcast
+            lhs: b x 1 x m x n
+            rhs: 1 x k x n x l
+            res: b x k x m x l
+    */
+    fn infer_result_shape<T>(
+        &self,
+        opcode: CRTOpCode,
+        in_buffer_functors: &Vec<UniBuffer<concrete_backend::Backend, T>>,
+        attrs: &[usize],
+    ) -> (Vec<usize>, usize) {
+        let lhs_shape = &in_buffer_functors[0].shape;
+        match opcode {
+            // batch dims of matmul broadcast, transpose flags come from attrs if any
+            CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => {
+                let rhs_shape = &in_buffer_functors[1].shape;
+                let (transpose_a, transpose_b) = match attrs {
+                    [transpose_a, transpose_b] => (*transpose_a != 0, *transpose_b != 0),
+                    _ => (false, false),
+                };
+                let res_shape = matmul_shape(lhs_shape, rhs_shape, transpose_a, transpose_b)
+                    .expect(&format!(
+                        "cannot matmul shapes {:?} and {:?}",
+                        lhs_shape, rhs_shape
+                    ));
+                let res_dsize = res_shape.iter().product();
+                (res_shape, res_dsize)
             }
-        }
-
-        for (id, value) in rhs_shape.iter().enumerate() {
-            let spec_shape = shape_transfer_to_specialization(id + lhs_shape_size);
-            opcode_constant.push(spec_shape);
-
-            let value_bytes = (*value as u32).to_le_bytes();
-            for x in value_bytes {
-                opcode_data.push(x);
+            // reduction ops take the input viewed as [outer, axis_len, inner],
+            // result is [outer, inner] and reshaped by the caller
+            CRTOpCode::REDUCESUMF32
+            | CRTOpCode::REDUCEMEANF32
+            | CRTOpCode::REDUCEMAXF32
+            | CRTOpCode::REDUCEMINF32
+            | CRTOpCode::ARGMAXF32
+            | CRTOpCode::ARGMINF32 => {
+                assert_eq!(lhs_shape.len(), 3);
+                let res_shape = vec![lhs_shape[0], lhs_shape[2]];
+                let res_dsize = lhs_shape[0] * lhs_shape[2];
+                (res_shape, res_dsize)
             }
-        }
-
-        let spec_constant: Cow<[pso::SpecializationConstant]> = Cow::Owned(opcode_constant);
-        let spec_data: Cow<[u8]> = Cow::Owned(opcode_data);
-
-        match opcode {
-            CRTOpCode::ADDI32
-            | CRTOpCode::SUBI32
-            | CRTOpCode::MULI32
-            | CRTOpCode::FLOORDIVI32
-            | CRTOpCode::ADDF32
-            | CRTOpCode::SUBF32
-            | CRTOpCode::MULF32
-            | CRTOpCode::DIVF32 => {
-                res_shape = lhs_shape.to_vec();
-                res_dsize = lhs_buffer_functor.data_size;
+            // ternary ops take the first operand as lhs and the others packed as
+            // [2, shape of lhs] as rhs
+            CRTOpCode::SELECT | CRTOpCode::FMAF32 => {
+                (lhs_shape.to_vec(), in_buffer_functors[0].data_size)
             }
-            CRTOpCode::MATMULF32 => {
-                // assert_eq!(lhs_buffer_functor.shape.len(), 2);
-                assert_eq!(rhs_shape.len(), 2);
-
-                assert_eq!(lhs_shape[lhs_shape_size - 1], rhs_shape[0]);
-
-                res_shape = (lhs_shape[..lhs_shape_size - 1]).to_vec();
-                res_shape.push(rhs_shape[1]);
-                // res_shape = lhs_shape.to_vec();
-                // res_shape[0] = rhs_shape[0];
-
-                res_dsize =
-                    lhs_buffer_functor.data_size / lhs_shape[lhs_shape_size - 1] * rhs_shape[1];
-                // res_dsize = lhs_buffer_functor.data_size / lhs_shape[0] * rhs_shape[0];
+            // sliding window ops take the input (and the weight) as operands,
+            // window params come from attrs
+            CRTOpCode::CONV2DF32 => {
+                let rhs_shape = &in_buffer_functors[1].shape;
+                let attr = Conv2dAttr::from_vec(attrs);
+                let res_shape = conv2d_shape(lhs_shape, rhs_shape, &attr).expect(&format!(
+                    "cannot conv2d shapes {:?} and {:?} with {:?}",
+                    lhs_shape, rhs_shape, attr
+                ));
+                let res_dsize = res_shape.iter().product();
+                (res_shape, res_dsize)
+            }
+            CRTOpCode::MAXPOOL2DF32 | CRTOpCode::AVGPOOL2DF32 => {
+                let attr = Pool2dAttr::from_vec(attrs);
+                let res_shape = pool2d_shape(lhs_shape, &attr).expect(&format!(
+                    "cannot pool2d shape {:?} with {:?}",
+                    lhs_shape, attr
+                ));
+                let res_dsize = res_shape.iter().product();
+                (res_shape, res_dsize)
+            }
+            // fused ops broadcast all of their inputs
+            CRTOpCode::FUSEDF32 => {
+                let res_shape = in_buffer_functors[1..].iter().fold(
+                    lhs_shape.to_vec(),
+                    |res_shape, in_buffer_functor| {
+                        broadcast_shape(&res_shape, &in_buffer_functor.shape).expect(&format!(
+                            "cannot broadcast shapes {:?} and {:?}",
+                            res_shape, in_buffer_functor.shape
+                        ))
+                    },
+                );
+                let res_dsize = res_shape.iter().product();
+                (res_shape, res_dsize)
             }
-            _ => {
-                res_shape = lhs_shape.to_vec();
-                res_dsize = lhs_buffer_functor.data_size;
+            // binary elementwise ops broadcast lhs and rhs
+            _ if in_buffer_functors.len() == 2 => {
+                let rhs_shape = &in_buffer_functors[1].shape;
+                let res_shape = broadcast_shape(lhs_shape, rhs_shape).expect(&format!(
+                    "cannot broadcast shapes {:?} and {:?}",
+                    lhs_shape, rhs_shape
+                ));
+                let res_dsize = res_shape.iter().product();
+                (res_shape, res_dsize)
             }
+            // unary elementwise ops keeps the shape of lhs
+            _ => (lhs_shape.to_vec(), in_buffer_functors[0].data_size),
         }
-        // println!("res shape: {:?}", res_shape);
-
-        let res_tensor_view = TensorView::<T>::new(
-            vec![Default::default(); res_dsize as usize],
-            ElementType::F32,
-            res_shape,
-        );
-        let mut res_buffer_functor = UniBuffer::<concrete_backend::Backend, T>::new(
-            &device_context.device,
-            &device_instance_ref.memory_property().memory_types,
-            res_tensor_view,
-        );
+    }
 
+    // create the descriptor set layout and the descriptor set that binds a storage buffer
+    fn create_storage_binding<T>(
+        &self,
+        device_context: &mut VkGPUExecutor,
+        buffer_functor: &UniBuffer<concrete_backend::Backend, T>,
+    ) -> (
+        <concrete_backend::Backend as hal::Backend>::DescriptorSetLayout,
+        <concrete_backend::Backend as hal::Backend>::DescriptorSet,
+    ) {
         // TODO refactor into BufferView
         let _BINDING_ID = 0;
-        // step 6 create desc_set_layout
-        let descriptor_set_layout_lhs = unsafe {
+        let descriptor_set_layout = unsafe {
             device_context.device.create_descriptor_set_layout(
                 iter::once(pso::DescriptorSetLayoutBinding {
                     binding: _BINDING_ID,
@@ -181,10 +198,10 @@ impl TensorFunctor {
         }
         .expect("Can't create descriptor set layout");
         // alloc desc sets
-        let desc_set_lhs = unsafe {
+        let desc_set = unsafe {
             let mut desc_set = device_context
                 .descriptor_pool
-                .allocate_one(&descriptor_set_layout_lhs)
+                .allocate_one(&descriptor_set_layout)
                 .unwrap();
             device_context
                 .device
@@ -193,103 +210,150 @@ impl TensorFunctor {
                     binding: _BINDING_ID,
                     array_offset: 0,
                     descriptors: iter::once(pso::Descriptor::Buffer(
-                        &lhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
+                        &buffer_functor.device_buffer.as_ref().unwrap().buffer,
                         buffer::SubRange::WHOLE,
                     )),
                 });
             desc_set
         };
+        (descriptor_set_layout, desc_set)
+    }
 
-        // experiments
-        let descriptor_set_layout_rhs = unsafe {
-            device_context.device.create_descriptor_set_layout(
-                iter::once(pso::DescriptorSetLayoutBinding {
-                    binding: _BINDING_ID,
-                    ty: pso::DescriptorType::Buffer {
-                        ty: pso::BufferDescriptorType::Storage { read_only: false },
-                        format: pso::BufferDescriptorFormat::Structured {
-                            dynamic_offset: false,
-                        },
-                    },
-                    count: 1,
-                    stage_flags: pso::ShaderStageFlags::COMPUTE,
-                    immutable_samplers: false,
-                }),
-                iter::empty(),
-            )
-        }
-        .expect("Can't create descriptor set layout");
-        // alloc desc sets
-        let desc_set_rhs = unsafe {
-            let mut desc_set = device_context
-                .descriptor_pool
-                .allocate_one(&descriptor_set_layout_rhs)
-                .unwrap();
-            device_context
-                .device
-                .write_descriptor_set(pso::DescriptorSetWrite {
-                    set: &mut desc_set,
-                    binding: _BINDING_ID,
-                    array_offset: 0,
-                    descriptors: iter::once(pso::Descriptor::Buffer(
-                        &rhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                        buffer::SubRange::WHOLE,
-                    )),
-                });
-            desc_set
+    // apply the kernel of opcode on any count of operands, binding each operand to its own
+    // descriptor set by order, and the result to the last one
+    pub fn apply_n<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        in_buffer_functors: Vec<UniBuffer<concrete_backend::Backend, T>>,
+        opcode: CRTOpCode,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        self.apply_n_with_attrs(device_context, in_buffer_functors, opcode, vec![])
+    }
+
+    // same as apply_n, attrs of the op are specialised after the dims of all operands
+    pub fn apply_n_with_attrs<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        in_buffer_functors: Vec<UniBuffer<concrete_backend::Backend, T>>,
+        opcode: CRTOpCode,
+        attrs: Vec<usize>,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        let shader = device_context.dispatch_kernel(opcode);
+        self.launch(device_context, in_buffer_functors, opcode, shader, attrs)
+    }
+
+    // same as apply_n with the kernel generated for a fused op, registered under query_entry
+    pub fn apply_generated<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        in_buffer_functors: Vec<UniBuffer<concrete_backend::Backend, T>>,
+        query_entry: String,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        self.apply_entry::<T>(
+            device_context,
+            in_buffer_functors,
+            CRTOpCode::FUSEDF32,
+            query_entry,
+        )
+    }
+
+    // same as apply_n with the kernel registered under query_entry rather than the one of
+    // opcode, such as the 64-bit kernels
+    pub fn apply_entry<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        in_buffer_functors: Vec<UniBuffer<concrete_backend::Backend, T>>,
+        opcode: CRTOpCode,
+        query_entry: String,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        let shader = device_context.dispatch_kernel_entry(opcode, query_entry);
+        self.launch(device_context, in_buffer_functors, opcode, shader, vec![])
+    }
+
+    fn launch<T: SupportedType + std::clone::Clone + std::default::Default>(
+        &mut self,
+        device_context: &mut VkGPUExecutor,
+        mut in_buffer_functors: Vec<UniBuffer<concrete_backend::Backend, T>>,
+        opcode: CRTOpCode,
+        shader: Kernel,
+        attrs: Vec<usize>,
+    ) -> UniBuffer<concrete_backend::Backend, T> {
+        let shape_transfer_to_specialization = |id: usize| {
+            let dim_const_struct = pso::SpecializationConstant {
+                id: (id + 1) as u32,
+                range: std::ops::Range {
+                    start: (id * 4 + 4) as u16,
+                    end: (id * 4 + 7) as u16,
+                },
+            };
+            dim_const_struct
         };
 
-        // experiments
-        let descriptor_set_layout_outs = unsafe {
-            device_context.device.create_descriptor_set_layout(
-                iter::once(pso::DescriptorSetLayoutBinding {
-                    binding: _BINDING_ID,
-                    ty: pso::DescriptorType::Buffer {
-                        ty: pso::BufferDescriptorType::Storage { read_only: false },
-                        format: pso::BufferDescriptorFormat::Structured {
-                            dynamic_offset: false,
-                        },
-                    },
-                    count: 1,
-                    stage_flags: pso::ShaderStageFlags::COMPUTE,
-                    immutable_samplers: false,
-                }),
-                iter::empty(),
-            )
+        let mut opcode_constant = self.wrap_kernel_specialise_attr(opcode).0;
+        let mut opcode_data = self.wrap_kernel_specialise_attr(opcode).1;
+
+        // dims of all operands are specialised by order, lhs dims first, then rhs and so on
+        let mut dim_id = 0;
+        for in_buffer_functor in in_buffer_functors.iter() {
+            for value in in_buffer_functor.shape.iter() {
+                let spec_shape = shape_transfer_to_specialization(dim_id);
+                opcode_constant.push(spec_shape);
+
+                let value_bytes = (*value as u32).to_le_bytes();
+                for x in value_bytes {
+                    opcode_data.push(x);
+                }
+                dim_id += 1;
+            }
+        }
+        for value in attrs.iter() {
+            opcode_constant.push(shape_transfer_to_specialization(dim_id));
+            for x in (*value as u32).to_le_bytes() {
+                opcode_data.push(x);
+            }
+            dim_id += 1;
         }
-        .expect("Can't create descriptor set layout");
-        // alloc desc sets
-        let desc_set_outs = unsafe {
-            let mut desc_set = device_context
-                .descriptor_pool
-                .allocate_one(&descriptor_set_layout_outs)
-                .unwrap();
-            device_context
-                .device
-                .write_descriptor_set(pso::DescriptorSetWrite {
-                    set: &mut desc_set,
-                    binding: _BINDING_ID,
-                    array_offset: 0,
-                    descriptors: iter::once(pso::Descriptor::Buffer(
-                        &res_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                        buffer::SubRange::WHOLE,
-                    )),
-                });
-            desc_set
-        };
+
+        let spec_constant: Cow<[pso::SpecializationConstant]> = Cow::Owned(opcode_constant);
+        let spec_data: Cow<[u8]> = Cow::Owned(opcode_data);
+
+        let (res_shape, res_dsize) = self.infer_result_shape(opcode, &in_buffer_functors, &attrs);
+        // println!("res shape: {:?}", res_shape);
+
+        let res_element: T = Default::default();
+        let res_tensor_view = TensorView::<T>::new(
+            vec![Default::default(); res_dsize as usize],
+            res_element.get_type_code(),
+            res_shape,
+        );
+        let mut res_buffer_functor = UniBuffer::<concrete_backend::Backend, T>::new(
+            &device_context.device,
+            &device_context
+                .device_instance
+                .memory_property()
+                .memory_types,
+            res_tensor_view,
+        );
+
+        // step 6 create desc_set_layouts and desc_sets, one for each operand and one for outs
+        let mut descriptor_set_layouts = vec![];
+        let mut desc_sets = vec![];
+        for in_buffer_functor in in_buffer_functors.iter() {
+            let (descriptor_set_layout, desc_set) =
+                self.create_storage_binding(device_context, in_buffer_functor);
+            descriptor_set_layouts.push(descriptor_set_layout);
+            desc_sets.push(desc_set);
+        }
+        let (descriptor_set_layout_outs, desc_set_outs) =
+            self.create_storage_binding(device_context, &res_buffer_functor);
+        descriptor_set_layouts.push(descriptor_set_layout_outs);
+        desc_sets.push(desc_set_outs);
 
         // step 7 create pipeline_layout
         let pipeline_layout = unsafe {
-            device_context.device.create_pipeline_layout(
-                [
-                    &descriptor_set_layout_lhs,
-                    &descriptor_set_layout_rhs,
-                    &descriptor_set_layout_outs,
-                ]
-                .into_iter(),
-                iter::empty(),
-            )
-            //unsafe { device.create_pipeline_layout(iter::once(&descriptor_set_layout_lhs), iter::empty())
+            device_context
+                .device
+                .create_pipeline_layout(descriptor_set_layouts.iter(), iter::empty())
         }
         .expect("Can't create pipeline layout");
 
@@ -299,9 +363,7 @@ impl TensorFunctor {
             entry: "main",
             module: &shader,
             specialization: pso::Specialization {
-                // constants: self.wrap_kernel_specialise_attr(opcode).0,
                 constants: spec_constant,
-                // data: self.wrap_kernel_specialise_attr(opcode).1,
                 data: spec_data,
             },
         };
@@ -315,7 +377,7 @@ impl TensorFunctor {
 
         let mut command_pool = unsafe {
             device_context.device.create_command_pool(
-                device_instance_ref.queue_family().id(),
+                device_context.device_instance.queue_family().id(),
                 pool::CommandPoolCreateFlags::empty(),
             )
         }
@@ -327,61 +389,44 @@ impl TensorFunctor {
             command_buffer.begin_primary(command::CommandBufferFlags::ONE_TIME_SUBMIT);
 
             // move ins data
-            command_buffer.copy_buffer(
-                &lhs_buffer_functor.host_buffer.as_ref().unwrap().buffer,
-                &lhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                iter::once(command::BufferCopy {
-                    src: 0,
-                    dst: 0,
-                    size: F32STRIDE as u64 * lhs_buffer_functor.data_size as u64,
-                }),
-            );
-            command_buffer.copy_buffer(
-                &rhs_buffer_functor.host_buffer.as_ref().unwrap().buffer,
-                &rhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                iter::once(command::BufferCopy {
-                    src: 0,
-                    dst: 0,
-                    size: F32STRIDE as u64 * rhs_buffer_functor.data_size as u64,
-                }),
-            );
+            for in_buffer_functor in in_buffer_functors.iter() {
+                command_buffer.copy_buffer(
+                    &in_buffer_functor.host_buffer.as_ref().unwrap().buffer,
+                    &in_buffer_functor.device_buffer.as_ref().unwrap().buffer,
+                    iter::once(command::BufferCopy {
+                        src: 0,
+                        dst: 0,
+                        size: std::mem::size_of::<T>() as u64 * in_buffer_functor.data_size as u64,
+                    }),
+                );
+            }
 
             // ensure ins are all copied
-            command_buffer.pipeline_barrier(
-                pso::PipelineStage::TRANSFER..pso::PipelineStage::COMPUTE_SHADER,
-                memory::Dependencies::empty(),
-                iter::once(memory::Barrier::Buffer {
-                    states: buffer::Access::TRANSFER_WRITE
-                        ..buffer::Access::SHADER_READ | buffer::Access::SHADER_WRITE,
-                    families: None,
-                    target: &lhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                    range: buffer::SubRange::WHOLE,
-                }),
-            );
-            command_buffer.pipeline_barrier(
-                pso::PipelineStage::TRANSFER..pso::PipelineStage::COMPUTE_SHADER,
-                memory::Dependencies::empty(),
-                iter::once(memory::Barrier::Buffer {
-                    states: buffer::Access::TRANSFER_WRITE
-                        ..buffer::Access::SHADER_READ | buffer::Access::SHADER_WRITE,
-                    families: None,
-                    target: &rhs_buffer_functor.device_buffer.as_ref().unwrap().buffer,
-                    range: buffer::SubRange::WHOLE,
-                }),
-            );
+            for in_buffer_functor in in_buffer_functors.iter() {
+                command_buffer.pipeline_barrier(
+                    pso::PipelineStage::TRANSFER..pso::PipelineStage::COMPUTE_SHADER,
+                    memory::Dependencies::empty(),
+                    iter::once(memory::Barrier::Buffer {
+                        states: buffer::Access::TRANSFER_WRITE
+                            ..buffer::Access::SHADER_READ | buffer::Access::SHADER_WRITE,
+                        families: None,
+                        target: &in_buffer_functor.device_buffer.as_ref().unwrap().buffer,
+                        range: buffer::SubRange::WHOLE,
+                    }),
+                );
+            }
 
             command_buffer.bind_compute_pipeline(&pipeline);
 
             command_buffer.bind_compute_descriptor_sets(
                 &pipeline_layout,
                 0,
-                [&desc_set_lhs, &desc_set_rhs, &desc_set_outs].into_iter(),
+                desc_sets.iter(),
                 iter::empty(),
             );
-            //[&desc_set_lhs, &desc_set_rhs].into_iter(),
-            //iter::once(&desc_set_lhs),
 
-            command_buffer.dispatch([3, 1, 1]);
+            // one invocation per element of outs, kernels are in local_size_x = 3
+            command_buffer.dispatch([((res_dsize + 2) / 3) as u32, 1, 1]);
 
             command_buffer.pipeline_barrier(
                 pso::PipelineStage::COMPUTE_SHADER..pso::PipelineStage::TRANSFER,
@@ -402,7 +447,7 @@ impl TensorFunctor {
                 iter::once(command::BufferCopy {
                     src: 0,
                     dst: 0,
-                    size: F32STRIDE as u64 * res_buffer_functor.data_size as u64,
+                    size: std::mem::size_of::<T>() as u64 * res_buffer_functor.data_size as u64,
                 }),
             );
 
@@ -427,61 +472,18 @@ impl TensorFunctor {
             device_context.device.destroy_command_pool(command_pool);
             device_context.device.destroy_fence(fence);
 
-            device_context
-                .device
-                .destroy_descriptor_set_layout(descriptor_set_layout_lhs);
-            device_context
-                .device
-                .destroy_descriptor_set_layout(descriptor_set_layout_rhs);
+            for descriptor_set_layout in descriptor_set_layouts {
+                device_context
+                    .device
+                    .destroy_descriptor_set_layout(descriptor_set_layout);
+            }
 
-            lhs_buffer_functor.try_drop(&device_context.device);
-            rhs_buffer_functor.try_drop(&device_context.device);
+            for in_buffer_functor in in_buffer_functors.iter_mut() {
+                in_buffer_functor.try_drop(&device_context.device);
+            }
             // TODO-fix When run many iterations, pool is full, use this way to workaround
             device_context.descriptor_pool.reset();
 
-            // buffer
-            // device_context
-            //     .device
-            //     .destroy_buffer(lhs_buffer_functor.device_buffer.buffer);
-            // device_context
-            //     .device
-            //     .destroy_buffer(lhs_buffer_functor.host_buffer.buffer);
-            // device_context
-            //     .device
-            //     .destroy_buffer(rhs_buffer_functor.device_buffer.buffer);
-            // device_context
-            //     .device
-            //     .destroy_buffer(rhs_buffer_functor.host_buffer.buffer);
-            // // res
-            // // device_context
-            // //     .device
-            // //     .destroy_buffer(res_buffer_functor.device_buffer.buffer);
-            // // device_context
-            // //     .device
-            // //     .destroy_buffer(res_buffer_functor.host_buffer.buffer);
-            // res_buffer_functor.try_drop(&device_context.device);
-
-            // // memory
-            // device_context
-            //     .device
-            //     .free_memory(lhs_buffer_functor.device_buffer.memory);
-            // device_context
-            //     .device
-            //     .free_memory(lhs_buffer_functor.host_buffer.memory);
-            // device_context
-            //     .device
-            //     .free_memory(rhs_buffer_functor.device_buffer.memory);
-            // device_context
-            //     .device
-            //     .free_memory(rhs_buffer_functor.host_buffer.memory);
-            // // res
-            // // device_context
-            // //     .device
-            // //     .free_memory(res_buffer_functor.device_buffer.memory);
-            // // device_context
-            // //     .device
-            // //     .free_memory(res_buffer_functor.host_buffer.memory);
-
             device_context
                 .device
                 .destroy_pipeline_layout(pipeline_layout);
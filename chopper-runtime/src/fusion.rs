@@ -0,0 +1,327 @@
+// fused elementwise expressions, built by the fusion pass from chains of f32 elementwise ops. The
+// vulkan executor generates a compute shader for each expression and caches it by the hash of the
+// source, so that the chain takes a single upload, dispatch and download. Other executors compute
+// the steps one by one on host
+use serde::{Deserialize, Serialize};
+
+use crate::base::constants::FUSION_MAX_INPUTS;
+use crate::instruction::CRTOpCode;
+
+// operands of generated kernels are viewed in this rank, same as the elementwise kernels
+pub const FUSION_KERNEL_RANK: usize = 4;
+
+// ops that can be steps of fused expressions, the f32 elementwise math with a kernel expression
+pub fn is_fusible(code: CRTOpCode) -> bool {
+    fusible_arity(code).is_some()
+}
+
+fn fusible_arity(code: CRTOpCode) -> Option<usize> {
+    match code {
+        CRTOpCode::EXPF32
+        | CRTOpCode::ABSF32
+        | CRTOpCode::NEGF32
+        | CRTOpCode::CEILF32
+        | CRTOpCode::FLOORF32
+        | CRTOpCode::ATANF32
+        | CRTOpCode::COSF32
+        | CRTOpCode::SINF32
+        | CRTOpCode::EXP2F32
+        | CRTOpCode::EXPM1F32
+        | CRTOpCode::LOGF32
+        | CRTOpCode::LOG10F32
+        | CRTOpCode::LOG1PF32
+        | CRTOpCode::LOG2F32
+        | CRTOpCode::RSQRTF32
+        | CRTOpCode::SQRTF32
+        | CRTOpCode::TANHF32 => Some(1),
+        CRTOpCode::ADDF32
+        | CRTOpCode::SUBF32
+        | CRTOpCode::MULF32
+        | CRTOpCode::DIVF32
+        | CRTOpCode::POWF32
+        | CRTOpCode::ATAN2F32
+        | CRTOpCode::MAXF32
+        | CRTOpCode::MINF32
+        | CRTOpCode::REMF32 => Some(2),
+        _ => None,
+    }
+}
+
+// glsl expression of a step over the variables of its operands, same as the bodies of the kernels
+// in unary_arithmetic_f32 and binary_arithmetic_f32
+fn glsl_expression(code: CRTOpCode, operands: &[String]) -> String {
+    let x = &operands[0];
+    match code {
+        CRTOpCode::EXPF32 => format!("exp({})", x),
+        CRTOpCode::ABSF32 => format!("abs({})", x),
+        CRTOpCode::NEGF32 => format!("-{}", x),
+        CRTOpCode::CEILF32 => format!("ceil({})", x),
+        CRTOpCode::FLOORF32 => format!("floor({})", x),
+        CRTOpCode::ATANF32 => format!("atan({})", x),
+        CRTOpCode::COSF32 => format!("cos({})", x),
+        CRTOpCode::SINF32 => format!("sin({})", x),
+        CRTOpCode::EXP2F32 => format!("exp2({})", x),
+        CRTOpCode::EXPM1F32 => format!(
+            "abs({x}) < 1e-5 ? {x} + 0.5 * {x} * {x} : exp({x}) - 1.0",
+            x = x
+        ),
+        CRTOpCode::LOGF32 => format!("log({})", x),
+        CRTOpCode::LOG10F32 => format!("log({}) * 0.4342944819032518", x),
+        CRTOpCode::LOG1PF32 => format!(
+            "abs({x}) < 1e-4 ? {x} - 0.5 * {x} * {x} : log(1.0 + {x})",
+            x = x
+        ),
+        CRTOpCode::LOG2F32 => format!("log2({})", x),
+        CRTOpCode::RSQRTF32 => format!("inversesqrt({})", x),
+        CRTOpCode::SQRTF32 => format!("sqrt({})", x),
+        CRTOpCode::TANHF32 => format!("tanh({})", x),
+        CRTOpCode::ADDF32 => format!("{} + {}", x, operands[1]),
+        CRTOpCode::SUBF32 => format!("{} - {}", x, operands[1]),
+        CRTOpCode::MULF32 => format!("{} * {}", x, operands[1]),
+        CRTOpCode::DIVF32 => format!("{} / {}", x, operands[1]),
+        CRTOpCode::POWF32 => format!("pow({}, {})", x, operands[1]),
+        CRTOpCode::ATAN2F32 => format!("atan({}, {})", x, operands[1]),
+        CRTOpCode::MAXF32 => format!("max({}, {})", x, operands[1]),
+        CRTOpCode::MINF32 => format!("min({}, {})", x, operands[1]),
+        // truncated remainder, takes the sign of lhs same as host
+        CRTOpCode::REMF32 => format!("{l} - {r} * trunc({l} / {r})", l = x, r = operands[1]),
+        _ => panic!("{:?} is not fusible", code),
+    }
+}
+
+// FNV-1a, stable across runs and builds, unlike the hasher of std
+fn content_hash(source: &str) -> u64 {
+    source.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// an op of a fused expression, operands index the inputs of the fused op first, then the results
+// of earlier steps
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FusedStep {
+    pub opcode: CRTOpCode,
+    pub operands: Vec<usize>,
+}
+
+// steps in the order of computing, the result of the last one is the result of the expression
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FusedExpr {
+    pub steps: Vec<FusedStep>,
+}
+
+impl FusedExpr {
+    // whether the steps are fusible ops, reading only the inputs and earlier steps
+    pub fn check(&self, inputs: usize) -> Result<(), String> {
+        if inputs == 0 || inputs > FUSION_MAX_INPUTS {
+            return Err(format!(
+                "fused ops take 1 to {} operands, got {}",
+                FUSION_MAX_INPUTS, inputs
+            ));
+        }
+        if self.steps.is_empty() {
+            return Err(String::from("fused ops take at least one step"));
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            match fusible_arity(step.opcode) {
+                Some(arity) if arity == step.operands.len() => {}
+                Some(arity) => {
+                    return Err(format!(
+                        "step {} takes {} operands, got {}",
+                        index,
+                        arity,
+                        step.operands.len()
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "step {} is {}, which is not fusible",
+                        index,
+                        step.opcode.to_mnemonic()
+                    ))
+                }
+            }
+            if let Some(operand) = step.operands.iter().find(|x| **x >= inputs + index) {
+                return Err(format!(
+                    "step {} reads {}, which is not computed before it",
+                    index, operand
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // compute shader of the expression over inputs operands. Dims of the operands in
+    // FUSION_KERNEL_RANK are specialised by order from constant_id 1, and operands are bound to
+    // sets by order, followed by the result, same as TensorFunctor::apply_n lays them out
+    pub fn to_glsl(&self, inputs: usize) -> String {
+        let rank = FUSION_KERNEL_RANK;
+        let mut glsl = String::from("#version 450\n\n");
+        glsl +=
+            "// generated for a fused elementwise expression, operands are broadcasted as the\n";
+        glsl += "// elementwise kernels do\n";
+        for input in 0..inputs {
+            for d in 0..rank {
+                glsl += &format!(
+                    "layout(constant_id = {}) const uint in{}_dim{} = 1;\n",
+                    1 + input * rank + d,
+                    input,
+                    d
+                );
+            }
+        }
+        glsl += "\nlayout(local_size_x = 3) in;\n\n";
+        for input in 0..inputs {
+            glsl += &format!(
+                "layout(set = {0}, binding = 0) buffer ins{0} {{\n    float in{0}_operand[];\n}};\n\n",
+                input
+            );
+        }
+        glsl += &format!(
+            "layout(set = {}, binding = 0) buffer outs {{\n    float outputs[];\n}};\n\n",
+            inputs
+        );
+
+        glsl += "void main() {\n";
+        glsl += "    uint index = gl_GlobalInvocationID.x;\n";
+        glsl += "    if (index >= outputs.length()) {\n        return;\n    }\n\n";
+        glsl += "    // decompose index over the broadcasted shape, from the innermost dim\n";
+        for input in 0..inputs {
+            let dims: Vec<String> = (0..rank).map(|d| format!("in{}_dim{}", input, d)).collect();
+            glsl += &format!(
+                "    const uint in{}_dims[{}] = uint[{}]({});\n",
+                input,
+                rank,
+                rank,
+                dims.join(", ")
+            );
+        }
+        glsl += "    uint rem = index;\n";
+        for input in 0..inputs {
+            glsl += &format!("    uint in{}_index = 0;\n", input);
+            glsl += &format!("    uint in{}_stride = 1;\n", input);
+        }
+        let odim = (1..inputs).fold(String::from("in0_dims[d]"), |odim, input| {
+            format!("max({}, in{}_dims[d])", odim, input)
+        });
+        glsl += &format!("    for (int d = {}; d >= 0; d--) {{\n", rank - 1);
+        glsl += &format!("        uint odim = {};\n", odim);
+        glsl += "        uint coord = rem % odim;\n";
+        glsl += "        rem = rem / odim;\n";
+        for input in 0..inputs {
+            glsl += &format!(
+                "        if (in{0}_dims[d] != 1) {{\n            in{0}_index += coord * in{0}_stride;\n        }}\n",
+                input
+            );
+            glsl += &format!("        in{0}_stride *= in{0}_dims[d];\n", input);
+        }
+        glsl += "    }\n";
+
+        // inputs and the results of steps are numbered as the operands of steps index them
+        for input in 0..inputs {
+            glsl += &format!("    float v{0} = in{0}_operand[in{0}_index];\n", input);
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let operands: Vec<String> = step
+                .operands
+                .iter()
+                .map(|operand| format!("v{}", operand))
+                .collect();
+            glsl += &format!(
+                "    float v{} = {};\n",
+                inputs + index,
+                glsl_expression(step.opcode, &operands)
+            );
+        }
+        glsl += &format!(
+            "    outputs[index] = v{};\n}}\n",
+            inputs + self.steps.len() - 1
+        );
+        glsl
+    }
+
+    // entry of the generated kernel in the kernel registry, by the hash of its source. Same
+    // expressions share the kernel whatever the shapes of their operands are
+    pub fn to_query_entry(&self, inputs: usize) -> String {
+        format!("fused_f32_{:016x}", content_hash(&self.to_glsl(inputs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_exp_mul() -> FusedExpr {
+        FusedExpr {
+            steps: vec![
+                FusedStep {
+                    opcode: CRTOpCode::ADDF32,
+                    operands: vec![0, 1],
+                },
+                FusedStep {
+                    opcode: CRTOpCode::EXPF32,
+                    operands: vec![3],
+                },
+                FusedStep {
+                    opcode: CRTOpCode::MULF32,
+                    operands: vec![4, 2],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_fused_expr_check() {
+        let expr = add_exp_mul();
+        assert_eq!(expr.check(3), Ok(()));
+        // step 1 reads itself if only two operands are given
+        assert!(expr.check(2).is_err());
+        assert!(expr.check(FUSION_MAX_INPUTS + 1).is_err());
+
+        let expr = FusedExpr {
+            steps: vec![FusedStep {
+                opcode: CRTOpCode::CMPLTF32,
+                operands: vec![0, 1],
+            }],
+        };
+        assert!(expr.check(2).is_err());
+        let expr = FusedExpr {
+            steps: vec![FusedStep {
+                opcode: CRTOpCode::EXPF32,
+                operands: vec![0, 1],
+            }],
+        };
+        assert!(expr.check(2).is_err());
+        assert!(FusedExpr { steps: vec![] }.check(1).is_err());
+    }
+
+    #[test]
+    fn test_fused_expr_to_glsl() {
+        let glsl = add_exp_mul().to_glsl(3);
+        assert!(glsl.starts_with("#version 450\n"));
+        assert!(glsl.contains("layout(constant_id = 12) const uint in2_dim3 = 1;"));
+        assert!(!glsl.contains("constant_id = 13"));
+        assert!(glsl.contains("layout(set = 3, binding = 0) buffer outs {"));
+        assert!(glsl.contains("uint odim = max(max(in0_dims[d], in1_dims[d]), in2_dims[d]);"));
+        assert!(glsl.contains("    float v3 = v0 + v1;\n"));
+        assert!(glsl.contains("    float v4 = exp(v3);\n"));
+        assert!(glsl.contains("    float v5 = v4 * v2;\n"));
+        assert!(glsl.ends_with("    outputs[index] = v5;\n}\n"));
+    }
+
+    #[test]
+    fn test_fused_expr_query_entry() {
+        let expr = add_exp_mul();
+        let entry = expr.to_query_entry(3);
+        assert!(entry.starts_with("fused_f32_"));
+        assert_eq!(entry.len(), "fused_f32_".len() + 16);
+        assert_eq!(entry, add_exp_mul().to_query_entry(3));
+
+        // operands of steps change the source, so they get another kernel
+        let mut other = add_exp_mul();
+        other.steps[2].operands = vec![2, 4];
+        assert_ne!(other.to_query_entry(3), entry);
+        // so does the count of operands
+        assert_ne!(expr.to_query_entry(4), entry);
+    }
+}
@@ -0,0 +1,1720 @@
+use crate::base::*;
+use crate::instruction::*;
+use crate::tensors::*;
+
+// maps the view of every dtype into a view of the same dtype, for data movement that is generic
+// over the element, such as shape ops
+macro_rules! map_tensor {
+    ($tensor:expr, $data:ident => $compute:expr) => {
+        match $tensor {
+            ActTensorTypes::F32Tensor { data: $data } => {
+                ActTensorTypes::F32Tensor { data: $compute }
+            }
+            ActTensorTypes::I32Tensor { data: $data } => {
+                ActTensorTypes::I32Tensor { data: $compute }
+            }
+            ActTensorTypes::F16Tensor { data: $data } => {
+                ActTensorTypes::F16Tensor { data: $compute }
+            }
+            ActTensorTypes::BF16Tensor { data: $data } => {
+                ActTensorTypes::BF16Tensor { data: $compute }
+            }
+            ActTensorTypes::I8Tensor { data: $data } => ActTensorTypes::I8Tensor { data: $compute },
+            ActTensorTypes::U8Tensor { data: $data } => ActTensorTypes::U8Tensor { data: $compute },
+            ActTensorTypes::F64Tensor { data: $data } => {
+                ActTensorTypes::F64Tensor { data: $compute }
+            }
+            ActTensorTypes::I64Tensor { data: $data } => {
+                ActTensorTypes::I64Tensor { data: $compute }
+            }
+            _ => panic!("not support mock types"),
+        }
+    };
+}
+
+// same as map_tensor on two tensors of the same dtype
+macro_rules! zip_tensors {
+    ($lhs:expr, $rhs:expr, ($lhs_data:ident, $rhs_data:ident) => $compute:expr) => {
+        match ($lhs, $rhs) {
+            (
+                ActTensorTypes::F32Tensor { data: $lhs_data },
+                ActTensorTypes::F32Tensor { data: $rhs_data },
+            ) => ActTensorTypes::F32Tensor { data: $compute },
+            (
+                ActTensorTypes::I32Tensor { data: $lhs_data },
+                ActTensorTypes::I32Tensor { data: $rhs_data },
+            ) => ActTensorTypes::I32Tensor { data: $compute },
+            (
+                ActTensorTypes::F16Tensor { data: $lhs_data },
+                ActTensorTypes::F16Tensor { data: $rhs_data },
+            ) => ActTensorTypes::F16Tensor { data: $compute },
+            (
+                ActTensorTypes::BF16Tensor { data: $lhs_data },
+                ActTensorTypes::BF16Tensor { data: $rhs_data },
+            ) => ActTensorTypes::BF16Tensor { data: $compute },
+            (
+                ActTensorTypes::I8Tensor { data: $lhs_data },
+                ActTensorTypes::I8Tensor { data: $rhs_data },
+            ) => ActTensorTypes::I8Tensor { data: $compute },
+            (
+                ActTensorTypes::U8Tensor { data: $lhs_data },
+                ActTensorTypes::U8Tensor { data: $rhs_data },
+            ) => ActTensorTypes::U8Tensor { data: $compute },
+            (
+                ActTensorTypes::F64Tensor { data: $lhs_data },
+                ActTensorTypes::F64Tensor { data: $rhs_data },
+            ) => ActTensorTypes::F64Tensor { data: $compute },
+            (
+                ActTensorTypes::I64Tensor { data: $lhs_data },
+                ActTensorTypes::I64Tensor { data: $rhs_data },
+            ) => ActTensorTypes::I64Tensor { data: $compute },
+            _ => panic!("dtype mismatch"),
+        }
+    };
+}
+
+// CPU reference implementation of CRTOpCodes, used by mock and blas executors for the ops that
+// have no counterpart in MockOpCode/BlasOpCode.
+pub(crate) struct HostFunctor {}
+
+impl HostFunctor {
+    pub fn new() -> HostFunctor {
+        return Self {};
+    }
+
+    // dispatch by dtype of the operand, casting ops dispatch by the dtype of result
+    pub fn unary_compute(&self, opcode: CRTOpCode, in_tensor: &ActTensorTypes) -> ActTensorTypes {
+        if opcode.to_cast_element_type().is_some() {
+            return self.cast_compute(opcode, in_tensor);
+        }
+        match in_tensor {
+            ActTensorTypes::F32Tensor { data } => ActTensorTypes::F32Tensor {
+                data: self.unary_compute_f32(opcode, data),
+            },
+            ActTensorTypes::I32Tensor { data } => ActTensorTypes::I32Tensor {
+                data: self.unary_compute_i32(opcode, data),
+            },
+            ActTensorTypes::F64Tensor { data } => ActTensorTypes::F64Tensor {
+                data: self.unary_compute_f64(opcode, data),
+            },
+            ActTensorTypes::I64Tensor { data } => ActTensorTypes::I64Tensor {
+                data: self.unary_compute_i64(opcode, data),
+            },
+            _ if in_tensor.is_host_only() => {
+                let outs = self.unary_compute(opcode, &self.widen(in_tensor));
+                self.cast(&outs, in_tensor.element_type())
+            }
+            _ => panic!("dtype-comp not implemented"),
+        }
+    }
+
+    // dispatch by dtype of the operands, lhs and rhs must share the dtype
+    pub fn binary_compute(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        // reduction ops carry their attributes as rhs
+        if opcode.is_reduction() {
+            return self.reduce_compute(opcode, lhs_tensor, rhs_tensor);
+        }
+        if opcode.is_comparison() {
+            return self.compare(opcode, lhs_tensor, rhs_tensor);
+        }
+        // sliding window ops carry their attributes as rhs, conv2d carries the input and the
+        // weight packed as lhs
+        if opcode == CRTOpCode::CONV2DF32 {
+            return self.conv2d(lhs_tensor, rhs_tensor);
+        }
+        if opcode == CRTOpCode::MAXPOOL2DF32 || opcode == CRTOpCode::AVGPOOL2DF32 {
+            return self.pool2d(opcode, lhs_tensor, rhs_tensor);
+        }
+        // matmul with transpose flags carries both operands packed as lhs, flags as rhs
+        if opcode == CRTOpCode::MATMULF32 {
+            return self.matmul(lhs_tensor, rhs_tensor, false, false);
+        }
+        if opcode == CRTOpCode::MATMULTF32 {
+            let (transpose_a, transpose_b) = rhs_tensor.to_matmul_attr();
+            return match lhs_tensor {
+                ActTensorTypes::TupleTensor { data } if data.len() == 2 => {
+                    self.matmul(&data[0], &data[1], transpose_a, transpose_b)
+                }
+                _ => panic!("matmul expects the operands packed as lhs"),
+            };
+        }
+        // ternary ops carry the first operand as lhs and the packed others as rhs
+        if opcode.is_ternary() {
+            return self.ternary_compute(opcode, lhs_tensor, rhs_tensor);
+        }
+        // fused ops carry their inputs packed as lhs, the expression as rhs
+        if opcode == CRTOpCode::FUSEDF32 {
+            return self.fused(lhs_tensor, rhs_tensor);
+        }
+        match (lhs_tensor, rhs_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => ActTensorTypes::F32Tensor {
+                data: self.binary_compute_f32(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::I32Tensor { data: lhs_data },
+                ActTensorTypes::I32Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: self.binary_compute_i32(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::F64Tensor { data: lhs_data },
+                ActTensorTypes::F64Tensor { data: rhs_data },
+            ) => ActTensorTypes::F64Tensor {
+                data: self.binary_compute_f64(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::I64Tensor { data: lhs_data },
+                ActTensorTypes::I64Tensor { data: rhs_data },
+            ) => ActTensorTypes::I64Tensor {
+                data: self.binary_compute_i64(opcode, lhs_data, rhs_data),
+            },
+            _ if lhs_tensor.is_host_only()
+                && lhs_tensor.element_type() == rhs_tensor.element_type() =>
+            {
+                let outs =
+                    self.binary_compute(opcode, &self.widen(lhs_tensor), &self.widen(rhs_tensor));
+                self.cast(&outs, lhs_tensor.element_type())
+            }
+            _ => panic!("dtype mismatch"),
+        }
+    }
+
+    // f16, bf16, i8 and u8 are storage types, computed in f32 or i32 and narrowed back
+    fn widen(&self, in_tensor: &ActTensorTypes) -> ActTensorTypes {
+        match in_tensor.element_type() {
+            ElementType::F16 | ElementType::BF16 => self.cast(in_tensor, ElementType::F32),
+            ElementType::I8 | ElementType::U8 => self.cast(in_tensor, ElementType::I32),
+            _ => in_tensor.clone(),
+        }
+    }
+
+    // value cast between any dtypes, through f64 if either side is float and through i64
+    // otherwise, see ActTensorTypes::from_f64_vec for the rounding rules
+    pub fn cast(&self, in_tensor: &ActTensorTypes, element_type: ElementType) -> ActTensorTypes {
+        let in_type = in_tensor.element_type();
+        if in_type == element_type {
+            return in_tensor.clone();
+        }
+        let shape = in_tensor.shape();
+        match in_type.is_float() || element_type.is_float() {
+            true => ActTensorTypes::from_f64_vec(in_tensor.to_f64_vec(), element_type, shape),
+            false => ActTensorTypes::from_i64_vec(in_tensor.to_i64_vec(), element_type, shape),
+        }
+    }
+
+    // value casts truncate floats towards zero (saturating, NaN to 0), bitcasts reinterpret the
+    // 32-bit pattern. Casting into the same dtype is a copy.
+    pub fn cast_compute(&self, opcode: CRTOpCode, in_tensor: &ActTensorTypes) -> ActTensorTypes {
+        match (opcode, in_tensor) {
+            (CRTOpCode::CASTF32, ActTensorTypes::I32Tensor { data }) => ActTensorTypes::F32Tensor {
+                data: TensorView::<f32>::new(
+                    data.data.iter().map(|x| *x as f32).collect(),
+                    ElementType::F32,
+                    data.shape.clone(),
+                ),
+            },
+            (CRTOpCode::CASTI32, ActTensorTypes::F32Tensor { data }) => ActTensorTypes::I32Tensor {
+                data: TensorView::<i32>::new(
+                    data.data.iter().map(|x| *x as i32).collect(),
+                    ElementType::I32,
+                    data.shape.clone(),
+                ),
+            },
+            (CRTOpCode::BITCASTF32, ActTensorTypes::I32Tensor { data }) => {
+                ActTensorTypes::F32Tensor {
+                    data: TensorView::<f32>::new(
+                        data.data
+                            .iter()
+                            .map(|x| f32::from_bits(*x as u32))
+                            .collect(),
+                        ElementType::F32,
+                        data.shape.clone(),
+                    ),
+                }
+            }
+            (CRTOpCode::BITCASTI32, ActTensorTypes::F32Tensor { data }) => {
+                ActTensorTypes::I32Tensor {
+                    data: TensorView::<i32>::new(
+                        data.data.iter().map(|x| x.to_bits() as i32).collect(),
+                        ElementType::I32,
+                        data.shape.clone(),
+                    ),
+                }
+            }
+            (CRTOpCode::CASTF32 | CRTOpCode::BITCASTF32, ActTensorTypes::F32Tensor { data }) => {
+                ActTensorTypes::F32Tensor { data: data.clone() }
+            }
+            (CRTOpCode::CASTI32 | CRTOpCode::BITCASTI32, ActTensorTypes::I32Tensor { data }) => {
+                ActTensorTypes::I32Tensor { data: data.clone() }
+            }
+            (CRTOpCode::BITCASTF32 | CRTOpCode::BITCASTI32, _) => {
+                panic!("bitcasts only reinterpret 32-bit tensors")
+            }
+            _ => self.cast(in_tensor, opcode.to_cast_element_type().unwrap()),
+        }
+    }
+
+    pub fn reduce_compute(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let (axis, keepdims) = attr_tensor.to_reduce_attr();
+        match in_tensor {
+            ActTensorTypes::F32Tensor { data } => match opcode {
+                CRTOpCode::ARGMAXF32 | CRTOpCode::ARGMINF32 => ActTensorTypes::I32Tensor {
+                    data: self.reduce_arg_compute_f32(opcode, data, axis, keepdims),
+                },
+                _ => ActTensorTypes::F32Tensor {
+                    data: self.reduce_compute_f32(opcode, data, axis, keepdims),
+                },
+            },
+            _ => panic!("not support this dtype for host reduce compute"),
+        }
+    }
+
+    pub fn reduce_compute_f32(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<f32>,
+        axis: Option<usize>,
+        keepdims: bool,
+    ) -> TensorView<f32> {
+        let (outer, axis_len, inner) = in_tensor.reduce_view(axis);
+        let mut out_data = Vec::<f32>::with_capacity(outer * inner);
+        for o in 0..outer {
+            for i in 0..inner {
+                let lane = (0..axis_len).map(|k| in_tensor.data[(o * axis_len + k) * inner + i]);
+                out_data.push(match opcode {
+                    CRTOpCode::REDUCESUMF32 => lane.sum(),
+                    CRTOpCode::REDUCEMEANF32 => lane.sum::<f32>() / axis_len as f32,
+                    CRTOpCode::REDUCEMAXF32 => lane.fold(f32::NEG_INFINITY, f32::max),
+                    CRTOpCode::REDUCEMINF32 => lane.fold(f32::INFINITY, f32::min),
+                    _ => panic!("not support this op for host reduce compute"),
+                });
+            }
+        }
+        TensorView::<f32>::new(
+            out_data,
+            ElementType::F32,
+            in_tensor.reduce_shape(axis, keepdims),
+        )
+    }
+
+    // index of the first max/min element along the axis
+    pub fn reduce_arg_compute_f32(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<f32>,
+        axis: Option<usize>,
+        keepdims: bool,
+    ) -> TensorView<i32> {
+        let better: fn(f32, f32) -> bool = match opcode {
+            CRTOpCode::ARGMAXF32 => |x, best| x > best,
+            CRTOpCode::ARGMINF32 => |x, best| x < best,
+            _ => panic!("not support this op for host reduce compute"),
+        };
+        let (outer, axis_len, inner) = in_tensor.reduce_view(axis);
+        let mut out_data = Vec::<i32>::with_capacity(outer * inner);
+        for o in 0..outer {
+            for i in 0..inner {
+                let mut best_idx = 0;
+                for k in 1..axis_len {
+                    let x = in_tensor.data[(o * axis_len + k) * inner + i];
+                    if better(x, in_tensor.data[(o * axis_len + best_idx) * inner + i]) {
+                        best_idx = k;
+                    }
+                }
+                out_data.push(best_idx as i32);
+            }
+        }
+        TensorView::<i32>::new(
+            out_data,
+            ElementType::I32,
+            in_tensor.reduce_shape(axis, keepdims),
+        )
+    }
+
+    pub fn compare(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        match (lhs_tensor, rhs_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: self.compare_compute(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::I32Tensor { data: lhs_data },
+                ActTensorTypes::I32Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: self.compare_compute(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::F64Tensor { data: lhs_data },
+                ActTensorTypes::F64Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: self.compare_compute(opcode, lhs_data, rhs_data),
+            },
+            (
+                ActTensorTypes::I64Tensor { data: lhs_data },
+                ActTensorTypes::I64Tensor { data: rhs_data },
+            ) => ActTensorTypes::I32Tensor {
+                data: self.compare_compute(opcode, lhs_data, rhs_data),
+            },
+            // widening keeps the order of values
+            _ if lhs_tensor.is_host_only()
+                && lhs_tensor.element_type() == rhs_tensor.element_type() =>
+            {
+                self.compare(opcode, &self.widen(lhs_tensor), &self.widen(rhs_tensor))
+            }
+            _ => panic!("dtype mismatch"),
+        }
+    }
+
+    // mask is 1 where the comparison holds and 0 elsewhere, NaN compares unequal to anything
+    pub fn compare_compute<T: Copy + PartialOrd>(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &TensorView<T>,
+        rhs_tensor: &TensorView<T>,
+    ) -> TensorView<i32> {
+        let kernel: fn(T, T) -> i32 = match opcode {
+            CRTOpCode::CMPEQF32 | CRTOpCode::CMPEQI32 => |l, r| (l == r) as i32,
+            CRTOpCode::CMPNEF32 | CRTOpCode::CMPNEI32 => |l, r| (l != r) as i32,
+            CRTOpCode::CMPLTF32 | CRTOpCode::CMPLTI32 => |l, r| (l < r) as i32,
+            CRTOpCode::CMPLEF32 | CRTOpCode::CMPLEI32 => |l, r| (l <= r) as i32,
+            CRTOpCode::CMPGTF32 | CRTOpCode::CMPGTI32 => |l, r| (l > r) as i32,
+            CRTOpCode::CMPGEF32 | CRTOpCode::CMPGEI32 => |l, r| (l >= r) as i32,
+            _ => panic!("not support this op for host compare compute"),
+        };
+        let (out_data, out_shape) = broadcast_compute(lhs_tensor, rhs_tensor, kernel);
+        TensorView::<i32>::new(out_data, ElementType::BOOL, out_shape)
+    }
+
+    // broadcast all operands of ternary ops into the shape of result, and stack the second and
+    // the third along a new leading axis, so that ternary ops fit into binary compute of executors
+    pub fn pack_ternary_operands(
+        &self,
+        first_tensor: &ActTensorTypes,
+        second_tensor: &ActTensorTypes,
+        third_tensor: &ActTensorTypes,
+        shape: &[usize],
+    ) -> (ActTensorTypes, ActTensorTypes) {
+        let first_tensor = self.broadcast_to(first_tensor, shape);
+        let mut second_tensor = self.broadcast_to(second_tensor, shape);
+        let mut third_tensor = self.broadcast_to(third_tensor, shape);
+        second_tensor.reshape(unsqueeze_shape(shape, &[0]));
+        third_tensor.reshape(unsqueeze_shape(shape, &[0]));
+        (first_tensor, self.concat(&second_tensor, &third_tensor, 0))
+    }
+
+    pub fn ternary_compute(
+        &self,
+        opcode: CRTOpCode,
+        first_tensor: &ActTensorTypes,
+        packed_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        match (opcode, first_tensor, packed_tensor) {
+            (CRTOpCode::SELECT, _, _) => self.select(first_tensor, packed_tensor),
+            (
+                CRTOpCode::FMAF32,
+                ActTensorTypes::F32Tensor { data: first_data },
+                ActTensorTypes::F32Tensor { data: packed_data },
+            ) => ActTensorTypes::F32Tensor {
+                data: self.fma_compute(first_data, packed_data),
+            },
+            _ => panic!("not support this op for host ternary compute"),
+        }
+    }
+
+    // lhs * rhs + addend, rounded once. rhs and addend are packed as [2, shape of lhs]
+    pub fn fma_compute(
+        &self,
+        lhs_tensor: &TensorView<f32>,
+        packed_tensor: &TensorView<f32>,
+    ) -> TensorView<f32> {
+        let size = lhs_tensor.data.len();
+        assert_eq!(packed_tensor.data.len(), 2 * size);
+        let out_data = lhs_tensor
+            .data
+            .iter()
+            .enumerate()
+            .map(|(index, x)| {
+                x.mul_add(packed_tensor.data[index], packed_tensor.data[size + index])
+            })
+            .collect();
+        TensorView::<f32>::new(out_data, ElementType::F32, lhs_tensor.shape.clone())
+    }
+
+    pub fn select(&self, mask_tensor: &ActTensorTypes, values: &ActTensorTypes) -> ActTensorTypes {
+        let mask = match mask_tensor {
+            ActTensorTypes::I32Tensor { data } => data,
+            _ => panic!("mask of select must be a bool or i32 tensor"),
+        };
+        map_tensor!(values, data => self.select_compute(mask, data))
+    }
+
+    // values are on_true and on_false packed as [2, mask-shape], picks on_true where mask is
+    // non-zero
+    pub fn select_compute<T: Copy>(
+        &self,
+        mask: &TensorView<i32>,
+        values: &TensorView<T>,
+    ) -> TensorView<T> {
+        let size = mask.data.len();
+        assert!(
+            values.shape.len() == mask.shape.len() + 1
+                && values.shape[0] == 2
+                && values.shape[1..] == mask.shape[..],
+            "select values of shape {:?} mismatch mask of shape {:?}",
+            values.shape,
+            mask.shape
+        );
+        let out_data = mask
+            .data
+            .iter()
+            .enumerate()
+            .map(|(index, m)| match m {
+                0 => values.data[size + index],
+                _ => values.data[index],
+            })
+            .collect();
+        TensorView::<T>::new(out_data, values.dtype, mask.shape.clone())
+    }
+
+    pub fn broadcast_to(&self, in_tensor: &ActTensorTypes, shape: &[usize]) -> ActTensorTypes {
+        map_tensor!(in_tensor, data => self.broadcast_to_compute(data, shape))
+    }
+
+    // materialise the tensor in the broadcasted shape
+    pub fn broadcast_to_compute<T: Copy>(
+        &self,
+        in_tensor: &TensorView<T>,
+        shape: &[usize],
+    ) -> TensorView<T> {
+        assert!(
+            broadcast_shape(&in_tensor.shape, shape).as_deref() == Some(shape),
+            "cannot broadcast shape {:?} to {:?}",
+            in_tensor.shape,
+            shape
+        );
+        let in_strides = broadcast_strides(&in_tensor.shape, shape);
+        let out_data = (0..shape.iter().product())
+            .map(|index| {
+                let mut rem = index;
+                let mut in_index = 0;
+                for d in (0..shape.len()).rev() {
+                    in_index += (rem % shape[d]) * in_strides[d];
+                    rem /= shape[d];
+                }
+                in_tensor.data[in_index]
+            })
+            .collect();
+        TensorView::<T>::new(out_data, in_tensor.dtype, shape.to_vec())
+    }
+
+    pub fn matmul(
+        &self,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+        transpose_a: bool,
+        transpose_b: bool,
+    ) -> ActTensorTypes {
+        match (lhs_tensor, rhs_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => ActTensorTypes::F32Tensor {
+                data: self.matmul_compute(lhs_data, rhs_data, transpose_a, transpose_b),
+            },
+            _ => panic!("dtype mismatch"),
+        }
+    }
+
+    // batched matmul, batch dims of lhs and rhs broadcast and each batch is a plain gemm
+    pub fn matmul_compute(
+        &self,
+        lhs_tensor: &TensorView<f32>,
+        rhs_tensor: &TensorView<f32>,
+        transpose_a: bool,
+        transpose_b: bool,
+    ) -> TensorView<f32> {
+        let out_shape = matmul_shape(
+            &lhs_tensor.shape,
+            &rhs_tensor.shape,
+            transpose_a,
+            transpose_b,
+        )
+        .expect(&format!(
+            "cannot matmul shapes {:?} and {:?}",
+            lhs_tensor.shape, rhs_tensor.shape
+        ));
+        let (lhs_rank, rhs_rank, out_rank) = (
+            lhs_tensor.shape.len(),
+            rhs_tensor.shape.len(),
+            out_shape.len(),
+        );
+        let batch_shape = &out_shape[..out_rank - 2];
+        let (m, n) = (out_shape[out_rank - 2], out_shape[out_rank - 1]);
+        let k = lhs_tensor.shape[lhs_rank - 2] * lhs_tensor.shape[lhs_rank - 1] / m;
+        // strides of batch dims in units of matrices, 0 for broadcasted dims
+        let lhs_batch_strides = broadcast_strides(&lhs_tensor.shape[..lhs_rank - 2], batch_shape);
+        let rhs_batch_strides = broadcast_strides(&rhs_tensor.shape[..rhs_rank - 2], batch_shape);
+
+        let mut out_data = Vec::<f32>::with_capacity(out_shape.iter().product());
+        for batch in 0..batch_shape.iter().product::<usize>() {
+            let (mut lhs_offset, mut rhs_offset, mut rem) = (0, 0, batch);
+            for d in (0..batch_shape.len()).rev() {
+                lhs_offset += (rem % batch_shape[d]) * lhs_batch_strides[d];
+                rhs_offset += (rem % batch_shape[d]) * rhs_batch_strides[d];
+                rem /= batch_shape[d];
+            }
+            let lhs = &lhs_tensor.data[lhs_offset * m * k..(lhs_offset + 1) * m * k];
+            let rhs = &rhs_tensor.data[rhs_offset * k * n..(rhs_offset + 1) * k * n];
+            for i in 0..m {
+                for j in 0..n {
+                    out_data.push(
+                        (0..k)
+                            .map(|p| {
+                                let l = match transpose_a {
+                                    true => lhs[p * m + i],
+                                    false => lhs[i * k + p],
+                                };
+                                let r = match transpose_b {
+                                    true => rhs[j * k + p],
+                                    false => rhs[p * n + j],
+                                };
+                                l * r
+                            })
+                            .sum(),
+                    );
+                }
+            }
+        }
+        TensorView::<f32>::new(out_data, ElementType::F32, out_shape)
+    }
+
+    pub fn conv2d(
+        &self,
+        operands: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        match operands {
+            ActTensorTypes::TupleTensor { data } if data.len() == 2 => match (&data[0], &data[1]) {
+                (
+                    ActTensorTypes::F32Tensor { data: in_data },
+                    ActTensorTypes::F32Tensor { data: weight_data },
+                ) => ActTensorTypes::F32Tensor {
+                    data: self.conv2d_compute(in_data, weight_data, &attr_tensor.to_conv2d_attr()),
+                },
+                _ => panic!("dtype mismatch"),
+            },
+            _ => panic!("conv2d expects the input and the weight packed as lhs"),
+        }
+    }
+
+    // computes the steps of a fused expression one by one, the reference of generated kernels
+    pub fn fused(&self, operands: &ActTensorTypes, expr_tensor: &ActTensorTypes) -> ActTensorTypes {
+        let mut values = match operands {
+            ActTensorTypes::TupleTensor { data } => data.clone(),
+            _ => panic!("fused op expects the inputs packed as lhs"),
+        };
+        for step in expr_tensor.to_fused_expr().steps {
+            let value = match step.operands.as_slice() {
+                [x] => self.unary_compute(step.opcode, &values[*x]),
+                [l, r] => self.binary_compute(step.opcode, &values[*l], &values[*r]),
+                _ => panic!("fused step of {:?} has no arity", step.opcode),
+            };
+            values.push(value);
+        }
+        values.pop().expect("fused expression has no steps")
+    }
+
+    pub fn pool2d(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        match in_tensor {
+            ActTensorTypes::F32Tensor { data } => ActTensorTypes::F32Tensor {
+                data: self.pool2d_compute(opcode, data, &attr_tensor.to_pool2d_attr()),
+            },
+            _ => panic!("not support this dtype for host pool2d compute"),
+        }
+    }
+
+    // im2col + GEMM for each image and group, input is NCHW and weight is [O, C / groups, KH, KW]
+    pub fn conv2d_compute(
+        &self,
+        in_tensor: &TensorView<f32>,
+        weight_tensor: &TensorView<f32>,
+        attr: &Conv2dAttr,
+    ) -> TensorView<f32> {
+        let out_shape =
+            conv2d_shape(&in_tensor.shape, &weight_tensor.shape, attr).expect(&format!(
+                "cannot conv2d shapes {:?} and {:?} with {:?}",
+                in_tensor.shape, weight_tensor.shape, attr
+            ));
+        let (batch, channels, height, width) = (
+            in_tensor.shape[0],
+            in_tensor.shape[1],
+            in_tensor.shape[2],
+            in_tensor.shape[3],
+        );
+        let (out_channels, group_channels, kernel_h, kernel_w) = (
+            weight_tensor.shape[0],
+            weight_tensor.shape[1],
+            weight_tensor.shape[2],
+            weight_tensor.shape[3],
+        );
+        let (out_h, out_w) = (out_shape[2], out_shape[3]);
+        let group_out_channels = out_channels / attr.groups;
+        let cols_rows = group_channels * kernel_h * kernel_w;
+        let cols_len = out_h * out_w;
+
+        let mut cols = vec![0f32; cols_rows * cols_len];
+        let mut out_data = vec![0f32; out_shape.iter().product()];
+        for n in 0..batch {
+            for g in 0..attr.groups {
+                // rows of cols are (channel, ki, kj) of the window, padded positions are zeros
+                for row in 0..cols_rows {
+                    let ic = row / (kernel_h * kernel_w);
+                    let ki = row / kernel_w % kernel_h;
+                    let kj = row % kernel_w;
+                    let plane = (n * channels + g * group_channels + ic) * height * width;
+                    for oi in 0..out_h {
+                        for oj in 0..out_w {
+                            let ih = (oi * attr.stride[0] + ki * attr.dilation[0]) as isize
+                                - attr.padding[0] as isize;
+                            let iw = (oj * attr.stride[1] + kj * attr.dilation[1]) as isize
+                                - attr.padding[1] as isize;
+                            cols[row * cols_len + oi * out_w + oj] = match ih >= 0
+                                && iw >= 0
+                                && (ih as usize) < height
+                                && (iw as usize) < width
+                            {
+                                true => in_tensor.data[plane + ih as usize * width + iw as usize],
+                                false => 0f32,
+                            };
+                        }
+                    }
+                }
+                // [group_out_channels, cols_rows] x [cols_rows, cols_len]
+                for oc in g * group_out_channels..(g + 1) * group_out_channels {
+                    let out_offset = (n * out_channels + oc) * cols_len;
+                    for row in 0..cols_rows {
+                        let weight = weight_tensor.data[oc * cols_rows + row];
+                        for col in 0..cols_len {
+                            out_data[out_offset + col] += weight * cols[row * cols_len + col];
+                        }
+                    }
+                }
+            }
+        }
+        TensorView::<f32>::new(out_data, ElementType::F32, out_shape)
+    }
+
+    // padded positions are ignored by max pooling and count as zeros in average pooling
+    pub fn pool2d_compute(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<f32>,
+        attr: &Pool2dAttr,
+    ) -> TensorView<f32> {
+        let out_shape = pool2d_shape(&in_tensor.shape, attr).expect(&format!(
+            "cannot pool2d shape {:?} with {:?}",
+            in_tensor.shape, attr
+        ));
+        let (height, width) = (in_tensor.shape[2], in_tensor.shape[3]);
+        let (out_h, out_w) = (out_shape[2], out_shape[3]);
+        let planes = out_shape[0] * out_shape[1];
+        let mut out_data = Vec::<f32>::with_capacity(out_shape.iter().product());
+        for p in 0..planes {
+            for oi in 0..out_h {
+                for oj in 0..out_w {
+                    let window = (0..attr.kernel[0] * attr.kernel[1]).filter_map(|k| {
+                        let ih = (oi * attr.stride[0] + k / attr.kernel[1]) as isize
+                            - attr.padding[0] as isize;
+                        let iw = (oj * attr.stride[1] + k % attr.kernel[1]) as isize
+                            - attr.padding[1] as isize;
+                        match ih >= 0 && iw >= 0 && (ih as usize) < height && (iw as usize) < width
+                        {
+                            true => Some(
+                                in_tensor.data[(p * height + ih as usize) * width + iw as usize],
+                            ),
+                            false => None,
+                        }
+                    });
+                    out_data.push(match opcode {
+                        CRTOpCode::MAXPOOL2DF32 => window.fold(f32::NEG_INFINITY, f32::max),
+                        CRTOpCode::AVGPOOL2DF32 => {
+                            window.sum::<f32>() / (attr.kernel[0] * attr.kernel[1]) as f32
+                        }
+                        _ => panic!("not support this op for host pool2d compute"),
+                    });
+                }
+            }
+        }
+        TensorView::<f32>::new(out_data, ElementType::F32, out_shape)
+    }
+
+    // data movement of shape ops, dispatch by dtype of the operand
+    pub fn transpose(&self, in_tensor: &ActTensorTypes, perm: &[usize]) -> ActTensorTypes {
+        map_tensor!(in_tensor, data => self.transpose_compute(data, perm))
+    }
+
+    pub fn slice(
+        &self,
+        in_tensor: &ActTensorTypes,
+        begins: &[usize],
+        ends: &[usize],
+    ) -> ActTensorTypes {
+        map_tensor!(in_tensor, data => self.slice_compute(data, begins, ends))
+    }
+
+    // split the tensor into parts of equal size along the axis
+    pub fn split(
+        &self,
+        in_tensor: &ActTensorTypes,
+        axis: usize,
+        parts: usize,
+    ) -> Vec<ActTensorTypes> {
+        let shape = in_tensor.shape();
+        let size = shape[axis] / parts;
+        (0..parts)
+            .map(|part| {
+                let mut begins = vec![0; shape.len()];
+                let mut ends = shape.clone();
+                begins[axis] = part * size;
+                ends[axis] = (part + 1) * size;
+                self.slice(in_tensor, &begins, &ends)
+            })
+            .collect()
+    }
+
+    pub fn concat(
+        &self,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+        axis: usize,
+    ) -> ActTensorTypes {
+        zip_tensors!(lhs_tensor, rhs_tensor, (lhs_data, rhs_data) => {
+            self.concat_compute(lhs_data, rhs_data, axis)
+        })
+    }
+
+    // permute dims of the tensor, reverse all dims if perm is empty
+    pub fn transpose_compute<T: Copy>(
+        &self,
+        in_tensor: &TensorView<T>,
+        perm: &[usize],
+    ) -> TensorView<T> {
+        let rank = in_tensor.shape.len();
+        let perm: Vec<usize> = match perm.is_empty() {
+            true => (0..rank).rev().collect(),
+            false => perm.to_vec(),
+        };
+        let mut sorted_perm = perm.clone();
+        sorted_perm.sort();
+        assert!(
+            sorted_perm == (0..rank).collect::<Vec<usize>>(),
+            "{:?} is not a permutation of shape {:?}",
+            perm,
+            in_tensor.shape
+        );
+        let out_shape: Vec<usize> = perm.iter().map(|d| in_tensor.shape[*d]).collect();
+        let in_strides = contiguous_strides(&in_tensor.shape);
+        let out_data = (0..in_tensor.data.len())
+            .map(|index| {
+                let mut rem = index;
+                let mut in_index = 0;
+                for d in (0..rank).rev() {
+                    in_index += (rem % out_shape[d]) * in_strides[perm[d]];
+                    rem /= out_shape[d];
+                }
+                in_tensor.data[in_index]
+            })
+            .collect();
+        TensorView::<T>::new(out_data, in_tensor.dtype, out_shape)
+    }
+
+    // take [begins[d], ends[d]) of each dim d, ends are clamped into the dim
+    pub fn slice_compute<T: Copy>(
+        &self,
+        in_tensor: &TensorView<T>,
+        begins: &[usize],
+        ends: &[usize],
+    ) -> TensorView<T> {
+        let rank = in_tensor.shape.len();
+        assert!(
+            begins.len() == rank && ends.len() == rank,
+            "slice bounds {:?}, {:?} mismatch rank of shape {:?}",
+            begins,
+            ends,
+            in_tensor.shape
+        );
+        let ends: Vec<usize> = ends
+            .iter()
+            .zip(in_tensor.shape.iter())
+            .map(|(end, dim)| (*end).min(*dim))
+            .collect();
+        let out_shape: Vec<usize> = begins
+            .iter()
+            .zip(ends.iter())
+            .map(|(begin, end)| end.saturating_sub(*begin))
+            .collect();
+        let in_strides = contiguous_strides(&in_tensor.shape);
+        let out_data = (0..out_shape.iter().product())
+            .map(|index| {
+                let mut rem = index;
+                let mut in_index = 0;
+                for d in (0..rank).rev() {
+                    in_index += (rem % out_shape[d] + begins[d]) * in_strides[d];
+                    rem /= out_shape[d];
+                }
+                in_tensor.data[in_index]
+            })
+            .collect();
+        TensorView::<T>::new(out_data, in_tensor.dtype, out_shape)
+    }
+
+    // join two tensors along the axis, other dims must be equal
+    pub fn concat_compute<T: Copy>(
+        &self,
+        lhs_tensor: &TensorView<T>,
+        rhs_tensor: &TensorView<T>,
+        axis: usize,
+    ) -> TensorView<T> {
+        let lhs_shape = &lhs_tensor.shape;
+        let rhs_shape = &rhs_tensor.shape;
+        assert!(
+            axis < lhs_shape.len()
+                && lhs_shape.len() == rhs_shape.len()
+                && (0..lhs_shape.len()).all(|d| d == axis || lhs_shape[d] == rhs_shape[d]),
+            "cannot concat shapes {:?} and {:?} on axis {}",
+            lhs_shape,
+            rhs_shape,
+            axis
+        );
+        let (outer, lhs_len, inner) = lhs_tensor.reduce_view(Some(axis));
+        let (_, rhs_len, _) = rhs_tensor.reduce_view(Some(axis));
+        let mut out_data = Vec::<T>::with_capacity(lhs_tensor.data.len() + rhs_tensor.data.len());
+        for o in 0..outer {
+            out_data.extend_from_slice(
+                &lhs_tensor.data[o * lhs_len * inner..(o + 1) * lhs_len * inner],
+            );
+            out_data.extend_from_slice(
+                &rhs_tensor.data[o * rhs_len * inner..(o + 1) * rhs_len * inner],
+            );
+        }
+        let mut out_shape = lhs_shape.clone();
+        out_shape[axis] += rhs_shape[axis];
+        TensorView::<T>::new(out_data, lhs_tensor.dtype, out_shape)
+    }
+
+    pub fn unary_compute_f32(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<f32>,
+    ) -> TensorView<f32> {
+        let kernel: fn(f32) -> f32 = match opcode {
+            CRTOpCode::EXPF32 => f32::exp,
+            CRTOpCode::ABSF32 => f32::abs,
+            CRTOpCode::NEGF32 => |x| -x,
+            CRTOpCode::CEILF32 => f32::ceil,
+            CRTOpCode::FLOORF32 => f32::floor,
+            CRTOpCode::ATANF32 => f32::atan,
+            CRTOpCode::COSF32 => f32::cos,
+            CRTOpCode::SINF32 => f32::sin,
+            CRTOpCode::EXP2F32 => f32::exp2,
+            CRTOpCode::EXPM1F32 => f32::exp_m1,
+            CRTOpCode::LOGF32 => f32::ln,
+            CRTOpCode::LOG10F32 => f32::log10,
+            CRTOpCode::LOG1PF32 => f32::ln_1p,
+            CRTOpCode::LOG2F32 => f32::log2,
+            CRTOpCode::RSQRTF32 => |x| 1f32 / x.sqrt(),
+            CRTOpCode::SQRTF32 => f32::sqrt,
+            CRTOpCode::TANHF32 => f32::tanh,
+            _ => panic!("not support this op for host unary compute"),
+        };
+        let out_data = in_tensor.data.iter().map(|x| kernel(*x)).collect();
+        TensorView::<f32>::new(out_data, ElementType::F32, in_tensor.shape.clone())
+    }
+
+    pub fn unary_compute_i32(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<i32>,
+    ) -> TensorView<i32> {
+        let kernel: fn(i32) -> i32 = match opcode {
+            CRTOpCode::NOTI32 => |x| !x,
+            _ => panic!("not support this op for host unary compute"),
+        };
+        let out_data = in_tensor.data.iter().map(|x| kernel(*x)).collect();
+        TensorView::<i32>::new(out_data, ElementType::I32, in_tensor.shape.clone())
+    }
+
+    pub fn binary_compute_f32(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &TensorView<f32>,
+        rhs_tensor: &TensorView<f32>,
+    ) -> TensorView<f32> {
+        let kernel: fn(f32, f32) -> f32 = match opcode {
+            CRTOpCode::ADDF32 => |l, r| l + r,
+            CRTOpCode::SUBF32 => |l, r| l - r,
+            CRTOpCode::MULF32 => |l, r| l * r,
+            CRTOpCode::DIVF32 => |l, r| l / r,
+            CRTOpCode::POWF32 => f32::powf,
+            CRTOpCode::ATAN2F32 => f32::atan2,
+            CRTOpCode::MAXF32 => f32::max,
+            CRTOpCode::MINF32 => f32::min,
+            // truncated remainder, takes the sign of lhs
+            CRTOpCode::REMF32 => |l, r| l % r,
+            _ => panic!("not support this op for host binary compute"),
+        };
+        let (out_data, out_shape) = broadcast_compute(lhs_tensor, rhs_tensor, kernel);
+        TensorView::<f32>::new(out_data, ElementType::F32, out_shape)
+    }
+
+    pub fn binary_compute_i32(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &TensorView<i32>,
+        rhs_tensor: &TensorView<i32>,
+    ) -> TensorView<i32> {
+        let kernel: fn(i32, i32) -> i32 = match opcode {
+            CRTOpCode::ADDI32 => |l, r| l.wrapping_add(r),
+            CRTOpCode::SUBI32 => |l, r| l.wrapping_sub(r),
+            CRTOpCode::MULI32 => |l, r| l.wrapping_mul(r),
+            CRTOpCode::FLOORDIVI32 => |l, r| floor_div(l as i64, r as i64) as i32,
+            CRTOpCode::ANDI32 => |l, r| l & r,
+            CRTOpCode::ORI32 => |l, r| l | r,
+            CRTOpCode::XORI32 => |l, r| l ^ r,
+            // shift amount is masked into [0, 32), same as the vulkan kernels
+            CRTOpCode::SHLI32 => |l, r| l.wrapping_shl(r as u32),
+            CRTOpCode::SHRI32 => |l, r| l.wrapping_shr(r as u32),
+            CRTOpCode::MAXI32 => i32::max,
+            CRTOpCode::MINI32 => i32::min,
+            // truncated remainder, takes the sign of lhs, 0 on a zero divisor
+            CRTOpCode::REMI32 => |l, r| match r {
+                0 => 0,
+                _ => l.wrapping_rem(r),
+            },
+            _ => panic!("not support this op for host binary compute"),
+        };
+        let (out_data, out_shape) = broadcast_compute(lhs_tensor, rhs_tensor, kernel);
+        TensorView::<i32>::new(out_data, ElementType::I32, out_shape)
+    }
+
+    pub fn unary_compute_f64(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<f64>,
+    ) -> TensorView<f64> {
+        let kernel: fn(f64) -> f64 = match opcode {
+            CRTOpCode::EXPF32 => f64::exp,
+            CRTOpCode::ABSF32 => f64::abs,
+            CRTOpCode::NEGF32 => |x| -x,
+            CRTOpCode::CEILF32 => f64::ceil,
+            CRTOpCode::FLOORF32 => f64::floor,
+            CRTOpCode::ATANF32 => f64::atan,
+            CRTOpCode::COSF32 => f64::cos,
+            CRTOpCode::SINF32 => f64::sin,
+            CRTOpCode::EXP2F32 => f64::exp2,
+            CRTOpCode::EXPM1F32 => f64::exp_m1,
+            CRTOpCode::LOGF32 => f64::ln,
+            CRTOpCode::LOG10F32 => f64::log10,
+            CRTOpCode::LOG1PF32 => f64::ln_1p,
+            CRTOpCode::LOG2F32 => f64::log2,
+            CRTOpCode::RSQRTF32 => |x| 1f64 / x.sqrt(),
+            CRTOpCode::SQRTF32 => f64::sqrt,
+            CRTOpCode::TANHF32 => f64::tanh,
+            _ => panic!("not support this op for host unary compute"),
+        };
+        let out_data = in_tensor.data.iter().map(|x| kernel(*x)).collect();
+        TensorView::<f64>::new(out_data, ElementType::F64, in_tensor.shape.clone())
+    }
+
+    pub fn unary_compute_i64(
+        &self,
+        opcode: CRTOpCode,
+        in_tensor: &TensorView<i64>,
+    ) -> TensorView<i64> {
+        let kernel: fn(i64) -> i64 = match opcode {
+            CRTOpCode::NOTI32 => |x| !x,
+            _ => panic!("not support this op for host unary compute"),
+        };
+        let out_data = in_tensor.data.iter().map(|x| kernel(*x)).collect();
+        TensorView::<i64>::new(out_data, ElementType::I64, in_tensor.shape.clone())
+    }
+
+    pub fn binary_compute_f64(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &TensorView<f64>,
+        rhs_tensor: &TensorView<f64>,
+    ) -> TensorView<f64> {
+        let kernel: fn(f64, f64) -> f64 = match opcode {
+            CRTOpCode::ADDF32 => |l, r| l + r,
+            CRTOpCode::SUBF32 => |l, r| l - r,
+            CRTOpCode::MULF32 => |l, r| l * r,
+            CRTOpCode::DIVF32 => |l, r| l / r,
+            CRTOpCode::POWF32 => f64::powf,
+            CRTOpCode::ATAN2F32 => f64::atan2,
+            CRTOpCode::MAXF32 => f64::max,
+            CRTOpCode::MINF32 => f64::min,
+            CRTOpCode::REMF32 => |l, r| l % r,
+            _ => panic!("not support this op for host binary compute"),
+        };
+        let (out_data, out_shape) = broadcast_compute(lhs_tensor, rhs_tensor, kernel);
+        TensorView::<f64>::new(out_data, ElementType::F64, out_shape)
+    }
+
+    pub fn binary_compute_i64(
+        &self,
+        opcode: CRTOpCode,
+        lhs_tensor: &TensorView<i64>,
+        rhs_tensor: &TensorView<i64>,
+    ) -> TensorView<i64> {
+        let kernel: fn(i64, i64) -> i64 = match opcode {
+            CRTOpCode::ADDI32 => |l, r| l.wrapping_add(r),
+            CRTOpCode::SUBI32 => |l, r| l.wrapping_sub(r),
+            CRTOpCode::MULI32 => |l, r| l.wrapping_mul(r),
+            CRTOpCode::FLOORDIVI32 => floor_div,
+            CRTOpCode::ANDI32 => |l, r| l & r,
+            CRTOpCode::ORI32 => |l, r| l | r,
+            CRTOpCode::XORI32 => |l, r| l ^ r,
+            // shift amount is masked into [0, 64)
+            CRTOpCode::SHLI32 => |l, r| l.wrapping_shl(r as u32),
+            CRTOpCode::SHRI32 => |l, r| l.wrapping_shr(r as u32),
+            CRTOpCode::MAXI32 => i64::max,
+            CRTOpCode::MINI32 => i64::min,
+            CRTOpCode::REMI32 => |l, r| match r {
+                0 => 0,
+                _ => l.wrapping_rem(r),
+            },
+            _ => panic!("not support this op for host binary compute"),
+        };
+        let (out_data, out_shape) = broadcast_compute(lhs_tensor, rhs_tensor, kernel);
+        TensorView::<i64>::new(out_data, ElementType::I64, out_shape)
+    }
+}
+
+// rounds towards negative infinity, a zero divisor gives 0, same as the vulkan kernels
+fn floor_div(lhs: i64, rhs: i64) -> i64 {
+    if rhs == 0 {
+        return 0;
+    }
+    let quotient = lhs.wrapping_div(rhs);
+    match quotient.wrapping_mul(rhs) != lhs && (lhs < 0) != (rhs < 0) {
+        true => quotient - 1,
+        false => quotient,
+    }
+}
+
+// apply the elementwise kernel on the broadcasted shape of lhs and rhs
+fn broadcast_compute<T: Copy, U>(
+    lhs_tensor: &TensorView<T>,
+    rhs_tensor: &TensorView<T>,
+    kernel: fn(T, T) -> U,
+) -> (Vec<U>, Vec<usize>) {
+    let out_shape = broadcast_shape(&lhs_tensor.shape, &rhs_tensor.shape).expect(&format!(
+        "cannot broadcast shapes {:?} and {:?}",
+        lhs_tensor.shape, rhs_tensor.shape
+    ));
+    let lhs_strides = broadcast_strides(&lhs_tensor.shape, &out_shape);
+    let rhs_strides = broadcast_strides(&rhs_tensor.shape, &out_shape);
+    let out_size: usize = out_shape.iter().product();
+    let mut out_data = Vec::<U>::with_capacity(out_size);
+    for index in 0..out_size {
+        let mut rem = index;
+        let mut lhs_index = 0;
+        let mut rhs_index = 0;
+        for d in (0..out_shape.len()).rev() {
+            let coord = rem % out_shape[d];
+            rem /= out_shape[d];
+            lhs_index += coord * lhs_strides[d];
+            rhs_index += coord * rhs_strides[d];
+        }
+        out_data.push(kernel(
+            lhs_tensor.data[lhs_index],
+            rhs_tensor.data[rhs_index],
+        ));
+    }
+    (out_data, out_shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fusion::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_host_unary_compute_f32() {
+        let functor = HostFunctor::new();
+        let in_tensor = TensorView::<f32>::new(vec![-4.0, 1.0, 2.25], ElementType::F32, vec![3]);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::ABSF32, &in_tensor);
+        assert_eq!(outs.data, vec![4.0, 1.0, 2.25]);
+        assert_eq!(outs.shape, vec![3]);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::NEGF32, &in_tensor);
+        assert_eq!(outs.data, vec![4.0, -1.0, -2.25]);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::FLOORF32, &in_tensor);
+        assert_eq!(outs.data, vec![-4.0, 1.0, 2.0]);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::CEILF32, &in_tensor);
+        assert_eq!(outs.data, vec![-4.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn test_host_bitwise_compute_i32() {
+        let functor = HostFunctor::new();
+        let lhs = TensorView::<i32>::new(vec![12, -8, 1], ElementType::I32, vec![3]);
+        let rhs = TensorView::<i32>::new(vec![10, 1, 33], ElementType::I32, vec![3]);
+
+        let outs = functor.unary_compute_i32(CRTOpCode::NOTI32, &lhs);
+        assert_eq!(outs.data, vec![-13, 7, -2]);
+
+        let outs = functor.binary_compute_i32(CRTOpCode::ANDI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![8, 0, 1]);
+
+        let outs = functor.binary_compute_i32(CRTOpCode::ORI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![14, -7, 33]);
+
+        let outs = functor.binary_compute_i32(CRTOpCode::XORI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![6, -7, 32]);
+
+        let outs = functor.binary_compute_i32(CRTOpCode::SHLI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![12288, -16, 2]);
+
+        let outs = functor.binary_compute_i32(CRTOpCode::SHRI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![0, -4, 0]);
+    }
+
+    #[test]
+    fn test_host_unary_compute_f32_math() {
+        let functor = HostFunctor::new();
+        let in_tensor = TensorView::<f32>::new(vec![1.0, 4.0], ElementType::F32, vec![2]);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::SQRTF32, &in_tensor);
+        assert_float_eq!(outs.data, vec![1.0, 2.0], rmax_all <= 0.00001);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::RSQRTF32, &in_tensor);
+        assert_float_eq!(outs.data, vec![1.0, 0.5], rmax_all <= 0.00001);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::LOG2F32, &in_tensor);
+        assert_float_eq!(outs.data, vec![0.0, 2.0], abs_all <= 0.00001);
+
+        let outs = functor.unary_compute_f32(CRTOpCode::TANHF32, &in_tensor);
+        assert_float_eq!(outs.data, vec![0.7615942, 0.9993293], rmax_all <= 0.00001);
+    }
+
+    #[test]
+    fn test_host_cast_compute() {
+        let functor = HostFunctor::new();
+        let in_tensor = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![-1.7, 2.5, 1.0], ElementType::F32, vec![3, 1]),
+        };
+
+        let outs = functor.unary_compute(CRTOpCode::CASTI32, &in_tensor);
+        match outs {
+            ActTensorTypes::I32Tensor { data } => {
+                assert_eq!(data.data, vec![-1, 2, 1]);
+                assert_eq!(data.dtype, ElementType::I32);
+                assert_eq!(data.shape, vec![3, 1]);
+            }
+            _ => panic!("expect i32 tensor"),
+        }
+
+        let outs = functor.unary_compute(CRTOpCode::BITCASTI32, &in_tensor);
+        match outs {
+            ActTensorTypes::I32Tensor { data } => {
+                assert_eq!(data.data[2], 0x3f800000);
+            }
+            _ => panic!("expect i32 tensor"),
+        }
+
+        let in_tensor = ActTensorTypes::I32Tensor {
+            data: TensorView::<i32>::new(vec![-3, 0x40000000], ElementType::I32, vec![2]),
+        };
+        let outs = functor.unary_compute(CRTOpCode::CASTF32, &in_tensor);
+        match outs {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_eq!(data.data, vec![-3.0, 1073741824.0]);
+                assert_eq!(data.dtype, ElementType::F32);
+            }
+            _ => panic!("expect f32 tensor"),
+        }
+
+        let outs = functor.unary_compute(CRTOpCode::BITCASTF32, &in_tensor);
+        match outs {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_eq!(data.data[1], 2.0);
+            }
+            _ => panic!("expect f32 tensor"),
+        }
+    }
+
+    #[test]
+    fn test_host_reduce_compute_f32() {
+        let functor = HostFunctor::new();
+        let in_tensor = TensorView::<f32>::new(
+            vec![1.0, 5.0, 3.0, 4.0, 2.0, 6.0],
+            ElementType::F32,
+            vec![2, 3],
+        );
+
+        let outs = functor.reduce_compute_f32(CRTOpCode::REDUCESUMF32, &in_tensor, Some(1), false);
+        assert_eq!(outs.data, vec![9.0, 12.0]);
+        assert_eq!(outs.shape, vec![2]);
+
+        let outs = functor.reduce_compute_f32(CRTOpCode::REDUCEMEANF32, &in_tensor, Some(0), true);
+        assert_eq!(outs.data, vec![2.5, 3.5, 4.5]);
+        assert_eq!(outs.shape, vec![1, 3]);
+
+        let outs = functor.reduce_compute_f32(CRTOpCode::REDUCEMAXF32, &in_tensor, None, false);
+        assert_eq!(outs.data, vec![6.0]);
+        assert_eq!(outs.shape, Vec::<usize>::new());
+
+        let outs = functor.reduce_compute_f32(CRTOpCode::REDUCEMINF32, &in_tensor, Some(0), false);
+        assert_eq!(outs.data, vec![1.0, 2.0, 3.0]);
+
+        let outs = functor.reduce_arg_compute_f32(CRTOpCode::ARGMAXF32, &in_tensor, Some(1), false);
+        assert_eq!(outs.data, vec![1, 2]);
+        assert_eq!(outs.dtype, ElementType::I32);
+
+        let outs = functor.reduce_arg_compute_f32(CRTOpCode::ARGMINF32, &in_tensor, None, true);
+        assert_eq!(outs.data, vec![0]);
+        assert_eq!(outs.shape, vec![1, 1]);
+    }
+
+    #[test]
+    fn test_host_binary_compute_broadcast() {
+        let functor = HostFunctor::new();
+        let lhs = TensorView::<f32>::new(
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            ElementType::F32,
+            vec![2, 3],
+        );
+        let bias = TensorView::<f32>::new(vec![10.0, 20.0, 30.0], ElementType::F32, vec![3]);
+        let outs = functor.binary_compute_f32(CRTOpCode::ADDF32, &lhs, &bias);
+        assert_eq!(outs.data, vec![11.0, 22.0, 33.0, 14.0, 25.0, 36.0]);
+        assert_eq!(outs.shape, vec![2, 3]);
+
+        let col = TensorView::<f32>::new(vec![2.0, 4.0], ElementType::F32, vec![2, 1]);
+        let outs = functor.binary_compute_f32(CRTOpCode::MULF32, &col, &bias);
+        assert_eq!(outs.data, vec![20.0, 40.0, 60.0, 40.0, 80.0, 120.0]);
+        assert_eq!(outs.shape, vec![2, 3]);
+
+        let lhs = TensorView::<i32>::new(vec![-7, 7], ElementType::I32, vec![2]);
+        let rhs = TensorView::<i32>::new(vec![2], ElementType::I32, vec![]);
+        let outs = functor.binary_compute_i32(CRTOpCode::FLOORDIVI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![-4, 3]);
+
+        // zero divisors give 0 instead of panicking
+        let zero = TensorView::<i32>::new(vec![0], ElementType::I32, vec![]);
+        let outs = functor.binary_compute_i32(CRTOpCode::FLOORDIVI32, &lhs, &zero);
+        assert_eq!(outs.data, vec![0, 0]);
+        let outs = functor.binary_compute_i32(CRTOpCode::REMI32, &lhs, &zero);
+        assert_eq!(outs.data, vec![0, 0]);
+        let lhs = TensorView::<i64>::new(vec![-7, 7], ElementType::I64, vec![2]);
+        let rhs = TensorView::<i64>::new(vec![-2, 0], ElementType::I64, vec![2]);
+        let outs = functor.binary_compute_i64(CRTOpCode::FLOORDIVI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![3, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot broadcast shapes")]
+    fn test_host_binary_compute_shape_mismatch() {
+        let functor = HostFunctor::new();
+        let lhs = TensorView::<f32>::new(vec![0.0; 6], ElementType::F32, vec![2, 3]);
+        let rhs = TensorView::<f32>::new(vec![0.0; 2], ElementType::F32, vec![2]);
+        functor.binary_compute_f32(CRTOpCode::ADDF32, &lhs, &rhs);
+    }
+
+    #[test]
+    fn test_host_compare_and_select() {
+        let functor = HostFunctor::new();
+        let lhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![-1.0, 0.0, 2.0, 3.0], ElementType::F32, vec![2, 2]),
+        };
+        let zero = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0.0], ElementType::F32, vec![]),
+        };
+        let mask = functor.binary_compute(CRTOpCode::CMPGTF32, &lhs, &zero);
+        match mask {
+            ActTensorTypes::I32Tensor { ref data } => {
+                assert_eq!(data.data, vec![0, 0, 1, 1]);
+                assert_eq!(data.dtype, ElementType::BOOL);
+                assert_eq!(data.shape, vec![2, 2]);
+            }
+            _ => panic!("comparison must produce a mask"),
+        }
+
+        // relu as select(x > 0, x, 0)
+        let (mask, values) = functor.pack_ternary_operands(&mask, &lhs, &zero, &[2, 2]);
+        match values {
+            ActTensorTypes::F32Tensor { ref data } => assert_eq!(data.shape, vec![2, 2, 2]),
+            _ => panic!("select values must keep the dtype"),
+        }
+        match functor.binary_compute(CRTOpCode::SELECT, &mask, &values) {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_eq!(data.data, vec![0.0, 0.0, 2.0, 3.0]);
+                assert_eq!(data.shape, vec![2, 2]);
+            }
+            _ => panic!("select must keep the dtype of values"),
+        }
+
+        let lhs = TensorView::<i32>::new(vec![1, 2, 3], ElementType::I32, vec![3]);
+        let rhs = TensorView::<i32>::new(vec![2], ElementType::I32, vec![1]);
+        let outs = functor.compare_compute(CRTOpCode::CMPLEI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![1, 1, 0]);
+        let outs = functor.compare_compute(CRTOpCode::CMPNEI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_host_binary_math_compute() {
+        let functor = HostFunctor::new();
+        let lhs = TensorView::<f32>::new(vec![2.0, -7.5, 1.0], ElementType::F32, vec![3]);
+        let rhs = TensorView::<f32>::new(vec![3.0, 2.0, -1.0], ElementType::F32, vec![3]);
+
+        let outs = functor.binary_compute_f32(CRTOpCode::POWF32, &lhs, &rhs);
+        assert_float_eq!(outs.data, vec![8.0, 56.25, 1.0], rmax_all <= 0.00001);
+        let outs = functor.binary_compute_f32(CRTOpCode::ATAN2F32, &lhs, &rhs);
+        assert_float_eq!(
+            outs.data,
+            vec![
+                2f32.atan2(3.0),
+                (-7.5f32).atan2(2.0),
+                std::f32::consts::PI * 0.75
+            ],
+            rmax_all <= 0.00001
+        );
+        let outs = functor.binary_compute_f32(CRTOpCode::MAXF32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![3.0, 2.0, 1.0]);
+        let outs = functor.binary_compute_f32(CRTOpCode::MINF32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![2.0, -7.5, -1.0]);
+        let outs = functor.binary_compute_f32(CRTOpCode::REMF32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![2.0, -1.5, 0.0]);
+
+        let lhs = TensorView::<i32>::new(vec![7, -7, 3], ElementType::I32, vec![3]);
+        let rhs = TensorView::<i32>::new(vec![3], ElementType::I32, vec![1]);
+        let outs = functor.binary_compute_i32(CRTOpCode::REMI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![1, -1, 0]);
+        let outs = functor.binary_compute_i32(CRTOpCode::MAXI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![7, 3, 3]);
+        let outs = functor.binary_compute_i32(CRTOpCode::MINI32, &lhs, &rhs);
+        assert_eq!(outs.data, vec![3, -7, 3]);
+    }
+
+    #[test]
+    fn test_host_fma_compute() {
+        let functor = HostFunctor::new();
+        let lhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![1.0, 2.0, 3.0, 4.0], ElementType::F32, vec![2, 2]),
+        };
+        let rhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![10.0, 100.0], ElementType::F32, vec![2]),
+        };
+        let addend = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0.5], ElementType::F32, vec![1]),
+        };
+        let (first, packed) = functor.pack_ternary_operands(&lhs, &rhs, &addend, &[2, 2]);
+        match functor.binary_compute(CRTOpCode::FMAF32, &first, &packed) {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_eq!(data.data, vec![10.5, 200.5, 30.5, 400.5]);
+                assert_eq!(data.shape, vec![2, 2]);
+            }
+            _ => panic!("fma must produce f32 tensor"),
+        }
+    }
+
+    #[test]
+    fn test_host_matmul_compute() {
+        let functor = HostFunctor::new();
+        let lhs = TensorView::<f32>::new(
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            ElementType::F32,
+            vec![2, 3],
+        );
+        let rhs = TensorView::<f32>::new(
+            vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0],
+            ElementType::F32,
+            vec![3, 2],
+        );
+        let outs = functor.matmul_compute(&lhs, &rhs, false, false);
+        assert_eq!(outs.shape, vec![2, 2]);
+        assert_eq!(outs.data, vec![58.0, 64.0, 139.0, 154.0]);
+
+        // transpose flags agree with transposing operands explicitly
+        let lhs_t = functor.transpose_compute(&lhs, &[]);
+        let rhs_t = functor.transpose_compute(&rhs, &[]);
+        assert_eq!(functor.matmul_compute(&lhs_t, &rhs, true, false), outs);
+        assert_eq!(functor.matmul_compute(&lhs, &rhs_t, false, true), outs);
+        assert_eq!(functor.matmul_compute(&lhs_t, &rhs_t, true, true), outs);
+
+        // batch dims broadcast, [2, 1, 2, 3] x [3, 3, 2] => [2, 3, 2, 2]
+        let lhs = TensorView::<f32>::new(
+            (0..12).map(|x| x as f32).collect(),
+            ElementType::F32,
+            vec![2, 1, 2, 3],
+        );
+        let rhs = TensorView::<f32>::new(
+            (0..18).map(|x| (x % 5) as f32).collect(),
+            ElementType::F32,
+            vec![3, 3, 2],
+        );
+        let outs = functor.matmul_compute(&lhs, &rhs, false, false);
+        assert_eq!(outs.shape, vec![2, 3, 2, 2]);
+        for b0 in 0..2 {
+            for b1 in 0..3 {
+                let lhs_batch = TensorView::<f32>::new(
+                    lhs.data[b0 * 6..b0 * 6 + 6].to_vec(),
+                    ElementType::F32,
+                    vec![2, 3],
+                );
+                let rhs_batch = TensorView::<f32>::new(
+                    rhs.data[b1 * 6..b1 * 6 + 6].to_vec(),
+                    ElementType::F32,
+                    vec![3, 2],
+                );
+                let expected = functor.matmul_compute(&lhs_batch, &rhs_batch, false, false);
+                let offset = (b0 * 3 + b1) * 4;
+                assert_eq!(outs.data[offset..offset + 4], expected.data[..]);
+            }
+        }
+    }
+
+    // direct loops over the definition of conv2d, as reference of the im2col implementation
+    fn naive_conv2d(
+        input: &TensorView<f32>,
+        weight: &TensorView<f32>,
+        attr: &Conv2dAttr,
+        out_shape: &[usize],
+    ) -> Vec<f32> {
+        let (c, h, w) = (input.shape[1], input.shape[2], input.shape[3]);
+        let (o, cg, kh, kw) = (
+            weight.shape[0],
+            weight.shape[1],
+            weight.shape[2],
+            weight.shape[3],
+        );
+        let og = o / attr.groups;
+        let mut outs = vec![];
+        for n in 0..out_shape[0] {
+            for oc in 0..out_shape[1] {
+                for oi in 0..out_shape[2] {
+                    for oj in 0..out_shape[3] {
+                        let mut acc = 0f32;
+                        for ic in 0..cg {
+                            for ki in 0..kh {
+                                for kj in 0..kw {
+                                    let ih = (oi * attr.stride[0] + ki * attr.dilation[0]) as i64
+                                        - attr.padding[0] as i64;
+                                    let iw = (oj * attr.stride[1] + kj * attr.dilation[1]) as i64
+                                        - attr.padding[1] as i64;
+                                    if ih < 0 || iw < 0 || ih >= h as i64 || iw >= w as i64 {
+                                        continue;
+                                    }
+                                    let channel = oc / og * cg + ic;
+                                    acc += input.data
+                                        [((n * c + channel) * h + ih as usize) * w + iw as usize]
+                                        * weight.data[((oc * cg + ic) * kh + ki) * kw + kj];
+                                }
+                            }
+                        }
+                        outs.push(acc);
+                    }
+                }
+            }
+        }
+        outs
+    }
+
+    #[test]
+    fn test_host_conv2d_compute() {
+        let functor = HostFunctor::new();
+        let input = TensorView::<f32>::new(
+            (0..2 * 4 * 7 * 6)
+                .map(|x| (x * 7 % 11) as f32 - 5.0)
+                .collect(),
+            ElementType::F32,
+            vec![2, 4, 7, 6],
+        );
+        let weight = TensorView::<f32>::new(
+            (0..6 * 2 * 3 * 2)
+                .map(|x| (x * 5 % 9) as f32 * 0.25 - 1.0)
+                .collect(),
+            ElementType::F32,
+            vec![6, 2, 3, 2],
+        );
+        for attr in [
+            Conv2dAttr::from_vec(&[1, 1, 0, 0, 1, 1, 2]),
+            Conv2dAttr::from_vec(&[2, 1, 1, 2, 1, 1, 2]),
+            Conv2dAttr::from_vec(&[1, 2, 2, 1, 2, 2, 2]),
+        ] {
+            let outs = functor.conv2d_compute(&input, &weight, &attr);
+            let expected = conv2d_shape(&input.shape, &weight.shape, &attr).unwrap();
+            assert_eq!(outs.shape, expected);
+            assert_float_eq!(
+                outs.data,
+                naive_conv2d(&input, &weight, &attr, &expected),
+                rmax_all <= 0.00001
+            );
+        }
+
+        // 1x1 input channel, 3x3 box filter with padding
+        let input = TensorView::<f32>::new(vec![1.0; 9], ElementType::F32, vec![1, 1, 3, 3]);
+        let weight = TensorView::<f32>::new(vec![1.0; 9], ElementType::F32, vec![1, 1, 3, 3]);
+        let outs = functor.conv2d_compute(
+            &input,
+            &weight,
+            &Conv2dAttr::from_vec(&[1, 1, 1, 1, 1, 1, 1]),
+        );
+        assert_eq!(outs.data, vec![4.0, 6.0, 4.0, 6.0, 9.0, 6.0, 4.0, 6.0, 4.0]);
+    }
+
+    #[test]
+    fn test_host_pool2d_compute() {
+        let functor = HostFunctor::new();
+        let input = TensorView::<f32>::new(
+            (0..16).map(|x| x as f32).collect(),
+            ElementType::F32,
+            vec![1, 1, 4, 4],
+        );
+        let attr = Pool2dAttr::from_vec(&[2, 2, 2, 2, 0, 0]);
+        let outs = functor.pool2d_compute(CRTOpCode::MAXPOOL2DF32, &input, &attr);
+        assert_eq!(outs.shape, vec![1, 1, 2, 2]);
+        assert_eq!(outs.data, vec![5.0, 7.0, 13.0, 15.0]);
+        let outs = functor.pool2d_compute(CRTOpCode::AVGPOOL2DF32, &input, &attr);
+        assert_eq!(outs.data, vec![2.5, 4.5, 10.5, 12.5]);
+
+        // padded positions count as zeros in average pooling
+        let attr = Pool2dAttr::from_vec(&[3, 3, 3, 3, 1, 1]);
+        let outs = functor.pool2d_compute(CRTOpCode::AVGPOOL2DF32, &input, &attr);
+        assert_eq!(outs.shape, vec![1, 1, 2, 2]);
+        assert_float_eq!(
+            outs.data,
+            vec![10.0 / 9.0, 18.0 / 9.0, 42.0 / 9.0, 50.0 / 9.0],
+            rmax_all <= 0.00001
+        );
+        let outs = functor.pool2d_compute(CRTOpCode::MAXPOOL2DF32, &input, &attr);
+        assert_eq!(outs.data, vec![5.0, 7.0, 13.0, 15.0]);
+    }
+
+    #[test]
+    fn test_host_shape_compute() {
+        let functor = HostFunctor::new();
+        let in_tensor =
+            TensorView::<i32>::new(vec![0, 1, 2, 3, 4, 5], ElementType::I32, vec![2, 3]);
+
+        let outs = functor.transpose_compute(&in_tensor, &[1, 0]);
+        assert_eq!(outs.data, vec![0, 3, 1, 4, 2, 5]);
+        assert_eq!(outs.shape, vec![3, 2]);
+
+        // reverse all dims if perm omitted
+        let outs = functor.transpose_compute(&in_tensor, &[]);
+        assert_eq!(outs.data, vec![0, 3, 1, 4, 2, 5]);
+
+        let outs = functor.slice_compute(&in_tensor, &[0, 1], &[2, 3]);
+        assert_eq!(outs.data, vec![1, 2, 4, 5]);
+        assert_eq!(outs.shape, vec![2, 2]);
+
+        let outs = functor.concat_compute(&in_tensor, &in_tensor, 1);
+        assert_eq!(outs.data, vec![0, 1, 2, 0, 1, 2, 3, 4, 5, 3, 4, 5]);
+        assert_eq!(outs.shape, vec![2, 6]);
+
+        let outs = functor.concat_compute(&in_tensor, &in_tensor, 0);
+        assert_eq!(outs.shape, vec![4, 3]);
+
+        let parts = functor.split(
+            &ActTensorTypes::I32Tensor {
+                data: in_tensor.clone(),
+            },
+            1,
+            3,
+        );
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[1].shape(), vec![2, 1]);
+        match &parts[2] {
+            ActTensorTypes::I32Tensor { data } => assert_eq!(data.data, vec![2, 5]),
+            _ => panic!("expects i32 part"),
+        }
+
+        // shape ops move elements of every dtype
+        let data = vec![0., 1., 2., 3., 4., 5.];
+        let f64_tensor = ActTensorTypes::from_f64_vec(data.clone(), ElementType::F64, vec![2, 3]);
+        let outs = functor.transpose(&f64_tensor, &[]);
+        assert_eq!(outs.element_type(), ElementType::F64);
+        assert_eq!(outs.to_f64_vec(), vec![0., 3., 1., 4., 2., 5.]);
+
+        let u8_tensor = ActTensorTypes::from_f64_vec(data, ElementType::U8, vec![2, 3]);
+        let outs = functor.concat(&u8_tensor, &functor.slice(&u8_tensor, &[0, 0], &[1, 3]), 0);
+        assert_eq!(outs.element_type(), ElementType::U8);
+        assert_eq!(outs.shape(), vec![3, 3]);
+        let outs = functor.broadcast_to(&functor.slice(&u8_tensor, &[0, 2], &[2, 3]), &[2, 2]);
+        assert_eq!(outs.to_f64_vec(), vec![2., 2., 5., 5.]);
+    }
+
+    #[test]
+    fn test_host_fused_compute() {
+        let functor = HostFunctor::new();
+        let lhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0.0, 1.0, 2.0, 3.0], ElementType::F32, vec![2, 2]),
+        };
+        let rhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![-1.0, 1.0], ElementType::F32, vec![2]),
+        };
+        let expr = FusedExpr {
+            steps: vec![
+                FusedStep {
+                    opcode: CRTOpCode::ADDF32,
+                    operands: vec![0, 1],
+                },
+                FusedStep {
+                    opcode: CRTOpCode::ABSF32,
+                    operands: vec![2],
+                },
+                FusedStep {
+                    opcode: CRTOpCode::MULF32,
+                    operands: vec![3, 1],
+                },
+            ],
+        };
+        let outs = functor.binary_compute(
+            CRTOpCode::FUSEDF32,
+            &ActTensorTypes::TupleTensor {
+                data: vec![lhs, rhs],
+            },
+            &ActTensorTypes::from_fused_expr(&expr),
+        );
+        // same as computing the steps by the vm
+        match outs {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_eq!(data.data, vec![-1.0, 2.0, -1.0, 4.0]);
+                assert_eq!(data.shape, vec![2, 2]);
+            }
+            _ => panic!("expects f32 result"),
+        }
+    }
+}
@@ -84,12 +84,20 @@ impl DeviceInstance {
         &self.queue_family
     }
 
+    // 64-bit shader features that the device offers, f64 and i64 tensors run on device with
+    // them and on host without. hal has no f16 or int8 shader features, so f16, bf16, i8 and u8
+    // tensors always run on host
+    pub(crate) fn shader_features(&self) -> hal::Features {
+        self.computable_adapter().physical_device.features()
+            & (hal::Features::SHADER_FLOAT64 | hal::Features::SHADER_INT64)
+    }
+
     // TODO need to make sure this create method only run once
     pub(crate) fn device_and_queue(&self) -> hal::adapter::Gpu<concrete_backend::Backend> {
         let mut device_and_queue = unsafe {
             self.computable_adapter()
                 .physical_device
-                .open(&[(&self.queue_family, &[1.0])], hal::Features::empty())
+                .open(&[(&self.queue_family, &[1.0])], self.shader_features())
                 .unwrap()
         };
         device_and_queue
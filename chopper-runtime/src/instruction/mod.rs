@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use raptors::prelude::*;
 
+use crate::base::ElementType;
+
 #[cfg(any(feature = "mock", feature = "blas"))]
 use rublas::prelude::*;
 
@@ -33,7 +35,150 @@ pub enum CRTOpCode {
 
     // Unary 16
     EXPF32,
-    RETV,
+    RETV, // 17
+
+    // unary elementwise math ops
+    ABSF32,   // 18
+    NEGF32,   // 19
+    CEILF32,  // 20
+    FLOORF32, // 21
+    ATANF32,  // 22
+    COSF32,   // 23
+    SINF32,   // 24
+    EXP2F32,  // 25
+    EXPM1F32, // 26
+    LOGF32,   // 27
+    LOG10F32, // 28
+    LOG1PF32, // 29
+    LOG2F32,  // 30
+    RSQRTF32, // 31
+    SQRTF32,  // 32
+    TANHF32,  // 33
+
+    // bitwise and shift ops on i32
+    NOTI32, // 34
+    ANDI32, // 35
+    ORI32,  // 36
+    XORI32, // 37
+    SHLI32, // 38
+    SHRI32, // 39
+
+    // casting ops, named by the element type of result
+    CASTF32,    // 40
+    CASTI32,    // 41
+    BITCASTF32, // 42
+    BITCASTI32, // 43
+
+    // reduction ops, over one axis or all axes
+    REDUCESUMF32,  // 44
+    REDUCEMEANF32, // 45
+    REDUCEMAXF32,  // 46
+    REDUCEMINF32,  // 47
+    ARGMAXF32,     // 48
+    ARGMINF32,     // 49
+
+    // shape ops, element type agnostic
+    RESHAPE,   // 50
+    TRANSPOSE, // 51
+    SLICE,     // 52
+    CONCAT,    // 53
+    SQUEEZE,   // 54
+    UNSQUEEZE, // 55
+
+    // comparison ops, producing masks of bool
+    CMPEQF32, // 56
+    CMPNEF32, // 57
+    CMPLTF32, // 58
+    CMPLEF32, // 59
+    CMPGTF32, // 60
+    CMPGEF32, // 61
+    CMPEQI32, // 62
+    CMPNEI32, // 63
+    CMPLTI32, // 64
+    CMPLEI32, // 65
+    CMPGTI32, // 66
+    CMPGEI32, // 67
+
+    // picks elementwise from two tensors by mask, element type agnostic
+    SELECT, // 68
+
+    // binary elementwise math ops
+    POWF32,   // 69
+    ATAN2F32, // 70
+    MAXF32,   // 71
+    MINF32,   // 72
+    REMF32,   // 73
+    MAXI32,   // 74
+    MINI32,   // 75
+    REMI32,   // 76
+
+    // ternary elementwise math ops
+    FMAF32, // 77
+
+    // sliding window ops on NCHW tensors
+    CONV2DF32,    // 78
+    MAXPOOL2DF32, // 79
+    AVGPOOL2DF32, // 80
+
+    // matmul with transpose flags on either operand, written as crt.matmul.f32 with
+    // transpose_a/transpose_b attributes
+    MATMULTF32, // 81
+
+    // control flow ops, targets are byte offsets relative to the next instruction
+    JMP,    // 82
+    JMPIF,  // 83, on a scalar tensor
+    JMPIFR, // 84, on an i32 register
+    LOOP,   // 85, counted loop that jumps back until its trip count is done
+
+    // scalar ops on i32 registers, comparisons write 1 or 0
+    ADDR,   // 86
+    SUBR,   // 87
+    MULR,   // 88
+    CMPEQR, // 89
+    CMPNER, // 90
+    CMPLTR, // 91
+    CMPLER, // 92
+    CMPGTR, // 93
+    CMPGER, // 94
+
+    // moves between i32 registers and scalar tensors
+    MOVRT, // 95, register to 0-d i32 tensor
+    MOVTR, // 96, scalar tensor to register
+
+    // splits a tensor into equal parts along an axis, one part per output
+    SPLIT, // 97
+
+    // scalar literals of extended element types
+    CONSTF16,  // 98
+    CONSTBF16, // 99
+    CONSTI8,   // 100
+    CONSTU8,   // 101
+    CONSTF64,  // 102
+    CONSTI64,  // 103
+    CONSTBOOL, // 104
+
+    // value casts into extended element types, computed on host
+    CASTF16,  // 105
+    CASTBF16, // 106
+    CASTI8,   // 107
+    CASTU8,   // 108
+    CASTF64,  // 109
+    CASTI64,  // 110
+    CASTBOOL, // 111
+
+    // generated tensors, sequences of arange and linspace, and identity matrices
+    RANGETENSOR, // 112
+    EYETENSOR,   // 113
+
+    // tensors of zeros or ones in the shape and element type of the operand
+    ZEROSLIKE, // 114
+    ONESLIKE,  // 115
+
+    // tensor of the constant section of a loaded module, by its index in the vm
+    CONSTREF, // 116
+
+    // chain of f32 elementwise ops fused into one, built by the fusion pass
+    FUSEDF32, // 117
 
     // ILLEGAL op always id at last index
     ILLEGAL, // rest
@@ -45,37 +190,6 @@ pub enum CRTOpCode {
 // sub
 // mul
 // div
-// rem
-// fma
-// abs f32
-// neg f32
-// ceil f32
-// floor f32
-// atan f32
-// atan2 f32
-// cos f32
-// sin f32
-// exp f32
-// exp2 f32
-// expm1 f32
-// log f32
-// log10 f32
-// log1p f32
-// log2 f32
-// pow f32
-// rsprt f32
-// sprt f32
-// tanh f32
-// NOT i32
-// AND i32
-// OR i32
-// XOR i32
-// ========================    casting ops
-// bc_i32tof32
-// bc_f32toi32
-// =====================      shift ops
-// shl
-// shr
 // ======================     invoke ops
 // invoke
 impl From<u8> for CRTOpCode {
@@ -135,6 +249,306 @@ impl From<u8> for CRTOpCode {
             17 => {
                 return CRTOpCode::RETV;
             }
+            18 => {
+                return CRTOpCode::ABSF32;
+            }
+            19 => {
+                return CRTOpCode::NEGF32;
+            }
+            20 => {
+                return CRTOpCode::CEILF32;
+            }
+            21 => {
+                return CRTOpCode::FLOORF32;
+            }
+            22 => {
+                return CRTOpCode::ATANF32;
+            }
+            23 => {
+                return CRTOpCode::COSF32;
+            }
+            24 => {
+                return CRTOpCode::SINF32;
+            }
+            25 => {
+                return CRTOpCode::EXP2F32;
+            }
+            26 => {
+                return CRTOpCode::EXPM1F32;
+            }
+            27 => {
+                return CRTOpCode::LOGF32;
+            }
+            28 => {
+                return CRTOpCode::LOG10F32;
+            }
+            29 => {
+                return CRTOpCode::LOG1PF32;
+            }
+            30 => {
+                return CRTOpCode::LOG2F32;
+            }
+            31 => {
+                return CRTOpCode::RSQRTF32;
+            }
+            32 => {
+                return CRTOpCode::SQRTF32;
+            }
+            33 => {
+                return CRTOpCode::TANHF32;
+            }
+            34 => {
+                return CRTOpCode::NOTI32;
+            }
+            35 => {
+                return CRTOpCode::ANDI32;
+            }
+            36 => {
+                return CRTOpCode::ORI32;
+            }
+            37 => {
+                return CRTOpCode::XORI32;
+            }
+            38 => {
+                return CRTOpCode::SHLI32;
+            }
+            39 => {
+                return CRTOpCode::SHRI32;
+            }
+            40 => {
+                return CRTOpCode::CASTF32;
+            }
+            41 => {
+                return CRTOpCode::CASTI32;
+            }
+            42 => {
+                return CRTOpCode::BITCASTF32;
+            }
+            43 => {
+                return CRTOpCode::BITCASTI32;
+            }
+            44 => {
+                return CRTOpCode::REDUCESUMF32;
+            }
+            45 => {
+                return CRTOpCode::REDUCEMEANF32;
+            }
+            46 => {
+                return CRTOpCode::REDUCEMAXF32;
+            }
+            47 => {
+                return CRTOpCode::REDUCEMINF32;
+            }
+            48 => {
+                return CRTOpCode::ARGMAXF32;
+            }
+            49 => {
+                return CRTOpCode::ARGMINF32;
+            }
+            50 => {
+                return CRTOpCode::RESHAPE;
+            }
+            51 => {
+                return CRTOpCode::TRANSPOSE;
+            }
+            52 => {
+                return CRTOpCode::SLICE;
+            }
+            53 => {
+                return CRTOpCode::CONCAT;
+            }
+            54 => {
+                return CRTOpCode::SQUEEZE;
+            }
+            55 => {
+                return CRTOpCode::UNSQUEEZE;
+            }
+            56 => {
+                return CRTOpCode::CMPEQF32;
+            }
+            57 => {
+                return CRTOpCode::CMPNEF32;
+            }
+            58 => {
+                return CRTOpCode::CMPLTF32;
+            }
+            59 => {
+                return CRTOpCode::CMPLEF32;
+            }
+            60 => {
+                return CRTOpCode::CMPGTF32;
+            }
+            61 => {
+                return CRTOpCode::CMPGEF32;
+            }
+            62 => {
+                return CRTOpCode::CMPEQI32;
+            }
+            63 => {
+                return CRTOpCode::CMPNEI32;
+            }
+            64 => {
+                return CRTOpCode::CMPLTI32;
+            }
+            65 => {
+                return CRTOpCode::CMPLEI32;
+            }
+            66 => {
+                return CRTOpCode::CMPGTI32;
+            }
+            67 => {
+                return CRTOpCode::CMPGEI32;
+            }
+            68 => {
+                return CRTOpCode::SELECT;
+            }
+            69 => {
+                return CRTOpCode::POWF32;
+            }
+            70 => {
+                return CRTOpCode::ATAN2F32;
+            }
+            71 => {
+                return CRTOpCode::MAXF32;
+            }
+            72 => {
+                return CRTOpCode::MINF32;
+            }
+            73 => {
+                return CRTOpCode::REMF32;
+            }
+            74 => {
+                return CRTOpCode::MAXI32;
+            }
+            75 => {
+                return CRTOpCode::MINI32;
+            }
+            76 => {
+                return CRTOpCode::REMI32;
+            }
+            77 => {
+                return CRTOpCode::FMAF32;
+            }
+            78 => {
+                return CRTOpCode::CONV2DF32;
+            }
+            79 => {
+                return CRTOpCode::MAXPOOL2DF32;
+            }
+            80 => {
+                return CRTOpCode::AVGPOOL2DF32;
+            }
+            81 => {
+                return CRTOpCode::MATMULTF32;
+            }
+            82 => {
+                return CRTOpCode::JMP;
+            }
+            83 => {
+                return CRTOpCode::JMPIF;
+            }
+            84 => {
+                return CRTOpCode::JMPIFR;
+            }
+            85 => {
+                return CRTOpCode::LOOP;
+            }
+            86 => {
+                return CRTOpCode::ADDR;
+            }
+            87 => {
+                return CRTOpCode::SUBR;
+            }
+            88 => {
+                return CRTOpCode::MULR;
+            }
+            89 => {
+                return CRTOpCode::CMPEQR;
+            }
+            90 => {
+                return CRTOpCode::CMPNER;
+            }
+            91 => {
+                return CRTOpCode::CMPLTR;
+            }
+            92 => {
+                return CRTOpCode::CMPLER;
+            }
+            93 => {
+                return CRTOpCode::CMPGTR;
+            }
+            94 => {
+                return CRTOpCode::CMPGER;
+            }
+            95 => {
+                return CRTOpCode::MOVRT;
+            }
+            96 => {
+                return CRTOpCode::MOVTR;
+            }
+            97 => {
+                return CRTOpCode::SPLIT;
+            }
+            98 => {
+                return CRTOpCode::CONSTF16;
+            }
+            99 => {
+                return CRTOpCode::CONSTBF16;
+            }
+            100 => {
+                return CRTOpCode::CONSTI8;
+            }
+            101 => {
+                return CRTOpCode::CONSTU8;
+            }
+            102 => {
+                return CRTOpCode::CONSTF64;
+            }
+            103 => {
+                return CRTOpCode::CONSTI64;
+            }
+            104 => {
+                return CRTOpCode::CONSTBOOL;
+            }
+            105 => {
+                return CRTOpCode::CASTF16;
+            }
+            106 => {
+                return CRTOpCode::CASTBF16;
+            }
+            107 => {
+                return CRTOpCode::CASTI8;
+            }
+            108 => {
+                return CRTOpCode::CASTU8;
+            }
+            109 => {
+                return CRTOpCode::CASTF64;
+            }
+            110 => {
+                return CRTOpCode::CASTI64;
+            }
+            111 => {
+                return CRTOpCode::CASTBOOL;
+            }
+            112 => {
+                return CRTOpCode::RANGETENSOR;
+            }
+            113 => {
+                return CRTOpCode::EYETENSOR;
+            }
+            114 => {
+                return CRTOpCode::ZEROSLIKE;
+            }
+            115 => {
+                return CRTOpCode::ONESLIKE;
+            }
+            116 => {
+                return CRTOpCode::CONSTREF;
+            }
+            117 => {
+                return CRTOpCode::FUSEDF32;
+            }
             _ => {
                 return CRTOpCode::ILLEGAL;
             }
@@ -149,22 +563,279 @@ impl CRTOpCode {
     pub fn to_kernel_query_entry(&self) -> String {
         match self {
             // i32 types
-            CRTOpCode::ADDI32 | CRTOpCode::SUBI32 | CRTOpCode::MULI32 | CRTOpCode::FLOORDIVI32 => {
-                String::from("binary_arithmetic_i32")
-            }
+            CRTOpCode::ADDI32
+            | CRTOpCode::SUBI32
+            | CRTOpCode::MULI32
+            | CRTOpCode::FLOORDIVI32
+            | CRTOpCode::ANDI32
+            | CRTOpCode::ORI32
+            | CRTOpCode::XORI32
+            | CRTOpCode::SHLI32
+            | CRTOpCode::SHRI32
+            | CRTOpCode::CMPEQI32
+            | CRTOpCode::CMPNEI32
+            | CRTOpCode::CMPLTI32
+            | CRTOpCode::CMPLEI32
+            | CRTOpCode::CMPGTI32
+            | CRTOpCode::CMPGEI32
+            | CRTOpCode::MAXI32
+            | CRTOpCode::MINI32
+            | CRTOpCode::REMI32 => String::from("binary_arithmetic_i32"),
+
+            CRTOpCode::NOTI32 => String::from("unary_arithmetic_i32"),
+
+            CRTOpCode::CASTF32 | CRTOpCode::CASTI32 => String::from("cast_arithmetic_32"),
+
+            CRTOpCode::REDUCESUMF32
+            | CRTOpCode::REDUCEMEANF32
+            | CRTOpCode::REDUCEMAXF32
+            | CRTOpCode::REDUCEMINF32
+            | CRTOpCode::ARGMAXF32
+            | CRTOpCode::ARGMINF32 => String::from("reduction_f32"),
 
             // f32 types
             // TODO(tianyu), this file specify the kernel code file name
-            CRTOpCode::ADDF32 | CRTOpCode::SUBF32 | CRTOpCode::MULF32 | CRTOpCode::DIVF32 => {
-                String::from("binary_arithmetic_f32")
-            }
+            CRTOpCode::ADDF32
+            | CRTOpCode::SUBF32
+            | CRTOpCode::MULF32
+            | CRTOpCode::DIVF32
+            | CRTOpCode::CMPEQF32
+            | CRTOpCode::CMPNEF32
+            | CRTOpCode::CMPLTF32
+            | CRTOpCode::CMPLEF32
+            | CRTOpCode::CMPGTF32
+            | CRTOpCode::CMPGEF32
+            | CRTOpCode::POWF32
+            | CRTOpCode::ATAN2F32
+            | CRTOpCode::MAXF32
+            | CRTOpCode::MINF32
+            | CRTOpCode::REMF32 => String::from("binary_arithmetic_f32"),
 
-            CRTOpCode::MATMULF32 => String::from("matrix_multiple_f32"),
+            CRTOpCode::SELECT => String::from("select_32"),
+            CRTOpCode::FMAF32 => String::from("ternary_arithmetic_f32"),
+
+            CRTOpCode::CONV2DF32 => String::from("conv2d_f32"),
+            CRTOpCode::MAXPOOL2DF32 | CRTOpCode::AVGPOOL2DF32 => String::from("pool2d_f32"),
+
+            CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => String::from("matrix_multiple_f32"),
+
+            // unary f32 types
+            CRTOpCode::EXPF32
+            | CRTOpCode::ABSF32
+            | CRTOpCode::NEGF32
+            | CRTOpCode::CEILF32
+            | CRTOpCode::FLOORF32
+            | CRTOpCode::ATANF32
+            | CRTOpCode::COSF32
+            | CRTOpCode::SINF32
+            | CRTOpCode::EXP2F32
+            | CRTOpCode::EXPM1F32
+            | CRTOpCode::LOGF32
+            | CRTOpCode::LOG10F32
+            | CRTOpCode::LOG1PF32
+            | CRTOpCode::LOG2F32
+            | CRTOpCode::RSQRTF32
+            | CRTOpCode::SQRTF32
+            | CRTOpCode::TANHF32 => String::from("unary_arithmetic_f32"),
 
             _ => panic!("not support this op for dispatch kernel"),
         }
     }
 
+    // element type of the result, for ops that converts between dtypes
+    pub fn to_cast_element_type(&self) -> Option<ElementType> {
+        match self {
+            CRTOpCode::CASTF32 | CRTOpCode::BITCASTF32 => Some(ElementType::F32),
+            CRTOpCode::CASTI32 | CRTOpCode::BITCASTI32 => Some(ElementType::I32),
+            CRTOpCode::CASTF16 => Some(ElementType::F16),
+            CRTOpCode::CASTBF16 => Some(ElementType::BF16),
+            CRTOpCode::CASTI8 => Some(ElementType::I8),
+            CRTOpCode::CASTU8 => Some(ElementType::U8),
+            CRTOpCode::CASTF64 => Some(ElementType::F64),
+            CRTOpCode::CASTI64 => Some(ElementType::I64),
+            CRTOpCode::CASTBOOL => Some(ElementType::BOOL),
+            _ => None,
+        }
+    }
+
+    // element type of scalar literals of extended element types
+    pub fn to_const_element_type(&self) -> Option<ElementType> {
+        match self {
+            CRTOpCode::CONSTF16 => Some(ElementType::F16),
+            CRTOpCode::CONSTBF16 => Some(ElementType::BF16),
+            CRTOpCode::CONSTI8 => Some(ElementType::I8),
+            CRTOpCode::CONSTU8 => Some(ElementType::U8),
+            CRTOpCode::CONSTF64 => Some(ElementType::F64),
+            CRTOpCode::CONSTI64 => Some(ElementType::I64),
+            CRTOpCode::CONSTBOOL => Some(ElementType::BOOL),
+            _ => None,
+        }
+    }
+
+    pub fn is_reduction(&self) -> bool {
+        match self {
+            CRTOpCode::REDUCESUMF32
+            | CRTOpCode::REDUCEMEANF32
+            | CRTOpCode::REDUCEMAXF32
+            | CRTOpCode::REDUCEMINF32
+            | CRTOpCode::ARGMAXF32
+            | CRTOpCode::ARGMINF32 => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        match self {
+            CRTOpCode::CMPEQF32
+            | CRTOpCode::CMPNEF32
+            | CRTOpCode::CMPLTF32
+            | CRTOpCode::CMPLEF32
+            | CRTOpCode::CMPGTF32
+            | CRTOpCode::CMPGEF32
+            | CRTOpCode::CMPEQI32
+            | CRTOpCode::CMPNEI32
+            | CRTOpCode::CMPLTI32
+            | CRTOpCode::CMPLEI32
+            | CRTOpCode::CMPGTI32
+            | CRTOpCode::CMPGEI32 => true,
+            _ => false,
+        }
+    }
+
+    // ternary ops take three operands, executors receive the first one and the packed others
+    pub fn is_ternary(&self) -> bool {
+        match self {
+            CRTOpCode::SELECT | CRTOpCode::FMAF32 => true,
+            _ => false,
+        }
+    }
+
+    // name of the opcode in the text form, the inverse of parsing mnemonics. Opcodes chosen by
+    // operands, such as matmul with transpose flags, share the mnemonic of their plain form
+    pub fn to_mnemonic(&self) -> &'static str {
+        match self {
+            CRTOpCode::HALT => "halt",
+            CRTOpCode::RETV => "return",
+            CRTOpCode::LOAD => "load",
+            CRTOpCode::SPLIT => "crt.tensor.split",
+            CRTOpCode::CONSTF16 => "crt.literal.const.f16",
+            CRTOpCode::CONSTBF16 => "crt.literal.const.bf16",
+            CRTOpCode::CONSTI8 => "crt.literal.const.i8",
+            CRTOpCode::CONSTU8 => "crt.literal.const.u8",
+            CRTOpCode::CONSTF64 => "crt.literal.const.f64",
+            CRTOpCode::CONSTI64 => "crt.literal.const.i64",
+            CRTOpCode::CONSTBOOL => "crt.literal.const.bool",
+            CRTOpCode::CASTF16 => "crt.cast.f16",
+            CRTOpCode::CASTBF16 => "crt.cast.bf16",
+            CRTOpCode::CASTI8 => "crt.cast.i8",
+            CRTOpCode::CASTU8 => "crt.cast.u8",
+            CRTOpCode::CASTF64 => "crt.cast.f64",
+            CRTOpCode::CASTI64 => "crt.cast.i64",
+            CRTOpCode::CASTBOOL => "crt.cast.bool",
+            CRTOpCode::RANGETENSOR => "crt.helper.range.tensor",
+            CRTOpCode::EYETENSOR => "crt.helper.eye.tensor",
+            CRTOpCode::ZEROSLIKE => "crt.zeros_like",
+            CRTOpCode::ONESLIKE => "crt.ones_like",
+            CRTOpCode::CONSTREF => "crt.literal.const.ref",
+            CRTOpCode::FUSEDF32 => "crt.fused.f32",
+            CRTOpCode::JMP => "jmp",
+            CRTOpCode::JMPIF => "jmp_if",
+            CRTOpCode::JMPIFR => "jmp_if",
+            CRTOpCode::LOOP => "loop",
+            CRTOpCode::ADDR => "crt.reg.add",
+            CRTOpCode::SUBR => "crt.reg.sub",
+            CRTOpCode::MULR => "crt.reg.mul",
+            CRTOpCode::CMPEQR => "crt.reg.cmp.eq",
+            CRTOpCode::CMPNER => "crt.reg.cmp.ne",
+            CRTOpCode::CMPLTR => "crt.reg.cmp.lt",
+            CRTOpCode::CMPLER => "crt.reg.cmp.le",
+            CRTOpCode::CMPGTR => "crt.reg.cmp.gt",
+            CRTOpCode::CMPGER => "crt.reg.cmp.ge",
+            CRTOpCode::MOVRT => "crt.reg.to_tensor",
+            CRTOpCode::MOVTR => "crt.reg.from_tensor",
+            CRTOpCode::ADDI32 => "crt.add.i32",
+            CRTOpCode::SUBI32 => "crt.sub.i32",
+            CRTOpCode::MULI32 => "crt.mul.i32",
+            CRTOpCode::FLOORDIVI32 => "crt.floordiv.i32",
+            CRTOpCode::NOTI32 => "crt.not.i32",
+            CRTOpCode::ANDI32 => "crt.and.i32",
+            CRTOpCode::ORI32 => "crt.or.i32",
+            CRTOpCode::XORI32 => "crt.xor.i32",
+            CRTOpCode::SHLI32 => "crt.shl.i32",
+            CRTOpCode::SHRI32 => "crt.shr.i32",
+            CRTOpCode::CASTF32 => "crt.cast.f32",
+            CRTOpCode::CASTI32 => "crt.cast.i32",
+            CRTOpCode::BITCASTF32 => "crt.bitcast.f32",
+            CRTOpCode::BITCASTI32 => "crt.bitcast.i32",
+            CRTOpCode::REDUCESUMF32 => "crt.reduce.sum.f32",
+            CRTOpCode::REDUCEMEANF32 => "crt.reduce.mean.f32",
+            CRTOpCode::REDUCEMAXF32 => "crt.reduce.max.f32",
+            CRTOpCode::REDUCEMINF32 => "crt.reduce.min.f32",
+            CRTOpCode::ARGMAXF32 => "crt.argmax.f32",
+            CRTOpCode::ARGMINF32 => "crt.argmin.f32",
+            CRTOpCode::RESHAPE => "crt.tensor.reshape",
+            CRTOpCode::TRANSPOSE => "crt.tensor.transpose",
+            CRTOpCode::SLICE => "crt.tensor.slice",
+            CRTOpCode::CONCAT => "crt.tensor.concat",
+            CRTOpCode::SQUEEZE => "crt.tensor.squeeze",
+            CRTOpCode::UNSQUEEZE => "crt.tensor.unsqueeze",
+            CRTOpCode::CMPEQF32 => "crt.cmp.eq.f32",
+            CRTOpCode::CMPNEF32 => "crt.cmp.ne.f32",
+            CRTOpCode::CMPLTF32 => "crt.cmp.lt.f32",
+            CRTOpCode::CMPLEF32 => "crt.cmp.le.f32",
+            CRTOpCode::CMPGTF32 => "crt.cmp.gt.f32",
+            CRTOpCode::CMPGEF32 => "crt.cmp.ge.f32",
+            CRTOpCode::CMPEQI32 => "crt.cmp.eq.i32",
+            CRTOpCode::CMPNEI32 => "crt.cmp.ne.i32",
+            CRTOpCode::CMPLTI32 => "crt.cmp.lt.i32",
+            CRTOpCode::CMPLEI32 => "crt.cmp.le.i32",
+            CRTOpCode::CMPGTI32 => "crt.cmp.gt.i32",
+            CRTOpCode::CMPGEI32 => "crt.cmp.ge.i32",
+            CRTOpCode::SELECT => "crt.select",
+            CRTOpCode::POWF32 => "crt.pow.f32",
+            CRTOpCode::ATAN2F32 => "crt.atan2.f32",
+            CRTOpCode::MAXF32 => "crt.max.f32",
+            CRTOpCode::MINF32 => "crt.min.f32",
+            CRTOpCode::REMF32 => "crt.rem.f32",
+            CRTOpCode::MAXI32 => "crt.max.i32",
+            CRTOpCode::MINI32 => "crt.min.i32",
+            CRTOpCode::REMI32 => "crt.rem.i32",
+            CRTOpCode::FMAF32 => "crt.fma.f32",
+            CRTOpCode::CONV2DF32 => "crt.conv2d.f32",
+            CRTOpCode::MAXPOOL2DF32 => "crt.maxpool2d.f32",
+            CRTOpCode::AVGPOOL2DF32 => "crt.avgpool2d.f32",
+            CRTOpCode::CONSTI32 => "crt.literal.const.i32",
+            CRTOpCode::CONSTF32 => "crt.literal.const.f32",
+            CRTOpCode::CONSTTENSOR => "crt.literal.const.tensor",
+            CRTOpCode::SVALUETENSOR => "crt.helper.svalue.tensor",
+            CRTOpCode::RNGTENSOR => "crt.helper.rng.tensor",
+            CRTOpCode::ADDF32 => "crt.add.f32",
+            CRTOpCode::SUBF32 => "crt.sub.f32",
+            CRTOpCode::EXPF32 => "crt.exp.f32",
+            CRTOpCode::ABSF32 => "crt.abs.f32",
+            CRTOpCode::NEGF32 => "crt.neg.f32",
+            CRTOpCode::CEILF32 => "crt.ceil.f32",
+            CRTOpCode::FLOORF32 => "crt.floor.f32",
+            CRTOpCode::ATANF32 => "crt.atan.f32",
+            CRTOpCode::COSF32 => "crt.cos.f32",
+            CRTOpCode::SINF32 => "crt.sin.f32",
+            CRTOpCode::EXP2F32 => "crt.exp2.f32",
+            CRTOpCode::EXPM1F32 => "crt.expm1.f32",
+            CRTOpCode::LOGF32 => "crt.log.f32",
+            CRTOpCode::LOG10F32 => "crt.log10.f32",
+            CRTOpCode::LOG1PF32 => "crt.log1p.f32",
+            CRTOpCode::LOG2F32 => "crt.log2.f32",
+            CRTOpCode::RSQRTF32 => "crt.rsqrt.f32",
+            CRTOpCode::SQRTF32 => "crt.sqrt.f32",
+            CRTOpCode::TANHF32 => "crt.tanh.f32",
+            CRTOpCode::MULF32 => "crt.mul.f32",
+            CRTOpCode::MATMULF32 => "crt.matmul.f32",
+            CRTOpCode::MATMULTF32 => "crt.matmul.f32",
+            CRTOpCode::DIVF32 => "crt.div.f32",
+            CRTOpCode::ILLEGAL => "illegal",
+        }
+    }
+
     pub fn to_specialise_bits(&self) -> u32 {
         match self {
             // add spec data
@@ -180,7 +851,92 @@ impl CRTOpCode {
             CRTOpCode::FLOORDIVI32 | CRTOpCode::DIVF32 => 3_u32,
 
             // matrix-multiple
-            CRTOpCode::MATMULF32 => 4_u32,
+            CRTOpCode::MATMULF32 | CRTOpCode::MATMULTF32 => 4_u32,
+
+            // unary math, entries of unary_arithmetic_f32
+            CRTOpCode::EXPF32 => 0_u32,
+            CRTOpCode::ABSF32 => 1_u32,
+            CRTOpCode::NEGF32 => 2_u32,
+            CRTOpCode::CEILF32 => 3_u32,
+            CRTOpCode::FLOORF32 => 4_u32,
+            CRTOpCode::ATANF32 => 5_u32,
+            CRTOpCode::COSF32 => 6_u32,
+            CRTOpCode::SINF32 => 7_u32,
+            CRTOpCode::EXP2F32 => 8_u32,
+            CRTOpCode::EXPM1F32 => 9_u32,
+            CRTOpCode::LOGF32 => 10_u32,
+            CRTOpCode::LOG10F32 => 11_u32,
+            CRTOpCode::LOG1PF32 => 12_u32,
+            CRTOpCode::LOG2F32 => 13_u32,
+            CRTOpCode::RSQRTF32 => 14_u32,
+            CRTOpCode::SQRTF32 => 15_u32,
+            CRTOpCode::TANHF32 => 16_u32,
+
+            // bitwise and shift, entries of binary_arithmetic_i32
+            CRTOpCode::ANDI32 => 5_u32,
+            CRTOpCode::ORI32 => 6_u32,
+            CRTOpCode::XORI32 => 7_u32,
+            CRTOpCode::SHLI32 => 8_u32,
+            CRTOpCode::SHRI32 => 9_u32,
+
+            // comparisons, entries of binary_arithmetic_f32
+            CRTOpCode::CMPEQF32 => 4_u32,
+            CRTOpCode::CMPNEF32 => 5_u32,
+            CRTOpCode::CMPLTF32 => 6_u32,
+            CRTOpCode::CMPLEF32 => 7_u32,
+            CRTOpCode::CMPGTF32 => 8_u32,
+            CRTOpCode::CMPGEF32 => 9_u32,
+
+            // comparisons, entries of binary_arithmetic_i32
+            CRTOpCode::CMPEQI32 => 10_u32,
+            CRTOpCode::CMPNEI32 => 11_u32,
+            CRTOpCode::CMPLTI32 => 12_u32,
+            CRTOpCode::CMPLEI32 => 13_u32,
+            CRTOpCode::CMPGTI32 => 14_u32,
+            CRTOpCode::CMPGEI32 => 15_u32,
+
+            // the only entry of select_32
+            CRTOpCode::SELECT => 0_u32,
+
+            // binary math, entries of binary_arithmetic_f32
+            CRTOpCode::POWF32 => 10_u32,
+            CRTOpCode::ATAN2F32 => 11_u32,
+            CRTOpCode::MAXF32 => 12_u32,
+            CRTOpCode::MINF32 => 13_u32,
+            CRTOpCode::REMF32 => 14_u32,
+
+            // binary math, entries of binary_arithmetic_i32
+            CRTOpCode::MAXI32 => 16_u32,
+            CRTOpCode::MINI32 => 17_u32,
+            CRTOpCode::REMI32 => 18_u32,
+
+            // entries of ternary_arithmetic_f32
+            CRTOpCode::FMAF32 => 0_u32,
+
+            // the only entry of conv2d_f32
+            CRTOpCode::CONV2DF32 => 0_u32,
+
+            // entries of pool2d_f32
+            CRTOpCode::MAXPOOL2DF32 => 0_u32,
+            CRTOpCode::AVGPOOL2DF32 => 1_u32,
+
+            // entries of unary_arithmetic_i32
+            CRTOpCode::NOTI32 => 0_u32,
+
+            // entries of cast_arithmetic_32
+            CRTOpCode::CASTF32 => 0_u32,
+            CRTOpCode::CASTI32 => 1_u32,
+
+            // kernels generated for fused ops have no other entry
+            CRTOpCode::FUSEDF32 => 0_u32,
+
+            // entries of reduction_f32
+            CRTOpCode::REDUCESUMF32 => 0_u32,
+            CRTOpCode::REDUCEMEANF32 => 1_u32,
+            CRTOpCode::REDUCEMAXF32 => 2_u32,
+            CRTOpCode::REDUCEMINF32 => 3_u32,
+            CRTOpCode::ARGMAXF32 => 4_u32,
+            CRTOpCode::ARGMINF32 => 5_u32,
 
             // TODO(tianyu): change matmul opcode into add opcode to fake the compute
             // CRTOpCode::MATMULF32 => 0_u32,
@@ -195,10 +951,93 @@ impl From<CompleteStr<'_>> for CRTOpCode {
             CompleteStr("halt") => CRTOpCode::HALT,
             CompleteStr("return") => CRTOpCode::RETV,
             CompleteStr("load") => CRTOpCode::LOAD,
+            CompleteStr("crt.tensor.split") => CRTOpCode::SPLIT,
+            CompleteStr("crt.literal.const.f16") => CRTOpCode::CONSTF16,
+            CompleteStr("crt.literal.const.bf16") => CRTOpCode::CONSTBF16,
+            CompleteStr("crt.literal.const.i8") => CRTOpCode::CONSTI8,
+            CompleteStr("crt.literal.const.u8") => CRTOpCode::CONSTU8,
+            CompleteStr("crt.literal.const.f64") => CRTOpCode::CONSTF64,
+            CompleteStr("crt.literal.const.i64") => CRTOpCode::CONSTI64,
+            CompleteStr("crt.literal.const.bool") => CRTOpCode::CONSTBOOL,
+            CompleteStr("crt.cast.f16") => CRTOpCode::CASTF16,
+            CompleteStr("crt.cast.bf16") => CRTOpCode::CASTBF16,
+            CompleteStr("crt.cast.i8") => CRTOpCode::CASTI8,
+            CompleteStr("crt.cast.u8") => CRTOpCode::CASTU8,
+            CompleteStr("crt.cast.f64") => CRTOpCode::CASTF64,
+            CompleteStr("crt.cast.i64") => CRTOpCode::CASTI64,
+            CompleteStr("crt.cast.bool") => CRTOpCode::CASTBOOL,
+            CompleteStr("crt.helper.range.tensor") => CRTOpCode::RANGETENSOR,
+            CompleteStr("crt.helper.eye.tensor") => CRTOpCode::EYETENSOR,
+            CompleteStr("crt.zeros_like") => CRTOpCode::ZEROSLIKE,
+            CompleteStr("crt.ones_like") => CRTOpCode::ONESLIKE,
+            CompleteStr("crt.literal.const.ref") => CRTOpCode::CONSTREF,
+            CompleteStr("crt.fused.f32") => CRTOpCode::FUSEDF32,
+            CompleteStr("jmp") => CRTOpCode::JMP,
+            CompleteStr("jmp_if") => CRTOpCode::JMPIF,
+            CompleteStr("loop") => CRTOpCode::LOOP,
+            CompleteStr("crt.reg.add") => CRTOpCode::ADDR,
+            CompleteStr("crt.reg.sub") => CRTOpCode::SUBR,
+            CompleteStr("crt.reg.mul") => CRTOpCode::MULR,
+            CompleteStr("crt.reg.cmp.eq") => CRTOpCode::CMPEQR,
+            CompleteStr("crt.reg.cmp.ne") => CRTOpCode::CMPNER,
+            CompleteStr("crt.reg.cmp.lt") => CRTOpCode::CMPLTR,
+            CompleteStr("crt.reg.cmp.le") => CRTOpCode::CMPLER,
+            CompleteStr("crt.reg.cmp.gt") => CRTOpCode::CMPGTR,
+            CompleteStr("crt.reg.cmp.ge") => CRTOpCode::CMPGER,
+            CompleteStr("crt.reg.to_tensor") => CRTOpCode::MOVRT,
+            CompleteStr("crt.reg.from_tensor") => CRTOpCode::MOVTR,
             CompleteStr("crt.add.i32") => CRTOpCode::ADDI32,
             CompleteStr("crt.sub.i32") => CRTOpCode::SUBI32,
             CompleteStr("crt.mul.i32") => CRTOpCode::MULI32,
             CompleteStr("crt.floordiv.i32") => CRTOpCode::FLOORDIVI32,
+            CompleteStr("crt.not.i32") => CRTOpCode::NOTI32,
+            CompleteStr("crt.and.i32") => CRTOpCode::ANDI32,
+            CompleteStr("crt.or.i32") => CRTOpCode::ORI32,
+            CompleteStr("crt.xor.i32") => CRTOpCode::XORI32,
+            CompleteStr("crt.shl.i32") => CRTOpCode::SHLI32,
+            CompleteStr("crt.shr.i32") => CRTOpCode::SHRI32,
+            CompleteStr("crt.cast.f32") => CRTOpCode::CASTF32,
+            CompleteStr("crt.cast.i32") => CRTOpCode::CASTI32,
+            CompleteStr("crt.bitcast.f32") => CRTOpCode::BITCASTF32,
+            CompleteStr("crt.bitcast.i32") => CRTOpCode::BITCASTI32,
+            CompleteStr("crt.reduce.sum.f32") => CRTOpCode::REDUCESUMF32,
+            CompleteStr("crt.reduce.mean.f32") => CRTOpCode::REDUCEMEANF32,
+            CompleteStr("crt.reduce.max.f32") => CRTOpCode::REDUCEMAXF32,
+            CompleteStr("crt.reduce.min.f32") => CRTOpCode::REDUCEMINF32,
+            CompleteStr("crt.argmax.f32") => CRTOpCode::ARGMAXF32,
+            CompleteStr("crt.argmin.f32") => CRTOpCode::ARGMINF32,
+            CompleteStr("crt.tensor.reshape") => CRTOpCode::RESHAPE,
+            CompleteStr("crt.tensor.transpose") => CRTOpCode::TRANSPOSE,
+            CompleteStr("crt.tensor.permute") => CRTOpCode::TRANSPOSE,
+            CompleteStr("crt.tensor.slice") => CRTOpCode::SLICE,
+            CompleteStr("crt.tensor.concat") => CRTOpCode::CONCAT,
+            CompleteStr("crt.tensor.squeeze") => CRTOpCode::SQUEEZE,
+            CompleteStr("crt.tensor.unsqueeze") => CRTOpCode::UNSQUEEZE,
+            CompleteStr("crt.cmp.eq.f32") => CRTOpCode::CMPEQF32,
+            CompleteStr("crt.cmp.ne.f32") => CRTOpCode::CMPNEF32,
+            CompleteStr("crt.cmp.lt.f32") => CRTOpCode::CMPLTF32,
+            CompleteStr("crt.cmp.le.f32") => CRTOpCode::CMPLEF32,
+            CompleteStr("crt.cmp.gt.f32") => CRTOpCode::CMPGTF32,
+            CompleteStr("crt.cmp.ge.f32") => CRTOpCode::CMPGEF32,
+            CompleteStr("crt.cmp.eq.i32") => CRTOpCode::CMPEQI32,
+            CompleteStr("crt.cmp.ne.i32") => CRTOpCode::CMPNEI32,
+            CompleteStr("crt.cmp.lt.i32") => CRTOpCode::CMPLTI32,
+            CompleteStr("crt.cmp.le.i32") => CRTOpCode::CMPLEI32,
+            CompleteStr("crt.cmp.gt.i32") => CRTOpCode::CMPGTI32,
+            CompleteStr("crt.cmp.ge.i32") => CRTOpCode::CMPGEI32,
+            CompleteStr("crt.select") => CRTOpCode::SELECT,
+            CompleteStr("crt.pow.f32") => CRTOpCode::POWF32,
+            CompleteStr("crt.atan2.f32") => CRTOpCode::ATAN2F32,
+            CompleteStr("crt.max.f32") => CRTOpCode::MAXF32,
+            CompleteStr("crt.min.f32") => CRTOpCode::MINF32,
+            CompleteStr("crt.rem.f32") => CRTOpCode::REMF32,
+            CompleteStr("crt.max.i32") => CRTOpCode::MAXI32,
+            CompleteStr("crt.min.i32") => CRTOpCode::MINI32,
+            CompleteStr("crt.rem.i32") => CRTOpCode::REMI32,
+            CompleteStr("crt.fma.f32") => CRTOpCode::FMAF32,
+            CompleteStr("crt.conv2d.f32") => CRTOpCode::CONV2DF32,
+            CompleteStr("crt.maxpool2d.f32") => CRTOpCode::MAXPOOL2DF32,
+            CompleteStr("crt.avgpool2d.f32") => CRTOpCode::AVGPOOL2DF32,
             CompleteStr("crt.literal.const.i32") => CRTOpCode::CONSTI32,
             CompleteStr("crt.literal.const.f32") => CRTOpCode::CONSTF32,
             CompleteStr("crt.literal.const.tensor") => CRTOpCode::CONSTTENSOR,
@@ -207,6 +1046,22 @@ impl From<CompleteStr<'_>> for CRTOpCode {
             CompleteStr("crt.add.f32") => CRTOpCode::ADDF32,
             CompleteStr("crt.sub.f32") => CRTOpCode::SUBF32,
             CompleteStr("crt.exp.f32") => CRTOpCode::EXPF32,
+            CompleteStr("crt.abs.f32") => CRTOpCode::ABSF32,
+            CompleteStr("crt.neg.f32") => CRTOpCode::NEGF32,
+            CompleteStr("crt.ceil.f32") => CRTOpCode::CEILF32,
+            CompleteStr("crt.floor.f32") => CRTOpCode::FLOORF32,
+            CompleteStr("crt.atan.f32") => CRTOpCode::ATANF32,
+            CompleteStr("crt.cos.f32") => CRTOpCode::COSF32,
+            CompleteStr("crt.sin.f32") => CRTOpCode::SINF32,
+            CompleteStr("crt.exp2.f32") => CRTOpCode::EXP2F32,
+            CompleteStr("crt.expm1.f32") => CRTOpCode::EXPM1F32,
+            CompleteStr("crt.log.f32") => CRTOpCode::LOGF32,
+            CompleteStr("crt.log10.f32") => CRTOpCode::LOG10F32,
+            CompleteStr("crt.log1p.f32") => CRTOpCode::LOG1PF32,
+            CompleteStr("crt.log2.f32") => CRTOpCode::LOG2F32,
+            CompleteStr("crt.rsqrt.f32") => CRTOpCode::RSQRTF32,
+            CompleteStr("crt.sqrt.f32") => CRTOpCode::SQRTF32,
+            CompleteStr("crt.tanh.f32") => CRTOpCode::TANHF32,
             CompleteStr("crt.mul.f32") => CRTOpCode::MULF32,
             CompleteStr("crt.matmul.f32") => CRTOpCode::MATMULF32,
             CompleteStr("crt.div.f32") => CRTOpCode::DIVF32,
@@ -348,6 +1203,199 @@ mod tests {
         assert_eq!(opcode, CRTOpCode::CONSTTENSOR);
     }
 
+    #[test]
+    fn test_unary_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(16u8), CRTOpCode::EXPF32);
+        assert_eq!(CRTOpCode::from(17u8), CRTOpCode::RETV);
+        assert_eq!(CRTOpCode::from(18u8), CRTOpCode::ABSF32);
+        assert_eq!(CRTOpCode::from(33u8), CRTOpCode::TANHF32);
+        assert_eq!(CRTOpCode::SQRTF32 as u8, 32);
+        assert_eq!(
+            CRTOpCode::from(CRTOpCode::LOG10F32 as u8),
+            CRTOpCode::LOG10F32
+        );
+    }
+
+    #[test]
+    fn test_reduction_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(44u8), CRTOpCode::REDUCESUMF32);
+        assert_eq!(CRTOpCode::from(49u8), CRTOpCode::ARGMINF32);
+        assert_eq!(CRTOpCode::ARGMAXF32.is_reduction(), true);
+        assert_eq!(CRTOpCode::MATMULF32.is_reduction(), false);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.reduce.mean.f32")),
+            CRTOpCode::REDUCEMEANF32
+        );
+    }
+
+    #[test]
+    fn test_shape_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(50u8), CRTOpCode::RESHAPE);
+        assert_eq!(CRTOpCode::from(55u8), CRTOpCode::UNSQUEEZE);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.tensor.permute")),
+            CRTOpCode::TRANSPOSE
+        );
+    }
+
+    #[test]
+    fn test_comparison_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(56u8), CRTOpCode::CMPEQF32);
+        assert_eq!(CRTOpCode::from(67u8), CRTOpCode::CMPGEI32);
+        assert_eq!(CRTOpCode::from(68u8), CRTOpCode::SELECT);
+        assert_eq!(CRTOpCode::CMPLTI32.is_comparison(), true);
+        assert_eq!(CRTOpCode::SELECT.is_comparison(), false);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.cmp.le.f32")),
+            CRTOpCode::CMPLEF32
+        );
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.select")),
+            CRTOpCode::SELECT
+        );
+    }
+
+    #[test]
+    fn test_binary_math_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(69u8), CRTOpCode::POWF32);
+        assert_eq!(CRTOpCode::from(76u8), CRTOpCode::REMI32);
+        assert_eq!(CRTOpCode::from(77u8), CRTOpCode::FMAF32);
+        assert_eq!(CRTOpCode::FMAF32.is_ternary(), true);
+        assert_eq!(CRTOpCode::SELECT.is_ternary(), true);
+        assert_eq!(CRTOpCode::POWF32.is_ternary(), false);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.atan2.f32")),
+            CRTOpCode::ATAN2F32
+        );
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.fma.f32")),
+            CRTOpCode::FMAF32
+        );
+    }
+
+    #[test]
+    fn test_window_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(78u8), CRTOpCode::CONV2DF32);
+        assert_eq!(CRTOpCode::from(80u8), CRTOpCode::AVGPOOL2DF32);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.maxpool2d.f32")),
+            CRTOpCode::MAXPOOL2DF32
+        );
+    }
+
+    #[test]
+    fn test_matmul_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(81u8), CRTOpCode::MATMULTF32);
+        assert_eq!(
+            CRTOpCode::MATMULTF32.to_kernel_query_entry(),
+            CRTOpCode::MATMULF32.to_kernel_query_entry()
+        );
+    }
+
+    #[test]
+    fn test_control_flow_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(82u8), CRTOpCode::JMP);
+        assert_eq!(CRTOpCode::from(83u8), CRTOpCode::JMPIF);
+        assert_eq!(CRTOpCode::from(84u8), CRTOpCode::JMPIFR);
+        assert_eq!(CRTOpCode::from(85u8), CRTOpCode::LOOP);
+        assert_eq!(CRTOpCode::from(CompleteStr("jmp_if")), CRTOpCode::JMPIF);
+    }
+
+    #[test]
+    fn test_register_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(86u8), CRTOpCode::ADDR);
+        assert_eq!(CRTOpCode::from(91u8), CRTOpCode::CMPLTR);
+        assert_eq!(CRTOpCode::from(96u8), CRTOpCode::MOVTR);
+        assert_eq!(CRTOpCode::from(97u8), CRTOpCode::SPLIT);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.reg.cmp.ge")),
+            CRTOpCode::CMPGER
+        );
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.reg.to_tensor")),
+            CRTOpCode::MOVRT
+        );
+    }
+
+    #[test]
+    fn test_bitwise_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(34u8), CRTOpCode::NOTI32);
+        assert_eq!(CRTOpCode::from(35u8), CRTOpCode::ANDI32);
+        assert_eq!(CRTOpCode::from(39u8), CRTOpCode::SHRI32);
+        assert_eq!(CRTOpCode::XORI32 as u8, 37);
+        assert_eq!(CRTOpCode::from(40u8), CRTOpCode::CASTF32);
+        assert_eq!(CRTOpCode::from(43u8), CRTOpCode::BITCASTI32);
+        assert_eq!(CRTOpCode::from(255u8), CRTOpCode::ILLEGAL);
+    }
+
+    #[test]
+    fn test_cast_element_type() {
+        assert_eq!(
+            CRTOpCode::CASTF32.to_cast_element_type(),
+            Some(ElementType::F32)
+        );
+        assert_eq!(
+            CRTOpCode::BITCASTI32.to_cast_element_type(),
+            Some(ElementType::I32)
+        );
+        assert_eq!(CRTOpCode::ADDF32.to_cast_element_type(), None);
+        assert_eq!(
+            CRTOpCode::CASTBF16.to_cast_element_type(),
+            Some(ElementType::BF16)
+        );
+    }
+
+    #[test]
+    fn test_extended_dtype_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(98u8), CRTOpCode::CONSTF16);
+        assert_eq!(CRTOpCode::from(104u8), CRTOpCode::CONSTBOOL);
+        assert_eq!(CRTOpCode::from(105u8), CRTOpCode::CASTF16);
+        assert_eq!(CRTOpCode::from(111u8), CRTOpCode::CASTBOOL);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.literal.const.i64")),
+            CRTOpCode::CONSTI64
+        );
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.cast.u8")),
+            CRTOpCode::CASTU8
+        );
+        assert_eq!(
+            CRTOpCode::CONSTBF16.to_const_element_type(),
+            Some(ElementType::BF16)
+        );
+        assert_eq!(CRTOpCode::CONSTF32.to_const_element_type(), None);
+    }
+
+    #[test]
+    fn test_generator_opcodes_encoding() {
+        assert_eq!(CRTOpCode::from(112u8), CRTOpCode::RANGETENSOR);
+        assert_eq!(CRTOpCode::from(115u8), CRTOpCode::ONESLIKE);
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.helper.eye.tensor")),
+            CRTOpCode::EYETENSOR
+        );
+        assert_eq!(
+            CRTOpCode::from(CompleteStr("crt.zeros_like")),
+            CRTOpCode::ZEROSLIKE
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_round_trip() {
+        for byte in 0..CRTOpCode::ILLEGAL as u8 {
+            let opcode = CRTOpCode::from(byte);
+            let parsed = CRTOpCode::from(CompleteStr(opcode.to_mnemonic()));
+            match opcode {
+                CRTOpCode::MATMULTF32 => assert_eq!(parsed, CRTOpCode::MATMULF32),
+                CRTOpCode::JMPIFR => assert_eq!(parsed, CRTOpCode::JMPIF),
+                _ => assert_eq!(parsed, opcode),
+            }
+        }
+        assert_eq!(CRTOpCode::TRANSPOSE.to_mnemonic(), "crt.tensor.transpose");
+        assert_eq!(CRTOpCode::from(116u8), CRTOpCode::CONSTREF);
+        assert_eq!(CRTOpCode::from(117u8), CRTOpCode::FUSEDF32);
+    }
+
     #[test]
     fn test_create_opcode() {
         let opcode = CRTOpCode::ILLEGAL;
@@ -15,20 +15,32 @@ use tracing::{debug, info};
 // use tracing_subscriber::prelude::*;
 // use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-use nom::types::CompleteStr;
 use std;
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
 use std::num::ParseIntError;
 
-use crate::assembler::parse_bytecode;
+use crate::assembler::assemble;
+use crate::assembler::assembler_base::{Function, Program, SymbolTable};
+use crate::assembler::disassembler::disassemble;
+use crate::assembler::module::Module;
+use crate::assembler::passes::PassManager;
+use crate::assembler::verifier::verify;
 use crate::base::errors::*;
 use crate::instance::*;
+use crate::tensors::ActTensorTypes;
 use crate::vm::VM;
 
 #[derive(Debug)]
 pub struct Interpreter {
     history: Vec<String>,
+    // functions defined by loaded programs, callable by name
+    functions: HashMap<String, Function>,
+    // slots of named values, shared by all programs pushed into the vm
+    symbols: SymbolTable,
+    // optimisations over loaded programs, none by default
+    passes: PassManager,
     pub vm: VM,
 }
 
@@ -37,6 +49,9 @@ impl Interpreter {
         // let ist = DeviceInstance::new();
         Interpreter {
             history: vec![],
+            functions: HashMap::new(),
+            symbols: SymbolTable::new(),
+            passes: PassManager::new(),
             vm: VM::new(),
         }
     }
@@ -93,13 +108,30 @@ impl Interpreter {
         Ok(results)
     }
 
-    pub fn run_bytecode_eagerly(&mut self, bytecode: &str) -> Result<u8, RuntimeStatusError> {
-        let parsed_program = parse_bytecode(CompleteStr(bytecode));
-        let (_, result_program) = parsed_program.expect("failed to parse bytecode");
-        let bytecode = result_program.to_bytes();
-        for byte in bytecode {
+    // selects the passes run over programs before they are pushed into the vm
+    pub fn set_passes(&mut self, passes: PassManager) {
+        self.passes = passes;
+    }
+
+    // pushes top-level instructions into the vm and registers the defined functions, programs
+    // failing verification leave the vm untouched
+    fn load_program(&mut self, program: Program) -> Result<(), RuntimeStatusError> {
+        let program = program.resolve_names(&mut self.symbols);
+        let vm = &self.vm;
+        verify(&program, |slot| vm.tensor_type(slot)).map_err(RuntimeStatusError::VERIFY_ERROR)?;
+        let program = self.passes.run(program);
+        for byte in program.to_bytes() {
             self.vm.push_bytecode_into_cmdbuffer(byte);
         }
+        for function in program.functions {
+            self.functions.insert(function.name.clone(), function);
+        }
+        Ok(())
+    }
+
+    pub fn run_bytecode_eagerly(&mut self, bytecode: &str) -> Result<u8, RuntimeStatusError> {
+        let result_program = assemble(bytecode).map_err(RuntimeStatusError::SYNTAX_ERROR)?;
+        self.load_program(result_program)?;
         let status = self.vm.run_eagerly();
         // let status = self.vm.run_eagerly();
         // TODO keep this wait here until all done, since currently we do not wait all spawned
@@ -109,12 +141,8 @@ impl Interpreter {
     }
 
     pub fn run_bytecode_lazily(&mut self, bytecode: &str) -> Result<u8, RuntimeStatusError> {
-        let parsed_program = parse_bytecode(CompleteStr(bytecode));
-        let (_, result_program) = parsed_program.expect("failed to parse bytecode");
-        let bytecode = result_program.to_bytes();
-        for byte in bytecode {
-            self.vm.push_bytecode_into_cmdbuffer(byte);
-        }
+        let result_program = assemble(bytecode).map_err(RuntimeStatusError::SYNTAX_ERROR)?;
+        self.load_program(result_program)?;
         let status = self.vm.run_lazily();
         // let status = self.vm.run_eagerly();
         // TODO keep this wait here until all done, since currently we do not wait all spawned
@@ -123,6 +151,94 @@ impl Interpreter {
         status
     }
 
+    // runs a module loaded from a .crtb file, its constants are appended to those of earlier
+    // modules in the vm, and its functions become callable like those of programs
+    pub fn run_module(&mut self, module: &Module) -> Result<u8, RuntimeStatusError> {
+        let base = self.vm.push_constants(module.constants.clone());
+        self.load_program(module.program_at(base))?;
+        self.vm.run_eagerly()
+    }
+
+    // binds arguments to the function's argument slots by position, runs its body eagerly and
+    // returns the tensors of its return operands
+    pub fn call_function(
+        &mut self,
+        name: &str,
+        arguments: Vec<ActTensorTypes>,
+    ) -> Result<Vec<ActTensorTypes>, RuntimeStatusError> {
+        let function =
+            self.functions
+                .get(name)
+                .cloned()
+                .ok_or(RuntimeStatusError::FUNCTION_NOT_FOUND(format!(
+                    "function @{} is not defined",
+                    name
+                )))?;
+        if arguments.len() != function.arguments.len() {
+            return Err(RuntimeStatusError::ARGUMENT_MISMATCH(format!(
+                "@{} expects {} arguments, got {}",
+                name,
+                function.arguments.len(),
+                arguments.len()
+            )));
+        }
+        for (position, ((_, tensor_type), argument)) in
+            function.arguments.iter().zip(&arguments).enumerate()
+        {
+            if !tensor_type.accepts(argument.element_type(), &argument.shape()) {
+                return Err(RuntimeStatusError::ARGUMENT_MISMATCH(format!(
+                    "argument #{} of @{} is {:?} of shape {:?}, expects {:?}",
+                    position,
+                    name,
+                    argument.element_type(),
+                    argument.shape(),
+                    tensor_type
+                )));
+            }
+        }
+        for (slot, argument) in function.argument_slots().into_iter().zip(arguments) {
+            self.vm.push_tensor(slot, argument);
+        }
+
+        for byte in function.to_bytes() {
+            self.vm.push_bytecode_into_cmdbuffer(byte);
+        }
+        self.vm.take_returned();
+        self.vm.run_eagerly()?;
+
+        let result_slots = self.vm.take_returned();
+        if result_slots.len() != function.results.len() {
+            return Err(RuntimeStatusError::ARGUMENT_MISMATCH(format!(
+                "@{} declares {} results, returns {}",
+                name,
+                function.results.len(),
+                result_slots.len()
+            )));
+        }
+        let mut results = vec![];
+        for (slot, tensor_type) in result_slots.into_iter().zip(&function.results) {
+            if !self.vm.has_tensor(slot) {
+                return Err(RuntimeStatusError::ARGUMENT_MISMATCH(format!(
+                    "result %{} of @{} is never defined",
+                    slot, name
+                )));
+            }
+            let result = self.vm.get_tensor(&slot).read().unwrap().clone();
+            if !tensor_type.accepts(result.element_type(), &result.shape()) {
+                return Err(RuntimeStatusError::ARGUMENT_MISMATCH(format!(
+                    "result %{} of @{} is {:?} of shape {:?}, declared {:?}",
+                    slot,
+                    name,
+                    result.element_type(),
+                    result.shape(),
+                    tensor_type
+                )));
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
     fn consume_command(&mut self, bytecode: &str) -> Result<u8, RuntimeStatusError> {
         match bytecode {
             "exit" | "quit" | "q" => {
@@ -139,8 +255,13 @@ impl Interpreter {
             }
             "list" | "l" => {
                 info!("action: Showing instruction queue");
-                for inst in self.vm.inst_buffer() {
-                    info!("|-- {}", inst);
+                match disassemble(self.vm.inst_buffer()) {
+                    Ok(program) => {
+                        for inst in program.to_string().lines() {
+                            info!("|-- {}", inst);
+                        }
+                    }
+                    Err(e) => info!("|-- {:?}", e),
                 }
                 // TODO
                 Ok(5)
@@ -182,6 +303,21 @@ impl Interpreter {
                     RuntimeStatusError::RT_ERROR => panic!("::ipt::RT status incorrect"),
                     // TODO use OK rather Err for program_finish
                     RuntimeStatusError::EXEC_FINISH => info!("::ipt::computation-finish"),
+                    RuntimeStatusError::SHAPE_MISMATCH(msg)
+                    | RuntimeStatusError::DTYPE_MISMATCH(msg)
+                    | RuntimeStatusError::FUNCTION_NOT_FOUND(msg)
+                    | RuntimeStatusError::ARGUMENT_MISMATCH(msg)
+                    | RuntimeStatusError::INVALID_MODULE(msg) => {
+                        println!("error: {}", msg);
+                    }
+                    RuntimeStatusError::SYNTAX_ERROR(err) => {
+                        println!("error: {}", err);
+                    }
+                    RuntimeStatusError::VERIFY_ERROR(errors) => {
+                        for err in errors {
+                            println!("error: {}", err);
+                        }
+                    }
                 },
             }
             self.history.push(bytecode.to_string());
@@ -225,12 +361,48 @@ mod tests {
         let mut ipt = Interpreter::new();
         ipt.init(2);
         // ok
-        // TODO make runtime check on matching const.i32 and i32 type annotation
         let status = ipt.run_bytecode_eagerly("%17 = crt.literal.const.i32! 13 : i32\n");
         assert_eq!(status.is_ok(), true);
         let status_code = status.unwrap();
         assert_eq!(status_code, 0);
         assert_eq!(*ipt.vm.get_raw_vec_i32(17), vec![13]);
+
+        // annotation mismatches the const.i32, rejected before anything runs
+        let status = ipt.run_bytecode_eagerly("%18 = crt.literal.const.i32! 13 : f32\n");
+        assert_eq!(status.is_err(), true);
+        assert_eq!(ipt.vm.has_tensor(18), false);
+    }
+
+    #[test]
+    fn test_mock_verify_before_load() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.helper.svalue.tensor! ones<[2 3]> : f32\n%1 = crt.helper.svalue.tensor! ones<[2 3]> : f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        // shapes of earlier programs are taken from the vm
+        let loaded = ipt.vm.inst_buffer().len();
+        let status = ipt.run_bytecode_eagerly("%2 = crt.matmul.f32! %0, %1 : f32\n");
+        match status {
+            Err(RuntimeStatusError::VERIFY_ERROR(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].index, 0);
+                assert_eq!(
+                    errors[0].message,
+                    "inner dims of matmul do not match, 3 of [2, 3] and 2 of [2, 3]"
+                );
+            }
+            _ => panic!("matmul of mismatched shapes passes verification"),
+        }
+        assert_eq!(ipt.vm.inst_buffer().len(), loaded);
+
+        let status = ipt.run_bytecode_eagerly("%2 = crt.exp.f32! %9 : f32\n");
+        assert_eq!(status.is_err(), true);
+        let status =
+            ipt.run_bytecode_eagerly("%2 = crt.matmul.f32! %0, %1, transpose_b = true : f32\n");
+        assert_eq!(status.is_ok(), true);
     }
 
     #[test]
@@ -297,6 +469,87 @@ mod tests {
         assert_eq!(status_code, 0);
     }
 
+    #[test]
+    fn test_mock_bytecode_tensor_generator_helpers() {
+        use crate::base::ElementType;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(1);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.helper.svalue.tensor! full<[2 2], 0.5> : f32
+%1 = crt.helper.svalue.tensor! full<[3], -4> : i32
+%2 = crt.helper.range.tensor! arange<0, 8, 3> : i64
+%3 = crt.helper.range.tensor! linspace<-1., 1., 5> : f32
+%4 = crt.helper.eye.tensor! eye<2, 3> : i32
+%5 = crt.zeros_like! %2 : i64
+%6 = crt.ones_like! %0 : f32
+%7 = crt.helper.svalue.tensor! zeros<[2]> : bf16
+",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        assert_eq!(ipt.vm.get_raw_vec_f32(0), vec![0.5; 4]);
+        assert_eq!(ipt.vm.get_raw_vec_i32(1), vec![-4; 3]);
+        let positions = ipt.vm.get_tensor(&2);
+        assert_eq!(positions.read().unwrap().element_type(), ElementType::I64);
+        assert_eq!(positions.read().unwrap().to_i64_vec(), vec![0, 3, 6]);
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(3),
+            vec![-1., -0.5, 0., 0.5, 1.],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_raw_vec_i32(4), vec![1, 0, 0, 0, 1, 0]);
+        assert_eq!(ipt.vm.get_tensor_shape(4), vec![2, 3]);
+
+        // like-shaped helpers keep the shape and element type of the operand
+        let zeros = ipt.vm.get_tensor(&5);
+        assert_eq!(zeros.read().unwrap().element_type(), ElementType::I64);
+        assert_eq!(zeros.read().unwrap().to_i64_vec(), vec![0, 0, 0]);
+        assert_eq!(ipt.vm.get_raw_vec_f32(6), vec![1.; 4]);
+        assert_eq!(ipt.vm.get_tensor_shape(6), vec![2, 2]);
+        let zeros = ipt.vm.get_tensor(&7);
+        assert_eq!(zeros.read().unwrap().element_type(), ElementType::BF16);
+    }
+
+    #[test]
+    fn test_mock_bytecode_tensor_seeded_rng_helpers() {
+        let mut ipt = Interpreter::new();
+        ipt.init(1);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.helper.rng.tensor! normal<[4 4], 1., 0.5, 42> : f32
+%1 = crt.helper.rng.tensor! normal<[4 4], 1., 0.5, 42> : f32
+%2 = crt.helper.rng.tensor! normal<[4 4], 1., 0.5, 43> : f32
+%3 = crt.helper.rng.tensor! uniform<[64], 2., 3., 42> : f32
+%4 = crt.helper.rng.tensor! truncated_normal<[64], 0., 0.02, 42> : f32
+%5 = crt.helper.rng.tensor! bernoulli<[64], 0.5, 42> : bool
+",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        // same seed, same tensor
+        assert_eq!(ipt.vm.get_raw_vec_f32(0), ipt.vm.get_raw_vec_f32(1));
+        assert_ne!(ipt.vm.get_raw_vec_f32(0), ipt.vm.get_raw_vec_f32(2));
+
+        assert_eq!(
+            ipt.vm
+                .get_raw_vec_f32(3)
+                .iter()
+                .all(|x| *x >= 2.0 && *x < 3.0),
+            true
+        );
+        assert_eq!(
+            ipt.vm
+                .get_raw_vec_f32(4)
+                .iter()
+                .all(|x| x.abs() <= 0.04 + 1e-6),
+            true
+        );
+        assert_eq!(
+            ipt.vm.get_raw_vec_i32(5).iter().all(|x| *x == 0 || *x == 1),
+            true
+        );
+    }
+
     #[test]
     // TODO fix integer end2end pipeline
     fn test_mock_bytecode_binary_add_i32() {
@@ -417,6 +670,342 @@ mod tests {
         assert_eq!(*ipt.vm.get_raw_vec_i32(3), vec![3]);
     }
 
+    #[test]
+    fn test_mock_bytecode_bitwise_i32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly("%1 = crt.literal.const.i32! 12 : i32\n");
+        let status = ipt.run_bytecode_eagerly("%2 = crt.literal.const.i32! 10 : i32\n");
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%3 = crt.and.i32! %1, %2 : i32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(3), vec![8]);
+
+        let status = ipt.run_bytecode_eagerly("%4 = crt.xor.i32! %1, %2 : i32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(4), vec![6]);
+
+        let status = ipt.run_bytecode_eagerly("%5 = crt.shl.i32! %1, %2 : i32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(5), vec![12288]);
+
+        let status = ipt.run_bytecode_eagerly("%6 = crt.not.i32! %1 : i32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(6), vec![-13]);
+    }
+
+    #[test]
+    fn test_mock_bytecode_cast_mixed_dtypes() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly("%1 = crt.literal.const.i32! 7 : i32\n");
+        let status = ipt.run_bytecode_eagerly("%2 = crt.literal.const.f32! 0.5 : f32\n");
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%3 = crt.cast.f32! %1 : i32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(3), vec![7.0], rmax_all <= 0.00001);
+
+        let status = ipt.run_bytecode_eagerly("%4 = crt.add.f32! %3, %2 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly("%5 = crt.cast.i32! %4 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(5), vec![7]);
+
+        let status = ipt.run_bytecode_eagerly("%6 = crt.bitcast.i32! %2 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(6), vec![0x3f000000]);
+    }
+
+    #[test]
+    fn test_mock_bytecode_reduce_f32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 5. 3. 4. 2. 6.], shape=[2 3]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%1 = crt.reduce.sum.f32! %0, axis = 1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(1),
+            vec![9.0, 12.0],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(1), vec![2]);
+
+        let status = ipt.run_bytecode_eagerly(
+            "%2 = crt.reduce.mean.f32! %0, axis = 0, keepdims = true : f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![2.5, 3.5, 4.5],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![1, 3]);
+
+        let status = ipt.run_bytecode_eagerly("%3 = crt.reduce.max.f32! %0 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(3), vec![6.0], rmax_all <= 0.00001);
+
+        let status = ipt.run_bytecode_eagerly("%4 = crt.argmax.f32! %0, axis = -1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(4), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_mock_bytecode_broadcast_bias_add_f32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4. 5. 6.], shape=[2 3]>: f32\n",
+        );
+        let status = ipt.run_bytecode_eagerly(
+            "%1 = crt.literal.const.tensor! dense<[10. 20. 30.], shape=[3]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%2 = crt.add.f32! %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![11.0, 22.0, 33.0, 14.0, 25.0, 36.0],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![2, 3]);
+
+        // [2, 3] cannot broadcast with [2]
+        let status = ipt.run_bytecode_eagerly(
+            "%3 = crt.literal.const.tensor! dense<[1. 2.], shape=[2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly("%4 = crt.mul.f32! %0, %3 : f32\n");
+        assert_eq!(status.is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_bytecode_compare_select_f32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[-2. -0.5 0.5 2.], shape=[2 2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt
+            .run_bytecode_eagerly("%1 = crt.literal.const.tensor! dense<[0.], shape=[1]>: f32\n");
+        assert_eq!(status.is_ok(), true);
+
+        // relu
+        let status = ipt.run_bytecode_eagerly("%2 = crt.cmp.gt.f32! %0, %1 : bool\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(*ipt.vm.get_raw_vec_i32(2), vec![0, 0, 1, 1]);
+        let status = ipt.run_bytecode_eagerly("%3 = crt.select! %2, %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(3),
+            vec![0.0, 0.0, 0.5, 2.0],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![2, 2]);
+
+        // clip into [-1, 1]
+        let status = ipt
+            .run_bytecode_eagerly("%4 = crt.literal.const.tensor! dense<[1.], shape=[1]>: f32\n");
+        let status = ipt
+            .run_bytecode_eagerly("%5 = crt.literal.const.tensor! dense<[-1.], shape=[1]>: f32\n");
+        let status = ipt.run_bytecode_eagerly("%6 = crt.cmp.gt.f32! %0, %4 : bool\n");
+        let status = ipt.run_bytecode_eagerly("%7 = crt.select! %6, %4, %0 : f32\n");
+        let status = ipt.run_bytecode_eagerly("%6 = crt.cmp.lt.f32! %7, %5 : bool\n");
+        let status = ipt.run_bytecode_eagerly("%7 = crt.select! %6, %5, %7 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(7),
+            vec![-1.0, -0.5, 0.5, 1.0],
+            rmax_all <= 0.00001
+        );
+    }
+
+    #[test]
+    fn test_mock_typed_dense_literals() {
+        use crate::base::ElementType;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[-2. -0.5 0.5 2.], shape=[2 2]>: f32
+%1 = crt.literal.const.tensor! dense<[0. 0. 0. 0.], shape=[2 2]> : f32
+%2 = crt.literal.const.tensor! dense<[true false false true], shape=[2 2]> : bool
+%3 = crt.select! %2, %0, %1 : f32
+%4 = crt.literal.const.tensor! dense<[3 -1 4], shape=[3]> : i32
+%5 = crt.literal.const.tensor! dense<[1 2], shape=[2 1]> : i64
+",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        // masks embedded in programs are bool tensors of 0 or 1
+        let mask = ipt.vm.get_tensor(&2);
+        assert_eq!(mask.read().unwrap().element_type(), ElementType::BOOL);
+        assert_eq!(ipt.vm.get_raw_vec_i32(2), vec![1, 0, 0, 1]);
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(3),
+            vec![-2.0, 0.0, 0.0, 2.0],
+            rmax_all <= 0.00001
+        );
+
+        assert_eq!(ipt.vm.get_raw_vec_i32(4), vec![3, -1, 4]);
+        let indices = ipt.vm.get_tensor(&5);
+        assert_eq!(indices.read().unwrap().element_type(), ElementType::I64);
+        assert_eq!(indices.read().unwrap().shape(), vec![2, 1]);
+        assert_eq!(indices.read().unwrap().to_i64_vec(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_mock_bytecode_binary_math_and_fma_f32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4.], shape=[2 2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly(
+            "%1 = crt.literal.const.tensor! dense<[2. 2.5], shape=[2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%2 = crt.pow.f32! %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![1.0, 2f32.powf(2.5), 9.0, 32.0],
+            rmax_all <= 0.00001
+        );
+        let status = ipt.run_bytecode_eagerly("%3 = crt.max.f32! %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(3),
+            vec![2.0, 2.5, 3.0, 4.0],
+            rmax_all <= 0.00001
+        );
+        let status = ipt.run_bytecode_eagerly("%4 = crt.rem.f32! %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            vec![1.0, 2.0, 1.0, 1.5],
+            rmax_all <= 0.00001
+        );
+
+        // %0 * %1 + %3
+        let status = ipt.run_bytecode_eagerly("%5 = crt.fma.f32! %0, %1, %3 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(5),
+            vec![4.0, 7.5, 9.0, 14.0],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(5), vec![2, 2]);
+    }
+
+    #[test]
+    fn test_mock_bytecode_conv2d_and_pool2d_f32() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[0. 1. 2. 3. 4. 5. 6. 7. 8. 9. 10. 11. 12. 13. 14. 15.], shape=[1 1 4 4]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly(
+            "%1 = crt.literal.const.tensor! dense<[1. 1. 1. 1.], shape=[1 1 2 2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status =
+            ipt.run_bytecode_eagerly("%2 = crt.conv2d.f32! %0, %1, stride = [2, 2] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![1, 1, 2, 2]);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![10.0, 18.0, 42.0, 50.0],
+            rmax_all <= 0.00001
+        );
+        let status = ipt.run_bytecode_eagerly(
+            "%3 = crt.conv2d.f32! %0, %1, padding = [1, 1], dilation = [2, 2] : f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![1, 1, 4, 4]);
+
+        let status =
+            ipt.run_bytecode_eagerly("%4 = crt.maxpool2d.f32! %0, kernel = [2, 2] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            vec![5.0, 7.0, 13.0, 15.0],
+            rmax_all <= 0.00001
+        );
+        let status =
+            ipt.run_bytecode_eagerly("%5 = crt.avgpool2d.f32! %0, kernel = [2, 2] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(5),
+            vec![2.5, 4.5, 10.5, 12.5],
+            rmax_all <= 0.00001
+        );
+
+        // channels of input and weight mismatch
+        let status = ipt.run_bytecode_eagerly("%6 = crt.conv2d.f32! %1, %2, groups = 2 : f32\n");
+        assert_eq!(status.is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_bytecode_shape_ops() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[0. 1. 2. 3. 4. 5.], shape=[2 3]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%1 = crt.tensor.transpose! %0, [1, 0] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(1),
+            vec![0.0, 3.0, 1.0, 4.0, 2.0, 5.0],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(ipt.vm.get_tensor_shape(1), vec![3, 2]);
+
+        let status = ipt.run_bytecode_eagerly("%2 = crt.tensor.reshape! %0, [3, 2] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![3, 2]);
+        assert_eq!(ipt.vm.get_tensor_shape(0), vec![2, 3]);
+
+        let status = ipt.run_bytecode_eagerly("%3 = crt.tensor.unsqueeze! %0, [0] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![1, 2, 3]);
+        let status = ipt.run_bytecode_eagerly("%3 = crt.tensor.squeeze! %3 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![2, 3]);
+
+        let status = ipt.run_bytecode_eagerly("%4 = crt.tensor.slice! %0, [0, 1], [2, 3] : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            vec![1.0, 2.0, 4.0, 5.0],
+            rmax_all <= 0.00001
+        );
+
+        let status = ipt.run_bytecode_eagerly("%5 = crt.tensor.concat! %0, %4, axis = 1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(5), vec![2, 5]);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(5),
+            vec![0.0, 1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 5.0, 4.0, 5.0],
+            rmax_all <= 0.00001
+        );
+    }
+
     #[test]
     // TODO fix integer end2end pipeline
     fn test_mock_bytecode_binary_add_f32() {
@@ -579,6 +1168,41 @@ mod tests {
         assert_float_eq!(*ipt.vm.get_raw_vec_f32(5), vec![3.2], rmax_all <= 0.00001);
     }
 
+    #[test]
+    fn test_mock_bytecode_tensor_unary_math() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 4. 9. 16.], shape=[2 2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let status = ipt.run_bytecode_eagerly("%1 = crt.sqrt.f32! %0 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(1),
+            vec![1., 2., 3., 4.],
+            rmax_all <= 0.00001
+        );
+        assert_eq!(*ipt.vm.get_tensor_shape(1), vec![2, 2]);
+
+        let status = ipt.run_bytecode_eagerly("%2 = crt.neg.f32! %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![-1., -2., -3., -4.],
+            rmax_all <= 0.00001
+        );
+
+        let status = ipt.run_bytecode_eagerly("%3 = crt.abs.f32! %2 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(3),
+            vec![1., 2., 3., 4.],
+            rmax_all <= 0.00001
+        );
+    }
+
     #[test]
     fn test_mock_bytecode_tensor_add_lazy() {
         let mut ipt = Interpreter::new();
@@ -738,6 +1362,552 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mock_bytecode_batched_matmul() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4. 5. 6. 7. 8. 9. 10. 11. 12.], shape=[2 2 3]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly(
+            "%1 = crt.literal.const.tensor! dense<[1. 0. 0. 1. 1. 1.], shape=[3 2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        // [2, 2, 3] x [3, 2], rhs broadcasts over the batch
+        let status = ipt.run_bytecode_eagerly("%2 = crt.matmul.f32! %0, %1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![2, 2, 2]);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(2),
+            vec![4., 5., 10., 11., 16., 17., 22., 23.],
+            rmax_all <= 0.00001
+        );
+
+        // [2, 2, 3] x [2, 2, 3]^T => [2, 2, 2]
+        let status =
+            ipt.run_bytecode_eagerly("%3 = crt.matmul.f32! %0, %0, transpose_b = true : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![2, 2, 2]);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(3),
+            vec![14., 32., 32., 77., 194., 266., 266., 365.],
+            rmax_all <= 0.00001
+        );
+
+        // [3, 2]^T x [2, 2, 3]^T => [2, 2, 2]
+        let status = ipt.run_bytecode_eagerly(
+            "%4 = crt.matmul.f32! %1, %0, transpose_a = true, transpose_b = true : f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            vec![4., 10., 5., 11., 16., 22., 17., 23.],
+            rmax_all <= 0.00001
+        );
+
+        // inner dims mismatch
+        let status = ipt.run_bytecode_eagerly("%5 = crt.matmul.f32! %0, %0 : f32\n");
+        assert_eq!(status.is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_bytecode_jump_if() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        // sums a countdown from 3, the counter is the branch condition
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[3.], shape=[1]>: f32
+%1 = crt.literal.const.tensor! dense<[1.], shape=[1]>: f32
+%2 = crt.literal.const.tensor! dense<[0.], shape=[1]>: f32
+^head:
+%2 = crt.add.f32! %2, %0 : f32
+%0 = crt.sub.f32! %0, %1 : f32
+jmp_if %0, ^head
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(2), vec![6.], rmax_all <= 0.00001);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(0), vec![0.], rmax_all <= 0.00001);
+
+        // registers are zero-initialised, so only the first add runs before jmp
+        let status = ipt.run_bytecode_eagerly(
+            "%3 = crt.literal.const.tensor! dense<[1.], shape=[1]>: f32
+jmp_if $0, ^end
+%3 = crt.add.f32! %3, %3 : f32
+jmp ^end
+%3 = crt.add.f32! %3, %3 : f32
+^end:
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(3), vec![2.], rmax_all <= 0.00001);
+
+        // conditions must be scalars
+        let status = ipt.run_bytecode_eagerly(
+            "%4 = crt.helper.svalue.tensor! ones<[2 2]> : f32\njmp_if %4, ^end\n^end:\n",
+        );
+        assert_eq!(status.is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_bytecode_counted_loop() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        // nested loops, the inner one restarts its count on every outer trip
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+^outer:
+^inner:
+%1 = crt.add.f32! %1, %0 : f32
+loop 3, ^inner
+loop 2, ^outer
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(1), vec![7.; 4], rmax_all <= 0.00001);
+    }
+
+    #[test]
+    fn test_mock_bytecode_counted_loop_lazy() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let bytecode = "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%2 = crt.literal.const.tensor! dense<[1.], shape=[1]>: f32
+%3 = crt.literal.const.tensor! dense<[0.], shape=[1]>: f32
+^head:
+%1 = crt.add.f32! %1, %0 : f32
+loop 3, ^head
+jmp_if %3, ^head
+jmp_if %2, ^end
+%1 = crt.add.f32! %1, %0 : f32
+^end:
+";
+        let status = ipt.run_bytecode_lazily(bytecode);
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(1), vec![4.; 4], rmax_all <= 0.00001);
+    }
+
+    #[test]
+    fn test_mock_bytecode_register_ops() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        // sums a countdown from 3 with a register loop counter
+        let status = ipt.run_bytecode_eagerly(
+            "load $0, 3
+load $1, 1
+load $2, 0
+^head:
+$2 = crt.reg.add! $2, $0
+$0 = crt.reg.sub! $0, $1
+jmp_if $0, ^head
+$3 = crt.reg.cmp.gt! $2, $1
+$4 = crt.reg.mul! $2, $3
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.registers()[..5], [0, 1, 6, 1, 6]);
+
+        // moves between registers and 0-d tensors
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.reg.to_tensor! $2 : i32\n$5 = crt.reg.from_tensor! %0\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_raw_vec_i32(0), vec![6]);
+        assert_eq!(ipt.vm.get_tensor_shape(0), Vec::<usize>::new());
+        assert_eq!(ipt.vm.registers()[5], 6);
+
+        // registers as shape and axis arguments
+        let status = ipt.run_bytecode_eagerly(
+            "%1 = crt.helper.svalue.tensor! ones<[2 3]> : f32
+load $6, 3
+load $7, 1
+%2 = crt.tensor.reshape! %1, [$6, 2] : f32
+%3 = crt.reduce.sum.f32! %2, axis = $7 : f32
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![3, 2]);
+        assert_eq!(ipt.vm.get_tensor_shape(3), vec![3]);
+        assert_float_eq!(*ipt.vm.get_raw_vec_f32(3), vec![2.; 3], rmax_all <= 0.00001);
+
+        // negative dims are rejected
+        let status = ipt.run_bytecode_eagerly(
+            "$8 = crt.reg.sub! $0, $1\n%4 = crt.tensor.reshape! %1, [$8, 6] : f32\n",
+        );
+        assert_eq!(status.is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_call_function() {
+        use crate::base::ElementType;
+        use crate::tensors::TensorView;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "func @axpy(%0: tensor<?x2xf32>, %1: tensor<?x2xf32>) -> (tensor<?x2xf32>) {
+    %2 = crt.mul.f32! %0, %1 : f32
+    %3 = crt.add.f32! %2, %1 : f32
+    return %3
+}
+",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        let lhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![1., 2., 3., 4.], ElementType::F32, vec![2, 2]),
+        };
+        let rhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![2., 2., 2., 2.], ElementType::F32, vec![2, 2]),
+        };
+        let results = ipt
+            .call_function("axpy", vec![lhs.clone(), rhs.clone()])
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].shape(), vec![2, 2]);
+        match &results[0] {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_float_eq!(data.data, vec![4., 6., 8., 10.], rmax_all <= 0.00001)
+            }
+            _ => panic!("expects f32 result"),
+        }
+
+        // functions can be called again with other arguments
+        let results = ipt
+            .call_function("axpy", vec![rhs.clone(), lhs.clone()])
+            .unwrap();
+        match &results[0] {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_float_eq!(data.data, vec![3., 6., 9., 12.], rmax_all <= 0.00001)
+            }
+            _ => panic!("expects f32 result"),
+        }
+
+        // arity, shape and name are checked against the signature
+        assert_eq!(ipt.call_function("axpy", vec![lhs.clone()]).is_err(), true);
+        let wrong_shape = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![1., 2., 3.], ElementType::F32, vec![1, 3]),
+        };
+        assert_eq!(
+            ipt.call_function("axpy", vec![lhs.clone(), wrong_shape])
+                .is_err(),
+            true
+        );
+        assert_eq!(ipt.call_function("gemm", vec![lhs, rhs]).is_err(), true);
+    }
+
+    #[test]
+    fn test_mock_optimized_program() {
+        use crate::assembler::passes::PassManager;
+        use crate::base::ElementType;
+        use crate::tensors::TensorView;
+
+        let source = "%0 = crt.helper.svalue.tensor! ones<[2 2]> : f32
+%1 = crt.literal.const.tensor! dense<[1. 2. 3. 4.], shape=[2 2]>: f32
+%2 = crt.add.f32! %0, %1 : f32
+%3 = crt.add.f32! %0, %1 : f32
+%4 = crt.mul.f32! %2, %3 : f32
+return %4
+func @f(%0: tensor<2x2xf32>) -> (tensor<2x2xf32>) {
+    %1 = crt.exp.f32! %0 : f32
+    %2 = crt.exp.f32! %0 : f32
+    %3 = crt.add.f32! %1, %2 : f32
+    %4 = crt.neg.f32! %3 : f32
+    return %3
+}
+";
+        let mut plain = Interpreter::new();
+        plain.init(2);
+        assert_eq!(plain.run_bytecode_eagerly(source).is_ok(), true);
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        ipt.set_passes(PassManager::standard());
+        assert_eq!(ipt.run_bytecode_eagerly(source).is_ok(), true);
+
+        // the top-level program folds into a literal, the function keeps an exp and an add
+        assert_eq!(
+            ipt.vm.inst_buffer().len() < plain.vm.inst_buffer().len(),
+            true
+        );
+        assert_eq!(ipt.functions["f"].instructions.len(), 3);
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            *plain.vm.get_raw_vec_f32(4),
+            rmax_all <= 0.00001
+        );
+        assert_float_eq!(
+            *ipt.vm.get_raw_vec_f32(4),
+            vec![4., 9., 16., 25.],
+            rmax_all <= 0.00001
+        );
+
+        let argument = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0., 1., 0., 1.], ElementType::F32, vec![2, 2]),
+        };
+        let expected = plain.call_function("f", vec![argument.clone()]).unwrap();
+        let results = ipt.call_function("f", vec![argument]).unwrap();
+        match (&results[0], &expected[0]) {
+            (ActTensorTypes::F32Tensor { data }, ActTensorTypes::F32Tensor { data: expected }) => {
+                assert_float_eq!(data.data, expected.data, rmax_all <= 0.00001)
+            }
+            _ => panic!("expects f32 result"),
+        }
+    }
+
+    #[test]
+    fn test_mock_fused_function() {
+        use crate::assembler::passes::{Pass, PassManager};
+        use crate::base::ElementType;
+        use crate::tensors::TensorView;
+
+        let source = "func @f(%0: tensor<2x2xf32>, %1: tensor<2xf32>) -> (tensor<2x2xf32>) {
+    %2 = crt.add.f32! %0, %1 : f32
+    %3 = crt.exp.f32! %2 : f32
+    %4 = crt.mul.f32! %3, %1 : f32
+    %5 = crt.sqrt.f32! %4 : f32
+    return %5
+}
+";
+        let mut plain = Interpreter::new();
+        plain.init(2);
+        assert_eq!(plain.run_bytecode_eagerly(source).is_ok(), true);
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let mut passes = PassManager::new();
+        passes.add_pass(Pass::ElementwiseFusion);
+        ipt.set_passes(passes);
+        assert_eq!(ipt.run_bytecode_eagerly(source).is_ok(), true);
+
+        // the chain takes a single op
+        assert_eq!(ipt.functions["f"].instructions.len(), 2);
+        let lhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0., 1., -1., 2.], ElementType::F32, vec![2, 2]),
+        };
+        let rhs = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![1., 4.], ElementType::F32, vec![2]),
+        };
+        let expected = plain
+            .call_function("f", vec![lhs.clone(), rhs.clone()])
+            .unwrap();
+        let results = ipt.call_function("f", vec![lhs, rhs]).unwrap();
+        assert_eq!(results[0].shape(), vec![2, 2]);
+        match (&results[0], &expected[0]) {
+            (ActTensorTypes::F32Tensor { data }, ActTensorTypes::F32Tensor { data: expected }) => {
+                assert_float_eq!(data.data, expected.data, rmax_all <= 0.00001)
+            }
+            _ => panic!("expects f32 result"),
+        }
+    }
+
+    #[test]
+    fn test_mock_syntax_error_is_recoverable() {
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2.], shape=[2]>: f32
+%1 = crt.exp.f32! %0 : f23
+",
+        );
+        match status {
+            Err(RuntimeStatusError::SYNTAX_ERROR(err)) => {
+                assert_eq!((err.line, err.column), (2, 24));
+                assert_eq!(err.snippet, "f23");
+            }
+            _ => panic!("expects a syntax error"),
+        }
+        // nothing of the failed bytecodes runs, and the interpreter keeps working
+        assert_eq!(ipt.vm.has_tensor(0), false);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2.], shape=[2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_raw_vec_f32(0), vec![1., 2.]);
+    }
+
+    #[test]
+    fn test_mock_run_module() {
+        let scalars: Vec<String> = (0..80).map(|x| x.to_string()).collect();
+        let source = format!(
+            "func @double(%0: tensor<80xf32>) -> (tensor<80xf32>) {{
+    %1 = crt.add.f32! %0, %0 : f32
+    return %1
+}}
+%0 = crt.literal.const.tensor! dense<[{}], shape=[80]> : f32
+%1 = crt.literal.const.tensor! dense<[1. 2.], shape=[2]> : f32
+",
+            scalars.join(" ")
+        );
+        let mut bytes = vec![];
+        assemble(&source).unwrap().write_to(&mut bytes).unwrap();
+        let module = Module::load_from(&mut &bytes[..]).unwrap();
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        // modules loaded earlier keep their constants
+        assert_eq!(ipt.run_module(&module).is_ok(), true);
+        assert_eq!(ipt.run_module(&module).is_ok(), true);
+        assert_eq!(ipt.vm.get_raw_vec_f32(1), vec![1., 2.]);
+        let expected: Vec<f32> = (0..80).map(|x| x as f32).collect();
+        assert_eq!(ipt.vm.get_raw_vec_f32(0), expected);
+
+        let argument = ipt.vm.get_tensor(&0).read().unwrap().clone();
+        let results = ipt.call_function("double", vec![argument]).unwrap();
+        assert_eq!(results[0].shape(), vec![80]);
+    }
+
+    #[test]
+    fn test_mock_named_values() {
+        use crate::base::constants::NAMED_SLOT_BASE;
+        use crate::base::ElementType;
+        use crate::tensors::TensorView;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%hidden1 = crt.literal.const.tensor! dense<[1. 2.], shape=[2]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        // names stay bound across bytecodes pushed into the same interpreter
+        let status = ipt.run_bytecode_eagerly(
+            "%w_q = crt.literal.const.tensor! dense<[3. 4.], shape=[2]>: f32\n\
+             %300 = crt.add.f32! %hidden1, %w_q : f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly("%out = crt.mul.f32! %300, %w_q : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(300),
+            vec![4., 6.],
+            rmax_all <= 0.00001
+        );
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(NAMED_SLOT_BASE + 2),
+            vec![12., 24.],
+            rmax_all <= 0.00001
+        );
+
+        // function arguments and results may be named as well
+        let status = ipt.run_bytecode_eagerly(
+            "func @axpy(%x: tensor<2xf32>, %y: tensor<2xf32>) -> (tensor<2xf32>) {
+    %xy = crt.mul.f32! %x, %y : f32
+    %axpy = crt.add.f32! %xy, %y : f32
+    return %axpy
+}
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        let x = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![1., 2.], ElementType::F32, vec![2]),
+        };
+        let y = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![3., 3.], ElementType::F32, vec![2]),
+        };
+        let results = ipt.call_function("axpy", vec![x, y]).unwrap();
+        match &results[0] {
+            ActTensorTypes::F32Tensor { data } => {
+                assert_float_eq!(data.data, vec![6., 9.], rmax_all <= 0.00001)
+            }
+            _ => panic!("expects f32 result"),
+        }
+    }
+
+    #[test]
+    fn test_mock_extended_dtypes() {
+        use crate::base::ElementType;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.f64! 1.5 : f64
+%1 = crt.literal.const.f64! 2.25 : f64
+%2 = crt.add.f32! %0, %1 : f64
+%3 = crt.cast.bf16! %2 : bf16
+%4 = crt.literal.const.i8! -7 : i8
+%5 = crt.cast.i32! %4 : i32
+%6 = crt.literal.const.bool! true : bool
+",
+        );
+        assert_eq!(status.is_ok(), true);
+
+        // f64 is computed natively on host
+        let sum = ipt.vm.get_tensor(&2);
+        assert_eq!(sum.read().unwrap().element_type(), ElementType::F64);
+        assert_eq!(sum.read().unwrap().to_f64_vec(), vec![3.75]);
+
+        // 3.75 is exact in bf16
+        let narrowed = ipt.vm.get_tensor(&3);
+        assert_eq!(narrowed.read().unwrap().element_type(), ElementType::BF16);
+        assert_eq!(narrowed.read().unwrap().to_f64_vec(), vec![3.75]);
+
+        assert_eq!(ipt.vm.get_raw_vec_i32(5), vec![-7]);
+        let mask = ipt.vm.get_tensor(&6);
+        assert_eq!(mask.read().unwrap().element_type(), ElementType::BOOL);
+        assert_eq!(mask.read().unwrap().to_i64_vec(), vec![1]);
+    }
+
+    #[test]
+    fn test_mock_split_and_multi_return() {
+        use crate::base::ElementType;
+        use crate::tensors::TensorView;
+
+        let mut ipt = Interpreter::new();
+        ipt.init(2);
+        let status = ipt.run_bytecode_eagerly(
+            "%0 = crt.literal.const.tensor! dense<[1. 2. 3. 4. 5. 6. 7. 8.], shape=[2 4]>: f32\n",
+        );
+        assert_eq!(status.is_ok(), true);
+        let status = ipt.run_bytecode_eagerly("%1, %2 = crt.tensor.split! %0, axis = 1 : f32\n");
+        assert_eq!(status.is_ok(), true);
+        assert_eq!(ipt.vm.get_tensor_shape(1), vec![2, 2]);
+        assert_eq!(ipt.vm.get_tensor_shape(2), vec![2, 2]);
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(1),
+            vec![1., 2., 5., 6.],
+            rmax_all <= 0.00001
+        );
+        assert_float_eq!(
+            ipt.vm.get_raw_vec_f32(2),
+            vec![3., 4., 7., 8.],
+            rmax_all <= 0.00001
+        );
+
+        // axis dim 4 is not divisible into 3 parts
+        let status =
+            ipt.run_bytecode_eagerly("%3, %4, %5 = crt.tensor.split! %0, axis = 1 : f32\n");
+        assert_eq!(status.is_err(), true);
+
+        // functions may return several values
+        let status = ipt.run_bytecode_eagerly(
+            "func @halves(%0: tensor<?x4xf32>) -> (tensor<?x2xf32>, tensor<?x2xf32>) {
+    %1, %2 = crt.tensor.split! %0, axis = -1 : f32
+    %3 = crt.add.f32! %1, %2 : f32
+    return %3, %2
+}
+",
+        );
+        assert_eq!(status.is_ok(), true);
+        let arg = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(
+                vec![1., 2., 3., 4., 5., 6., 7., 8.],
+                ElementType::F32,
+                vec![2, 4],
+            ),
+        };
+        let results = ipt.call_function("halves", vec![arg]).unwrap();
+        assert_eq!(results.len(), 2);
+        match (&results[0], &results[1]) {
+            (ActTensorTypes::F32Tensor { data: sum }, ActTensorTypes::F32Tensor { data: rhs }) => {
+                assert_float_eq!(sum.data, vec![4., 6., 12., 14.], rmax_all <= 0.00001);
+                assert_float_eq!(rhs.data, vec![3., 4., 7., 8.], rmax_all <= 0.00001);
+            }
+            _ => panic!("expects f32 results"),
+        }
+    }
+
     #[test]
     fn test_big_matrix_add() {
         // step 1, init device instance, also in VM instance init part
@@ -38,6 +38,10 @@ impl KernelRegistry {
         self.executable_cache_table.insert(query_entry, kernel);
     }
 
+    pub fn contains(&self, query_entry: &str) -> bool {
+        self.executable_cache_table.contains_key(query_entry)
+    }
+
     pub fn dispatch_kernel(
         &self,
         dc: &VkGPUExecutor,
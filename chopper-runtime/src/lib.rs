@@ -2,7 +2,6 @@
 extern crate nom;
 
 extern crate raptors;
-extern crate transpose;
 
 extern crate backend_vulkan as concrete_backend;
 extern crate hal;
@@ -16,6 +15,8 @@ pub mod assembler;
 pub mod base;
 pub mod buffer_types;
 pub mod executors;
+pub mod fusion;
+pub mod host_functor;
 pub mod instruction;
 pub mod interpreter;
 pub mod session;
@@ -52,13 +53,13 @@ use instruction::*;
 use interpreter::*;
 use numpy::ndarray::{array, ArrayD, ArrayViewD, ArrayViewMutD};
 use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayDyn, PyReadonlyArrayDyn, ToPyArray};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyTuple};
 use pyo3::wrap_pyfunction;
 use pyo3::{pymodule, types::PyModule, PyResult, Python};
 use session::*;
 use tensors::*;
-use transpose::*;
 use vkgpu_executor::*;
 
 #[cfg(feature = "vulkan")]
@@ -70,61 +71,90 @@ pub struct CallableModule {
     kernel_option: String,
 }
 
-// TODO hardcoded with explictiy PyArray2 types, consider PyTuple or other way to accept variadic
+// builtin @forward and @backward functions for kernel options, used when no bytecodes are given
 #[cfg(feature = "vulkan")]
-fn get_workaround_forward_bytecodes(kernel_option: &str) -> Vec<String> {
-    let mut bc_array: Vec<String> = vec![];
-
-    // TODO (now use manual way to load arg0 and arg1) load arguments for function at %0, %1 .. %arg_cnt
-    // bc_array.push(format!("%0 = crt.literal.const.tensor! dense<[0.0], shape=[1]>\n"));
-    // bc_array.push(format!("%1 = crt.literal.const.tensor! dense<[0.0], shape=[1]>\n"));
-
-    match kernel_option {
-        "add" => {
-            // add forward
-            bc_array.push(format!("%2 = crt.add.f32! %0, %1 : f32\n"));
-        }
-        "matmul" => {
-            // matmul forward
-            bc_array.push(format!("%2 = crt.matmul.f32! %0, %1 : f32\n"));
-        }
-        _ => {}
-    }
-
-    bc_array
+fn get_builtin_functions(kernel_option: &str) -> String {
+    let (forward, backward) = match kernel_option {
+        "matmul" => (
+            "%2 = crt.matmul.f32! %0, %1 : f32",
+            "%3 = crt.matmul.f32! %0, %2, transpose_b = true : f32\n\
+             %4 = crt.matmul.f32! %1, %0, transpose_a = true : f32",
+        ),
+        _ => (
+            "%2 = crt.add.f32! %0, %1 : f32",
+            "%3 = crt.add.f32! %0, %2 : f32\n%4 = crt.add.f32! %1, %0 : f32",
+        ),
+    };
+    format!(
+        "func @forward(%0: tensor<?x?xf32>, %1: tensor<?x?xf32>) -> (tensor<?x?xf32>) {{\n\
+         {}\nreturn %2\n}}\n\
+         func @backward(%0: tensor<?x?xf32>, %1: tensor<?x?xf32>, %2: tensor<?x?xf32>) \
+         -> (tensor<?x?xf32>, tensor<?x?xf32>) {{\n{}\nreturn %3, %4\n}}\n",
+        forward, backward
+    )
 }
 
-// TODO hardcoded with explictiy PyArray2 types, consider PyTuple or other way to accept variadic
+// syntax errors of bytecodes raise ValueError, other failures raise RuntimeError
 #[cfg(feature = "vulkan")]
-fn get_workaround_backward_bytecodes(kernel_option: &str) -> Vec<String> {
-    let mut bc_array: Vec<String> = vec![];
-
-    // TODO (now use manual way to load arg0 and arg1) load arguments for function at %0, %1 .. %arg_cnt
-    // bc_array.push(format!("%0 = crt.literal.const.tensor! dense<[0.0], shape=[1]>\n"));
-    // bc_array.push(format!("%1 = crt.literal.const.tensor! dense<[0.0], shape=[1]>\n"));
-    //
-    match kernel_option {
-        "add" => {
-            // add forward
-            // TODO make a unconsuming functor, 9 should be 0, reusage of register 0
-            bc_array.push(format!("%3 = crt.add.f32! %9, %2 : f32\n"));
-            bc_array.push(format!("%4 = crt.add.f32! %1, %0 : f32\n"));
+fn to_py_err(err: base::errors::RuntimeStatusError) -> PyErr {
+    match err {
+        base::errors::RuntimeStatusError::SYNTAX_ERROR(err) => {
+            PyValueError::new_err(format!("failed to assemble bytecodes, {}", err))
         }
-        "matmul" => {
-            // matmul forward
-            // TODO make a unconsuming functor
-            // TODO use tmp solution to transpose, so no need to transpose
-            // bc_array.push(format!("%6 = crt.tensor.transpose! %2, [1, 0] : f32\n"));
-            // bc_array.push(format!("%5 = crt.tensor.transpose! %1, [1, 0] : f32\n"));
-            // bc_array.push(format!("%3 = crt.matmul.f32! %9, %6 : f32\n"));
-            // bc_array.push(format!("%4 = crt.matmul.f32! %5, %0 : f32\n"));
-            bc_array.push(format!("%3 = crt.matmul.f32! %9, %2 : f32\n"));
-            bc_array.push(format!("%4 = crt.matmul.f32! %1, %0 : f32\n"));
+        base::errors::RuntimeStatusError::VERIFY_ERROR(errors) => {
+            let messages: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+            PyValueError::new_err(format!(
+                "failed to verify bytecodes, {}",
+                messages.join("; ")
+            ))
         }
-        _ => {}
+        err => PyRuntimeError::new_err(err.to_string()),
     }
+}
 
-    bc_array
+// calls the function defined by bytecodes, or the builtin one of the kernel option
+#[cfg(feature = "vulkan")]
+fn call_module_function(
+    module: &CallableModule,
+    name: &str,
+    args: Vec<&PyArray2<f32>>,
+) -> PyResult<Vec<Vec<f32>>> {
+    let mut ipt = interpreter::Interpreter::new();
+    // bytecodes of the frontend carry dead values and common subexpressions
+    ipt.set_passes(assembler::passes::PassManager::standard());
+    let source = match &module.bytecodes[..] {
+        "" => get_builtin_functions(&module.kernel_option[..]),
+        bytecodes => bytecodes.to_string(),
+    };
+    ipt.run_bytecode_eagerly(&source).map_err(to_py_err)?;
+
+    // parsing args and get func arguments and its shapes
+    // TODO change vec to array abstraction on databuffer
+    let arguments = args
+        .into_iter()
+        .map(|arg| {
+            let data = unsafe { arg.as_slice().unwrap() };
+            ActTensorTypes::F32Tensor {
+                data: TensorView::<f32>::new(
+                    data.to_vec(),
+                    base::ElementType::F32,
+                    arg.shape().to_vec(),
+                ),
+            }
+        })
+        .collect();
+
+    ipt.call_function(name, arguments)
+        .map_err(to_py_err)?
+        .into_iter()
+        .map(|result| match result {
+            ActTensorTypes::F32Tensor { data } => Ok(data.data),
+            _ => Err(PyRuntimeError::new_err(format!(
+                "@{} must return f32 tensors",
+                name
+            ))),
+        })
+        .collect()
 }
 
 #[cfg(feature = "vulkan")]
@@ -160,36 +190,10 @@ impl CallableModule {
         arg0: &PyArray2<f32>,
         arg1: &PyArray2<f32>,
         kwargs: Option<&PyDict>,
-    ) -> &'py PyArray1<f32> {
-        // println!("create interpreter");
-        let mut ipt = interpreter::Interpreter::new();
-
-        let lhs_operand = 0;
-        let rhs_operand = 1;
-        let outs_register = 2;
-
-        // parsing args and get func arguments and its shapes
-        // TODO change vec to array abstraction on databuffer
-        let shape0 = arg0.shape();
-        let data0 = unsafe { arg0.as_slice().unwrap() };
-        let shape1 = arg1.shape();
-        let data1 = unsafe { arg1.as_slice().unwrap() };
-        // println!("{:?}", data1);
-        // TODO tmp HARDCODE for demo use
-        ipt.vm
-            .push_tensor_buffer(lhs_operand, data0.to_vec(), shape0.to_vec());
-        ipt.vm
-            .push_tensor_buffer(rhs_operand, data1.to_vec(), shape1.to_vec());
-
-        // let bytecode_array = get_workaround_forward_bytecodes("add");
-        let bytecode_array = get_workaround_forward_bytecodes(&self.kernel_option[..]);
-        for _bytecode_string in bytecode_array {
-            // println!("Executing {}", _bytecode_string.as_str());
-            let status = ipt.run_bytecode(_bytecode_string);
-        }
-
-        let out_data = ipt.vm.get_fdata(outs_register).to_vec();
-        out_data.to_pyarray(py)
+    ) -> PyResult<&'py PyArray1<f32>> {
+        // bytecodes must define @forward, arguments are bound to it by position
+        let results = call_module_function(self, "forward", vec![arg0, arg1])?;
+        Ok(results[0].to_pyarray(py))
         //let _data = vec![
         //    outs_dataview.data[0..3],
         //    outs_dataview.data[3..6],
@@ -211,58 +215,10 @@ impl CallableModule {
         arg1: &PyArray2<f32>,
         arg2: &PyArray2<f32>,
         kwargs: Option<&PyDict>,
-    ) -> (&'py PyArray1<f32>, &'py PyArray1<f32>) {
-        // println!("create interpreter");
-        let mut ipt = interpreter::Interpreter::new();
-
-        let grad = 0;
-        let gradworkaroundduplicate = 9;
-        let act0 = 1;
-        let act1 = 2;
-        let lhs_grad = 3;
-        let rhs_grad = 4;
-
-        // parsing args and get func arguments and its shapes
-        // TODO change vec to array abstraction on databuffer
-        let shape0 = arg0.shape();
-        let data0 = unsafe { arg0.as_slice().unwrap() };
-        let shape1 = arg1.shape();
-        let data1 = unsafe { arg1.as_slice().unwrap() };
-        let shape2 = arg2.shape();
-        let data2 = unsafe { arg2.as_slice().unwrap() };
-
-        let mut data1_transposed = vec![0.0; data1.len()];
-        transpose::transpose(&data1.to_vec(), &mut data1_transposed, shape1[0], shape1[1]);
-        let shape1_transposed = vec![shape1[1], shape1[0]];
-        let mut data2_transposed = vec![0.0; data2.len()];
-        transpose::transpose(&data2.to_vec(), &mut data2_transposed, shape2[0], shape2[1]);
-        let shape2_transposed = vec![shape2[1], shape2[0]];
-        //println!("{:?}", data1);
-        //println!("{:?}", data1_transposed);
-        //assert_eq!(0, 1);
-
-        // TODO tmp HARDCODE for demo use
-        ipt.vm
-            .push_tensor_buffer(grad, data0.to_vec(), shape0.to_vec());
-        ipt.vm
-            .push_tensor_buffer(gradworkaroundduplicate, data0.to_vec(), shape0.to_vec());
-        ipt.vm
-            .push_tensor_buffer(act0, data1_transposed, shape1_transposed);
-        ipt.vm
-            .push_tensor_buffer(act1, data2_transposed, shape2_transposed);
-        // TODO use tmp solution to transpose
-        //ipt.vm.push_tensor_buffer(act0, data1.to_vec(), shape1.to_vec());
-        //ipt.vm.push_tensor_buffer(act1, data2.to_vec(), shape2.to_vec());
-
-        let bytecode_array = get_workaround_backward_bytecodes(&self.kernel_option[..]);
-        for _bytecode_string in bytecode_array {
-            // println!("Executing {}", _bytecode_string.as_str());
-            let status = ipt.run_bytecode(_bytecode_string);
-        }
-
-        let lhs_outs = ipt.vm.get_fdata(lhs_grad).to_vec();
-        let rhs_outs = ipt.vm.get_fdata(rhs_grad).to_vec();
-        (lhs_outs.to_pyarray(py), rhs_outs.to_pyarray(py))
+    ) -> PyResult<(&'py PyArray1<f32>, &'py PyArray1<f32>)> {
+        // @backward takes the grad and both activations, returns grads of both operands
+        let results = call_module_function(self, "backward", vec![arg0, arg1, arg2])?;
+        Ok((results[0].to_pyarray(py), results[1].to_pyarray(py)))
     }
 }
 
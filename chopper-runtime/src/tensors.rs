@@ -1,5 +1,7 @@
 use std::{borrow::Cow, fs, iter, ptr, slice, str::FromStr, sync::Arc};
 
+use half::{bf16, f16};
+
 use raptors::prelude::*;
 
 #[cfg(any(feature = "mock", feature = "blas"))]
@@ -7,6 +9,7 @@ use rublas::prelude::*;
 
 use crate::base::constants::*;
 use crate::buffer_types::*;
+use crate::fusion::FusedExpr;
 use crate::vkgpu_executor::*;
 
 use crate::base::*;
@@ -15,7 +18,16 @@ use crate::base::*;
 pub enum ActTensorTypes {
     F32Tensor { data: TensorView<f32> },
     I32Tensor { data: TensorView<i32> },
+    F16Tensor { data: TensorView<f16> },
+    BF16Tensor { data: TensorView<bf16> },
+    I8Tensor { data: TensorView<i8> },
+    U8Tensor { data: TensorView<u8> },
+    F64Tensor { data: TensorView<f64> },
+    I64Tensor { data: TensorView<i64> },
     MockTensor { data: MockTensor },
+    // operands packed for ops that take more tensors than executors can carry, such as the
+    // input and the weight of conv2d
+    TupleTensor { data: Vec<ActTensorTypes> },
 }
 
 impl TensorLike for ActTensorTypes {}
@@ -37,8 +49,665 @@ impl<T> TensorView<T> {
     }
 }
 
+// numpy-style broadcasting, shapes are aligned from the trailing dims and each pair of dims
+// must be equal or one of them is 1. returns None if shapes are not broadcastable
+pub fn broadcast_shape(lhs_shape: &[usize], rhs_shape: &[usize]) -> Option<Vec<usize>> {
+    let rank = lhs_shape.len().max(rhs_shape.len());
+    let lhs_shape = expand_shape(lhs_shape, rank);
+    let rhs_shape = expand_shape(rhs_shape, rank);
+    let mut shape = Vec::<usize>::with_capacity(rank);
+    for (l, r) in lhs_shape.iter().zip(rhs_shape.iter()) {
+        match (*l, *r) {
+            (l, r) if l == r => shape.push(l),
+            (1, r) => shape.push(r),
+            (l, 1) => shape.push(l),
+            _ => return None,
+        }
+    }
+    Some(shape)
+}
+
+// prepend dims of size 1 up to rank
+pub fn expand_shape(shape: &[usize], rank: usize) -> Vec<usize> {
+    assert!(shape.len() <= rank);
+    let mut expanded = vec![1; rank - shape.len()];
+    expanded.extend_from_slice(shape);
+    expanded
+}
+
+// strides to index a tensor of shape from the broadcasted out_shape, broadcasted dims get 0
+pub fn broadcast_strides(shape: &[usize], out_shape: &[usize]) -> Vec<usize> {
+    let shape = expand_shape(shape, out_shape.len());
+    let mut strides = vec![0; shape.len()];
+    let mut stride = 1;
+    for d in (0..shape.len()).rev() {
+        if shape[d] != 1 {
+            strides[d] = stride;
+        }
+        stride *= shape[d];
+    }
+    strides
+}
+
+// row-major strides of a contiguous tensor
+pub fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for d in (0..shape.len().saturating_sub(1)).rev() {
+        strides[d] = strides[d + 1] * shape[d + 1];
+    }
+    strides
+}
+
+// values of arange in [start, stop) by step
+pub fn arange_values(start: f64, stop: f64, step: f64) -> Vec<f64> {
+    let len = ((stop - start) / step).ceil().max(0f64) as usize;
+    (0..len).map(|i| start + i as f64 * step).collect()
+}
+
+// num values evenly spaced in [start, stop], both ends included
+pub fn linspace_values(start: f64, stop: f64, num: usize) -> Vec<f64> {
+    match num {
+        1 => vec![start],
+        _ => (0..num)
+            .map(|i| start + (stop - start) * i as f64 / (num - 1) as f64)
+            .collect(),
+    }
+}
+
+// identity matrix of rows x cols in row major
+pub fn eye_values(rows: usize, cols: usize) -> Vec<f64> {
+    (0..rows * cols)
+        .map(|i| (i / cols == i % cols) as i32 as f64)
+        .collect()
+}
+
+// drop the given axes that must be of size 1, or all dims of size 1 if axes is empty
+pub fn squeeze_shape(shape: &[usize], axes: &[usize]) -> Vec<usize> {
+    for axis in axes {
+        assert!(
+            *axis < shape.len() && shape[*axis] == 1,
+            "cannot squeeze axis {} of shape {:?}",
+            axis,
+            shape
+        );
+    }
+    shape
+        .iter()
+        .enumerate()
+        .filter(|(d, dim)| **dim != 1 || (!axes.is_empty() && !axes.contains(d)))
+        .map(|(_, dim)| *dim)
+        .collect()
+}
+
+// insert dims of size 1, axes are positions in the result shape
+pub fn unsqueeze_shape(shape: &[usize], axes: &[usize]) -> Vec<usize> {
+    let mut axes = axes.to_vec();
+    axes.sort();
+    let mut unsqueezed = shape.to_vec();
+    for axis in axes {
+        assert!(
+            axis <= unsqueezed.len(),
+            "cannot unsqueeze axis {} of shape {:?}",
+            axis,
+            shape
+        );
+        unsqueezed.insert(axis, 1);
+    }
+    unsqueezed
+}
+
+// shape of result when reducing over `axis`, or over all axes if it is None
+pub fn reduce_shape(shape: &[usize], axis: Option<usize>, keepdims: bool) -> Vec<usize> {
+    match axis {
+        Some(axis) => {
+            assert!(axis < shape.len(), "reduce axis out of range");
+            let mut shape = shape.to_vec();
+            if keepdims {
+                shape[axis] = 1;
+            } else {
+                shape.remove(axis);
+            }
+            shape
+        }
+        None => match keepdims {
+            true => vec![1; shape.len()],
+            false => vec![],
+        },
+    }
+}
+
+// attributes of conv2d, pairs are in order of [height, width]. Carried by executors as i32
+// tensor of [stride_h, stride_w, padding_h, padding_w, dilation_h, dilation_w, groups]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conv2dAttr {
+    pub stride: [usize; 2],
+    pub padding: [usize; 2],
+    pub dilation: [usize; 2],
+    pub groups: usize,
+}
+
+impl Conv2dAttr {
+    pub fn from_vec(attr: &[usize]) -> Self {
+        assert_eq!(attr.len(), 7, "conv2d attributes must have 7 values");
+        Self {
+            stride: [attr[0], attr[1]],
+            padding: [attr[2], attr[3]],
+            dilation: [attr[4], attr[5]],
+            groups: attr[6],
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<usize> {
+        vec![
+            self.stride[0],
+            self.stride[1],
+            self.padding[0],
+            self.padding[1],
+            self.dilation[0],
+            self.dilation[1],
+            self.groups,
+        ]
+    }
+}
+
+// attributes of 2-D pooling, pairs are in order of [height, width]. Carried by executors as i32
+// tensor of [kernel_h, kernel_w, stride_h, stride_w, padding_h, padding_w]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pool2dAttr {
+    pub kernel: [usize; 2],
+    pub stride: [usize; 2],
+    pub padding: [usize; 2],
+}
+
+impl Pool2dAttr {
+    pub fn from_vec(attr: &[usize]) -> Self {
+        assert_eq!(attr.len(), 6, "pool2d attributes must have 6 values");
+        Self {
+            kernel: [attr[0], attr[1]],
+            stride: [attr[2], attr[3]],
+            padding: [attr[4], attr[5]],
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<usize> {
+        vec![
+            self.kernel[0],
+            self.kernel[1],
+            self.stride[0],
+            self.stride[1],
+            self.padding[0],
+            self.padding[1],
+        ]
+    }
+}
+
+// length of a spatial dim of sliding window ops, None if the dilated window exceeds the padded
+// input
+pub fn window_out_dim(
+    in_dim: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+) -> Option<usize> {
+    let window = dilation * (kernel.max(1) - 1) + 1;
+    match stride > 0 && in_dim + 2 * padding >= window {
+        true => Some((in_dim + 2 * padding - window) / stride + 1),
+        false => None,
+    }
+}
+
+// input is [N, C, H, W] and weight is [O, C / groups, KH, KW], result is [N, O, OH, OW].
+// returns None if shapes are not compatible
+pub fn conv2d_shape(
+    in_shape: &[usize],
+    weight_shape: &[usize],
+    attr: &Conv2dAttr,
+) -> Option<Vec<usize>> {
+    if in_shape.len() != 4 || weight_shape.len() != 4 || attr.groups == 0 {
+        return None;
+    }
+    if in_shape[1] % attr.groups != 0
+        || weight_shape[0] % attr.groups != 0
+        || weight_shape[1] * attr.groups != in_shape[1]
+    {
+        return None;
+    }
+    let out_h = window_out_dim(
+        in_shape[2],
+        weight_shape[2],
+        attr.stride[0],
+        attr.padding[0],
+        attr.dilation[0],
+    )?;
+    let out_w = window_out_dim(
+        in_shape[3],
+        weight_shape[3],
+        attr.stride[1],
+        attr.padding[1],
+        attr.dilation[1],
+    )?;
+    Some(vec![in_shape[0], weight_shape[0], out_h, out_w])
+}
+
+// input is [N, C, H, W], result is [N, C, OH, OW]
+pub fn pool2d_shape(in_shape: &[usize], attr: &Pool2dAttr) -> Option<Vec<usize>> {
+    if in_shape.len() != 4 || attr.kernel[0] == 0 || attr.kernel[1] == 0 {
+        return None;
+    }
+    let out_h = window_out_dim(
+        in_shape[2],
+        attr.kernel[0],
+        attr.stride[0],
+        attr.padding[0],
+        1,
+    )?;
+    let out_w = window_out_dim(
+        in_shape[3],
+        attr.kernel[1],
+        attr.stride[1],
+        attr.padding[1],
+        1,
+    )?;
+    Some(vec![in_shape[0], in_shape[1], out_h, out_w])
+}
+
+// operands are [..., M, K] and [..., K, N] after transposing their last two dims, batch dims
+// broadcast in NumPy style and result is [batch..., M, N]. returns None if shapes are not compatible
+pub fn matmul_shape(
+    lhs_shape: &[usize],
+    rhs_shape: &[usize],
+    transpose_a: bool,
+    transpose_b: bool,
+) -> Option<Vec<usize>> {
+    let (lhs_rank, rhs_rank) = (lhs_shape.len(), rhs_shape.len());
+    if lhs_rank < 2 || rhs_rank < 2 {
+        return None;
+    }
+    let (m, lhs_k) = match transpose_a {
+        true => (lhs_shape[lhs_rank - 1], lhs_shape[lhs_rank - 2]),
+        false => (lhs_shape[lhs_rank - 2], lhs_shape[lhs_rank - 1]),
+    };
+    let (rhs_k, n) = match transpose_b {
+        true => (rhs_shape[rhs_rank - 1], rhs_shape[rhs_rank - 2]),
+        false => (rhs_shape[rhs_rank - 2], rhs_shape[rhs_rank - 1]),
+    };
+    if lhs_k != rhs_k {
+        return None;
+    }
+    let mut shape = broadcast_shape(&lhs_shape[..lhs_rank - 2], &rhs_shape[..rhs_rank - 2])?;
+    shape.push(m);
+    shape.push(n);
+    Some(shape)
+}
+
+impl<T> TensorView<T> {
+    pub fn reduce_shape(&self, axis: Option<usize>, keepdims: bool) -> Vec<usize> {
+        reduce_shape(&self.shape, axis, keepdims)
+    }
+
+    // view the shape as [outer, axis_len, inner] around the reduced axis
+    pub fn reduce_view(&self, axis: Option<usize>) -> (usize, usize, usize) {
+        match axis {
+            Some(axis) => (
+                self.shape[..axis].iter().product(),
+                self.shape[axis],
+                self.shape[axis + 1..].iter().product(),
+            ),
+            None => (1, self.data.len(), 1),
+        }
+    }
+}
+
 impl<T> TensorLike for TensorView<T> {}
 
+impl ActTensorTypes {
+    // metadata-only, data is kept in place
+    pub fn reshape(&mut self, shape: Vec<usize>) {
+        let size: usize = shape.iter().product();
+        let old_shape = self.shape();
+        assert_eq!(
+            size,
+            old_shape.iter().product(),
+            "cannot reshape {:?} into {:?}",
+            old_shape,
+            shape
+        );
+        match self {
+            ActTensorTypes::F32Tensor { data } => data.shape = shape,
+            ActTensorTypes::I32Tensor { data } => data.shape = shape,
+            ActTensorTypes::F16Tensor { data } => data.shape = shape,
+            ActTensorTypes::BF16Tensor { data } => data.shape = shape,
+            ActTensorTypes::I8Tensor { data } => data.shape = shape,
+            ActTensorTypes::U8Tensor { data } => data.shape = shape,
+            ActTensorTypes::F64Tensor { data } => data.shape = shape,
+            ActTensorTypes::I64Tensor { data } => data.shape = shape,
+            _ => panic!("not support mock types"),
+        }
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        match self {
+            ActTensorTypes::F32Tensor { data } => data.shape.clone(),
+            ActTensorTypes::I32Tensor { data } => data.shape.clone(),
+            ActTensorTypes::F16Tensor { data } => data.shape.clone(),
+            ActTensorTypes::BF16Tensor { data } => data.shape.clone(),
+            ActTensorTypes::I8Tensor { data } => data.shape.clone(),
+            ActTensorTypes::U8Tensor { data } => data.shape.clone(),
+            ActTensorTypes::F64Tensor { data } => data.shape.clone(),
+            ActTensorTypes::I64Tensor { data } => data.shape.clone(),
+            _ => panic!("not support mock types"),
+        }
+    }
+
+    pub fn element_type(&self) -> ElementType {
+        match self {
+            ActTensorTypes::F32Tensor { data } => data.dtype,
+            ActTensorTypes::I32Tensor { data } => data.dtype,
+            ActTensorTypes::F16Tensor { data } => data.dtype,
+            ActTensorTypes::BF16Tensor { data } => data.dtype,
+            ActTensorTypes::I8Tensor { data } => data.dtype,
+            ActTensorTypes::U8Tensor { data } => data.dtype,
+            ActTensorTypes::F64Tensor { data } => data.dtype,
+            ActTensorTypes::I64Tensor { data } => data.dtype,
+            _ => panic!("not support mock types"),
+        }
+    }
+
+    // vulkan kernels and the mock/blas executors only cover f32 and i32. f16, bf16, i8, u8, f64
+    // and i64 tensors are computed by HostFunctor, but for f64 and i64 binary arithmetic that the
+    // vulkan executor runs on device when it offers the 64-bit shader features
+    pub fn is_host_only(&self) -> bool {
+        match self {
+            ActTensorTypes::F16Tensor { .. }
+            | ActTensorTypes::BF16Tensor { .. }
+            | ActTensorTypes::I8Tensor { .. }
+            | ActTensorTypes::U8Tensor { .. }
+            | ActTensorTypes::F64Tensor { .. }
+            | ActTensorTypes::I64Tensor { .. } => true,
+            _ => false,
+        }
+    }
+
+    // values widened into f64, exact but for i64 beyond 2^53
+    pub fn to_f64_vec(&self) -> Vec<f64> {
+        match self {
+            ActTensorTypes::F32Tensor { data } => data.data.iter().map(|x| *x as f64).collect(),
+            ActTensorTypes::I32Tensor { data } => data.data.iter().map(|x| *x as f64).collect(),
+            ActTensorTypes::F16Tensor { data } => data.data.iter().map(|x| x.to_f64()).collect(),
+            ActTensorTypes::BF16Tensor { data } => data.data.iter().map(|x| x.to_f64()).collect(),
+            ActTensorTypes::I8Tensor { data } => data.data.iter().map(|x| *x as f64).collect(),
+            ActTensorTypes::U8Tensor { data } => data.data.iter().map(|x| *x as f64).collect(),
+            ActTensorTypes::F64Tensor { data } => data.data.clone(),
+            ActTensorTypes::I64Tensor { data } => data.data.iter().map(|x| *x as f64).collect(),
+            _ => panic!("not support mock types"),
+        }
+    }
+
+    // values widened into i64, floats truncate towards zero
+    pub fn to_i64_vec(&self) -> Vec<i64> {
+        match self {
+            ActTensorTypes::I32Tensor { data } => data.data.iter().map(|x| *x as i64).collect(),
+            ActTensorTypes::I8Tensor { data } => data.data.iter().map(|x| *x as i64).collect(),
+            ActTensorTypes::U8Tensor { data } => data.data.iter().map(|x| *x as i64).collect(),
+            ActTensorTypes::I64Tensor { data } => data.data.clone(),
+            _ => self.to_f64_vec().into_iter().map(|x| x as i64).collect(),
+        }
+    }
+
+    // floats converted into integers truncate towards zero and saturate, NaN becomes 0.
+    // bools hold 0 or 1 in i32 tensors
+    pub fn from_f64_vec(data: Vec<f64>, element_type: ElementType, shape: Vec<usize>) -> Self {
+        match element_type {
+            ElementType::F32 => ActTensorTypes::F32Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| *x as f32).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::F16 => ActTensorTypes::F16Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| f16::from_f64(*x)).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::BF16 => ActTensorTypes::BF16Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| bf16::from_f64(*x)).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::F64 => ActTensorTypes::F64Tensor {
+                data: TensorView::new(data, element_type, shape),
+            },
+            ElementType::BOOL => ActTensorTypes::I32Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| (*x != 0f64) as i32).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I32 => ActTensorTypes::I32Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| *x as i32).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I8 => ActTensorTypes::I8Tensor {
+                data: TensorView::new(data.iter().map(|x| *x as i8).collect(), element_type, shape),
+            },
+            ElementType::U8 => ActTensorTypes::U8Tensor {
+                data: TensorView::new(data.iter().map(|x| *x as u8).collect(), element_type, shape),
+            },
+            ElementType::I64 => ActTensorTypes::I64Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| *x as i64).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+        }
+    }
+
+    // integers narrowed into smaller ones wrap around
+    pub fn from_i64_vec(data: Vec<i64>, element_type: ElementType, shape: Vec<usize>) -> Self {
+        match element_type {
+            ElementType::I32 => ActTensorTypes::I32Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| *x as i32).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I8 => ActTensorTypes::I8Tensor {
+                data: TensorView::new(data.iter().map(|x| *x as i8).collect(), element_type, shape),
+            },
+            ElementType::U8 => ActTensorTypes::U8Tensor {
+                data: TensorView::new(data.iter().map(|x| *x as u8).collect(), element_type, shape),
+            },
+            ElementType::I64 => ActTensorTypes::I64Tensor {
+                data: TensorView::new(data, element_type, shape),
+            },
+            ElementType::BOOL => ActTensorTypes::I32Tensor {
+                data: TensorView::new(
+                    data.iter().map(|x| (*x != 0) as i32).collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            _ => Self::from_f64_vec(
+                data.into_iter().map(|x| x as f64).collect(),
+                element_type,
+                shape,
+            ),
+        }
+    }
+
+    // decodes little endian scalars of literals, each takes element_type.byte_width() bytes
+    pub fn from_le_bytes(bytes: &[u8], element_type: ElementType, shape: Vec<usize>) -> Self {
+        let scalars = bytes.chunks_exact(element_type.byte_width());
+        match element_type {
+            ElementType::F32 => ActTensorTypes::F32Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| f32::from_le_bytes(x.try_into().unwrap()))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I32 => ActTensorTypes::I32Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| i32::from_le_bytes(x.try_into().unwrap()))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::F16 => ActTensorTypes::F16Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| f16::from_bits(u16::from_le_bytes(x.try_into().unwrap())))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::BF16 => ActTensorTypes::BF16Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| bf16::from_bits(u16::from_le_bytes(x.try_into().unwrap())))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::F64 => ActTensorTypes::F64Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| f64::from_le_bytes(x.try_into().unwrap()))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I64 => ActTensorTypes::I64Tensor {
+                data: TensorView::new(
+                    scalars
+                        .map(|x| i64::from_le_bytes(x.try_into().unwrap()))
+                        .collect(),
+                    element_type,
+                    shape,
+                ),
+            },
+            ElementType::I8 | ElementType::U8 | ElementType::BOOL => Self::from_i64_vec(
+                bytes
+                    .iter()
+                    .map(|x| match element_type {
+                        ElementType::I8 => *x as i8 as i64,
+                        _ => *x as i64,
+                    })
+                    .collect(),
+                element_type,
+                shape,
+            ),
+        }
+    }
+
+    // encodes scalars in little endian, the inverse of from_le_bytes
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        match self {
+            ActTensorTypes::F32Tensor { data } => {
+                data.data.iter().flat_map(|x| x.to_le_bytes()).collect()
+            }
+            ActTensorTypes::I32Tensor { data } => match data.dtype {
+                ElementType::BOOL => data.data.iter().map(|x| (*x != 0) as u8).collect(),
+                _ => data.data.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            },
+            ActTensorTypes::F16Tensor { data } => data
+                .data
+                .iter()
+                .flat_map(|x| x.to_bits().to_le_bytes())
+                .collect(),
+            ActTensorTypes::BF16Tensor { data } => data
+                .data
+                .iter()
+                .flat_map(|x| x.to_bits().to_le_bytes())
+                .collect(),
+            ActTensorTypes::I8Tensor { data } => data.data.iter().map(|x| *x as u8).collect(),
+            ActTensorTypes::U8Tensor { data } => data.data.clone(),
+            ActTensorTypes::F64Tensor { data } => {
+                data.data.iter().flat_map(|x| x.to_le_bytes()).collect()
+            }
+            ActTensorTypes::I64Tensor { data } => {
+                data.data.iter().flat_map(|x| x.to_le_bytes()).collect()
+            }
+            _ => panic!("not support mock types"),
+        }
+    }
+
+    // attributes of sliding window ops are carried as i32 tensor of non-negative values
+    fn to_attr_vec(&self) -> Vec<usize> {
+        match self {
+            ActTensorTypes::I32Tensor { data } => data.data.iter().map(|x| *x as usize).collect(),
+            _ => panic!("attributes must be an i32 tensor"),
+        }
+    }
+
+    pub fn to_conv2d_attr(&self) -> Conv2dAttr {
+        Conv2dAttr::from_vec(&self.to_attr_vec())
+    }
+
+    pub fn to_pool2d_attr(&self) -> Pool2dAttr {
+        Pool2dAttr::from_vec(&self.to_attr_vec())
+    }
+
+    // transpose flags of matmul are carried as i32 tensor of [transpose_a, transpose_b]
+    pub fn to_matmul_attr(&self) -> (bool, bool) {
+        let attr = self.to_attr_vec();
+        (attr[0] != 0, attr[1] != 0)
+    }
+
+    // fused ops carry their expressions as i32 tensor of the serialized bytes
+    pub fn from_fused_expr(expr: &FusedExpr) -> ActTensorTypes {
+        let bytes: Vec<i32> = bincode::serialize(expr)
+            .unwrap()
+            .iter()
+            .map(|x| *x as i32)
+            .collect();
+        let len = bytes.len();
+        ActTensorTypes::I32Tensor {
+            data: TensorView::new(bytes, ElementType::I32, vec![len]),
+        }
+    }
+
+    pub fn to_fused_expr(&self) -> FusedExpr {
+        let bytes: Vec<u8> = self.to_attr_vec().iter().map(|x| *x as u8).collect();
+        bincode::deserialize(&bytes).expect("malformed fused expression")
+    }
+
+    // attributes of reduction ops are carried as i32 tensor of [axis, keepdims],
+    // where a negative axis means reducing over all axes
+    pub fn to_reduce_attr(&self) -> (Option<usize>, bool) {
+        match self {
+            ActTensorTypes::I32Tensor { data } => {
+                assert_eq!(data.data.len(), 2);
+                let axis = match data.data[0] {
+                    x if x < 0 => None,
+                    x => Some(x as usize),
+                };
+                (axis, data.data[1] != 0)
+            }
+            _ => panic!("reduce attributes must be i32 tensor"),
+        }
+    }
+}
+
 #[cfg(any(feature = "mock", feature = "blas"))]
 impl From<TensorView<f32>> for BlasTensor {
     fn from(item: TensorView<f32>) -> Self {
@@ -106,4 +775,150 @@ mod tests {
     fn dummy_test() {
         assert_eq!(0, 0);
     }
+
+    #[test]
+    fn test_generated_values() {
+        assert_eq!(arange_values(0., 10., 3.), vec![0., 3., 6., 9.]);
+        assert_eq!(arange_values(1., -1., -0.5), vec![1., 0.5, 0., -0.5]);
+        assert_eq!(arange_values(2., 1., 1.), Vec::<f64>::new());
+        assert_eq!(linspace_values(0., 1., 5), vec![0., 0.25, 0.5, 0.75, 1.]);
+        assert_eq!(linspace_values(3., 7., 1), vec![3.]);
+        assert_eq!(eye_values(2, 3), vec![1., 0., 0., 0., 1., 0.]);
+    }
+
+    #[test]
+    fn test_extended_dtype_conversion() {
+        let tensor =
+            ActTensorTypes::from_f64_vec(vec![1.5, -2.25, 300.0], ElementType::F16, vec![3]);
+        assert_eq!(tensor.element_type(), ElementType::F16);
+        assert_eq!(tensor.is_host_only(), true);
+        assert_eq!(tensor.to_f64_vec(), vec![1.5, -2.25, 300.0]);
+
+        // floats truncate and saturate, integers wrap
+        let tensor =
+            ActTensorTypes::from_f64_vec(vec![-1.7, 300.0, f64::NAN], ElementType::U8, vec![3]);
+        assert_eq!(tensor.to_i64_vec(), vec![0, 255, 0]);
+        let tensor = ActTensorTypes::from_i64_vec(vec![-1, 300], ElementType::U8, vec![2]);
+        assert_eq!(tensor.to_i64_vec(), vec![255, 44]);
+        let tensor = ActTensorTypes::from_i64_vec(vec![i64::MAX, -3], ElementType::I64, vec![2]);
+        assert_eq!(tensor.to_i64_vec(), vec![i64::MAX, -3]);
+
+        // bools are carried by i32 tensors
+        let tensor = ActTensorTypes::from_f64_vec(vec![0.0, -0.5], ElementType::BOOL, vec![2]);
+        assert_eq!(tensor.element_type(), ElementType::BOOL);
+        assert_eq!(tensor.is_host_only(), false);
+        assert_eq!(tensor.to_i64_vec(), vec![0, 1]);
+
+        let bytes = [
+            bf16::from_f32(0.5).to_bits().to_le_bytes(),
+            bf16::from_f32(-4.0).to_bits().to_le_bytes(),
+        ]
+        .concat();
+        let tensor = ActTensorTypes::from_le_bytes(&bytes, ElementType::BF16, vec![2, 1]);
+        assert_eq!(tensor.shape(), vec![2, 1]);
+        assert_eq!(tensor.to_f64_vec(), vec![0.5, -4.0]);
+        let tensor = ActTensorTypes::from_le_bytes(&[0xff, 0x02], ElementType::I8, vec![2]);
+        assert_eq!(tensor.to_i64_vec(), vec![-1, 2]);
+        let tensor =
+            ActTensorTypes::from_le_bytes(&(-5i64).to_le_bytes(), ElementType::I64, vec![]);
+        assert_eq!(tensor.to_i64_vec(), vec![-5]);
+        assert_eq!(tensor.to_le_bytes(), (-5i64).to_le_bytes().to_vec());
+
+        // bools take a byte each
+        let tensor = ActTensorTypes::from_le_bytes(&[1, 0, 1], ElementType::BOOL, vec![3]);
+        assert_eq!(tensor.to_le_bytes(), vec![1, 0, 1]);
+        assert_eq!(
+            ActTensorTypes::from_le_bytes(&bytes, ElementType::BF16, vec![2]).to_le_bytes(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_broadcast_shape() {
+        assert_eq!(broadcast_shape(&[4, 3], &[3]), Some(vec![4, 3]));
+        assert_eq!(broadcast_shape(&[2, 1, 3], &[4, 1]), Some(vec![2, 4, 3]));
+        assert_eq!(broadcast_shape(&[2, 3], &[2, 3]), Some(vec![2, 3]));
+        assert_eq!(broadcast_shape(&[], &[2]), Some(vec![2]));
+        assert_eq!(broadcast_shape(&[4, 3], &[4]), None);
+        assert_eq!(broadcast_strides(&[3], &[4, 3]), vec![0, 1]);
+        assert_eq!(broadcast_strides(&[4, 1], &[2, 4, 3]), vec![0, 1, 0]);
+        assert_eq!(broadcast_strides(&[2, 3], &[2, 3]), vec![3, 1]);
+    }
+
+    #[test]
+    fn test_shape_helpers() {
+        assert_eq!(contiguous_strides(&[2, 3, 4]), vec![12, 4, 1]);
+        assert_eq!(contiguous_strides(&[]), Vec::<usize>::new());
+        assert_eq!(squeeze_shape(&[1, 3, 1, 2], &[]), vec![3, 2]);
+        assert_eq!(squeeze_shape(&[1, 3, 1, 2], &[2]), vec![1, 3, 2]);
+        assert_eq!(unsqueeze_shape(&[3, 2], &[0, 2]), vec![1, 3, 1, 2]);
+        assert_eq!(unsqueeze_shape(&[3], &[1]), vec![3, 1]);
+
+        let mut tensor = ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(vec![0.0; 6], ElementType::F32, vec![2, 3]),
+        };
+        tensor.reshape(vec![3, 2]);
+        assert_eq!(tensor.shape(), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_window_shape() {
+        assert_eq!(window_out_dim(5, 3, 1, 0, 1), Some(3));
+        assert_eq!(window_out_dim(5, 3, 2, 1, 1), Some(3));
+        assert_eq!(window_out_dim(7, 3, 1, 0, 2), Some(3));
+        assert_eq!(window_out_dim(2, 3, 1, 0, 1), None);
+
+        let attr = Conv2dAttr::from_vec(&[1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(attr.to_vec(), vec![1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(
+            conv2d_shape(&[2, 3, 8, 8], &[4, 3, 3, 3], &attr),
+            Some(vec![2, 4, 8, 8])
+        );
+        let attr = Conv2dAttr::from_vec(&[2, 2, 0, 0, 1, 1, 2]);
+        assert_eq!(
+            conv2d_shape(&[1, 4, 8, 6], &[6, 2, 2, 2], &attr),
+            Some(vec![1, 6, 4, 3])
+        );
+        // channels of weight mismatch input channels per group
+        assert_eq!(conv2d_shape(&[1, 4, 8, 6], &[6, 4, 2, 2], &attr), None);
+
+        let attr = Pool2dAttr::from_vec(&[2, 2, 2, 2, 0, 0]);
+        assert_eq!(pool2d_shape(&[1, 3, 5, 4], &attr), Some(vec![1, 3, 2, 2]));
+        assert_eq!(pool2d_shape(&[3, 5, 4], &attr), None);
+    }
+
+    #[test]
+    fn test_matmul_shape() {
+        assert_eq!(
+            matmul_shape(&[2, 3], &[3, 4], false, false),
+            Some(vec![2, 4])
+        );
+        assert_eq!(
+            matmul_shape(&[5, 2, 3], &[5, 3, 4], false, false),
+            Some(vec![5, 2, 4])
+        );
+        assert_eq!(
+            matmul_shape(&[2, 1, 2, 3], &[4, 3, 4], false, false),
+            Some(vec![2, 4, 2, 4])
+        );
+        assert_eq!(
+            matmul_shape(&[5, 3, 2], &[4, 3], true, true),
+            Some(vec![5, 2, 4])
+        );
+        assert_eq!(matmul_shape(&[2, 3], &[2, 4], false, false), None);
+        assert_eq!(matmul_shape(&[2, 2, 3], &[3, 3, 4], false, false), None);
+        assert_eq!(matmul_shape(&[3], &[3, 4], false, false), None);
+    }
+
+    #[test]
+    fn test_reduce_shape() {
+        let tensor = TensorView::<f32>::new(vec![0.0; 24], ElementType::F32, vec![2, 3, 4]);
+        assert_eq!(tensor.reduce_shape(Some(1), false), vec![2, 4]);
+        assert_eq!(tensor.reduce_shape(Some(1), true), vec![2, 1, 4]);
+        assert_eq!(tensor.reduce_shape(None, false), Vec::<usize>::new());
+        assert_eq!(tensor.reduce_shape(None, true), vec![1, 1, 1]);
+        assert_eq!(tensor.reduce_view(Some(1)), (2, 3, 4));
+        assert_eq!(tensor.reduce_view(Some(2)), (6, 4, 1));
+        assert_eq!(tensor.reduce_view(None), (1, 24, 1));
+    }
 }
@@ -13,10 +13,42 @@ use crate::base::*;
 use crate::buffer_types::*;
 use crate::functor::TensorFunctor;
 use crate::functor::*;
+use crate::fusion::FUSION_KERNEL_RANK;
+use crate::host_functor::HostFunctor;
 use crate::instance::*;
 use crate::kernel::kernel_registry::KernelRegistry;
 use crate::tensors::*;
 
+// elementwise kernels broadcast operands viewed in this rank
+const BROADCAST_KERNEL_RANK: usize = 4;
+
+// matmul kernel views operands as [batch0, batch1, rows, cols]
+const MATMUL_KERNEL_RANK: usize = 4;
+
+// view operands of elementwise ops in BROADCAST_KERNEL_RANK by prepending dims of size 1, returns
+// the broadcasted shape of result to reshape the kernel output back
+fn expand_for_broadcast<T>(
+    lhs_tensor: TensorView<T>,
+    rhs_tensor: TensorView<T>,
+) -> (TensorView<T>, TensorView<T>, Vec<usize>) {
+    let out_shape = broadcast_shape(&lhs_tensor.shape, &rhs_tensor.shape).expect(&format!(
+        "cannot broadcast shapes {:?} and {:?}",
+        lhs_tensor.shape, rhs_tensor.shape
+    ));
+    assert!(
+        out_shape.len() <= BROADCAST_KERNEL_RANK,
+        "vulkan elementwise kernels support rank up to {}",
+        BROADCAST_KERNEL_RANK
+    );
+    let lhs_shape = expand_shape(&lhs_tensor.shape, BROADCAST_KERNEL_RANK);
+    let rhs_shape = expand_shape(&rhs_tensor.shape, BROADCAST_KERNEL_RANK);
+    (
+        TensorView::<T>::new(lhs_tensor.data, lhs_tensor.dtype, lhs_shape),
+        TensorView::<T>::new(rhs_tensor.data, rhs_tensor.dtype, rhs_shape),
+        out_shape,
+    )
+}
+
 #[derive(Debug)]
 pub struct VkGPUExecutor {
     // TODO refactor into kernel_registry
@@ -29,6 +61,8 @@ pub struct VkGPUExecutor {
     pub descriptor_pool: concrete_backend::native::DescriptorPool,
     pub queue_groups: Vec<hal::queue::family::QueueGroup<concrete_backend::Backend>>,
     pub device: concrete_backend::Device,
+    // shader features the device is opened with, see DeviceInstance::shader_features
+    pub features: hal::Features,
 
     // FIX: device instance have to put at last since the drop rule of Rust is a sequence order
     // rather than a reverse order in struct. Thus, we have to make sure device instance is dropped
@@ -51,6 +85,7 @@ impl VkGPUExecutor {
         let mut di = DeviceInstance::new();
         println!("finish to Init-VkDevice");
         let mut device_and_queue = di.device_and_queue();
+        let features = di.shader_features();
         let mut descriptor_pool = unsafe {
             device_and_queue.device.create_descriptor_pool(
                 100, // TODO count of desc sets which below max_sets
@@ -73,6 +108,7 @@ impl VkGPUExecutor {
             device: device_and_queue.device,
             queue_groups: device_and_queue.queue_groups,
             descriptor_pool: descriptor_pool,
+            features: features,
         };
     }
 
@@ -85,11 +121,538 @@ impl VkGPUExecutor {
             "/root/project/glsl_src/binary_arithmetic_i32.comp",
             String::from("binary_arithmetic_i32"),
         );
+        // 64-bit kernels only compile on devices that offer the shader features
+        if self.features.contains(hal::Features::SHADER_FLOAT64) {
+            self.register_kernels(
+                "/root/project/glsl_src/binary_arithmetic_f64.comp",
+                String::from("binary_arithmetic_f64"),
+            );
+        }
+        if self.features.contains(hal::Features::SHADER_INT64) {
+            self.register_kernels(
+                "/root/project/glsl_src/binary_arithmetic_i64.comp",
+                String::from("binary_arithmetic_i64"),
+            );
+        }
         self.register_kernels(
             "/root/project/glsl_src/matrix_multiple_f32.comp",
             //    "/root/project/chopper/backend-rs/chopper-runtime/src/kernel/glsl_src/matrix_multiple_f32.comp",
             String::from("matrix_multiple_f32"),
         );
+        self.register_kernels(
+            "/root/project/glsl_src/unary_arithmetic_f32.comp",
+            String::from("unary_arithmetic_f32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/unary_arithmetic_i32.comp",
+            String::from("unary_arithmetic_i32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/cast_arithmetic_32.comp",
+            String::from("cast_arithmetic_32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/reduction_f32.comp",
+            String::from("reduction_f32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/select_32.comp",
+            String::from("select_32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/ternary_arithmetic_f32.comp",
+            String::from("ternary_arithmetic_f32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/conv2d_f32.comp",
+            String::from("conv2d_f32"),
+        );
+        self.register_kernels(
+            "/root/project/glsl_src/pool2d_f32.comp",
+            String::from("pool2d_f32"),
+        );
+    }
+
+    pub(crate) fn unary_compute_f32(
+        &mut self,
+        op: CRTOpCode,
+        in_tensor: TensorView<f32>,
+    ) -> TensorView<f32> {
+        let mut in_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            in_tensor,
+        );
+
+        let mut out_buffer_functor =
+            TensorFunctor::new().apply_unary::<f32>(self, in_buffer_functor, op);
+        // TODO-fix destroy memory when compute done, consider keep this in future for fusion
+        // purpose
+        out_buffer_functor.try_drop(&self.device);
+
+        let out_tensor = TensorView::<f32>::new(
+            out_buffer_functor.raw_data,
+            ElementType::F32,
+            out_buffer_functor.shape,
+        );
+        out_tensor
+    }
+
+    pub(crate) fn unary_compute_i32(
+        &mut self,
+        op: CRTOpCode,
+        in_tensor: TensorView<i32>,
+    ) -> TensorView<i32> {
+        let mut in_buffer_functor = UniBuffer::<concrete_backend::Backend, i32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            in_tensor,
+        );
+
+        let mut out_buffer_functor =
+            TensorFunctor::new().apply_unary::<i32>(self, in_buffer_functor, op);
+        out_buffer_functor.try_drop(&self.device);
+
+        let out_tensor = TensorView::<i32>::new(
+            out_buffer_functor.raw_data,
+            ElementType::I32,
+            out_buffer_functor.shape,
+        );
+        out_tensor
+    }
+
+    // value casts run on device with the raw 32-bit patterns carried by an i32 buffer,
+    // bitcasts only reinterpret on host
+    pub(crate) fn cast_compute(
+        &mut self,
+        op: CRTOpCode,
+        in_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let in_bits = match in_tensor {
+            ActTensorTypes::F32Tensor { data } => TensorView::<i32>::new(
+                data.data.iter().map(|x| x.to_bits() as i32).collect(),
+                ElementType::I32,
+                data.shape.clone(),
+            ),
+            ActTensorTypes::I32Tensor { data } => data.clone(),
+            _ => panic!("dtype-comp not implemented"),
+        };
+        let in_dtype = match in_tensor {
+            ActTensorTypes::F32Tensor { .. } => ElementType::F32,
+            _ => ElementType::I32,
+        };
+        let out_dtype = op.to_cast_element_type().unwrap();
+        let out_bits = match op {
+            CRTOpCode::CASTF32 | CRTOpCode::CASTI32 if in_dtype != out_dtype => {
+                let mut in_buffer_functor = UniBuffer::<concrete_backend::Backend, i32>::new(
+                    &self.device,
+                    &self.device_instance.memory_property().memory_types,
+                    in_bits,
+                );
+                let mut out_buffer_functor =
+                    TensorFunctor::new().apply_unary::<i32>(self, in_buffer_functor, op);
+                out_buffer_functor.try_drop(&self.device);
+                TensorView::<i32>::new(
+                    out_buffer_functor.raw_data,
+                    ElementType::I32,
+                    out_buffer_functor.shape,
+                )
+            }
+            _ => in_bits,
+        };
+        match out_dtype {
+            ElementType::F32 => ActTensorTypes::F32Tensor {
+                data: TensorView::<f32>::new(
+                    out_bits
+                        .data
+                        .iter()
+                        .map(|x| f32::from_bits(*x as u32))
+                        .collect(),
+                    ElementType::F32,
+                    out_bits.shape,
+                ),
+            },
+            ElementType::I32 => ActTensorTypes::I32Tensor { data: out_bits },
+            _ => panic!("not support casting into {:?}", out_dtype),
+        }
+    }
+
+    // reduce over the [outer, axis_len, inner] view of input, arg-reductions produce indices
+    // as f32 on device that converted to i32 on host
+    pub(crate) fn reduce_compute(
+        &mut self,
+        op: CRTOpCode,
+        in_tensor: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let (axis, keepdims) = attr_tensor.to_reduce_attr();
+        let in_tensor = match in_tensor {
+            ActTensorTypes::F32Tensor { data } => data,
+            _ => panic!("dtype-comp not implemented"),
+        };
+        let (outer, axis_len, inner) = in_tensor.reduce_view(axis);
+        let out_shape = in_tensor.reduce_shape(axis, keepdims);
+        let in_view = TensorView::<f32>::new(
+            in_tensor.data.clone(),
+            ElementType::F32,
+            vec![outer, axis_len, inner],
+        );
+        let mut in_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            in_view,
+        );
+
+        let mut out_buffer_functor =
+            TensorFunctor::new().apply_unary::<f32>(self, in_buffer_functor, op);
+        out_buffer_functor.try_drop(&self.device);
+
+        match op {
+            CRTOpCode::ARGMAXF32 | CRTOpCode::ARGMINF32 => ActTensorTypes::I32Tensor {
+                data: TensorView::<i32>::new(
+                    out_buffer_functor
+                        .raw_data
+                        .iter()
+                        .map(|x| *x as i32)
+                        .collect(),
+                    ElementType::I32,
+                    out_shape,
+                ),
+            },
+            _ => ActTensorTypes::F32Tensor {
+                data: TensorView::<f32>::new(
+                    out_buffer_functor.raw_data,
+                    ElementType::F32,
+                    out_shape,
+                ),
+            },
+        }
+    }
+
+    // comparisons share the broadcasting binary kernels, f32 kernels produce masks as 1.0 or 0.0
+    pub(crate) fn compare_compute(
+        &mut self,
+        op: CRTOpCode,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let mask = match (lhs_tensor, rhs_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => {
+                let outs = self.binary_compute_f32(op, lhs_data.clone(), rhs_data.clone());
+                TensorView::<i32>::new(
+                    outs.data.iter().map(|x| *x as i32).collect(),
+                    ElementType::BOOL,
+                    outs.shape,
+                )
+            }
+            (
+                ActTensorTypes::I32Tensor { data: lhs_data },
+                ActTensorTypes::I32Tensor { data: rhs_data },
+            ) => {
+                let outs = self.binary_compute_i32(op, lhs_data.clone(), rhs_data.clone());
+                TensorView::<i32>::new(outs.data, ElementType::BOOL, outs.shape)
+            }
+            _ => panic!("dtype mismatch"),
+        };
+        ActTensorTypes::I32Tensor { data: mask }
+    }
+
+    // values are packed as [2, shape of mask] and carried by an i32 buffer as raw 32-bit patterns
+    pub(crate) fn select_compute(
+        &mut self,
+        mask_tensor: &ActTensorTypes,
+        values: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let mask = match mask_tensor {
+            ActTensorTypes::I32Tensor { data } => data.clone(),
+            _ => panic!("mask of select must be a bool or i32 tensor"),
+        };
+        let values_bits = match values {
+            ActTensorTypes::F32Tensor { data } => TensorView::<i32>::new(
+                data.data.iter().map(|x| x.to_bits() as i32).collect(),
+                ElementType::I32,
+                data.shape.clone(),
+            ),
+            ActTensorTypes::I32Tensor { data } => data.clone(),
+            _ => panic!("dtype-comp not implemented"),
+        };
+        let mut mask_buffer_functor = UniBuffer::<concrete_backend::Backend, i32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            mask,
+        );
+        let mut values_buffer_functor = UniBuffer::<concrete_backend::Backend, i32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            values_bits,
+        );
+        let mut out_buffer_functor = TensorFunctor::new().apply::<i32>(
+            self,
+            mask_buffer_functor,
+            values_buffer_functor,
+            CRTOpCode::SELECT,
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        match values {
+            ActTensorTypes::F32Tensor { .. } => ActTensorTypes::F32Tensor {
+                data: TensorView::<f32>::new(
+                    out_buffer_functor
+                        .raw_data
+                        .iter()
+                        .map(|x| f32::from_bits(*x as u32))
+                        .collect(),
+                    ElementType::F32,
+                    out_buffer_functor.shape,
+                ),
+            },
+            _ => ActTensorTypes::I32Tensor {
+                data: TensorView::<i32>::new(
+                    out_buffer_functor.raw_data,
+                    ElementType::I32,
+                    out_buffer_functor.shape,
+                ),
+            },
+        }
+    }
+
+    // the second and the third operands are packed as [2, shape of the first]
+    pub(crate) fn ternary_compute_f32(
+        &mut self,
+        op: CRTOpCode,
+        first_tensor: &ActTensorTypes,
+        packed_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let (first_data, packed_data) = match (first_tensor, packed_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: first_data },
+                ActTensorTypes::F32Tensor { data: packed_data },
+            ) => (first_data.clone(), packed_data.clone()),
+            _ => panic!("dtype mismatch"),
+        };
+        let mut first_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            first_data,
+        );
+        let mut packed_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            packed_data,
+        );
+        let mut out_buffer_functor = TensorFunctor::new().apply::<f32>(
+            self,
+            first_buffer_functor,
+            packed_buffer_functor,
+            op,
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(
+                out_buffer_functor.raw_data,
+                ElementType::F32,
+                out_buffer_functor.shape,
+            ),
+        }
+    }
+
+    // batch dims are broadcasted by the kernel, transpose flags are specialised as attrs
+    pub(crate) fn matmul_compute(
+        &mut self,
+        lhs_tensor: &ActTensorTypes,
+        rhs_tensor: &ActTensorTypes,
+        transpose_a: bool,
+        transpose_b: bool,
+    ) -> ActTensorTypes {
+        let (lhs_data, rhs_data) = match (lhs_tensor, rhs_tensor) {
+            (
+                ActTensorTypes::F32Tensor { data: lhs_data },
+                ActTensorTypes::F32Tensor { data: rhs_data },
+            ) => (lhs_data, rhs_data),
+            _ => panic!("dtype mismatch"),
+        };
+        let out_shape = matmul_shape(&lhs_data.shape, &rhs_data.shape, transpose_a, transpose_b)
+            .expect(&format!(
+                "cannot matmul shapes {:?} and {:?}",
+                lhs_data.shape, rhs_data.shape
+            ));
+        assert!(
+            out_shape.len() <= MATMUL_KERNEL_RANK,
+            "vulkan matmul kernel supports rank up to {}",
+            MATMUL_KERNEL_RANK
+        );
+        let lhs_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            TensorView::<f32>::new(
+                lhs_data.data.clone(),
+                ElementType::F32,
+                expand_shape(&lhs_data.shape, MATMUL_KERNEL_RANK),
+            ),
+        );
+        let rhs_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            TensorView::<f32>::new(
+                rhs_data.data.clone(),
+                ElementType::F32,
+                expand_shape(&rhs_data.shape, MATMUL_KERNEL_RANK),
+            ),
+        );
+        let mut out_buffer_functor = TensorFunctor::new().apply_n_with_attrs::<f32>(
+            self,
+            vec![lhs_buffer_functor, rhs_buffer_functor],
+            CRTOpCode::MATMULTF32,
+            vec![transpose_a as usize, transpose_b as usize],
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(out_buffer_functor.raw_data, ElementType::F32, out_shape),
+        }
+    }
+
+    // input and weight come packed as a tuple, window params are specialised as attrs
+    pub(crate) fn conv2d_compute(
+        &mut self,
+        operands: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let (in_data, weight_data) = match operands {
+            ActTensorTypes::TupleTensor { data } if data.len() == 2 => match (&data[0], &data[1]) {
+                (
+                    ActTensorTypes::F32Tensor { data: in_data },
+                    ActTensorTypes::F32Tensor { data: weight_data },
+                ) => (in_data.clone(), weight_data.clone()),
+                _ => panic!("dtype mismatch"),
+            },
+            _ => panic!("conv2d expects the input and the weight packed as lhs"),
+        };
+        let in_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            in_data,
+        );
+        let weight_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            weight_data,
+        );
+        let mut out_buffer_functor = TensorFunctor::new().apply_n_with_attrs::<f32>(
+            self,
+            vec![in_buffer_functor, weight_buffer_functor],
+            CRTOpCode::CONV2DF32,
+            attr_tensor.to_conv2d_attr().to_vec(),
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(
+                out_buffer_functor.raw_data,
+                ElementType::F32,
+                out_buffer_functor.shape,
+            ),
+        }
+    }
+
+    // inputs come packed as a tuple, and the expression as rhs. The kernel is generated and
+    // compiled on first use, later launches of the same expression hit the cache
+    pub(crate) fn fused_compute(
+        &mut self,
+        operands: &ActTensorTypes,
+        expr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let in_data: Option<Vec<&TensorView<f32>>> = match operands {
+            ActTensorTypes::TupleTensor { data } => data
+                .iter()
+                .map(|operand| match operand {
+                    ActTensorTypes::F32Tensor { data } => Some(data),
+                    _ => None,
+                })
+                .collect(),
+            _ => panic!("fused op expects the inputs packed as lhs"),
+        };
+        // the generated kernels only take f32 operands
+        let in_data = match in_data {
+            Some(in_data) => in_data,
+            None => return HostFunctor::new().fused(operands, expr_tensor),
+        };
+        let out_shape = in_data[1..]
+            .iter()
+            .fold(in_data[0].shape.clone(), |out_shape, data| {
+                broadcast_shape(&out_shape, &data.shape).expect(&format!(
+                    "cannot broadcast shapes {:?} and {:?}",
+                    out_shape, data.shape
+                ))
+            });
+        assert!(
+            out_shape.len() <= FUSION_KERNEL_RANK,
+            "vulkan fused kernels support rank up to {}",
+            FUSION_KERNEL_RANK
+        );
+
+        let expr = expr_tensor.to_fused_expr();
+        let query_entry = expr.to_query_entry(in_data.len());
+        if !self.kernel_registry.contains(&query_entry) {
+            self.register_source(&expr.to_glsl(in_data.len()), query_entry.clone());
+        }
+        let in_buffer_functors = in_data
+            .iter()
+            .map(|data| {
+                UniBuffer::<concrete_backend::Backend, f32>::new(
+                    &self.device,
+                    &self.device_instance.memory_property().memory_types,
+                    TensorView::<f32>::new(
+                        data.data.clone(),
+                        ElementType::F32,
+                        expand_shape(&data.shape, FUSION_KERNEL_RANK),
+                    ),
+                )
+            })
+            .collect();
+        let mut out_buffer_functor =
+            TensorFunctor::new().apply_generated::<f32>(self, in_buffer_functors, query_entry);
+        out_buffer_functor.try_drop(&self.device);
+
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(out_buffer_functor.raw_data, ElementType::F32, out_shape),
+        }
+    }
+
+    pub(crate) fn pool2d_compute(
+        &mut self,
+        op: CRTOpCode,
+        in_tensor: &ActTensorTypes,
+        attr_tensor: &ActTensorTypes,
+    ) -> ActTensorTypes {
+        let in_data = match in_tensor {
+            ActTensorTypes::F32Tensor { data } => data.clone(),
+            _ => panic!("dtype-comp not implemented"),
+        };
+        let in_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            in_data,
+        );
+        let mut out_buffer_functor = TensorFunctor::new().apply_n_with_attrs::<f32>(
+            self,
+            vec![in_buffer_functor],
+            op,
+            attr_tensor.to_pool2d_attr().to_vec(),
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        ActTensorTypes::F32Tensor {
+            data: TensorView::<f32>::new(
+                out_buffer_functor.raw_data,
+                ElementType::F32,
+                out_buffer_functor.shape,
+            ),
+        }
     }
 
     pub(crate) fn binary_compute_i32(
@@ -100,6 +663,8 @@ impl VkGPUExecutor {
     ) -> TensorView<i32> {
         // println!("============ on computing binary =============");
         // default dtype for compute
+        let (lhs_tensor, rhs_tensor, broadcast_shape) =
+            expand_for_broadcast::<i32>(lhs_tensor, rhs_tensor);
         let mut lhs_buffer_functor = UniBuffer::<concrete_backend::Backend, i32>::new(
             &self.device,
             &self.device_instance.memory_property().memory_types,
@@ -122,7 +687,7 @@ impl VkGPUExecutor {
         let out_tensor = TensorView::<i32>::new(
             out_buffer_functor.raw_data,
             ElementType::I32,
-            out_buffer_functor.shape,
+            broadcast_shape,
         );
         out_tensor
     }
@@ -135,6 +700,8 @@ impl VkGPUExecutor {
     ) -> TensorView<f32> {
         // println!("============ on computing binary =============");
         // default dtype for compute
+        let (lhs_tensor, rhs_tensor, broadcast_shape) =
+            expand_for_broadcast::<f32>(lhs_tensor, rhs_tensor);
         let mut lhs_buffer_functor = UniBuffer::<concrete_backend::Backend, f32>::new(
             &self.device,
             &self.device_instance.memory_property().memory_types,
@@ -157,17 +724,94 @@ impl VkGPUExecutor {
         let out_tensor = TensorView::<f32>::new(
             out_buffer_functor.raw_data,
             ElementType::F32,
-            out_buffer_functor.shape,
+            broadcast_shape,
         );
         out_tensor
     }
 
+    // kernel of a binary arithmetic op on f64 or i64 operands, None if the device lacks the shader
+    // feature or the 64-bit kernels have no entry of op, such as comparisons, pow and atan2
+    pub(crate) fn binary_entry_64(&self, op: CRTOpCode, dtype: ElementType) -> Option<String> {
+        let (entry, feature) = match (op, dtype) {
+            (
+                CRTOpCode::ADDF32
+                | CRTOpCode::SUBF32
+                | CRTOpCode::MULF32
+                | CRTOpCode::DIVF32
+                | CRTOpCode::MAXF32
+                | CRTOpCode::MINF32
+                | CRTOpCode::REMF32,
+                ElementType::F64,
+            ) => ("binary_arithmetic_f64", hal::Features::SHADER_FLOAT64),
+            (
+                CRTOpCode::ADDI32
+                | CRTOpCode::SUBI32
+                | CRTOpCode::MULI32
+                | CRTOpCode::FLOORDIVI32
+                | CRTOpCode::ANDI32
+                | CRTOpCode::ORI32
+                | CRTOpCode::XORI32
+                | CRTOpCode::SHLI32
+                | CRTOpCode::SHRI32
+                | CRTOpCode::MAXI32
+                | CRTOpCode::MINI32
+                | CRTOpCode::REMI32,
+                ElementType::I64,
+            ) => ("binary_arithmetic_i64", hal::Features::SHADER_INT64),
+            _ => return None,
+        };
+        match self.features.contains(feature) {
+            true => Some(String::from(entry)),
+            false => None,
+        }
+    }
+
+    // same as binary_compute_f32 on the kernel registered under query_entry
+    pub(crate) fn binary_compute_entry<
+        T: SupportedType + std::clone::Clone + std::default::Default,
+    >(
+        &mut self,
+        op: CRTOpCode,
+        lhs_tensor: TensorView<T>,
+        rhs_tensor: TensorView<T>,
+        query_entry: String,
+    ) -> TensorView<T> {
+        let dtype = lhs_tensor.dtype;
+        let (lhs_tensor, rhs_tensor, broadcast_shape) =
+            expand_for_broadcast::<T>(lhs_tensor, rhs_tensor);
+        let lhs_buffer_functor = UniBuffer::<concrete_backend::Backend, T>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            lhs_tensor,
+        );
+        let rhs_buffer_functor = UniBuffer::<concrete_backend::Backend, T>::new(
+            &self.device,
+            &self.device_instance.memory_property().memory_types,
+            rhs_tensor,
+        );
+
+        let mut out_buffer_functor = TensorFunctor::new().apply_entry::<T>(
+            self,
+            vec![lhs_buffer_functor, rhs_buffer_functor],
+            op,
+            query_entry,
+        );
+        out_buffer_functor.try_drop(&self.device);
+
+        TensorView::<T>::new(out_buffer_functor.raw_data, dtype, broadcast_shape)
+    }
+
     pub fn register_kernels(&mut self, file_path: &str, query_entry: String) {
         // glsl_to_spirv, TODO, support more, spv format and readable spirv ir.
         // TODO, read external config of all kernels, and cache it by CRTOpCode
         let glsl = fs::read_to_string(file_path).unwrap();
         // println!("{:?}", glsl);
-        let spirv_file = glsl_to_spirv::compile(&glsl, glsl_to_spirv::ShaderType::Compute).unwrap();
+        self.register_source(&glsl, query_entry);
+    }
+
+    // compiles glsl source into a kernel, such as the generated ones of fused ops
+    pub fn register_source(&mut self, glsl: &str, query_entry: String) {
+        let spirv_file = glsl_to_spirv::compile(glsl, glsl_to_spirv::ShaderType::Compute).unwrap();
         // println!("{:?}", spirv_file);
         // TODO need to impl implicit conversion
         let spirv: KernelByteCode = auxil::read_spirv(spirv_file).unwrap() as KernelByteCode;
@@ -181,7 +825,10 @@ impl VkGPUExecutor {
 
     // TODO seal raptors CRTOpCode inside and not expose
     pub fn dispatch_kernel(&self, op: CRTOpCode) -> Kernel {
-        let query_entry: String = op.to_kernel_query_entry();
+        self.dispatch_kernel_entry(op, op.to_kernel_query_entry())
+    }
+
+    pub fn dispatch_kernel_entry(&self, op: CRTOpCode, query_entry: String) -> Kernel {
         self.kernel_registry.dispatch_kernel(self, op, query_entry)
     }
 
@@ -3,19 +3,20 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::{thread, time};
 
-#[cfg(any(feature = "blas", feature = "mock"))]
-use rublas::prelude::*;
 use tracing::{debug, info};
 
 use tokio::sync::oneshot;
 
 use serde::{Deserialize, Serialize};
 
+use crate::base::constants::REGISTER_DIM_FLAG;
 use crate::base::errors::*;
 use crate::base::*;
+use crate::fusion::FusedExpr;
 use crate::instruction::CRTOpCode;
 
 use crate::buffer_types::*;
+use crate::host_functor::HostFunctor;
 use crate::instance::*;
 use crate::session::*;
 use crate::tensors::*;
@@ -35,6 +36,13 @@ pub struct VM {
     tensor_pool: HashMap<usize, Arc<RwLock<ActTensorTypes>>>,
     // TODO refactor with notifier
     ready_checkers: MultiMap<usize, oneshot::Receiver<u8>>,
+    // heads and remaining trips of active counted loops, keyed by the position of their loop
+    // instruction
+    loop_counters: HashMap<usize, (usize, i32)>,
+    // slots of the results of the last return
+    returned: Vec<usize>,
+    // constant sections of loaded modules, read by CONSTREF
+    constants: Vec<ActTensorTypes>,
     session: HostSession,
 }
 
@@ -56,6 +64,9 @@ impl VM {
             session: session,
             tensor_pool: HashMap::new(),
             ready_checkers: MultiMap::with_capacity(128),
+            loop_counters: HashMap::new(),
+            returned: vec![],
+            constants: vec![],
         }
     }
 
@@ -88,16 +99,10 @@ impl VM {
         self.decode_u16()
     }
 
-    fn decode_n_bytes_as_f32_vec(&mut self, lens: usize) -> Vec<f32> {
-        let mut encoded: Vec<u8> = vec![];
-        for _ in 0..lens {
-            encoded.push(self.inst_buffer[self.program_counter]);
-            self.program_counter += 1;
-        }
-        // println!("decoding data bytes {:?}", encoded);
-        let decoded: Vec<f32> = bincode::deserialize(&encoded).unwrap();
-        // println!("{:?}", decoded);
-        decoded
+    fn decode_n_bytes(&mut self, lens: usize) -> Vec<u8> {
+        let bytes = self.inst_buffer[self.program_counter..self.program_counter + lens].to_vec();
+        self.program_counter += lens;
+        bytes
     }
 
     fn decode_n_bytes_as_usize_vec(&mut self, lens: usize) -> Vec<usize> {
@@ -119,6 +124,50 @@ impl VM {
         _cmd_buffer
     }
 
+    // decodes a tensor slot id, encoded as LEB128 so that small ids take one byte
+    fn decode_operand(&mut self) -> usize {
+        let mut slot = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.decode_u8();
+            slot |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return slot;
+            }
+            shift += 7;
+        }
+    }
+
+    // decodes [kind, value] of axis attributes, kind 0 means absent and kind 2 reads a register
+    fn decode_axis(&mut self) -> Option<i32> {
+        let kind = self.decode_u8();
+        let value = self.decode_u8();
+        match kind {
+            0 => None,
+            2 => Some(self.registers[value as usize]),
+            _ => Some(value as i8 as i32),
+        }
+    }
+
+    // dims flagged with REGISTER_DIM_FLAG take the values of registers
+    fn resolve_dims(&self, dims: Vec<usize>) -> Result<Vec<usize>, RuntimeStatusError> {
+        dims.into_iter()
+            .map(|dim| match dim & REGISTER_DIM_FLAG {
+                0 => Ok(dim),
+                _ => {
+                    let index = dim & !REGISTER_DIM_FLAG;
+                    match self.registers[index] {
+                        value if value < 0 => Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                            "register ${} holds negative dim {}",
+                            index, value
+                        ))),
+                        value => Ok(value as usize),
+                    }
+                }
+            })
+            .collect()
+    }
+
     fn get_next_two_bytes(&mut self) -> u16 {
         let _cmd_buffer = ((self.inst_buffer[self.program_counter] as u16) << 8)
             | self.inst_buffer[self.program_counter + 1] as u16;
@@ -153,6 +202,75 @@ impl VM {
         (senders, receivers)
     }
 
+    // host-side ops read operands directly, wait for their producers in non-blocking mode
+    fn wait_until_ready(&mut self, exec_mode: u8, operand: usize) {
+        if exec_mode != 2u8 {
+            return;
+        }
+        info!("::vm::wait ready-checker for tensor #{}", operand);
+        let ready_checker = self
+            .ready_checkers
+            .get_vec_mut(&operand)
+            .expect(&format!("failed to fetch ready-checker {}", operand).to_string())
+            .remove(0 as usize);
+        ready_checker
+            .blocking_recv()
+            .expect(&format!("tensor #{} never gets ready", operand).to_string());
+    }
+
+    // notify consumers that the tensor produced on host is ready in non-blocking mode
+    fn mark_ready(&mut self, exec_mode: u8, operand: usize) {
+        if exec_mode != 2u8 {
+            return;
+        }
+        self.ready_checkers.remove(&operand);
+        for _ in 0..8 {
+            let (notifier, ready_checker) = oneshot::channel::<u8>();
+            notifier.send(0u8);
+            self.ready_checkers.insert(operand, ready_checker);
+        }
+        info!("::vm::fill data-ready-checker #{}", operand);
+    }
+
+    // moves the program counter by a byte offset relative to the next instruction
+    fn jump(&mut self, offset: i32) -> Result<u8, RuntimeStatusError> {
+        let target = self.program_counter as i64 + offset as i64;
+        if target < 0 || target > self.inst_buffer.len() as i64 {
+            info!("::vm::jump target {} out of command buffer", target);
+            return Err(RuntimeStatusError::RT_ERROR);
+        }
+        // jumping out of the body of a counted loop leaves it, entering it again starts a new count
+        let from = self.program_counter;
+        let target = target as usize;
+        self.loop_counters.retain(|position, (head, _)| {
+            let inside = |at: usize| *head <= at && at <= *position;
+            !inside(from) || inside(target)
+        });
+        self.program_counter = target;
+        Ok(0)
+    }
+
+    // reads the value of a tensor of one element, such as branch conditions, f64 holds values
+    // of all dtypes exactly but i64 beyond 2^53
+    fn read_scalar(&self, operand: usize) -> Result<f64, RuntimeStatusError> {
+        let not_scalar = || {
+            RuntimeStatusError::SHAPE_MISMATCH(format!(
+                "tensor #{} must be a scalar tensor",
+                operand
+            ))
+        };
+        let tensor = self.tensor_pool.get(&operand).ok_or_else(not_scalar)?;
+        match &*tensor.read().unwrap() {
+            ActTensorTypes::MockTensor { .. } | ActTensorTypes::TupleTensor { .. } => {
+                Err(not_scalar())
+            }
+            tensor => match tensor.to_f64_vec()[..] {
+                [value] => Ok(value),
+                _ => Err(not_scalar()),
+            },
+        }
+    }
+
     // TODO may replace status with a enum
     // TODO may replace exec_mode with enum
     // TODO exec_mode =
@@ -174,25 +292,116 @@ impl VM {
             }
             CRTOpCode::RETV => {
                 info!("::vm::return from module");
-                let operand_ret = self.decode_u8() as usize;
-                // clear ready_checker
-                // currently solution, wait for this retv ready-checker then returns
-                let ready_checker = self
-                    .ready_checkers
-                    .get_vec_mut(&operand_ret)
-                    .expect(&format!("failed to fetch ready-checker {}", operand_ret).to_string())
-                    .remove(0 as usize);
-                ready_checker
-                    .blocking_recv()
-                    .expect("return value computing not ready");
+                let count = self.decode_u8() as usize;
+                let operands: Vec<usize> = (0..count).map(|_| self.decode_operand()).collect();
+                // currently solution, wait for all results ready then returns
+                for operand in &operands {
+                    self.wait_until_ready(exec_mode, *operand);
+                    self.mark_ready(exec_mode, *operand);
+                }
                 info!("::vm::ret-value compute done");
-                // clear data_buffer before return
-                // TODO maybe we need a strategy to decide what results retains and drop
-                // considering function calls in module
-                self.tensor_pool.retain(|&k, _| k == operand_ret);
+                // clear data_buffer before return, functions have no side effects but results
+                self.tensor_pool.retain(|k, _| operands.contains(k));
+                self.returned = operands;
+                // skip the rest of the module
+                self.program_counter = self.inst_buffer.len();
                 info!("::vm::ret-value retain and return");
                 Ok(2)
             }
+            CRTOpCode::JMP => {
+                let offset = i32::from_le_bytes(self.get_next_four_bytes());
+                self.jump(offset)
+            }
+            CRTOpCode::JMPIF => {
+                let operand_cond = self.decode_operand();
+                let offset = i32::from_le_bytes(self.get_next_four_bytes());
+                // branching depends on the value, wait for its producer in non-blocking mode
+                self.wait_until_ready(exec_mode, operand_cond);
+                let taken = self.read_scalar(operand_cond)? != 0f64;
+                // the condition stays ready for later readers, such as the next iteration
+                self.mark_ready(exec_mode, operand_cond);
+                match taken {
+                    true => self.jump(offset),
+                    false => Ok(0),
+                }
+            }
+            CRTOpCode::JMPIFR => {
+                let register_id = self.decode_u8() as usize;
+                let offset = i32::from_le_bytes(self.get_next_four_bytes());
+                match self.registers[register_id] != 0 {
+                    true => self.jump(offset),
+                    false => Ok(0),
+                }
+            }
+            CRTOpCode::LOOP => {
+                let position = self.program_counter - 1;
+                let trip_count = i32::from_le_bytes(self.get_next_four_bytes());
+                let offset = i32::from_le_bytes(self.get_next_four_bytes());
+                // the assembler rejects these, since the body runs before the count is checked
+                if trip_count < 1 {
+                    return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                        "loop at byte {} runs {} times, expects at least 1",
+                        position, trip_count
+                    )));
+                }
+                // the body has run once when reaching the loop instruction at the first time
+                let head = (self.program_counter as i64 + offset as i64) as usize;
+                let (_, remaining) = self
+                    .loop_counters
+                    .entry(position)
+                    .or_insert((head, trip_count));
+                *remaining -= 1;
+                match *remaining > 0 {
+                    true => self.jump(offset),
+                    false => {
+                        // done, entering this loop again starts a new count
+                        self.loop_counters.remove(&position);
+                        Ok(0)
+                    }
+                }
+            }
+            CRTOpCode::ADDR
+            | CRTOpCode::SUBR
+            | CRTOpCode::MULR
+            | CRTOpCode::CMPEQR
+            | CRTOpCode::CMPNER
+            | CRTOpCode::CMPLTR
+            | CRTOpCode::CMPLER
+            | CRTOpCode::CMPGTR
+            | CRTOpCode::CMPGER => {
+                let register_out = self.decode_u8() as usize;
+                let lhs = self.registers[self.decode_u8() as usize];
+                let rhs = self.registers[self.decode_u8() as usize];
+                // registers are host-side, computed in place without executors
+                self.registers[register_out] = match _inst {
+                    CRTOpCode::ADDR => lhs.wrapping_add(rhs),
+                    CRTOpCode::SUBR => lhs.wrapping_sub(rhs),
+                    CRTOpCode::MULR => lhs.wrapping_mul(rhs),
+                    CRTOpCode::CMPEQR => (lhs == rhs) as i32,
+                    CRTOpCode::CMPNER => (lhs != rhs) as i32,
+                    CRTOpCode::CMPLTR => (lhs < rhs) as i32,
+                    CRTOpCode::CMPLER => (lhs <= rhs) as i32,
+                    CRTOpCode::CMPGTR => (lhs > rhs) as i32,
+                    _ => (lhs >= rhs) as i32,
+                };
+                Ok(0)
+            }
+            CRTOpCode::MOVRT => {
+                let operand_out = self.decode_operand();
+                let register_in = self.decode_u8() as usize;
+                self.push_tensor_buffer_i32(operand_out, vec![self.registers[register_in]], vec![]);
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::MOVTR => {
+                let register_out = self.decode_u8() as usize;
+                let operand_in = self.decode_operand();
+                self.wait_until_ready(exec_mode, operand_in);
+                // f32 values are truncated towards zero
+                self.registers[register_out] = self.read_scalar(operand_in)? as i32;
+                self.mark_ready(exec_mode, operand_in);
+                Ok(0)
+            }
             // TODO rename to loadu16
             CRTOpCode::LOAD => {
                 let register_id = self.decode_u8() as usize;
@@ -203,12 +412,40 @@ impl VM {
                 // TODO change into verbose string
                 Ok(0)
             }
-            CRTOpCode::EXPF32 => {
-                let operand_out = self.decode_u8() as usize;
-                let operand_in = self.decode_u8() as usize;
+            CRTOpCode::EXPF32
+            | CRTOpCode::ABSF32
+            | CRTOpCode::NEGF32
+            | CRTOpCode::CEILF32
+            | CRTOpCode::FLOORF32
+            | CRTOpCode::ATANF32
+            | CRTOpCode::COSF32
+            | CRTOpCode::SINF32
+            | CRTOpCode::EXP2F32
+            | CRTOpCode::EXPM1F32
+            | CRTOpCode::LOGF32
+            | CRTOpCode::LOG10F32
+            | CRTOpCode::LOG1PF32
+            | CRTOpCode::LOG2F32
+            | CRTOpCode::RSQRTF32
+            | CRTOpCode::SQRTF32
+            | CRTOpCode::TANHF32
+            | CRTOpCode::NOTI32
+            | CRTOpCode::CASTF32
+            | CRTOpCode::CASTI32
+            | CRTOpCode::BITCASTF32
+            | CRTOpCode::BITCASTI32
+            | CRTOpCode::CASTF16
+            | CRTOpCode::CASTBF16
+            | CRTOpCode::CASTI8
+            | CRTOpCode::CASTU8
+            | CRTOpCode::CASTF64
+            | CRTOpCode::CASTI64
+            | CRTOpCode::CASTBOOL => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
                 let in_dataview = self.get_tensor(&operand_in);
                 // TODO rename dataview into ActTensorTypes
-                let opcode = CRTOpCode::EXPF32;
+                let opcode = _inst;
                 match exec_mode {
                     // should deprecate since it is not a safe mode that consumes
                     // try to learn from functional, consumes inputs is also a side-effect
@@ -252,11 +489,19 @@ impl VM {
                         info!("::create placeholder tensor for ret-value-tensor");
                         info!("::vm::store-ret-value with index #{:?}", operand_out);
                         let shape = self.get_tensor_shape(operand_in).to_vec();
-                        self.push_tensor_buffer(
-                            operand_out,
-                            vec![0f32; shape.iter().product()],
-                            shape,
-                        );
+                        // casting ops decide the dtype of result
+                        match opcode.to_cast_element_type() {
+                            Some(ElementType::I32) => self.push_tensor_buffer_i32(
+                                operand_out,
+                                vec![0i32; shape.iter().product()],
+                                shape,
+                            ),
+                            _ => self.push_tensor_buffer(
+                                operand_out,
+                                vec![0f32; shape.iter().product()],
+                                shape,
+                            ),
+                        }
                         let out_dataview = self.get_tensor(&operand_out);
 
                         info!("::vm::call-session-launch-unary-compute eager+borrowed+blocking");
@@ -301,13 +546,63 @@ impl VM {
             | CRTOpCode::MULI32
             | CRTOpCode::DIVF32
             | CRTOpCode::FLOORDIVI32
+            | CRTOpCode::ANDI32
+            | CRTOpCode::ORI32
+            | CRTOpCode::XORI32
+            | CRTOpCode::SHLI32
+            | CRTOpCode::SHRI32
+            | CRTOpCode::CMPEQF32
+            | CRTOpCode::CMPNEF32
+            | CRTOpCode::CMPLTF32
+            | CRTOpCode::CMPLEF32
+            | CRTOpCode::CMPGTF32
+            | CRTOpCode::CMPGEF32
+            | CRTOpCode::CMPEQI32
+            | CRTOpCode::CMPNEI32
+            | CRTOpCode::CMPLTI32
+            | CRTOpCode::CMPLEI32
+            | CRTOpCode::CMPGTI32
+            | CRTOpCode::CMPGEI32
+            | CRTOpCode::POWF32
+            | CRTOpCode::ATAN2F32
+            | CRTOpCode::MAXF32
+            | CRTOpCode::MINF32
+            | CRTOpCode::REMF32
+            | CRTOpCode::MAXI32
+            | CRTOpCode::MINI32
+            | CRTOpCode::REMI32
             | CRTOpCode::MATMULF32 => {
-                let operand_out = self.decode_u8() as usize;
-                let operand_lhs = self.decode_u8() as usize;
-                let operand_rhs = self.decode_u8() as usize;
+                let operand_out = self.decode_operand();
+                let operand_lhs = self.decode_operand();
+                let operand_rhs = self.decode_operand();
                 let lhs_dataview = self.get_tensor(&operand_lhs);
                 let rhs_dataview = self.get_tensor(&operand_rhs);
                 let opcode = _inst;
+                // elementwise ops broadcast lhs and rhs, refuse incompatible shapes before launch
+                let lhs_shape = self.get_tensor_shape(operand_lhs);
+                let rhs_shape = self.get_tensor_shape(operand_rhs);
+                let out_shape = match opcode {
+                    CRTOpCode::MATMULF32 => {
+                        match matmul_shape(&lhs_shape, &rhs_shape, false, false) {
+                            Some(shape) => shape,
+                            None => {
+                                return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                                    "{:?} cannot multiply shapes {:?} and {:?}",
+                                    opcode, lhs_shape, rhs_shape
+                                )))
+                            }
+                        }
+                    }
+                    _ => match broadcast_shape(&lhs_shape, &rhs_shape) {
+                        Some(shape) => shape,
+                        None => {
+                            return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                                "{:?} cannot broadcast shapes {:?} and {:?}",
+                                opcode, lhs_shape, rhs_shape
+                            )))
+                        }
+                    },
+                };
                 match exec_mode {
                     0u8 => {
                         // consuming-inputs-style + blocking-style
@@ -356,13 +651,19 @@ impl VM {
 
                         info!("::create placeholder tensor for ret-value-tensor");
                         info!("::vm::store-ret-value with index #{:?}", operand_out);
-                        // insert the output_placeholder
-                        let shape = self.get_tensor_shape(operand_lhs).to_vec();
-                        self.push_tensor_buffer(
-                            operand_out,
-                            vec![0f32; shape.iter().product()],
-                            shape,
-                        );
+                        // insert the output_placeholder, comparisons produce masks
+                        match opcode.is_comparison() {
+                            true => self.push_tensor_buffer_i32(
+                                operand_out,
+                                vec![0i32; out_shape.iter().product()],
+                                out_shape,
+                            ),
+                            false => self.push_tensor_buffer(
+                                operand_out,
+                                vec![0f32; out_shape.iter().product()],
+                                out_shape,
+                            ),
+                        }
                         let out_placeholder = self.get_tensor(&operand_out);
 
                         info!("::vm::call-session-launch-unary-compute eager+borrowed+blocking");
@@ -390,49 +691,774 @@ impl VM {
                     _ => panic!("unknown exec-mode"),
                 }
             }
+            CRTOpCode::REDUCESUMF32
+            | CRTOpCode::REDUCEMEANF32
+            | CRTOpCode::REDUCEMAXF32
+            | CRTOpCode::REDUCEMINF32
+            | CRTOpCode::ARGMAXF32
+            | CRTOpCode::ARGMINF32 => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
+                let axis = self.decode_axis();
+                let keepdims = self.decode_u8() != 0;
+                let in_dataview = self.get_tensor(&operand_in);
+                let opcode = _inst;
+
+                // normalise negative axis with the rank of input, -1 in attributes means all axes
+                let in_shape = self.get_tensor_shape(operand_in).to_vec();
+                let axis = match axis {
+                    Some(axis) => Some(
+                        normalize_axis(axis, in_shape.len())
+                            .map_err(RuntimeStatusError::SHAPE_MISMATCH)?,
+                    ),
+                    None => None,
+                };
+                let attr_dataview = Arc::new(RwLock::new(ActTensorTypes::I32Tensor {
+                    data: TensorView::<i32>::new(
+                        vec![axis.map_or(-1, |x| x as i32), keepdims as i32],
+                        ElementType::I32,
+                        vec![2],
+                    ),
+                }));
+                match exec_mode {
+                    0u8 | 1u8 => {
+                        info!("::vm::call-session-launch-reduce-compute eager+blocking");
+                        let outs = self.session.launch_blocking_binary_compute(
+                            opcode,
+                            in_dataview,
+                            attr_dataview,
+                        );
+                        info!("::vm::store-ret-value with index #{:?}", operand_out);
+                        self.tensor_pool
+                            .insert(operand_out, Arc::new(RwLock::new(outs)));
+                        Ok(0)
+                    }
+                    2u8 => {
+                        info!("::vm::poll ready-checker for tensor #{}", operand_in);
+                        let in_ready_checker = self
+                            .ready_checkers
+                            .get_vec_mut(&operand_in)
+                            .expect(
+                                &format!("failed to fetch ready-checker {}", operand_in)
+                                    .to_string(),
+                            )
+                            .remove(0 as usize);
+                        // attributes are ready on host
+                        let (attr_notifier, attr_ready_checker) = oneshot::channel::<u8>();
+                        attr_notifier.send(0u8);
+
+                        info!("::create placeholder tensor for ret-value-tensor");
+                        let shape = reduce_shape(&in_shape, axis, keepdims);
+                        match opcode {
+                            CRTOpCode::ARGMAXF32 | CRTOpCode::ARGMINF32 => self
+                                .push_tensor_buffer_i32(
+                                    operand_out,
+                                    vec![0i32; shape.iter().product()],
+                                    shape,
+                                ),
+                            _ => self.push_tensor_buffer(
+                                operand_out,
+                                vec![0f32; shape.iter().product()],
+                                shape,
+                            ),
+                        }
+                        let out_placeholder = self.get_tensor(&operand_out);
+
+                        info!("::vm::call-session-launch-reduce-compute eager+non-blocking");
+                        let _ready_checkers = self.session.launch_non_blocking_binary_compute(
+                            opcode,
+                            in_dataview,
+                            attr_dataview,
+                            out_placeholder,
+                            in_ready_checker,
+                            attr_ready_checker,
+                            operand_out,
+                        );
+                        if self.ready_checkers.contains_key(&operand_out) {
+                            self.ready_checkers.remove(&operand_out);
+                        }
+                        self.ready_checkers
+                            .insert_many(operand_out, _ready_checkers);
+                        info!("::vm::store ready-checker for tensor #{}", operand_out);
+
+                        Ok(0)
+                    }
+                    _ => panic!("unknown exec-mode"),
+                }
+            }
+            CRTOpCode::SELECT | CRTOpCode::FMAF32 => {
+                let operand_out = self.decode_operand();
+                let operand_first = self.decode_operand();
+                let operand_second = self.decode_operand();
+                let operand_third = self.decode_operand();
+                let opcode = _inst;
+                // operands are packed on host, wait for all of them
+                self.wait_until_ready(exec_mode, operand_first);
+                self.wait_until_ready(exec_mode, operand_second);
+                self.wait_until_ready(exec_mode, operand_third);
+
+                let first_shape = self.get_tensor_shape(operand_first);
+                let second_shape = self.get_tensor_shape(operand_second);
+                let third_shape = self.get_tensor_shape(operand_third);
+                let out_shape = match broadcast_shape(&second_shape, &third_shape)
+                    .and_then(|shape| broadcast_shape(&first_shape, &shape))
+                {
+                    Some(shape) => shape,
+                    None => {
+                        return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                            "{:?} cannot broadcast shapes {:?}, {:?} and {:?}",
+                            opcode, first_shape, second_shape, third_shape
+                        )))
+                    }
+                };
+                // select takes a bool or i32 mask and values of one dtype, fma takes f32 only
+                let dtypes = (
+                    self.get_tensor_dtype(operand_first),
+                    self.get_tensor_dtype(operand_second),
+                    self.get_tensor_dtype(operand_third),
+                );
+                let supported = match (opcode, dtypes) {
+                    (CRTOpCode::SELECT, (ElementType::BOOL | ElementType::I32, second, third)) => {
+                        second == third
+                    }
+                    (CRTOpCode::FMAF32, (ElementType::F32, ElementType::F32, ElementType::F32)) => {
+                        true
+                    }
+                    _ => false,
+                };
+                if !supported {
+                    return Err(RuntimeStatusError::DTYPE_MISMATCH(format!(
+                        "{:?} does not take tensors of {}, {} and {}",
+                        opcode, dtypes.0, dtypes.1, dtypes.2
+                    )));
+                }
+                let (first, packed) = HostFunctor::new().pack_ternary_operands(
+                    &*self.tensor_pool[&operand_first].read().unwrap(),
+                    &*self.tensor_pool[&operand_second].read().unwrap(),
+                    &*self.tensor_pool[&operand_third].read().unwrap(),
+                    &out_shape,
+                );
+                let first_dataview = Arc::new(RwLock::new(first));
+                let packed_dataview = Arc::new(RwLock::new(packed));
+                match exec_mode {
+                    0u8 | 1u8 => {
+                        info!("::vm::call-session-launch-ternary-compute eager+blocking");
+                        let outs = self.session.launch_blocking_binary_compute(
+                            opcode,
+                            first_dataview,
+                            packed_dataview,
+                        );
+                        info!("::vm::store-ret-value with index #{:?}", operand_out);
+                        self.tensor_pool
+                            .insert(operand_out, Arc::new(RwLock::new(outs)));
+                        Ok(0)
+                    }
+                    2u8 => {
+                        // packed operands are ready on host
+                        let (first_notifier, first_ready_checker) = oneshot::channel::<u8>();
+                        first_notifier.send(0u8);
+                        let (packed_notifier, packed_ready_checker) = oneshot::channel::<u8>();
+                        packed_notifier.send(0u8);
+
+                        info!("::create placeholder tensor for ret-value-tensor");
+                        // results share the dtype of the second operand, on_true of select
+                        let is_i32 = matches!(
+                            *self.tensor_pool[&operand_second].read().unwrap(),
+                            ActTensorTypes::I32Tensor { .. }
+                        );
+                        match is_i32 {
+                            true => self.push_tensor_buffer_i32(
+                                operand_out,
+                                vec![0i32; out_shape.iter().product()],
+                                out_shape,
+                            ),
+                            false => self.push_tensor_buffer(
+                                operand_out,
+                                vec![0f32; out_shape.iter().product()],
+                                out_shape,
+                            ),
+                        }
+                        let out_placeholder = self.get_tensor(&operand_out);
+
+                        info!("::vm::call-session-launch-ternary-compute eager+non-blocking");
+                        let _ready_checkers = self.session.launch_non_blocking_binary_compute(
+                            opcode,
+                            first_dataview,
+                            packed_dataview,
+                            out_placeholder,
+                            first_ready_checker,
+                            packed_ready_checker,
+                            operand_out,
+                        );
+                        if self.ready_checkers.contains_key(&operand_out) {
+                            self.ready_checkers.remove(&operand_out);
+                        }
+                        self.ready_checkers
+                            .insert_many(operand_out, _ready_checkers);
+                        info!("::vm::store ready-checker for tensor #{}", operand_out);
+
+                        Ok(0)
+                    }
+                    _ => panic!("unknown exec-mode"),
+                }
+            }
+            CRTOpCode::MATMULTF32 => {
+                let operand_out = self.decode_operand();
+                let operand_lhs = self.decode_operand();
+                let operand_rhs = self.decode_operand();
+                let flags_size = self.decode_vec_len() as usize;
+                let flags = self.decode_n_bytes_as_usize_vec(flags_size);
+                let opcode = _inst;
+                if flags.len() != 2 {
+                    return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                        "{:?} takes [transpose_a, transpose_b], but gets {:?}",
+                        opcode, flags
+                    )));
+                }
+
+                let lhs_shape = self.get_tensor_shape(operand_lhs);
+                let rhs_shape = self.get_tensor_shape(operand_rhs);
+                let out_shape =
+                    match matmul_shape(&lhs_shape, &rhs_shape, flags[0] != 0, flags[1] != 0) {
+                        Some(shape) => shape,
+                        None => {
+                            return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                                "{:?} cannot multiply shapes {:?} and {:?} with transposes {:?}",
+                                opcode, lhs_shape, rhs_shape, flags
+                            )))
+                        }
+                    };
+                // operands are packed on host, wait for both of them
+                self.wait_until_ready(exec_mode, operand_lhs);
+                self.wait_until_ready(exec_mode, operand_rhs);
+                let packed_dataview = Arc::new(RwLock::new(ActTensorTypes::TupleTensor {
+                    data: vec![
+                        self.tensor_pool[&operand_lhs].read().unwrap().clone(),
+                        self.tensor_pool[&operand_rhs].read().unwrap().clone(),
+                    ],
+                }));
+                let flags_dataview = Arc::new(RwLock::new(ActTensorTypes::I32Tensor {
+                    data: TensorView::<i32>::new(
+                        flags.iter().map(|x| *x as i32).collect(),
+                        ElementType::I32,
+                        vec![flags.len()],
+                    ),
+                }));
+                match exec_mode {
+                    0u8 | 1u8 => {
+                        info!("::vm::call-session-launch-matmul-compute eager+blocking");
+                        let outs = self.session.launch_blocking_binary_compute(
+                            opcode,
+                            packed_dataview,
+                            flags_dataview,
+                        );
+                        info!("::vm::store-ret-value with index #{:?}", operand_out);
+                        self.tensor_pool
+                            .insert(operand_out, Arc::new(RwLock::new(outs)));
+                        Ok(0)
+                    }
+                    2u8 => {
+                        // packed operands and flags are ready on host
+                        let (packed_notifier, packed_ready_checker) = oneshot::channel::<u8>();
+                        packed_notifier.send(0u8);
+                        let (flags_notifier, flags_ready_checker) = oneshot::channel::<u8>();
+                        flags_notifier.send(0u8);
+
+                        info!("::create placeholder tensor for ret-value-tensor");
+                        self.push_tensor_buffer(
+                            operand_out,
+                            vec![0f32; out_shape.iter().product()],
+                            out_shape,
+                        );
+                        let out_placeholder = self.get_tensor(&operand_out);
+
+                        info!("::vm::call-session-launch-matmul-compute eager+non-blocking");
+                        let _ready_checkers = self.session.launch_non_blocking_binary_compute(
+                            opcode,
+                            packed_dataview,
+                            flags_dataview,
+                            out_placeholder,
+                            packed_ready_checker,
+                            flags_ready_checker,
+                            operand_out,
+                        );
+                        if self.ready_checkers.contains_key(&operand_out) {
+                            self.ready_checkers.remove(&operand_out);
+                        }
+                        self.ready_checkers
+                            .insert_many(operand_out, _ready_checkers);
+                        info!("::vm::store ready-checker for tensor #{}", operand_out);
+
+                        Ok(0)
+                    }
+                    _ => panic!("unknown exec-mode"),
+                }
+            }
+            CRTOpCode::FUSEDF32 => {
+                let operand_out = self.decode_operand();
+                let count = self.decode_u8() as usize;
+                let operands_in: Vec<usize> = (0..count).map(|_| self.decode_operand()).collect();
+                let expr_size = self.decode_vec_len() as usize;
+                let expr: FusedExpr =
+                    bincode::deserialize(&self.decode_n_bytes(expr_size)).unwrap();
+                let opcode = _inst;
+
+                // inputs are packed on host, wait for each of them once
+                for (position, operand_in) in operands_in.iter().enumerate() {
+                    if !operands_in[..position].contains(operand_in) {
+                        self.wait_until_ready(exec_mode, *operand_in);
+                    }
+                }
+                let in_shapes: Vec<Vec<usize>> = operands_in
+                    .iter()
+                    .map(|operand_in| self.get_tensor_shape(*operand_in))
+                    .collect();
+                let mut out_shape = in_shapes[0].clone();
+                for in_shape in &in_shapes[1..] {
+                    out_shape = broadcast_shape(&out_shape, in_shape).ok_or(
+                        RuntimeStatusError::SHAPE_MISMATCH(format!(
+                            "{:?} cannot broadcast shapes {:?}",
+                            opcode, in_shapes
+                        )),
+                    )?;
+                }
+                let packed_dataview = Arc::new(RwLock::new(ActTensorTypes::TupleTensor {
+                    data: operands_in
+                        .iter()
+                        .map(|operand_in| self.tensor_pool[operand_in].read().unwrap().clone())
+                        .collect(),
+                }));
+                let expr_dataview = Arc::new(RwLock::new(ActTensorTypes::from_fused_expr(&expr)));
+                match exec_mode {
+                    0u8 | 1u8 => {
+                        info!("::vm::call-session-launch-fused-compute eager+blocking");
+                        let outs = self.session.launch_blocking_binary_compute(
+                            opcode,
+                            packed_dataview,
+                            expr_dataview,
+                        );
+                        info!("::vm::store-ret-value with index #{:?}", operand_out);
+                        self.tensor_pool
+                            .insert(operand_out, Arc::new(RwLock::new(outs)));
+                        Ok(0)
+                    }
+                    2u8 => {
+                        // packed inputs and the expression are ready on host
+                        let (packed_notifier, packed_ready_checker) = oneshot::channel::<u8>();
+                        packed_notifier.send(0u8);
+                        let (expr_notifier, expr_ready_checker) = oneshot::channel::<u8>();
+                        expr_notifier.send(0u8);
+
+                        info!("::create placeholder tensor for ret-value-tensor");
+                        self.push_tensor_buffer(
+                            operand_out,
+                            vec![0f32; out_shape.iter().product()],
+                            out_shape,
+                        );
+                        let out_placeholder = self.get_tensor(&operand_out);
+
+                        info!("::vm::call-session-launch-fused-compute eager+non-blocking");
+                        let _ready_checkers = self.session.launch_non_blocking_binary_compute(
+                            opcode,
+                            packed_dataview,
+                            expr_dataview,
+                            out_placeholder,
+                            packed_ready_checker,
+                            expr_ready_checker,
+                            operand_out,
+                        );
+                        if self.ready_checkers.contains_key(&operand_out) {
+                            self.ready_checkers.remove(&operand_out);
+                        }
+                        self.ready_checkers
+                            .insert_many(operand_out, _ready_checkers);
+                        info!("::vm::store ready-checker for tensor #{}", operand_out);
+
+                        Ok(0)
+                    }
+                    _ => panic!("unknown exec-mode"),
+                }
+            }
+            CRTOpCode::CONV2DF32 | CRTOpCode::MAXPOOL2DF32 | CRTOpCode::AVGPOOL2DF32 => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
+                let operand_weight = match _inst {
+                    CRTOpCode::CONV2DF32 => Some(self.decode_operand()),
+                    _ => None,
+                };
+                let attr_size = self.decode_vec_len() as usize;
+                let attr = self.decode_n_bytes_as_usize_vec(attr_size);
+                let opcode = _inst;
+
+                // infer the shape of results before launching, window params travel as attrs
+                let in_shape = self.get_tensor_shape(operand_in).to_vec();
+                let (out_shape, attr) = match operand_weight {
+                    Some(operand_weight) => {
+                        let weight_shape = self.get_tensor_shape(operand_weight).to_vec();
+                        let attr = Conv2dAttr::from_vec(&attr);
+                        (
+                            conv2d_shape(&in_shape, &weight_shape, &attr).ok_or(
+                                RuntimeStatusError::SHAPE_MISMATCH(format!(
+                                    "{:?} cannot convolve {:?} with weight {:?} by {:?}",
+                                    opcode, in_shape, weight_shape, attr
+                                )),
+                            )?,
+                            attr.to_vec(),
+                        )
+                    }
+                    None => {
+                        let attr = Pool2dAttr::from_vec(&attr);
+                        (
+                            pool2d_shape(&in_shape, &attr).ok_or(
+                                RuntimeStatusError::SHAPE_MISMATCH(format!(
+                                    "{:?} cannot pool {:?} by {:?}",
+                                    opcode, in_shape, attr
+                                )),
+                            )?,
+                            attr.to_vec(),
+                        )
+                    }
+                };
+                let attr_dataview = Arc::new(RwLock::new(ActTensorTypes::I32Tensor {
+                    data: TensorView::<i32>::new(
+                        attr.iter().map(|x| *x as i32).collect(),
+                        ElementType::I32,
+                        vec![attr.len()],
+                    ),
+                }));
+                // the input and the weight of conv2d are packed on host, wait for both of them
+                let in_dataview = match operand_weight {
+                    Some(operand_weight) => {
+                        self.wait_until_ready(exec_mode, operand_in);
+                        self.wait_until_ready(exec_mode, operand_weight);
+                        Arc::new(RwLock::new(ActTensorTypes::TupleTensor {
+                            data: vec![
+                                self.tensor_pool[&operand_in].read().unwrap().clone(),
+                                self.tensor_pool[&operand_weight].read().unwrap().clone(),
+                            ],
+                        }))
+                    }
+                    None => self.get_tensor(&operand_in),
+                };
+                match exec_mode {
+                    0u8 | 1u8 => {
+                        info!("::vm::call-session-launch-window-compute eager+blocking");
+                        let outs = self.session.launch_blocking_binary_compute(
+                            opcode,
+                            in_dataview,
+                            attr_dataview,
+                        );
+                        info!("::vm::store-ret-value with index #{:?}", operand_out);
+                        self.tensor_pool
+                            .insert(operand_out, Arc::new(RwLock::new(outs)));
+                        Ok(0)
+                    }
+                    2u8 => {
+                        let in_ready_checker = match operand_weight {
+                            // packed operands are ready on host
+                            Some(_) => {
+                                let (in_notifier, in_ready_checker) = oneshot::channel::<u8>();
+                                in_notifier.send(0u8);
+                                in_ready_checker
+                            }
+                            None => {
+                                info!("::vm::poll ready-checker for tensor #{}", operand_in);
+                                self.ready_checkers
+                                    .get_vec_mut(&operand_in)
+                                    .expect(
+                                        &format!("failed to fetch ready-checker {}", operand_in)
+                                            .to_string(),
+                                    )
+                                    .remove(0 as usize)
+                            }
+                        };
+                        // attributes are ready on host
+                        let (attr_notifier, attr_ready_checker) = oneshot::channel::<u8>();
+                        attr_notifier.send(0u8);
+
+                        info!("::create placeholder tensor for ret-value-tensor");
+                        self.push_tensor_buffer(
+                            operand_out,
+                            vec![0f32; out_shape.iter().product()],
+                            out_shape,
+                        );
+                        let out_placeholder = self.get_tensor(&operand_out);
+
+                        info!("::vm::call-session-launch-window-compute eager+non-blocking");
+                        let _ready_checkers = self.session.launch_non_blocking_binary_compute(
+                            opcode,
+                            in_dataview,
+                            attr_dataview,
+                            out_placeholder,
+                            in_ready_checker,
+                            attr_ready_checker,
+                            operand_out,
+                        );
+                        if self.ready_checkers.contains_key(&operand_out) {
+                            self.ready_checkers.remove(&operand_out);
+                        }
+                        self.ready_checkers
+                            .insert_many(operand_out, _ready_checkers);
+                        info!("::vm::store ready-checker for tensor #{}", operand_out);
+
+                        Ok(0)
+                    }
+                    _ => panic!("unknown exec-mode"),
+                }
+            }
+            CRTOpCode::RESHAPE | CRTOpCode::SQUEEZE | CRTOpCode::UNSQUEEZE => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
+                let dims_size = self.decode_vec_len() as usize;
+                let dims = self.decode_n_bytes_as_usize_vec(dims_size);
+                let dims = self.resolve_dims(dims)?;
+                self.wait_until_ready(exec_mode, operand_in);
+
+                // metadata-only, rewrite the shape on host without launching executors
+                let in_shape = self.get_tensor_shape(operand_in);
+                let valid = match _inst {
+                    CRTOpCode::RESHAPE => {
+                        dims.iter().product::<usize>() == in_shape.iter().product::<usize>()
+                    }
+                    CRTOpCode::SQUEEZE => dims
+                        .iter()
+                        .all(|axis| *axis < in_shape.len() && in_shape[*axis] == 1),
+                    // axes are inserted in order, each one is in the shape grown so far
+                    _ => {
+                        let mut axes = dims.clone();
+                        axes.sort();
+                        axes.iter()
+                            .enumerate()
+                            .all(|(inserted, axis)| *axis <= in_shape.len() + inserted)
+                    }
+                };
+                if !valid {
+                    return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                        "{:?} cannot take shape {:?} with dims {:?}",
+                        _inst, in_shape, dims
+                    )));
+                }
+                let shape = match _inst {
+                    CRTOpCode::RESHAPE => dims,
+                    CRTOpCode::SQUEEZE => squeeze_shape(&in_shape, &dims),
+                    _ => unsqueeze_shape(&in_shape, &dims),
+                };
+                if operand_out == operand_in {
+                    // reshape in place, no copy
+                    self.tensor_pool[&operand_in]
+                        .write()
+                        .unwrap()
+                        .reshape(shape);
+                } else {
+                    let mut outs = self.tensor_pool[&operand_in].read().unwrap().clone();
+                    outs.reshape(shape);
+                    self.tensor_pool
+                        .insert(operand_out, Arc::new(RwLock::new(outs)));
+                }
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::TRANSPOSE | CRTOpCode::SLICE => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
+                let dims_size = self.decode_vec_len() as usize;
+                let dims = self.decode_n_bytes_as_usize_vec(dims_size);
+                let ends = match _inst {
+                    CRTOpCode::SLICE => {
+                        let ends_size = self.decode_vec_len() as usize;
+                        self.decode_n_bytes_as_usize_vec(ends_size)
+                    }
+                    _ => vec![],
+                };
+                let dims = self.resolve_dims(dims)?;
+                let ends = self.resolve_dims(ends)?;
+                self.wait_until_ready(exec_mode, operand_in);
+
+                let in_shape = self.get_tensor_shape(operand_in);
+                let rank = in_shape.len();
+                let valid = match _inst {
+                    CRTOpCode::TRANSPOSE => {
+                        let mut sorted_perm = dims.clone();
+                        sorted_perm.sort();
+                        dims.is_empty() || sorted_perm == (0..rank).collect::<Vec<usize>>()
+                    }
+                    _ => dims.len() == rank && ends.len() == rank,
+                };
+                if !valid {
+                    return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                        "{:?} cannot take shape {:?} with dims {:?}, {:?}",
+                        _inst, in_shape, dims, ends
+                    )));
+                }
+
+                // data movement of shape ops is done on host
+                let outs = match _inst {
+                    CRTOpCode::TRANSPOSE => HostFunctor::new()
+                        .transpose(&*self.tensor_pool[&operand_in].read().unwrap(), &dims),
+                    _ => HostFunctor::new().slice(
+                        &*self.tensor_pool[&operand_in].read().unwrap(),
+                        &dims,
+                        &ends,
+                    ),
+                };
+                self.tensor_pool
+                    .insert(operand_out, Arc::new(RwLock::new(outs)));
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::SPLIT => {
+                let count = self.decode_u8() as usize;
+                let operands_out: Vec<usize> = (0..count).map(|_| self.decode_operand()).collect();
+                let operand_in = self.decode_operand();
+                let axis = self.decode_axis().unwrap_or(0);
+                self.wait_until_ready(exec_mode, operand_in);
+
+                let in_shape = self.get_tensor_shape(operand_in);
+                let rank = in_shape.len() as i32;
+                let axis = match axis {
+                    axis if axis < 0 => rank + axis,
+                    axis => axis,
+                };
+                if axis < 0 || axis >= rank || count == 0 || in_shape[axis as usize] % count != 0 {
+                    return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                        "cannot split shape {:?} into {} parts on axis {}",
+                        in_shape, count, axis
+                    )));
+                }
+                let outs = HostFunctor::new().split(
+                    &*self.tensor_pool[&operand_in].read().unwrap(),
+                    axis as usize,
+                    count,
+                );
+                for (operand_out, out) in operands_out.into_iter().zip(outs) {
+                    self.tensor_pool
+                        .insert(operand_out, Arc::new(RwLock::new(out)));
+                    self.mark_ready(exec_mode, operand_out);
+                }
+                Ok(0)
+            }
+            CRTOpCode::CONCAT => {
+                let operand_out = self.decode_operand();
+                let operand_lhs = self.decode_operand();
+                let operand_rhs = self.decode_operand();
+                let axis = self.decode_axis().unwrap_or(0);
+                self.wait_until_ready(exec_mode, operand_lhs);
+                self.wait_until_ready(exec_mode, operand_rhs);
+
+                let lhs_shape = self.get_tensor_shape(operand_lhs);
+                let rhs_shape = self.get_tensor_shape(operand_rhs);
+                let axis = normalize_axis(axis, lhs_shape.len())
+                    .map_err(RuntimeStatusError::SHAPE_MISMATCH)?;
+                if lhs_shape.len() != rhs_shape.len()
+                    || (0..lhs_shape.len()).any(|d| d != axis && lhs_shape[d] != rhs_shape[d])
+                {
+                    return Err(RuntimeStatusError::SHAPE_MISMATCH(format!(
+                        "cannot concat shapes {:?} and {:?} on axis {}",
+                        lhs_shape, rhs_shape, axis
+                    )));
+                }
+                let lhs_dtype = self.get_tensor_dtype(operand_lhs);
+                let rhs_dtype = self.get_tensor_dtype(operand_rhs);
+                if lhs_dtype != rhs_dtype {
+                    return Err(RuntimeStatusError::DTYPE_MISMATCH(format!(
+                        "cannot concat tensors of {} and {}",
+                        lhs_dtype, rhs_dtype
+                    )));
+                }
+                let outs = HostFunctor::new().concat(
+                    &*self.tensor_pool[&operand_lhs].read().unwrap(),
+                    &*self.tensor_pool[&operand_rhs].read().unwrap(),
+                    axis,
+                );
+                self.tensor_pool
+                    .insert(operand_out, Arc::new(RwLock::new(outs)));
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
             CRTOpCode::CONSTI32 => {
                 // TODO do some action, add data_buffer
                 // create lhs dataview
                 // TODO enable it
-                let operand_out = self.decode_u8() as usize;
+                let operand_out = self.decode_operand();
                 let operand_in = self.get_next_four_bytes();
                 let operand_in_i32 = i32::from_le_bytes(operand_in);
                 self.push_data_buffer_i32(operand_out, vec![operand_in_i32]);
+                self.mark_ready(exec_mode, operand_out);
                 Ok(0)
             }
             CRTOpCode::CONSTF32 => {
                 // TODO do some action, add data_buffer
                 // create lhs dataview
-                let operand_out = self.decode_u8() as usize;
+                let operand_out = self.decode_operand();
                 let operand_in = self.get_next_four_bytes();
                 let operand_in_f32 = f32::from_le_bytes(operand_in);
                 self.push_tensor_pool(operand_out, vec![operand_in_f32]);
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::CONSTF16
+            | CRTOpCode::CONSTBF16
+            | CRTOpCode::CONSTI8
+            | CRTOpCode::CONSTU8
+            | CRTOpCode::CONSTF64
+            | CRTOpCode::CONSTI64
+            | CRTOpCode::CONSTBOOL => {
+                let operand_out = self.decode_operand();
+                let element_type = _inst.to_const_element_type().unwrap();
+                let bytes = self.decode_n_bytes(element_type.byte_width());
+                let literal = ActTensorTypes::from_le_bytes(&bytes, element_type, vec![1]);
+                self.tensor_pool
+                    .insert(operand_out, Arc::new(RwLock::new(literal)));
+                self.mark_ready(exec_mode, operand_out);
                 Ok(0)
             }
             CRTOpCode::CONSTTENSOR => {
-                let operand_out = self.decode_u8() as usize;
+                let operand_out = self.decode_operand();
+                let element_type = ElementType::from(self.decode_u8());
                 let data_size = self.decode_vec_len() as usize;
-                let raw_data_vec = self.decode_n_bytes_as_f32_vec(data_size);
+                let raw_data = self.decode_n_bytes(data_size);
                 let shape_size = self.decode_vec_len() as usize;
                 let raw_shape_vec = self.decode_n_bytes_as_usize_vec(shape_size);
-                self.push_tensor_buffer(operand_out, raw_data_vec, raw_shape_vec);
+                self.push_tensor(
+                    operand_out,
+                    ActTensorTypes::from_le_bytes(&raw_data, element_type, raw_shape_vec),
+                );
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::CONSTREF => {
+                let operand_out = self.decode_operand();
+                let index = i32::from_le_bytes(self.get_next_four_bytes()) as usize;
+                let constant = match self.constants.get(index) {
+                    Some(constant) => constant.clone(),
+                    None => {
+                        return Err(RuntimeStatusError::INVALID_MODULE(format!(
+                            "constant #{} is not loaded",
+                            index
+                        )))
+                    }
+                };
+                self.push_tensor(operand_out, constant);
+                self.mark_ready(exec_mode, operand_out);
                 Ok(0)
             }
             CRTOpCode::SVALUETENSOR => {
-                let operand_out = self.decode_u8() as usize;
-                let data_generator = self.get_next_four_bytes();
-                // TODO currently svalue is hardcoded as float
-                let data_generator_f32 = f32::from_le_bytes(data_generator);
+                let operand_out = self.decode_operand();
+                let element_type = ElementType::from(self.decode_u8());
+                let value = self.decode_n_bytes(element_type.byte_width());
                 let shape_size = self.decode_vec_len() as usize;
                 let raw_shape_vec = self.decode_n_bytes_as_usize_vec(shape_size);
                 info!(
                     "::vm::generate+store tensor-value with index #{:?}",
                     operand_out
                 );
-                self.push_tensor_buffer(
+                self.push_tensor(
                     operand_out,
-                    vec![data_generator_f32; raw_shape_vec.iter().product()],
-                    raw_shape_vec,
+                    ActTensorTypes::from_le_bytes(
+                        &value.repeat(raw_shape_vec.iter().product()),
+                        element_type,
+                        raw_shape_vec,
+                    ),
                 );
                 for i in 0..8 {
                     let (notifier, ready_checker) = oneshot::channel::<u8>();
@@ -442,22 +1468,82 @@ impl VM {
                 info!("::vm::fill data-ready-checker #{}", operand_out);
                 Ok(0)
             }
+            CRTOpCode::RANGETENSOR => {
+                let operand_out = self.decode_operand();
+                let kind = self.decode_u8();
+                let element_type = ElementType::from(self.decode_u8());
+                let params: Vec<f64> = (0..3)
+                    .map(|_| f64::from_le_bytes(self.decode_n_bytes(8).try_into().unwrap()))
+                    .collect();
+                let values = match kind {
+                    0 => arange_values(params[0], params[1], params[2]),
+                    1 => linspace_values(params[0], params[1], params[2] as usize),
+                    _ => panic!("unknown range category"),
+                };
+                let shape = vec![values.len()];
+                self.push_tensor(
+                    operand_out,
+                    ActTensorTypes::from_f64_vec(values, element_type, shape),
+                );
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::EYETENSOR => {
+                let operand_out = self.decode_operand();
+                let element_type = ElementType::from(self.decode_u8());
+                let shape_size = self.decode_vec_len() as usize;
+                let raw_shape_vec = self.decode_n_bytes_as_usize_vec(shape_size);
+                let values = eye_values(raw_shape_vec[0], raw_shape_vec[1]);
+                self.push_tensor(
+                    operand_out,
+                    ActTensorTypes::from_f64_vec(values, element_type, raw_shape_vec),
+                );
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
+            CRTOpCode::ZEROSLIKE | CRTOpCode::ONESLIKE => {
+                let operand_out = self.decode_operand();
+                let operand_in = self.decode_operand();
+                self.wait_until_ready(exec_mode, operand_in);
+
+                // only the shape and element type of the operand are read, filled on host
+                let (element_type, shape) = {
+                    let in_tensor = self.tensor_pool[&operand_in].read().unwrap();
+                    (in_tensor.element_type(), in_tensor.shape())
+                };
+                let value = match _inst {
+                    CRTOpCode::ZEROSLIKE => 0f64,
+                    _ => 1f64,
+                };
+                self.push_tensor(
+                    operand_out,
+                    ActTensorTypes::from_f64_vec(
+                        vec![value; shape.iter().product()],
+                        element_type,
+                        shape,
+                    ),
+                );
+                self.mark_ready(exec_mode, operand_out);
+                Ok(0)
+            }
             CRTOpCode::RNGTENSOR => {
-                let operand_out = self.decode_u8() as usize;
+                let operand_out = self.decode_operand();
                 let distribution = self.decode_u8();
+                let element_type = ElementType::from(self.decode_u8());
+                let params: Vec<f32> = (0..2)
+                    .map(|_| f32::from_le_bytes(self.get_next_four_bytes()))
+                    .collect();
+                let seed = u64::from_le_bytes(self.decode_n_bytes(8).try_into().unwrap());
                 let shape_size = self.decode_vec_len() as usize;
                 let raw_shape_vec = self.decode_n_bytes_as_usize_vec(shape_size);
-                let _tensor = match distribution {
-                    // TODO make min-max adjustable
-                    0 => BlasTensor::uniform(raw_shape_vec.clone(), -1f32, 1f32),
-                    1 => BlasTensor::normal(raw_shape_vec.clone(), 0f32, 1f32),
-                    _ => panic!("unknown rng category????"),
-                };
-                self.push_tensor_buffer(
+                // sampled on host, so the same seed gives the same tensor on every executor
+                let values =
+                    random::sample(distribution, &params, seed, raw_shape_vec.iter().product());
+                self.push_tensor(
                     operand_out,
-                    TensorView::<f32>::from(_tensor).data,
-                    raw_shape_vec,
+                    ActTensorTypes::from_f64_vec(values, element_type, raw_shape_vec),
                 );
+                self.mark_ready(exec_mode, operand_out);
                 Ok(0)
             }
             _ => {
@@ -534,10 +1620,11 @@ impl VM {
     }
 
     pub fn get_tensor_shape(&self, index: usize) -> Vec<usize> {
-        match *self.tensor_pool[&index].read().unwrap() {
-            ActTensorTypes::F32Tensor { ref data } => data.shape.clone(),
-            _ => panic!("not support int types"),
-        }
+        self.tensor_pool[&index].read().unwrap().shape()
+    }
+
+    pub fn get_tensor_dtype(&self, index: usize) -> ElementType {
+        self.tensor_pool[&index].read().unwrap().element_type()
     }
 
     pub fn push_tensor_pool(&mut self, index: usize, data: Vec<f32>) {
@@ -577,6 +1664,48 @@ impl VM {
         self.tensor_pool.insert(index, tensor_view);
     }
 
+    pub fn push_tensor_buffer_i32(&mut self, index: usize, data: Vec<i32>, shape: Vec<usize>) {
+        let tensor_view = Arc::new(RwLock::new(ActTensorTypes::I32Tensor {
+            data: TensorView::<i32>::new(data, ElementType::I32, shape),
+        }));
+        self.tensor_pool.insert(index, tensor_view);
+    }
+
+    // appends the constant section of a module, returns the index of its first constant
+    pub fn push_constants(&mut self, constants: Vec<ActTensorTypes>) -> usize {
+        let base = self.constants.len();
+        self.constants.extend(constants);
+        base
+    }
+
+    // slots of the results of the last return, cleared after taken
+    pub fn take_returned(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.returned)
+    }
+
+    // binds a ready tensor to the slot, such as function arguments
+    pub fn push_tensor(&mut self, index: usize, tensor: ActTensorTypes) {
+        self.tensor_pool
+            .insert(index, Arc::new(RwLock::new(tensor)));
+        self.ready_checkers.remove(&index);
+    }
+
+    pub fn has_tensor(&self, index: usize) -> bool {
+        self.tensor_pool.contains_key(&index)
+    }
+
+    // element type and shape of the tensor at index, which the verifier reads for values defined
+    // before the program it checks
+    pub fn tensor_type(&self, index: usize) -> Option<ValueType> {
+        let tensor = self.tensor_pool.get(&index)?.read().unwrap();
+        match &*tensor {
+            ActTensorTypes::MockTensor { .. } | ActTensorTypes::TupleTensor { .. } => {
+                Some(ValueType::unknown())
+            }
+            tensor => Some(ValueType::new(tensor.element_type(), tensor.shape())),
+        }
+    }
+
     // entry functions for execute, that is public
     pub fn eager_step(&mut self) -> Result<u8, RuntimeStatusError> {
         info!("::vm::eager-step");
@@ -681,6 +1810,97 @@ mod tests {
         assert_eq!(vm.program_counter, 1);
     }
 
+    #[test]
+    fn test_vm_constref_not_loaded() {
+        let mut vm = VM::new();
+        vm.init(2);
+        vm.inst_buffer = vec![116, 1, 3, 0, 0, 0];
+        let exit_code = vm.eager_step();
+        assert!(matches!(
+            exit_code,
+            Err(RuntimeStatusError::INVALID_MODULE(_))
+        ));
+    }
+
+    #[test]
+    fn test_vm_reduce_axis_out_of_range() {
+        let mut vm = VM::new();
+        vm.init(2);
+        vm.push_tensor_buffer(1, vec![0f32; 6], vec![2, 3]);
+        // reduce.sum %2 = %1 on axis 5, without keepdims
+        vm.inst_buffer = vec![44, 2, 1, 1, 5, 0];
+        let exit_code = vm.eager_step();
+        assert!(matches!(
+            exit_code,
+            Err(RuntimeStatusError::SHAPE_MISMATCH(_))
+        ));
+    }
+
+    #[test]
+    fn test_vm_shape_ops_mismatch() {
+        let mut vm = VM::new();
+        vm.init(2);
+        vm.push_tensor_buffer(1, vec![0f32; 6], vec![2, 3]);
+        let programs = [
+            "%2 = crt.tensor.reshape! %1, [4, 2] : f32\n",
+            "%2 = crt.tensor.transpose! %1, [0, 0] : f32\n",
+            "%2 = crt.tensor.slice! %1, [0], [1] : f32\n",
+            "%2 = crt.tensor.concat! %1, %1, axis = 2 : f32\n",
+        ];
+        for source in programs.iter() {
+            vm.inst_buffer = crate::assembler::assemble(source).unwrap().to_bytes();
+            vm.program_counter = 0;
+            assert!(matches!(
+                vm.eager_step(),
+                Err(RuntimeStatusError::SHAPE_MISMATCH(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vm_dtype_mismatch() {
+        let mut vm = VM::new();
+        vm.init(2);
+        vm.push_tensor_buffer(1, vec![0f32; 6], vec![2, 3]);
+        vm.push_tensor_buffer_i32(2, vec![0; 6], vec![2, 3]);
+        let programs = [
+            "%3 = crt.tensor.concat! %1, %2, axis = 0 : f32\n",
+            // masks are bool or i32, values share one dtype
+            "%3 = crt.select! %1, %1, %1 : f32\n",
+            "%3 = crt.select! %2, %1, %2 : f32\n",
+        ];
+        for source in programs.iter() {
+            vm.inst_buffer = crate::assembler::assemble(source).unwrap().to_bytes();
+            vm.program_counter = 0;
+            assert!(matches!(
+                vm.eager_step(),
+                Err(RuntimeStatusError::DTYPE_MISMATCH(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vm_loop_reentered() {
+        // the inner loop is left by a jump at its second trip, entering it again runs 3 trips
+        let source = "load $1, 1
+load $2, 2
+^outer:
+^inner:
+$0 = crt.reg.add! $0, $1
+$3 = crt.reg.cmp.eq! $0, $2
+jmp_if $3, ^out
+loop 3, ^inner
+^out:
+loop 2, ^outer
+";
+        let mut vm = VM::new();
+        vm.init(2);
+        vm.inst_buffer = crate::assembler::assemble(source).unwrap().to_bytes();
+        assert!(vm.run_eagerly().is_ok());
+        assert_eq!(vm.registers[0], 5);
+        assert!(vm.loop_counters.is_empty());
+    }
+
     #[test]
     fn test_vm_fetch_instruction() {
         let mut vm = VM::new();
@@ -697,6 +1917,16 @@ mod tests {
         assert_eq!(data, 8);
     }
 
+    #[test]
+    fn test_vm_decode_operand() {
+        let mut vm = VM::new();
+        vm.inst_buffer = vec![8, 0xac, 0x02, 0x80, 0x80, 0x40];
+        assert_eq!(vm.decode_operand(), 8);
+        assert_eq!(vm.decode_operand(), 300);
+        assert_eq!(vm.decode_operand(), 1 << 20);
+        assert_eq!(vm.program_counter, 6);
+    }
+
     #[test]
     fn test_vm_next_two_bytes() {
         let mut vm = VM::new();